@@ -31,7 +31,7 @@
 
 use std::{borrow::Cow, time::Duration};
 
-use crate::service::TransportType;
+use crate::service::{ProtocolVersion, TransportType};
 
 /// Configuration for the engine.io engine & transports
 #[derive(Debug, Clone)]
@@ -62,6 +62,12 @@ pub struct EngineIoConfig {
     /// Allowed transports on this server
     /// It is represented as a bitfield to allow to combine any number of transports easily
     pub transports: u8,
+
+    /// The minimum protocol version accepted on this server.
+    /// Clients connecting with an older version are refused with an "Unsupported protocol version" error.
+    ///
+    /// Defaults to [`ProtocolVersion::V3`], every supported version is accepted.
+    pub min_protocol_version: ProtocolVersion,
 }
 
 impl Default for EngineIoConfig {
@@ -73,6 +79,7 @@ impl Default for EngineIoConfig {
             max_buffer_size: 128,
             max_payload: 1e5 as u64, // 100kb
             transports: TransportType::Polling as u8 | TransportType::Websocket as u8,
+            min_protocol_version: ProtocolVersion::V3,
         }
     }
 }
@@ -88,6 +95,12 @@ impl EngineIoConfig {
     pub fn allowed_transport(&self, transport: TransportType) -> bool {
         self.transports & transport as u8 == transport as u8
     }
+
+    /// Check if a [`ProtocolVersion`] is accepted by the [`EngineIoConfig`]
+    #[inline(always)]
+    pub fn allowed_protocol(&self, protocol: ProtocolVersion) -> bool {
+        protocol >= self.min_protocol_version
+    }
 }
 
 /// Builder for [`EngineIoConfig`]
@@ -185,6 +198,14 @@ impl EngineIoConfigBuilder {
         self
     }
 
+    /// The minimum protocol version accepted on this server.
+    ///
+    /// Defaults to [`ProtocolVersion::V3`], every supported version is accepted.
+    pub fn min_protocol_version(mut self, min_protocol_version: ProtocolVersion) -> Self {
+        self.config.min_protocol_version = min_protocol_version;
+        self
+    }
+
     /// Build the config
     pub fn build(self) -> EngineIoConfig {
         self.config
@@ -219,4 +240,17 @@ mod tests {
         assert!(conf.allowed_transport(TransportType::Polling));
         assert!(conf.allowed_transport(TransportType::Websocket));
     }
+
+    #[test]
+    pub fn config_min_protocol_version() {
+        let conf = EngineIoConfig::default();
+        assert!(conf.allowed_protocol(ProtocolVersion::V3));
+        assert!(conf.allowed_protocol(ProtocolVersion::V4));
+
+        let conf = EngineIoConfig::builder()
+            .min_protocol_version(ProtocolVersion::V4)
+            .build();
+        assert!(!conf.allowed_protocol(ProtocolVersion::V3));
+        assert!(conf.allowed_protocol(ProtocolVersion::V4));
+    }
 }
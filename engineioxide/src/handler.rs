@@ -60,6 +60,12 @@ pub trait EngineIoHandler: std::fmt::Debug + Send + Sync + 'static {
 
     /// Called when a binary message is received from the client.
     fn on_binary(&self, data: Vec<u8>, socket: Arc<Socket<Self::Data>>);
+
+    /// Called when a socket starts upgrading its transport from polling to websocket.
+    fn on_upgrade_start(&self, _socket: Arc<Socket<Self::Data>>) {}
+
+    /// Called when a socket has successfully upgraded its transport from polling to websocket.
+    fn on_upgrade(&self, _socket: Arc<Socket<Self::Data>>) {}
 }
 
 impl<T: EngineIoHandler> EngineIoHandler for Arc<T> {
@@ -80,4 +86,12 @@ impl<T: EngineIoHandler> EngineIoHandler for Arc<T> {
     fn on_binary(&self, data: Vec<u8>, socket: Arc<Socket<Self::Data>>) {
         (**self).on_binary(data, socket)
     }
+
+    fn on_upgrade_start(&self, socket: Arc<Socket<Self::Data>>) {
+        (**self).on_upgrade_start(socket)
+    }
+
+    fn on_upgrade(&self, socket: Arc<Socket<Self::Data>>) {
+        (**self).on_upgrade(socket)
+    }
 }
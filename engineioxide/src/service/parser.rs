@@ -118,7 +118,7 @@ impl<B> From<ParseError> for Response<ResponseBody<B>> {
 }
 
 /// The engine.io protocol version
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum ProtocolVersion {
     /// The protocol version 3
     V3 = 3,
@@ -223,6 +223,10 @@ impl RequestInfo {
             .ok_or(UnsupportedProtocolVersion)
             .and_then(|t| t.parse())?;
 
+        if !config.allowed_protocol(protocol) {
+            return Err(UnsupportedProtocolVersion);
+        }
+
         let sid = query
             .split('&')
             .find(|s| s.starts_with("sid="))
@@ -347,6 +351,19 @@ mod tests {
         assert!(matches!(err, ParseError::UnsupportedProtocolVersion));
     }
     #[test]
+    #[cfg(feature = "v3")]
+    fn protocol_version_below_min() {
+        let config = EngineIoConfig::builder()
+            .min_protocol_version(ProtocolVersion::V4)
+            .build();
+        let req = build_request("http://localhost:3000/socket.io/?EIO=3&transport=polling");
+        let err = RequestInfo::parse(&req, &config).unwrap_err();
+        assert!(matches!(err, ParseError::UnsupportedProtocolVersion));
+
+        let req = build_request("http://localhost:3000/socket.io/?EIO=4&transport=polling");
+        assert!(RequestInfo::parse(&req, &config).is_ok());
+    }
+    #[test]
     fn bad_handshake_method() {
         let req = Request::post("http://localhost:3000/socket.io/?EIO=4&transport=polling")
             .body(())
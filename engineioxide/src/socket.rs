@@ -55,7 +55,7 @@
 //! ```
 use std::{
     sync::{
-        atomic::{AtomicU8, Ordering},
+        atomic::{AtomicU64, AtomicU8, Ordering},
         Arc,
     },
     time::Duration,
@@ -65,7 +65,10 @@ use http::request::Parts;
 use tokio::{
     sync::{
         mpsc::{self},
-        mpsc::{error::TrySendError, Receiver},
+        mpsc::{
+            error::{SendError, TrySendError},
+            Receiver,
+        },
         Mutex,
     },
     task::JoinHandle,
@@ -147,6 +150,11 @@ impl<'a> Iterator for PermitIterator<'a> {
         let inner = self.inner.next()?;
         Some(Permit { inner })
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
 }
 impl ExactSizeIterator for PermitIterator<'_> {
     #[inline]
@@ -156,6 +164,15 @@ impl ExactSizeIterator for PermitIterator<'_> {
 }
 impl std::iter::FusedIterator for PermitIterator<'_> {}
 
+/// A message to write to the transport of a detached socket, see [`Socket::recv_detached`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetachedMessage {
+    /// A text message
+    Text(String),
+    /// A binary message
+    Binary(Vec<u8>),
+}
+
 /// A [`Socket`] represents a client connection to the server.
 /// It is agnostic to the [`TransportType`].
 ///
@@ -205,6 +222,8 @@ where
     pub(crate) heartbeat_tx: mpsc::Sender<()>,
     /// Handle to the heartbeat job so that it can be aborted when the socket is closed
     heartbeat_handle: Mutex<Option<JoinHandle<()>>>,
+    /// The last ping/pong round-trip time in nanoseconds, `0` before the first measurement
+    latency: AtomicU64,
 
     /// Function to call when the socket is closed
     close_fn: Box<dyn Fn(Sid, DisconnectReason) + Send + Sync>,
@@ -245,6 +264,7 @@ where
             heartbeat_rx: Mutex::new(heartbeat_rx),
             heartbeat_tx,
             heartbeat_handle: Mutex::new(None),
+            latency: AtomicU64::new(0),
             close_fn,
 
             data: D::default(),
@@ -255,6 +275,66 @@ where
         }
     }
 
+    /// Creates a socket that is not bound to the engine, for a transport implemented outside of this crate.
+    ///
+    /// There is no heartbeat and no framing: the owner of the socket reads the packets to send
+    /// with [`Socket::recv_detached`], forwards the received messages to its handler and
+    /// is notified through `close_fn` when the socket is closed.
+    ///
+    /// The transport type is reported as [`TransportType::Websocket`] as the messages
+    /// and binary payloads are sent as separate frames.
+    pub fn new_detached(
+        protocol: ProtocolVersion,
+        max_buffer_size: usize,
+        req_parts: Parts,
+        close_fn: Box<dyn Fn(Sid, DisconnectReason) + Send + Sync>,
+    ) -> Self {
+        let (internal_tx, internal_rx) = mpsc::channel(max_buffer_size);
+        let (heartbeat_tx, heartbeat_rx) = mpsc::channel(1);
+
+        Self {
+            id: Sid::new(),
+            protocol,
+            transport: AtomicU8::new(TransportType::Websocket as u8),
+
+            internal_rx: Mutex::new(PeekableReceiver::new(internal_rx)),
+            internal_tx,
+
+            heartbeat_rx: Mutex::new(heartbeat_rx),
+            heartbeat_tx,
+            heartbeat_handle: Mutex::new(None),
+            latency: AtomicU64::new(0),
+            close_fn,
+
+            data: D::default(),
+            req_parts,
+
+            #[cfg(feature = "v3")]
+            supports_binary: true,
+        }
+    }
+
+    /// Receives the next message to write to the transport of a socket created with [`Socket::new_detached`].
+    ///
+    /// `None` is returned once the socket is closed.
+    pub async fn recv_detached(&self) -> Option<DetachedMessage> {
+        let mut rx = self.internal_rx.lock().await;
+        loop {
+            match rx.recv().await {
+                Some(Packet::Close) | None => {
+                    rx.close();
+                    return None;
+                }
+                Some(Packet::Message(msg)) => return Some(DetachedMessage::Text(msg)),
+                Some(Packet::Binary(bin) | Packet::BinaryV3(bin)) => {
+                    return Some(DetachedMessage::Binary(bin))
+                }
+                // The heartbeat and upgrade packets are not used without engine.io framing
+                Some(_) => (),
+            }
+        }
+    }
+
     /// Abort the heartbeat job if it is running
     pub(crate) fn abort_heartbeat(&self) {
         if let Ok(Some(handle)) = self.heartbeat_handle.try_lock().map(|mut h| h.take()) {
@@ -336,10 +416,13 @@ where
             self.internal_tx
                 .try_send(Packet::Ping)
                 .map_err(|_| Error::HeartbeatTimeout)?;
+            let sent = tokio::time::Instant::now();
             tokio::time::timeout(timeout, heartbeat_rx.recv())
                 .await
                 .map_err(|_| Error::HeartbeatTimeout)?
                 .ok_or(Error::HeartbeatTimeout)?;
+            let rtt = sent.elapsed().as_nanos().clamp(1, u64::MAX as u128) as u64;
+            self.latency.store(rtt, Ordering::Relaxed);
             interval_tick.tick().await;
         }
     }
@@ -368,6 +451,20 @@ where
         }
     }
 
+    /// Returns the round-trip time between the last ping sent by the server and the pong of the client,
+    /// updated on each heartbeat cycle.
+    ///
+    /// It includes the time spent by the ping in the packet buffer and, with the polling transport,
+    /// the time until the client polls it.
+    /// It is `None` before the first pong is received, and always with the v3 protocol
+    /// where the pings are sent by the client.
+    pub fn latency(&self) -> Option<Duration> {
+        match self.latency.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
     /// Returns true if the [`Socket`] has a websocket [`TransportType`]
     pub(crate) fn is_ws(&self) -> bool {
         self.transport.load(Ordering::Relaxed) == TransportType::Websocket as u8
@@ -400,6 +497,34 @@ where
         Ok(PermitIterator { inner })
     }
 
+    /// Reserve `n` permits to emit multiple messages, waiting for enough space in the internal chan.
+    ///
+    /// If the socket is closed, the function will return a [`SendError`] error.
+    ///
+    /// This method is cancel safe, dropping the future before completion
+    /// does not reserve any permit. However the place in the queue of waiting senders is lost.
+    #[inline]
+    pub async fn reserve_async(&self, n: usize) -> Result<PermitIterator<'_>, SendError<()>> {
+        let inner = self.internal_tx.reserve_many(n).await?;
+        Ok(PermitIterator { inner })
+    }
+
+    /// Returns the number of packets waiting in the internal chan to be written to the transport.
+    ///
+    /// It includes the reserved permits that have not been used yet,
+    /// as well as the ping/pong and close packets queued by the engine.
+    #[inline]
+    pub fn queue_len(&self) -> usize {
+        self.internal_tx.max_capacity() - self.internal_tx.capacity()
+    }
+
+    /// Returns the maximum number of packets the internal chan can hold,
+    /// it is the [`max_buffer_size`](crate::config::EngineIoConfigBuilder::max_buffer_size) of the config.
+    #[inline]
+    pub fn queue_capacity(&self) -> usize {
+        self.internal_tx.max_capacity()
+    }
+
     /// Emits a message to the client.
     ///
     /// If the transport is in websocket mode, the message is directly sent as a text frame.
@@ -503,6 +628,7 @@ where
             heartbeat_rx: Mutex::new(heartbeat_rx),
             heartbeat_tx,
             heartbeat_handle: Mutex::new(None),
+            latency: AtomicU64::new(0),
             close_fn,
 
             data: D::default(),
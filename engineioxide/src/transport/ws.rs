@@ -117,7 +117,9 @@ where
             Some(socket) if socket.is_ws() => return Err(Error::Upgrade),
             Some(socket) => {
                 let mut ws = ws_init().await;
+                engine.handler.on_upgrade_start(socket.clone());
                 upgrade_handshake::<H, S>(&socket, &mut ws).await?;
+                engine.handler.on_upgrade(socket.clone());
                 (socket, ws)
             }
         }
@@ -4,24 +4,33 @@
 //!
 //! - [`AckStream`]: A [`Stream`]/[`Future`] of [`AckResponse`] received from the client.
 //! - [`AckResponse`]: An acknowledgement sent by the client.
+//!
+//! Distributed adapters can collect the acknowledgements of the sockets of other nodes
+//! with [`AckInnerStream::with_remote`] and a [`RemoteAckSender`].
 use std::{
-    fmt::Debug,
+    collections::VecDeque,
+    fmt::{Debug, Display},
     pin::Pin,
+    str::FromStr,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 
 use engineioxide::sid::Sid;
 use futures::{
-    future::FusedFuture,
+    future::{BoxFuture, FusedFuture},
     stream::{FusedStream, FuturesUnordered},
     Future, Stream,
 };
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use tokio::{sync::oneshot::Receiver, time::Timeout};
+use tokio::sync::{mpsc, oneshot::Receiver};
 
-use crate::{adapter::Adapter, errors::AckError, extract::SocketRef, packet::Packet, SocketError};
+use crate::{
+    adapter::Adapter, clock::Clock, errors::AckError, extract::SocketRef, packet::Packet,
+    SocketError,
+};
 
 /// An acknowledgement sent by the client.
 /// It contains the data sent by the client and the binary payloads if there are any.
@@ -42,7 +51,18 @@ pin_project_lite::pin_project! {
     pub struct AckResultWithId<T> {
         id: Sid,
         #[pin]
-        result: Timeout<Receiver<AckResult<T>>>,
+        result: Receiver<AckResult<T>>,
+        timeout: BoxFuture<'static, ()>,
+    }
+}
+
+impl<T> AckResultWithId<T> {
+    fn new(id: Sid, result: Receiver<AckResult<T>>, duration: Duration, clock: &dyn Clock) -> Self {
+        Self {
+            id,
+            result,
+            timeout: clock.sleep(duration),
+        }
     }
 }
 
@@ -54,14 +74,16 @@ impl<T> Future for AckResultWithId<T> {
         match project.result.poll(cx) {
             Poll::Ready(v) => {
                 let v = match v {
-                    Ok(Ok(Ok(v))) => Ok(v),
-                    Ok(Ok(Err(e))) => Err(e),
-                    Ok(Err(_)) => Err(AckError::Socket(SocketError::Closed(()))),
-                    Err(_) => Err(AckError::Timeout),
+                    Ok(Ok(v)) => Ok(v),
+                    Ok(Err(e)) => Err(e),
+                    Err(_) => Err(AckError::Socket(SocketError::Closed(()))),
                 };
                 Poll::Ready((*project.id, v))
             }
-            Poll::Pending => Poll::Pending,
+            Poll::Pending => match project.timeout.as_mut().poll(cx) {
+                Poll::Ready(()) => Poll::Ready((*project.id, Err(AckError::Timeout))),
+                Poll::Pending => Poll::Pending,
+            },
         }
     }
 }
@@ -82,6 +104,14 @@ pin_project_lite::pin_project! {
     /// an [`AckError::Timeout`]. If the data sent by the client is not deserializable as `T`,
     /// an [`AckError::Serde`] will be yielded.
     ///
+    /// When used as a stream, each socket yields exactly one item, whether it is a response or an error:
+    /// a socket timing out doesn't end the stream for the sockets that can still respond.
+    /// The stream ends once every socket has resolved.
+    ///
+    /// The ack arguments are received as an array, so a client acking with several arguments
+    /// (`callback(a, b)`) can be deserialized into a tuple such as `(A, B)`. If the number of arguments
+    /// does not match the tuple size, an [`AckError::Serde`] will be yielded.
+    ///
     /// An [`AckStream`] can be created from:
     /// * The [`SocketRef::emit_with_ack`] method, in this case there will be only one [`AckResponse`].
     /// * The [`Operator::emit_with_ack`] method, in this case there will be as many [`AckResponse`]
@@ -115,10 +145,203 @@ pin_project_lite::pin_project! {
     pub struct AckStream<T> {
         #[pin]
         inner: AckInnerStream,
+        ack_id: Option<i64>,
         _marker: std::marker::PhantomData<T>,
     }
 }
 
+/// The extra time the node that broadcast a packet waits for the acknowledgements of the remote nodes,
+/// so that the ack timeouts of the remote sockets can still be forwarded to it, see [`AckInnerStream::with_remote`].
+pub const REMOTE_ACK_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// The identifier of a broadcast with acknowledgements forwarded to the other nodes by a distributed adapter.
+///
+/// It is sent along with the packet so that the remote nodes can tag the acknowledgements
+/// they send back to the originating node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AckRequestId(Sid);
+
+impl AckRequestId {
+    /// Generates a new random request id.
+    pub fn new() -> Self {
+        Self(Sid::new())
+    }
+}
+
+impl Default for AckRequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Display for AckRequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for AckRequestId {
+    type Err = <Sid as FromStr>::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Sid::from_str(s).map(Self)
+    }
+}
+
+#[derive(Debug)]
+enum RemoteAck {
+    /// A remote node sent the packet to this number of sockets
+    Expect(usize),
+    /// The acknowledgement of a remote socket
+    Response(Sid, AckResult<Value>),
+}
+
+/// A handle used by a distributed adapter to forward the acknowledgements of the sockets of remote nodes
+/// to the [`AckStream`] of the node that broadcast the packet, see [`AckInnerStream::with_remote`].
+///
+/// Each remote node should first announce the number of sockets it sent the packet to with
+/// [`RemoteAckSender::expect`], then forward each acknowledgement with [`RemoteAckSender::send`].
+#[derive(Debug, Clone)]
+pub struct RemoteAckSender {
+    id: AckRequestId,
+    timeout: Duration,
+    tx: mpsc::UnboundedSender<RemoteAck>,
+}
+
+impl RemoteAckSender {
+    /// The id of the request, to match the acknowledgements received from the remote nodes.
+    pub fn id(&self) -> AckRequestId {
+        self.id
+    }
+
+    /// The time to wait for the acknowledgements of the sockets.
+    /// It should be sent to the remote nodes so that they use it for their own sockets.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Announces that a remote node sent the packet to `count` sockets.
+    /// It must be called once by each remote node, before forwarding its acknowledgements.
+    ///
+    /// Returns `false` if the [`AckStream`] was dropped or timed out.
+    pub fn expect(&self, count: usize) -> bool {
+        self.tx.send(RemoteAck::Expect(count)).is_ok()
+    }
+
+    /// Forwards the acknowledgement of the remote socket `sid`.
+    ///
+    /// Returns `false` if the [`AckStream`] was dropped or timed out.
+    pub fn send(&self, sid: Sid, ack: Result<AckResponse<Value>, AckError<()>>) -> bool {
+        self.tx.send(RemoteAck::Response(sid, ack)).is_ok()
+    }
+}
+
+/// The acknowledgements expected from the remote nodes
+struct RemoteAcks {
+    rx: mpsc::UnboundedReceiver<RemoteAck>,
+    pending_nodes: usize,
+    pending_acks: usize,
+    timeout: BoxFuture<'static, ()>,
+    done: bool,
+}
+
+impl RemoteAcks {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<(Sid, AckResult<Value>)>> {
+        if self.done {
+            return Poll::Ready(None);
+        }
+        loop {
+            if self.pending_nodes == 0 && self.pending_acks == 0 {
+                self.done = true;
+                return Poll::Ready(None);
+            }
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(RemoteAck::Expect(count))) => {
+                    self.pending_nodes = self.pending_nodes.saturating_sub(1);
+                    self.pending_acks += count;
+                }
+                Poll::Ready(Some(RemoteAck::Response(sid, ack))) => {
+                    self.pending_acks = self.pending_acks.saturating_sub(1);
+                    return Poll::Ready(Some((sid, ack)));
+                }
+                // Every sender was dropped, no more acknowledgement can be received
+                Poll::Ready(None) => {
+                    self.done = true;
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => break,
+            }
+        }
+        match self.timeout.as_mut().poll(cx) {
+            Poll::Ready(()) => {
+                self.done = true;
+                self.rx.close();
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Debug for RemoteAcks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteAcks")
+            .field("pending_nodes", &self.pending_nodes)
+            .field("pending_acks", &self.pending_acks)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+/// A deferred send of the packet to a socket, returning its acknowledgement
+type PendingSend = Box<dyn FnOnce() -> AckResultWithId<Value> + Send>;
+
+/// The sockets that are not sent the packet yet because
+/// the maximum number of awaited acknowledgements is reached
+#[derive(Default)]
+struct PendingAcks {
+    sends: VecDeque<PendingSend>,
+    max_in_flight: usize,
+}
+
+impl PendingAcks {
+    /// Sends the packet to the pending sockets until `max_in_flight` acknowledgements are awaited
+    fn fill(&mut self, rxs: &FuturesUnordered<AckResultWithId<Value>>) {
+        while rxs.len() < self.max_in_flight {
+            match self.sends.pop_front() {
+                Some(send) => rxs.push(send()),
+                None => break,
+            }
+        }
+    }
+
+    fn poll_next(
+        &mut self,
+        mut rxs: Pin<&mut FuturesUnordered<AckResultWithId<Value>>>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<(Sid, AckResult<Value>)>> {
+        self.fill(&rxs);
+        let res = rxs.as_mut().poll_next(cx);
+        if matches!(res, Poll::Ready(Some(_))) {
+            self.fill(&rxs);
+        }
+        res
+    }
+
+    fn len(&self) -> usize {
+        self.sends.len()
+    }
+}
+
+impl Debug for PendingAcks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingAcks")
+            .field("sends", &self.sends.len())
+            .field("max_in_flight", &self.max_in_flight)
+            .finish()
+    }
+}
+
 pin_project_lite::pin_project! {
     #[allow(missing_docs)]
     #[project = InnerProj]
@@ -128,6 +351,7 @@ pin_project_lite::pin_project! {
         Stream {
             #[pin]
             rxs: FuturesUnordered<AckResultWithId<Value>>,
+            pending: PendingAcks,
         },
 
         Fut {
@@ -135,6 +359,13 @@ pin_project_lite::pin_project! {
             rx: AckResultWithId<Value>,
             polled: bool,
         },
+
+        Remote {
+            #[pin]
+            local: FuturesUnordered<AckResultWithId<Value>>,
+            pending: PendingAcks,
+            remotes: Vec<RemoteAcks>,
+        },
     }
 }
 
@@ -151,33 +382,153 @@ impl AckInnerStream {
         packet: Packet<'static>,
         sockets: Vec<SocketRef<A>>,
         duration: Option<Duration>,
+    ) -> Self {
+        Self::broadcast_windowed(packet, sockets, duration, None)
+    }
+
+    /// Creates a new [`AckInnerStream`] from a [`Packet`] and a list of sockets, like [`AckInnerStream::broadcast`],
+    /// but with at most `max_in_flight` acknowledgements awaited at the same time.
+    ///
+    /// The packet is sent to the first `max_in_flight` sockets right away, and to the next ones
+    /// as the stream yields the previous acknowledgements. The timeout of each acknowledgement starts
+    /// when the packet is sent to its socket, and the remaining sockets are not sent the packet if the stream
+    /// is dropped. With `None`, the packet is sent to all the sockets at once.
+    pub fn broadcast_windowed<A: Adapter>(
+        packet: Packet<'static>,
+        sockets: Vec<SocketRef<A>>,
+        duration: Option<Duration>,
+        max_in_flight: Option<usize>,
     ) -> Self {
         let rxs = FuturesUnordered::new();
+        let mut pending = PendingAcks::default();
 
         if sockets.is_empty() {
-            return AckInnerStream::Stream { rxs };
+            return AckInnerStream::Stream { rxs, pending };
         }
 
-        let duration = duration.unwrap_or_else(|| sockets.first().unwrap().config.ack_timeout);
+        let duration = duration.unwrap_or_else(|| sockets.first().unwrap().ack_timeout());
+        let send = move |socket: SocketRef<A>, packet: Packet<'static>| {
+            let rx = socket.send_with_ack(packet);
+            AckResultWithId::new(socket.id, rx, duration, socket.config.clock.as_ref())
+        };
+        let max_in_flight = max_in_flight.map_or(usize::MAX, |max| max.max(1));
+        let mut sockets = sockets.into_iter();
+        for socket in sockets.by_ref().take(max_in_flight) {
+            rxs.push(send(socket, packet.clone()));
+        }
+
+        let packet = Arc::new(packet);
+        pending.max_in_flight = max_in_flight;
+        pending.sends = sockets
+            .map(|socket| {
+                let packet = packet.clone();
+                Box::new(move || send(socket, packet.as_ref().clone())) as PendingSend
+            })
+            .collect();
+        AckInnerStream::Stream { rxs, pending }
+    }
+
+    /// Creates a new [`AckInnerStream`] sending a different [`Packet`] to each socket.
+    ///
+    /// If the packet of a socket cannot be created, nothing is sent to it and
+    /// its [`AckError::Serde`] is yielded by the stream.
+    pub(crate) fn broadcast_each<A: Adapter>(
+        sockets: Vec<SocketRef<A>>,
+        duration: Option<Duration>,
+        packet: impl Fn(Sid) -> Result<Packet<'static>, serde_json::Error>,
+    ) -> Self {
+        let rxs = FuturesUnordered::new();
         for socket in sockets {
-            let rx = socket.send_with_ack(packet.clone());
-            rxs.push(AckResultWithId {
-                result: tokio::time::timeout(duration, rx),
-                id: socket.id,
-            });
+            let duration = duration.unwrap_or_else(|| socket.ack_timeout());
+            let rx = match packet(socket.id) {
+                Ok(packet) => socket.send_with_ack(packet),
+                Err(e) => {
+                    let (tx, rx) = tokio::sync::oneshot::channel();
+                    tx.send(Err(AckError::Serde(e))).ok();
+                    rx
+                }
+            };
+            rxs.push(AckResultWithId::new(
+                socket.id,
+                rx,
+                duration,
+                socket.config.clock.as_ref(),
+            ));
         }
-        AckInnerStream::Stream { rxs }
+        AckInnerStream::Stream {
+            rxs,
+            pending: PendingAcks::default(),
+        }
+    }
+
+    /// Extends a local [`AckInnerStream`] with the acknowledgements of the sockets of `nodes` remote nodes.
+    ///
+    /// A distributed adapter calls it in [`Adapter::broadcast_with_ack`]
+    /// and forwards the packet to the other nodes with the [`RemoteAckSender::id`] and [`RemoteAckSender::timeout`].
+    /// The returned [`RemoteAckSender`] is then used to forward the acknowledgements received from the remote nodes.
+    ///
+    /// The stream ends when all the local and remote acknowledgements are received.
+    /// The remote part stops [`REMOTE_ACK_GRACE_PERIOD`] after the end of the `duration`, measured with
+    /// the given [`Clock`]: the acknowledgements forwarded afterwards are dropped.
+    ///
+    /// If `local` already collects remote acknowledgements, the new remote nodes are added to them,
+    /// each [`RemoteAckSender`] keeping its own id and timeout.
+    pub fn with_remote(
+        local: Self,
+        nodes: usize,
+        duration: Duration,
+        clock: &dyn Clock,
+    ) -> (Self, RemoteAckSender) {
+        let (local, pending, mut remotes) = match local {
+            AckInnerStream::Stream { rxs, pending } => (rxs, pending, Vec::new()),
+            AckInnerStream::Fut { rx, polled } => {
+                let rxs = if polled {
+                    FuturesUnordered::new()
+                } else {
+                    FuturesUnordered::from_iter([rx])
+                };
+                (rxs, PendingAcks::default(), Vec::new())
+            }
+            AckInnerStream::Remote {
+                local,
+                pending,
+                remotes,
+            } => (local, pending, remotes),
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        remotes.push(RemoteAcks {
+            rx,
+            pending_nodes: nodes,
+            pending_acks: 0,
+            timeout: clock.sleep(duration + REMOTE_ACK_GRACE_PERIOD),
+            done: false,
+        });
+        let sender = RemoteAckSender {
+            id: AckRequestId::new(),
+            timeout: duration,
+            tx,
+        };
+        (
+            AckInnerStream::Remote {
+                local,
+                pending,
+                remotes,
+            },
+            sender,
+        )
     }
 
     /// Creates a new [`AckInnerStream`] from a [`oneshot::Receiver`](tokio) corresponding to the acknowledgement
-    /// of a single socket.
-    pub fn send(rx: Receiver<AckResult<Value>>, duration: Duration, id: Sid) -> Self {
+    /// of a single socket. The timeout is measured with the given [`Clock`].
+    pub fn send(
+        rx: Receiver<AckResult<Value>>,
+        duration: Duration,
+        id: Sid,
+        clock: &dyn Clock,
+    ) -> Self {
         AckInnerStream::Fut {
             polled: false,
-            rx: AckResultWithId {
-                id,
-                result: tokio::time::timeout(duration, rx),
-            },
+            rx: AckResultWithId::new(id, rx, duration, clock),
         }
     }
 }
@@ -190,7 +541,7 @@ impl Stream for AckInnerStream {
 
         match self.project() {
             Fut { polled, .. } if *polled => Poll::Ready(None),
-            Stream { rxs } => rxs.poll_next(cx),
+            Stream { rxs, pending } => pending.poll_next(rxs, cx),
             Fut { rx, polled } => match rx.poll(cx) {
                 Poll::Ready(val) => {
                     *polled = true;
@@ -198,14 +549,41 @@ impl Stream for AckInnerStream {
                 }
                 Poll::Pending => Poll::Pending,
             },
+            Remote {
+                local,
+                pending,
+                remotes,
+            } => {
+                let mut done = match pending.poll_next(local, cx) {
+                    Poll::Ready(Some(val)) => return Poll::Ready(Some(val)),
+                    Poll::Ready(None) => true,
+                    Poll::Pending => false,
+                };
+                for remote in remotes.iter_mut() {
+                    match remote.poll_next(cx) {
+                        Poll::Ready(Some(val)) => return Poll::Ready(Some(val)),
+                        Poll::Ready(None) => (),
+                        Poll::Pending => done = false,
+                    }
+                }
+                if done {
+                    Poll::Ready(None)
+                } else {
+                    Poll::Pending
+                }
+            }
         }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         use AckInnerStream::*;
         match self {
-            Stream { rxs, .. } => rxs.size_hint(),
+            Stream { rxs, pending } => {
+                let len = rxs.len() + pending.len();
+                (len, Some(len))
+            }
             Fut { .. } => (1, Some(1)),
+            Remote { local, pending, .. } => (local.len() + pending.len(), None),
         }
     }
 }
@@ -214,8 +592,13 @@ impl FusedStream for AckInnerStream {
     fn is_terminated(&self) -> bool {
         use AckInnerStream::*;
         match self {
-            Stream { rxs, .. } => rxs.is_terminated(),
+            Stream { rxs, pending } => rxs.is_terminated() && pending.len() == 0,
             Fut { polled, .. } => *polled,
+            Remote {
+                local,
+                pending,
+                remotes,
+            } => local.is_terminated() && pending.len() == 0 && remotes.iter().all(|r| r.done),
         }
     }
 }
@@ -238,8 +621,13 @@ impl FusedFuture for AckInnerStream {
     fn is_terminated(&self) -> bool {
         use AckInnerStream::*;
         match self {
-            Stream { rxs, .. } => rxs.is_terminated(),
+            Stream { rxs, pending } => rxs.is_terminated() && pending.len() == 0,
             Fut { polled, .. } => *polled,
+            Remote {
+                local,
+                pending,
+                remotes,
+            } => local.is_terminated() && pending.len() == 0 && remotes.iter().all(|r| r.done),
         }
     }
 }
@@ -286,10 +674,30 @@ impl<T: DeserializeOwned> FusedFuture for AckStream<T> {
     }
 }
 
+impl<T> AckStream<T> {
+    /// Gets the id of the ack request, when the packet was emitted to a single socket
+    /// with [`SocketRef::emit_with_ack`]. It can be used to cancel the ack with [`Socket::cancel_ack`].
+    ///
+    /// It is `None` for broadcasts and when the packet was not sent.
+    ///
+    /// [`SocketRef::emit_with_ack`]: crate::extract::SocketRef#method.emit_with_ack
+    /// [`Socket::cancel_ack`]: crate::socket::Socket#method.cancel_ack
+    #[inline]
+    pub fn ack_id(&self) -> Option<i64> {
+        self.ack_id
+    }
+
+    pub(crate) fn with_ack_id(mut self, ack_id: Option<i64>) -> Self {
+        self.ack_id = ack_id;
+        self
+    }
+}
+
 impl<T> From<AckInnerStream> for AckStream<T> {
     fn from(inner: AckInnerStream) -> Self {
         Self {
             inner,
+            ack_id: None,
             _marker: std::marker::PhantomData,
         }
     }
@@ -297,12 +705,23 @@ impl<T> From<AckInnerStream> for AckStream<T> {
 
 fn map_ack_response<T: DeserializeOwned>(ack: AckResult<Value>) -> AckResult<T> {
     ack.and_then(|v| {
+        // The ack arguments are always received as an array, report its length
+        // to make arity mismatches with tuple types easier to diagnose.
+        let arity = match &v.data {
+            Value::Array(args) => Some(args.len()),
+            _ => None,
+        };
         serde_json::from_value(v.data)
             .map(|data| AckResponse {
                 data,
                 binary: v.binary,
             })
-            .map_err(|e| e.into())
+            .map_err(|e| match arity {
+                Some(n) => AckError::Serde(serde::de::Error::custom(format_args!(
+                    "cannot deserialize ack with {n} argument(s): {e}"
+                ))),
+                None => e.into(),
+            })
     })
 }
 
@@ -313,7 +732,14 @@ mod test {
     use engineioxide::sid::Sid;
     use futures::StreamExt;
 
-    use crate::{adapter::LocalAdapter, ns::Namespace, socket::Socket};
+    use crate::{
+        adapter::{BroadcastOptions, LocalAdapter, Room},
+        clock::{ManualClock, TokioClock},
+        errors::{BroadcastError, DisconnectError},
+        ns::Namespace,
+        operators::{BroadcastOperators, RoomParam},
+        socket::Socket,
+    };
 
     use super::*;
 
@@ -344,12 +770,47 @@ mod test {
         assert!(stream.next().await.is_none());
     }
 
+    #[tokio::test]
+    async fn broadcast_ack_windowed() {
+        let sockets = [create_socket(), create_socket(), create_socket()];
+        let packet = Packet::event("/", "test", "test".into());
+        let socks = sockets.iter().cloned().map(Into::into).collect();
+        let stream: AckStream<String> =
+            AckInnerStream::broadcast_windowed(packet, socks, None, Some(2)).into();
+        futures::pin_mut!(stream);
+
+        let in_flight = || {
+            sockets
+                .iter()
+                .filter(|s| !s.pending_acks().is_empty())
+                .count()
+        };
+        assert_eq!(in_flight(), 2);
+        assert_eq!(stream.size_hint(), (3, Some(3)));
+
+        let res_packet = Packet::ack("test", "test".into(), 1);
+        sockets[1].clone().recv(res_packet.inner.clone()).unwrap();
+        let (sid, ack) = stream.next().await.unwrap();
+        assert_eq!(sid, sockets[1].id);
+        assert!(ack.is_ok());
+        // The packet is sent to the last socket once an acknowledgement is received
+        assert_eq!(in_flight(), 2);
+        assert_eq!(sockets[2].pending_acks(), [1]);
+
+        sockets[0].clone().recv(res_packet.inner.clone()).unwrap();
+        sockets[2].clone().recv(res_packet.inner).unwrap();
+        assert!(stream.next().await.unwrap().1.is_ok());
+        assert!(stream.next().await.unwrap().1.is_ok());
+        assert!(stream.next().await.is_none());
+        assert_eq!(in_flight(), 0);
+    }
+
     #[tokio::test]
     async fn ack_stream() {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         tx.send(Ok(AckResponse {
             data: Value::String("test".into()),
             binary: vec![],
@@ -370,7 +831,7 @@ mod test {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         tx.send(Ok(AckResponse {
             data: Value::String("test".into()),
             binary: vec![],
@@ -411,7 +872,7 @@ mod test {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         tx.send(Ok(AckResponse {
             data: Value::Bool(true),
             binary: vec![],
@@ -434,7 +895,7 @@ mod test {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         tx.send(Ok(AckResponse {
             data: Value::Bool(true),
             binary: vec![],
@@ -444,6 +905,39 @@ mod test {
         assert!(matches!(stream.await.unwrap_err(), AckError::Serde(_)));
     }
 
+    #[tokio::test]
+    async fn ack_fut_with_tuple() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sid = Sid::new();
+        let stream: AckStream<(String, i32)> =
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
+        tx.send(Ok(AckResponse {
+            data: serde_json::json!(["test", 1]),
+            binary: vec![],
+        }))
+        .unwrap();
+
+        assert_eq!(stream.await.unwrap().data, ("test".to_string(), 1));
+    }
+
+    #[tokio::test]
+    async fn ack_fut_with_tuple_arity_mismatch() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sid = Sid::new();
+        let stream: AckStream<(String, i32)> =
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
+        tx.send(Ok(AckResponse {
+            data: serde_json::json!(["test"]),
+            binary: vec![],
+        }))
+        .unwrap();
+
+        match stream.await.unwrap_err() {
+            AckError::Serde(e) => assert!(e.to_string().contains("with 1 argument(s)")),
+            e => panic!("unexpected error: {e:?}"),
+        }
+    }
+
     #[tokio::test]
     async fn broadcast_ack_with_closed_socket() {
         let socket = create_socket();
@@ -474,7 +968,7 @@ mod test {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         drop(tx);
 
         futures::pin_mut!(stream);
@@ -490,7 +984,7 @@ mod test {
         let (tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_secs(1), sid).into();
+            AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock).into();
         drop(tx);
 
         assert!(matches!(
@@ -528,7 +1022,7 @@ mod test {
         let (_tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_millis(10), sid).into();
+            AckInnerStream::send(rx, Duration::from_millis(10), sid, &TokioClock).into();
 
         futures::pin_mut!(stream);
 
@@ -543,8 +1037,210 @@ mod test {
         let (_tx, rx) = tokio::sync::oneshot::channel();
         let sid = Sid::new();
         let stream: AckStream<String> =
-            AckInnerStream::send(rx, Duration::from_millis(10), sid).into();
+            AckInnerStream::send(rx, Duration::from_millis(10), sid, &TokioClock).into();
 
         assert!(matches!(stream.await.unwrap_err(), AckError::Timeout));
     }
+
+    #[tokio::test]
+    async fn ack_fut_with_manual_clock_timeout() {
+        let (_tx, rx) = tokio::sync::oneshot::channel();
+        let sid = Sid::new();
+        let clock = ManualClock::new();
+        let stream: AckStream<String> =
+            AckInnerStream::send(rx, Duration::from_secs(3600), sid, &clock).into();
+        futures::pin_mut!(stream);
+        assert!(futures::poll!(stream.as_mut()).is_pending());
+
+        clock.advance(Duration::from_secs(3600));
+        let res = tokio::time::timeout(Duration::from_millis(10), stream).await;
+        assert!(matches!(res.unwrap().unwrap_err(), AckError::Timeout));
+    }
+
+    /// A mock adapter of a two nodes cluster, the peer node is another namespace
+    #[derive(Debug)]
+    struct MockAdapter {
+        ns: std::sync::Weak<Namespace<Self>>,
+        peer: std::sync::Mutex<Option<Arc<Namespace<Self>>>>,
+    }
+
+    impl Adapter for MockAdapter {
+        type Error = std::convert::Infallible;
+
+        fn new(ns: std::sync::Weak<Namespace<Self>>) -> Self {
+            Self {
+                ns,
+                peer: Default::default(),
+            }
+        }
+        fn init(&self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn close(&self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn server_count(&self) -> Result<u16, Self::Error> {
+            Ok(2)
+        }
+        fn add_all(&self, _: Sid, _: impl RoomParam) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn del(&self, _: Sid, _: impl RoomParam) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn del_all(&self, _: Sid) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        fn broadcast(&self, _: Packet<'_>, _: BroadcastOptions) -> Result<(), BroadcastError> {
+            unimplemented!()
+        }
+        fn broadcast_with_ack(
+            &self,
+            packet: Packet<'static>,
+            _: BroadcastOptions,
+            timeout: Option<Duration>,
+        ) -> AckInnerStream {
+            let sockets = self.ns.upgrade().unwrap().get_sockets();
+            let local = AckInnerStream::broadcast(packet.clone(), into_refs(sockets), timeout);
+            let duration = timeout.unwrap_or(Duration::from_secs(5));
+            let (stream, remote) = AckInnerStream::with_remote(local, 1, duration, &TokioClock);
+
+            // "Forward" the packet to the peer node, which sends back the acks of its sockets
+            let peer = self.peer.lock().unwrap().clone().unwrap();
+            let sockets = peer.get_sockets();
+            remote.expect(sockets.len());
+            let acks =
+                AckInnerStream::broadcast(packet, into_refs(sockets), Some(remote.timeout()));
+            tokio::spawn(acks.for_each(move |(sid, ack)| {
+                remote.send(sid, ack);
+                async {}
+            }));
+            stream
+        }
+        fn sockets(&self, _: impl RoomParam) -> Result<Vec<Sid>, Self::Error> {
+            unimplemented!()
+        }
+        fn socket_rooms(&self, _: Sid) -> Result<Vec<Room>, Self::Error> {
+            unimplemented!()
+        }
+        fn fetch_sockets(&self, _: BroadcastOptions) -> Result<Vec<SocketRef<Self>>, Self::Error> {
+            unimplemented!()
+        }
+        fn add_sockets(&self, _: BroadcastOptions, _: impl RoomParam) -> Result<(), Self::Error> {
+            unimplemented!()
+        }
+        fn del_sockets(&self, _: BroadcastOptions, _: impl RoomParam) -> Result<(), Self::Error> {
+            unimplemented!()
+        }
+        fn disconnect_socket(&self, _: BroadcastOptions) -> Result<(), Vec<DisconnectError>> {
+            unimplemented!()
+        }
+        fn rooms(&self) -> Result<Vec<Room>, Self::Error> {
+            unimplemented!()
+        }
+    }
+
+    fn into_refs(sockets: Vec<Arc<Socket<MockAdapter>>>) -> Vec<SocketRef<MockAdapter>> {
+        sockets.into_iter().map(SocketRef::from).collect()
+    }
+
+    #[tokio::test]
+    async fn broadcast_ack_with_remote_node() {
+        let (local_sid, remote_sid, silent_sid) = (Sid::new(), Sid::new(), Sid::new());
+        let origin = Namespace::<MockAdapter>::new_dummy([local_sid]);
+        let peer = Namespace::<MockAdapter>::new_dummy([remote_sid, silent_sid]);
+        origin.adapter.peer.lock().unwrap().replace(peer.clone());
+
+        let stream = BroadcastOperators::new(origin.clone())
+            .broadcast()
+            .timeout(Duration::from_millis(50))
+            .emit_with_ack::<String>("test", "foo")
+            .unwrap();
+
+        let ack = Packet::ack("/", "local".into(), 1);
+        origin
+            .get_socket(local_sid)
+            .unwrap()
+            .recv(ack.inner)
+            .unwrap();
+        let ack = Packet::ack("/", "remote".into(), 1);
+        peer.get_socket(remote_sid)
+            .unwrap()
+            .recv(ack.inner)
+            .unwrap();
+
+        let mut acks = stream
+            .map(|(sid, ack)| (sid, ack.map(|a| a.data)))
+            .collect::<Vec<_>>()
+            .await;
+        acks.sort_by_key(|(sid, _)| {
+            [local_sid, remote_sid, silent_sid]
+                .iter()
+                .position(|s| s == sid)
+        });
+        assert_eq!(acks.len(), 3);
+        assert_eq!(acks[0].0, local_sid);
+        assert_eq!(acks[0].1.as_ref().unwrap(), "local");
+        assert_eq!(acks[1].0, remote_sid);
+        assert_eq!(acks[1].1.as_ref().unwrap(), "remote");
+        // The remote node forwards the timeout of its silent socket
+        assert_eq!(acks[2].0, silent_sid);
+        assert!(matches!(acks[2].1, Err(AckError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn remote_acks_stop_at_timeout() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sid = Sid::new();
+        let local = AckInnerStream::send(rx, Duration::from_secs(1), sid, &TokioClock);
+        let (stream, remote) =
+            AckInnerStream::with_remote(local, 2, Duration::from_millis(20), &TokioClock);
+        let stream: AckStream<String> = stream.into();
+        tx.send(Ok(AckResponse {
+            data: Value::String("local".into()),
+            binary: vec![],
+        }))
+        .unwrap();
+
+        // Only one of the two remote nodes answers, with one of its two sockets
+        assert!(remote.expect(2));
+        assert!(remote.send(
+            Sid::new(),
+            Ok(AckResponse {
+                data: Value::String("remote".into()),
+                binary: vec![],
+            })
+        ));
+        let acks = stream.collect::<Vec<_>>().await;
+        assert_eq!(acks.len(), 2);
+        assert!(!remote.send(Sid::new(), Err(AckError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn with_remote_chained() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let local = AckInnerStream::send(rx, Duration::from_secs(1), Sid::new(), &TokioClock);
+        let duration = Duration::from_secs(1);
+        let (stream, remote1) = AckInnerStream::with_remote(local, 1, duration, &TokioClock);
+        let (stream, remote2) = AckInnerStream::with_remote(stream, 1, duration, &TokioClock);
+        let stream: AckStream<String> = stream.into();
+        let ack = |data: &str| {
+            Ok(AckResponse {
+                data: Value::String(data.into()),
+                binary: vec![],
+            })
+        };
+        tx.send(ack("local")).unwrap();
+        assert!(remote1.expect(1));
+        assert!(remote1.send(Sid::new(), ack("remote1")));
+        assert!(remote2.expect(1));
+        assert!(remote2.send(Sid::new(), ack("remote2")));
+
+        let mut acks = stream
+            .map(|(_, ack)| ack.unwrap().data)
+            .collect::<Vec<_>>()
+            .await;
+        acks.sort();
+        assert_eq!(acks, ["local", "remote1", "remote2"]);
+    }
 }
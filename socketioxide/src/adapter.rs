@@ -8,7 +8,7 @@ use std::{
     collections::{HashMap, HashSet},
     convert::Infallible,
     sync::{RwLock, Weak},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use engineioxide::sid::Sid;
@@ -26,6 +26,16 @@ use crate::{
 /// A room identifier
 pub type Room = Cow<'static, str>;
 
+/// A change of the membership of a socket in a room,
+/// see [`SocketIo::on_room_change`](crate::SocketIo#method.on_room_change).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomChange {
+    /// The socket joined the room
+    Join,
+    /// The socket left the room
+    Leave,
+}
+
 /// Flags that can be used to modify the behavior of the broadcast methods.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BroadcastFlags {
@@ -44,8 +54,20 @@ pub struct BroadcastOptions {
     pub rooms: HashSet<Room>,
     /// The rooms to exclude from the broadcast.
     pub except: HashSet<Room>,
+    /// The sockets to exclude from the broadcast.
+    pub except_sids: HashSet<Sid>,
+    /// The local tags to broadcast to, see [`Socket::add_tag`](crate::socket::Socket::add_tag).
+    /// They are not part of the rooms and must not be sent to the other nodes.
+    pub tags: HashSet<Cow<'static, str>>,
     /// The socket id of the sender.
     pub sid: Option<Sid>,
+    /// Only select the sockets connected at or after this instant.
+    /// The instant is only meaningful on this server node: the remote adapters must ignore it
+    /// when the options are sent to the other nodes.
+    pub connected_after: Option<Instant>,
+    /// The maximum number of acknowledgements awaited at the same time by
+    /// [`Adapter::broadcast_with_ack`], see [`AckInnerStream::broadcast_windowed`].
+    pub max_in_flight: Option<usize>,
 }
 //TODO: Make an AsyncAdapter trait
 /// An adapter is responsible for managing the state of the server.
@@ -69,7 +91,52 @@ pub trait Adapter: std::fmt::Debug + Send + Sync + 'static {
     fn server_count(&self) -> Result<u16, Self::Error>;
 
     /// Adds the socket to all the rooms.
+    ///
+    /// The adapters should call `Namespace::notify_room_change` for each room the socket was not already in,
+    /// once the membership is changed. The same applies to [`Adapter::add_new`], [`Adapter::add_many`],
+    /// [`Adapter::del`], [`Adapter::del_all`] and [`Adapter::set_rooms`] with the changed memberships.
     fn add_all(&self, sid: Sid, rooms: impl RoomParam) -> Result<(), Self::Error>;
+    /// Adds the socket to the rooms and returns the ones it was not already in.
+    ///
+    /// The default implementation compares the rooms with [`Adapter::socket_rooms`]
+    /// before calling [`Adapter::add_all`], so a concurrent join of the same room can be counted twice.
+    fn add_new(&self, sid: Sid, rooms: impl RoomParam) -> Result<Vec<Room>, Self::Error>
+    where
+        Self: Sized,
+    {
+        let mut current = self.socket_rooms(sid)?;
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        self.add_all(sid, rooms.clone())?;
+        let mut joined = Vec::new();
+        for room in rooms {
+            if !current.contains(&room) {
+                current.push(room.clone());
+                joined.push(room);
+            }
+        }
+        Ok(joined)
+    }
+    /// Adds many sockets to the rooms at once.
+    ///
+    /// A socket that can't be added doesn't prevent the other ones from being added,
+    /// the errors are returned with the id of their socket.
+    /// The default implementation calls [`Adapter::add_all`] for every socket,
+    /// distributed adapters should override it to send a single request.
+    fn add_many(&self, sids: &[Sid], rooms: impl RoomParam) -> Result<(), Vec<(Sid, Self::Error)>>
+    where
+        Self: Sized,
+    {
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        let errors: Vec<_> = sids
+            .iter()
+            .filter_map(|&sid| self.add_all(sid, rooms.clone()).err().map(|e| (sid, e)))
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
     /// Removes the socket from the rooms.
     fn del(&self, sid: Sid, rooms: impl RoomParam) -> Result<(), Self::Error>;
     /// Removes the socket from all the rooms.
@@ -79,6 +146,9 @@ pub trait Adapter: std::fmt::Debug + Send + Sync + 'static {
     fn broadcast(&self, packet: Packet<'_>, opts: BroadcastOptions) -> Result<(), BroadcastError>;
 
     /// Broadcasts the packet to the sockets that match the [`BroadcastOptions`] and return a stream of ack responses.
+    ///
+    /// Distributed adapters can forward the packet to the other nodes and collect the acknowledgements
+    /// of their sockets in the returned stream with [`AckInnerStream::with_remote`].
     fn broadcast_with_ack(
         &self,
         packet: Packet<'static>,
@@ -110,6 +180,142 @@ pub trait Adapter: std::fmt::Debug + Send + Sync + 'static {
     /// Returns all the rooms for this adapter.
     fn rooms(&self) -> Result<Vec<Room>, Self::Error>;
 
+    /// Returns the number of sockets connected to the namespace.
+    /// A socket is counted once, whatever the number of rooms it is in.
+    ///
+    /// Distributed adapters should include the sockets of the other nodes, the count may then be approximate.
+    /// The default implementation counts the sockets returned by [`Adapter::fetch_sockets`].
+    fn sockets_count(&self) -> Result<usize, Self::Error>
+    where
+        Self: Sized,
+    {
+        let opts = BroadcastOptions {
+            flags: HashSet::from([BroadcastFlags::Broadcast]),
+            ..Default::default()
+        };
+        Ok(self.fetch_sockets(opts)?.len())
+    }
+
+    /// Returns the ids of the sockets connected to the namespace.
+    ///
+    /// The id of a socket is the one of its engine.io connection, so a connection attached to several
+    /// namespaces has the same id in each of them.
+    /// The default implementation returns the ids of the sockets returned by [`Adapter::fetch_sockets`].
+    fn socket_ids(&self) -> Result<Vec<Sid>, Self::Error>
+    where
+        Self: Sized,
+    {
+        let opts = BroadcastOptions {
+            flags: HashSet::from([BroadcastFlags::Broadcast]),
+            ..Default::default()
+        };
+        Ok(self
+            .fetch_sockets(opts)?
+            .into_iter()
+            .map(|s| s.id)
+            .collect())
+    }
+
+    /// Returns `true` if the socket is in the given room.
+    /// The default implementation looks for the room in the result of [`Adapter::socket_rooms`].
+    fn is_in_room(&self, sid: Sid, room: &Room) -> Result<bool, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(self.socket_rooms(sid)?.contains(room))
+    }
+
+    /// Returns the rooms of the socket tracked on this node, synchronously and without any network call.
+    /// It may be stale relative to the other nodes of the cluster.
+    ///
+    /// Distributed adapters should override it to read their local state.
+    /// The default implementation calls [`Adapter::socket_rooms`] and returns no room on error.
+    fn local_socket_rooms(&self, sid: Sid) -> Vec<Room>
+    where
+        Self: Sized,
+    {
+        self.socket_rooms(sid).unwrap_or_default()
+    }
+
+    /// Returns `true` if at least one socket is in the given room.
+    /// The default implementation checks the result of [`Adapter::sockets`].
+    fn room_exists(&self, room: &Room) -> Result<bool, Self::Error>
+    where
+        Self: Sized,
+    {
+        Ok(!self.sockets(room.clone())?.is_empty())
+    }
+
+    /// Calls `f` only if the socket is in the given room and returns its result.
+    ///
+    /// Implementations should prevent the membership of the room from changing while `f` runs.
+    /// `f` must therefore not join or leave rooms itself.
+    /// The default implementation only checks the membership with [`Adapter::socket_rooms`]
+    /// before calling `f`.
+    fn run_if_in_room<R>(
+        &self,
+        sid: Sid,
+        room: &Room,
+        f: impl FnOnce() -> R,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        Self: Sized,
+    {
+        let in_room = self.socket_rooms(sid)?.contains(room);
+        Ok(in_room.then(f))
+    }
+
+    /// Calls `f` only if the socket is in the given room, and removes the socket from the room
+    /// if `f` returns `Some`. The result of `f` is returned.
+    ///
+    /// Implementations should call `f` and remove the socket while the membership of the room
+    /// cannot change, so that no broadcast selects the socket in between.
+    /// `f` must therefore not join or leave rooms itself.
+    /// The default implementation checks the membership with [`Adapter::socket_rooms`],
+    /// then calls `f` and [`Adapter::del`].
+    fn del_if_in_room<R>(
+        &self,
+        sid: Sid,
+        room: &Room,
+        f: impl FnOnce() -> Option<R>,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        Self: Sized,
+    {
+        if !self.socket_rooms(sid)?.contains(room) {
+            return Ok(None);
+        }
+        let res = f();
+        if res.is_some() {
+            self.del(sid, room.clone())?;
+        }
+        Ok(res)
+    }
+
+    /// Replaces the rooms of the socket with exactly the given rooms.
+    /// Returns the rooms that were joined and the rooms that were left.
+    ///
+    /// Implementations should apply the change atomically, so that a concurrent broadcast sees either
+    /// the previous or the new room set. The rooms kept are never left.
+    /// The default implementation computes the diff with [`Adapter::socket_rooms`]
+    /// and then calls [`Adapter::del`] and [`Adapter::add_all`].
+    fn set_rooms(
+        &self,
+        sid: Sid,
+        rooms: impl RoomParam,
+    ) -> Result<(Vec<Room>, Vec<Room>), Self::Error>
+    where
+        Self: Sized,
+    {
+        let rooms: HashSet<Room> = rooms.into_room_iter().collect();
+        let current: HashSet<Room> = self.socket_rooms(sid)?.into_iter().collect();
+        let joined: Vec<Room> = rooms.difference(&current).cloned().collect();
+        let left: Vec<Room> = current.difference(&rooms).cloned().collect();
+        self.del(sid, left.clone())?;
+        self.add_all(sid, joined.clone())?;
+        Ok((joined, left))
+    }
+
     //TODO: implement
     // fn server_side_emit(&self, packet: Packet, opts: BroadcastOptions) -> Result<u64, Error>;
     // fn persist_session(&self, sid: i64);
@@ -157,36 +363,77 @@ impl Adapter for LocalAdapter {
     }
 
     fn add_all(&self, sid: Sid, rooms: impl RoomParam) -> Result<(), Infallible> {
-        let mut rooms_map = self.rooms.write().unwrap();
-        for room in rooms.into_room_iter() {
-            rooms_map.entry(room).or_default().insert(sid);
-        }
+        self.add_new(sid, rooms)?;
         Ok(())
     }
 
-    fn del(&self, sid: Sid, rooms: impl RoomParam) -> Result<(), Infallible> {
-        let mut rooms_map = self.rooms.write().unwrap();
-        for room in rooms.into_room_iter() {
-            if let Some(room) = rooms_map.get_mut(&room) {
-                room.remove(&sid);
+    fn add_new(&self, sid: Sid, rooms: impl RoomParam) -> Result<Vec<Room>, Infallible> {
+        let joined: Vec<Room> = {
+            let mut rooms_map = self.rooms.write().unwrap();
+            rooms
+                .into_room_iter()
+                .filter(|room| rooms_map.entry(room.clone()).or_default().insert(sid))
+                .collect()
+        };
+        self.notify_room_changes(joined.iter().map(|room| (sid, room)), RoomChange::Join);
+        Ok(joined)
+    }
+
+    fn add_many(&self, sids: &[Sid], rooms: impl RoomParam) -> Result<(), Vec<(Sid, Infallible)>> {
+        let mut joined = Vec::new();
+        {
+            let mut rooms_map = self.rooms.write().unwrap();
+            for room in rooms.into_room_iter() {
+                let members = rooms_map.entry(room.clone()).or_default();
+                for &sid in sids {
+                    if members.insert(sid) {
+                        joined.push((sid, room.clone()));
+                    }
+                }
             }
         }
+        self.notify_room_changes(
+            joined.iter().map(|(sid, room)| (*sid, room)),
+            RoomChange::Join,
+        );
+        Ok(())
+    }
+
+    fn del(&self, sid: Sid, rooms: impl RoomParam) -> Result<(), Infallible> {
+        let left: Vec<Room> = {
+            let mut rooms_map = self.rooms.write().unwrap();
+            rooms
+                .into_room_iter()
+                .filter(|room| rooms_map.get_mut(room).map_or(false, |m| m.remove(&sid)))
+                .collect()
+        };
+        self.notify_room_changes(left.iter().map(|room| (sid, room)), RoomChange::Leave);
         Ok(())
     }
 
     fn del_all(&self, sid: Sid) -> Result<(), Infallible> {
-        let mut rooms_map = self.rooms.write().unwrap();
-        for room in rooms_map.values_mut() {
-            room.remove(&sid);
-        }
+        let left: Vec<Room> = {
+            let mut rooms_map = self.rooms.write().unwrap();
+            rooms_map
+                .iter_mut()
+                .filter_map(|(room, members)| members.remove(&sid).then(|| room.clone()))
+                .collect()
+        };
+        self.notify_room_changes(left.iter().map(|room| (sid, room)), RoomChange::Leave);
         Ok(())
     }
 
     fn broadcast(&self, packet: Packet<'_>, opts: BroadcastOptions) -> Result<(), BroadcastError> {
+        let sender = opts.sid;
         let sockets = self.apply_opts(opts);
 
         #[cfg(feature = "tracing")]
         tracing::debug!("broadcasting packet to {} sockets", sockets.len());
+        if let Some(ns) = self.ns.upgrade() {
+            ns.mirror_to_observers(&packet, false, |sid| {
+                sender == Some(sid) || sockets.iter().any(|s| s.id == sid)
+            });
+        }
         let errors: Vec<_> = sockets
             .into_iter()
             .filter_map(|socket| socket.send(packet.clone()).err())
@@ -204,6 +451,7 @@ impl Adapter for LocalAdapter {
         opts: BroadcastOptions,
         timeout: Option<Duration>,
     ) -> AckInnerStream {
+        let (sender, max_in_flight) = (opts.sid, opts.max_in_flight);
         let sockets = self.apply_opts(opts);
         #[cfg(feature = "tracing")]
         tracing::debug!(
@@ -211,7 +459,12 @@ impl Adapter for LocalAdapter {
             sockets.len(),
             sockets.iter().map(|s| s.id).collect::<Vec<_>>()
         );
-        AckInnerStream::broadcast(packet, sockets, timeout)
+        if let Some(ns) = self.ns.upgrade() {
+            ns.mirror_to_observers(&packet, false, |sid| {
+                sender == Some(sid) || sockets.iter().any(|s| s.id == sid)
+            });
+        }
+        AckInnerStream::broadcast_windowed(packet, sockets, timeout, max_in_flight)
     }
 
     fn sockets(&self, rooms: impl RoomParam) -> Result<Vec<Sid>, Infallible> {
@@ -273,21 +526,133 @@ impl Adapter for LocalAdapter {
     fn rooms(&self) -> Result<Vec<Room>, Self::Error> {
         Ok(self.rooms.read().unwrap().keys().cloned().collect())
     }
+
+    fn sockets_count(&self) -> Result<usize, Infallible> {
+        Ok(self.ns.upgrade().map_or(0, |ns| ns.sockets_count()))
+    }
+
+    fn socket_ids(&self) -> Result<Vec<Sid>, Infallible> {
+        Ok(self.ns.upgrade().map_or(Vec::new(), |ns| ns.socket_ids()))
+    }
+
+    fn local_socket_rooms(&self, sid: Sid) -> Vec<Room> {
+        let rooms_map = self.rooms.read().unwrap();
+        rooms_map
+            .iter()
+            .filter(|(_, sockets)| sockets.contains(&sid))
+            .map(|(room, _)| room.clone())
+            .collect()
+    }
+
+    fn is_in_room(&self, sid: Sid, room: &Room) -> Result<bool, Infallible> {
+        let rooms_map = self.rooms.read().unwrap();
+        Ok(rooms_map.get(room).map_or(false, |s| s.contains(&sid)))
+    }
+
+    fn room_exists(&self, room: &Room) -> Result<bool, Infallible> {
+        let rooms_map = self.rooms.read().unwrap();
+        Ok(rooms_map.get(room).map_or(false, |s| !s.is_empty()))
+    }
+
+    fn run_if_in_room<R>(
+        &self,
+        sid: Sid,
+        room: &Room,
+        f: impl FnOnce() -> R,
+    ) -> Result<Option<R>, Infallible> {
+        // The read lock is held while `f` runs so the socket cannot leave the room in the meantime
+        let rooms_map = self.rooms.read().unwrap();
+        let in_room = rooms_map.get(room).map_or(false, |s| s.contains(&sid));
+        Ok(in_room.then(f))
+    }
+
+    fn del_if_in_room<R>(
+        &self,
+        sid: Sid,
+        room: &Room,
+        f: impl FnOnce() -> Option<R>,
+    ) -> Result<Option<R>, Infallible> {
+        let res = {
+            // The write lock is held while `f` runs so no broadcast selects the socket in the meantime
+            let mut rooms_map = self.rooms.write().unwrap();
+            let Some(members) = rooms_map.get_mut(room).filter(|m| m.contains(&sid)) else {
+                return Ok(None);
+            };
+            let res = f();
+            if res.is_some() {
+                members.remove(&sid);
+            }
+            res
+        };
+        if res.is_some() {
+            self.notify_room_changes(std::iter::once((sid, room)), RoomChange::Leave);
+        }
+        Ok(res)
+    }
+
+    fn set_rooms(
+        &self,
+        sid: Sid,
+        rooms: impl RoomParam,
+    ) -> Result<(Vec<Room>, Vec<Room>), Infallible> {
+        let rooms: HashSet<Room> = rooms.into_room_iter().collect();
+        let mut left = Vec::new();
+        let mut joined = Vec::new();
+        {
+            let mut rooms_map = self.rooms.write().unwrap();
+            for (room, sockets) in rooms_map.iter_mut() {
+                if !rooms.contains(room) && sockets.remove(&sid) {
+                    left.push(room.clone());
+                }
+            }
+            for room in rooms {
+                if rooms_map.entry(room.clone()).or_default().insert(sid) {
+                    joined.push(room);
+                }
+            }
+        }
+        self.notify_room_changes(left.iter().map(|room| (sid, room)), RoomChange::Leave);
+        self.notify_room_changes(joined.iter().map(|room| (sid, room)), RoomChange::Join);
+        Ok((joined, left))
+    }
 }
 
 impl LocalAdapter {
+    /// Calls the room change hook of the namespace for each changed membership
+    fn notify_room_changes<'a>(
+        &self,
+        changes: impl ExactSizeIterator<Item = (Sid, &'a Room)>,
+        change: RoomChange,
+    ) {
+        if changes.len() == 0 {
+            return;
+        }
+        if let Some(ns) = self.ns.upgrade() {
+            for (sid, room) in changes {
+                ns.notify_room_change(sid, room, change);
+            }
+        }
+    }
+
     /// Applies the given `opts` and return the sockets that match.
     fn apply_opts(&self, opts: BroadcastOptions) -> Vec<SocketRef<Self>> {
         let rooms = opts.rooms;
 
-        let except = self.get_except_sids(&opts.except);
+        let mut except = self.get_except_sids(&opts.except);
+        except.extend(opts.except_sids);
         let ns = self.ns.upgrade().unwrap();
-        if !rooms.is_empty() {
-            let rooms_map = self.rooms.read().unwrap();
-            rooms
-                .iter()
-                .filter_map(|room| rooms_map.get(room))
-                .flatten()
+        let mut sockets: Vec<SocketRef<Self>> = if !rooms.is_empty() || !opts.tags.is_empty() {
+            let mut sids: HashSet<Sid> = {
+                let rooms_map = self.rooms.read().unwrap();
+                rooms
+                    .iter()
+                    .filter_map(|room| rooms_map.get(room))
+                    .flatten()
+                    .copied()
+                    .collect()
+            };
+            sids.extend(ns.get_tagged_sids(opts.tags.iter().map(|t| t.as_ref())));
+            sids.iter()
                 .filter(|sid| {
                     !except.contains(*sid)
                         && (!opts.flags.contains(&BroadcastFlags::Broadcast)
@@ -309,7 +674,11 @@ impl LocalAdapter {
             vec![sock.into()]
         } else {
             vec![]
+        };
+        if let Some(instant) = opts.connected_after {
+            sockets.retain(|socket| socket.connected_at() >= instant);
         }
+        sockets
     }
 
     fn get_except_sids(&self, except: &HashSet<Room>) -> HashSet<Sid> {
@@ -342,6 +711,58 @@ mod test {
         assert_eq!(adapter.server_count().unwrap(), 1);
     }
 
+    #[tokio::test]
+    async fn test_room_change_hook() {
+        use std::sync::Mutex;
+        let (socket1, socket2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([socket1, socket2]);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let changes_ = changes.clone();
+        ns.room_change_hook.set(Arc::new(move |sid, room, change| {
+            changes_.lock().unwrap().push((sid, room.clone(), change));
+        }));
+        let adapter = &ns.adapter;
+        let take = || std::mem::take(&mut *changes.lock().unwrap());
+
+        adapter.add_all(socket1, ["room1", "room2"]).unwrap();
+        adapter.add_all(socket1, ["room1"]).unwrap();
+        assert_eq!(
+            take(),
+            [
+                (socket1, "room1".into(), RoomChange::Join),
+                (socket1, "room2".into(), RoomChange::Join)
+            ]
+        );
+
+        adapter.add_many(&[socket1, socket2], "room1").unwrap();
+        assert_eq!(take(), [(socket2, "room1".into(), RoomChange::Join)]);
+
+        adapter.del(socket2, ["room1", "room2"]).unwrap();
+        assert_eq!(take(), [(socket2, "room1".into(), RoomChange::Leave)]);
+
+        adapter.del_all(socket1).unwrap();
+        let mut left = take();
+        left.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            left,
+            [
+                (socket1, "room1".into(), RoomChange::Leave),
+                (socket1, "room2".into(), RoomChange::Leave)
+            ]
+        );
+
+        adapter.add_all(socket2, ["room1", "room2"]).unwrap();
+        take();
+        adapter.set_rooms(socket2, ["room2", "room3"]).unwrap();
+        assert_eq!(
+            take(),
+            [
+                (socket2, "room1".into(), RoomChange::Leave),
+                (socket2, "room3".into(), RoomChange::Join)
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_add_all() {
         let socket = Sid::new();
@@ -354,6 +775,58 @@ mod test {
         assert_eq!(rooms_map.get("room2").unwrap().len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_add_new() {
+        let socket = Sid::new();
+        let ns = Namespace::new_dummy([socket]);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        let joined = adapter.add_new(socket, ["room1", "room2"]).unwrap();
+        assert_eq!(joined, ["room1", "room2"]);
+        let joined = adapter.add_new(socket, ["room2", "room3"]).unwrap();
+        assert_eq!(joined, ["room3"]);
+        assert!(adapter.add_new(socket, "room1").unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_add_many() {
+        let sids: [Sid; 500] = std::array::from_fn(|_| Sid::new());
+        let ns = Namespace::new_dummy(sids);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(sids[0], "match").unwrap();
+        adapter.add_many(&sids, ["match", "game"]).unwrap();
+        let rooms_map = adapter.rooms.read().unwrap();
+        assert_eq!(rooms_map.len(), 2);
+        assert_eq!(rooms_map.get("match").unwrap().len(), 500);
+        assert_eq!(rooms_map.get("game").unwrap().len(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_sockets_count() {
+        let sids = [Sid::new(), Sid::new(), Sid::new()];
+        let ns = Namespace::<LocalAdapter>::new_dummy(sids);
+        ns.adapter.add_all(sids[0], ["room1", "room2"]).unwrap();
+        ns.adapter.add_all(sids[1], "room1").unwrap();
+        assert_eq!(ns.adapter.sockets_count().unwrap(), 3);
+        assert_eq!(ns.get_socket(sids[2]).unwrap().sockets_count().unwrap(), 3);
+
+        ns.remove_socket(sids[0]).unwrap();
+        assert_eq!(ns.adapter.sockets_count().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_socket_ids() {
+        let sids = [Sid::new(), Sid::new()];
+        let ns = Namespace::<LocalAdapter>::new_dummy(sids);
+        let mut ids = ns.adapter.socket_ids().unwrap();
+        ids.sort();
+        let mut expected = sids.to_vec();
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        ns.remove_socket(sids[0]).unwrap();
+        assert_eq!(ns.adapter.socket_ids().unwrap(), [sids[1]]);
+    }
+
     #[tokio::test]
     async fn test_del() {
         let socket = Sid::new();
@@ -577,5 +1050,199 @@ mod test {
         };
         let sockets = adapter.fetch_sockets(opts).unwrap();
         assert_eq!(sockets.len(), 0);
+
+        // Sockets are connected in order, so all of them are connected after socket 0
+        let mut opts = BroadcastOptions {
+            connected_after: Some(ns.get_socket(socket0).unwrap().connected_at()),
+            ..Default::default()
+        };
+        opts.rooms = hash_set!["room2".into()];
+        let sockets = adapter.fetch_sockets(opts).unwrap();
+        assert_eq!(sockets.len(), 2);
+
+        let mut opts = BroadcastOptions {
+            connected_after: Some(Instant::now() + Duration::from_secs(1)),
+            ..Default::default()
+        };
+        opts.flags.insert(BroadcastFlags::Broadcast);
+        let sockets = adapter.fetch_sockets(opts).unwrap();
+        assert_eq!(sockets.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_apply_opts_tags() {
+        let socket0 = Sid::new();
+        let socket1 = Sid::new();
+        let socket2 = Sid::new();
+        let ns = Namespace::new_dummy([socket0, socket1, socket2]);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(socket0, ["room1"]).unwrap();
+        assert!(ns.get_socket(socket0).unwrap().add_tag("beta"));
+        assert!(ns.get_socket(socket1).unwrap().add_tag("beta"));
+        assert!(!ns.get_socket(socket1).unwrap().add_tag("beta"));
+
+        let mut opts = BroadcastOptions {
+            tags: hash_set!["beta".into()],
+            ..Default::default()
+        };
+        opts.flags.insert(BroadcastFlags::Broadcast);
+        let sockets = adapter.fetch_sockets(opts).unwrap();
+        assert_eq!(sockets.len(), 2);
+        sockets.iter().for_each(|s| {
+            assert!(s.id == socket0 || s.id == socket1);
+        });
+
+        // The union with the rooms does not yield duplicates and keeps the sender filtering
+        let mut opts = BroadcastOptions {
+            sid: Some(socket1),
+            ..Default::default()
+        };
+        opts.tags = hash_set!["beta".into()];
+        opts.rooms = hash_set!["room1".into()];
+        opts.flags.insert(BroadcastFlags::Broadcast);
+        let sockets = adapter.fetch_sockets(opts).unwrap();
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].id, socket0);
+
+        assert!(ns.get_socket(socket0).unwrap().remove_tag("beta"));
+        ns.remove_socket(socket1).unwrap();
+        let mut opts = BroadcastOptions {
+            tags: hash_set!["beta".into()],
+            ..Default::default()
+        };
+        opts.flags.insert(BroadcastFlags::Broadcast);
+        assert_eq!(adapter.fetch_sockets(opts).unwrap().len(), 0);
+        assert!(ns.get_tagged_sids(["beta"]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_room_membership() {
+        let sids = [Sid::new(), Sid::new()];
+        let ns = Namespace::new_dummy(sids);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(sids[0], ["room1"]).unwrap();
+        let room1: Room = "room1".into();
+        let room2: Room = "room2".into();
+
+        assert!(adapter.is_in_room(sids[0], &room1).unwrap());
+        assert!(!adapter.is_in_room(sids[1], &room1).unwrap());
+        assert!(!adapter.is_in_room(sids[0], &room2).unwrap());
+        assert!(adapter.room_exists(&room1).unwrap());
+        assert!(!adapter.room_exists(&room2).unwrap());
+
+        // A room without any socket left doesn't exist anymore
+        adapter.del(sids[0], "room1").unwrap();
+        assert!(!adapter.room_exists(&room1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_local_socket_rooms() {
+        let sids = [Sid::new(), Sid::new()];
+        let ns = Namespace::new_dummy(sids);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(sids[0], ["room1", "room2"]).unwrap();
+
+        let mut rooms = adapter.local_socket_rooms(sids[0]);
+        rooms.sort();
+        assert_eq!(rooms, ["room1", "room2"]);
+        assert!(adapter.local_socket_rooms(sids[1]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_if_in_room() {
+        let socket = Sid::new();
+        let ns = Namespace::new_dummy([socket]);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(socket, ["room1"]).unwrap();
+        let room1: Room = "room1".into();
+        let room2: Room = "room2".into();
+
+        assert_eq!(
+            adapter.run_if_in_room(socket, &room1, || 1).unwrap(),
+            Some(1)
+        );
+        assert_eq!(adapter.run_if_in_room(socket, &room2, || 1).unwrap(), None);
+
+        // A concurrent leave must wait for the conditional call to complete
+        let left = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|s| {
+            adapter
+                .run_if_in_room(socket, &room1, || {
+                    s.spawn(|| {
+                        adapter.del(socket, "room1").unwrap();
+                        left.store(true, std::sync::atomic::Ordering::SeqCst);
+                    });
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    assert!(!left.load(std::sync::atomic::Ordering::SeqCst));
+                })
+                .unwrap();
+        });
+        assert!(left.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(adapter.run_if_in_room(socket, &room1, || 1).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_del_if_in_room() {
+        let socket = Sid::new();
+        let ns = Namespace::new_dummy([socket]);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(socket, ["room1"]).unwrap();
+        let room2: Room = "room2".into();
+        let room1: Room = "room1".into();
+
+        // Not in the room: `f` is not called
+        let res = adapter.del_if_in_room(socket, &room2, || -> Option<()> { unreachable!() });
+        assert_eq!(res.unwrap(), None);
+
+        // `f` returning `None` keeps the socket in the room
+        assert_eq!(
+            adapter
+                .del_if_in_room(socket, &room1, || None::<()>)
+                .unwrap(),
+            None
+        );
+        assert!(adapter.is_in_room(socket, &room1).unwrap());
+
+        assert_eq!(
+            adapter.del_if_in_room(socket, &room1, || Some(1)).unwrap(),
+            Some(1)
+        );
+        assert!(!adapter.is_in_room(socket, &room1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_set_rooms() {
+        let socket = Sid::new();
+        let ns = Namespace::new_dummy([socket]);
+        let adapter = LocalAdapter::new(Arc::downgrade(&ns));
+        adapter.add_all(socket, ["room1", "room2"]).unwrap();
+
+        let (mut joined, left) = adapter
+            .set_rooms(socket, ["room2", "room3", "room4"])
+            .unwrap();
+        joined.sort();
+        assert_eq!(joined, ["room3", "room4"]);
+        assert_eq!(left, ["room1"]);
+        let mut rooms = adapter.socket_rooms(socket).unwrap();
+        rooms.sort();
+        assert_eq!(rooms, ["room2", "room3", "room4"]);
+
+        // A concurrent reader never sees an intermediate room set
+        let done = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                while !done.load(std::sync::atomic::Ordering::SeqCst) {
+                    let len = adapter.socket_rooms(socket).unwrap().len();
+                    assert!(len == 3 || len == 1, "intermediate room set observed");
+                }
+            });
+            for _ in 0..1000 {
+                adapter.set_rooms(socket, "room5").unwrap();
+                adapter
+                    .set_rooms(socket, ["room1", "room2", "room3"])
+                    .unwrap();
+            }
+            done.store(true, std::sync::atomic::Ordering::SeqCst);
+        });
     }
 }
@@ -1,28 +1,73 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 use engineioxide::handler::EngineIoHandler;
 use engineioxide::socket::{DisconnectReason as EIoDisconnectReason, Socket as EIoSocket};
-use futures::TryFutureExt;
+use futures::future::Either;
+use futures::Future;
 
 use engineioxide::sid::Sid;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, Semaphore};
 
 use crate::adapter::Adapter;
-use crate::handler::ConnectHandler;
+use crate::handler::{middleware::BoxedMiddleware, ConnectHandler};
 use crate::ProtocolVersion;
 use crate::{
     errors::Error,
-    ns::Namespace,
+    io::{EngineConnectHook, EngineDisconnectHook, ProtocolHook, TransportEventHook},
+    ns::{Namespace, NsMap, NsPattern},
     packet::{Packet, PacketData},
-    SocketIoConfig,
+    PauseEventsPolicy, ShutdownDisconnectPolicy, SocketIoConfig, StagedShutdownConfig,
+    TransportEvent,
 };
 
+/// Creates the namespace of a path matched by a dynamic namespace pattern, with the matched parameters
+type DynamicNsFactory<A> = Box<
+    dyn Fn(Cow<'static, str>, Vec<(Cow<'static, str>, String)>) -> Arc<Namespace<A>> + Send + Sync,
+>;
+
+/// A dynamic namespace, see [`SocketIo::dynamic_ns`](crate::SocketIo#method.dynamic_ns)
+struct DynamicNs<A: Adapter> {
+    pattern: NsPattern,
+    factory: DynamicNsFactory<A>,
+}
+impl<A: Adapter> std::fmt::Debug for DynamicNs<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicNs")
+            .field("pattern", &self.pattern)
+            .finish()
+    }
+}
+
+/// Interval at which acks dropped without response are discarded when draining them
+const ACK_DRAIN_RECHECK: Duration = Duration::from_millis(50);
+
 #[derive(Debug)]
 pub struct Client<A: Adapter> {
     pub(crate) config: Arc<SocketIoConfig>,
-    ns: RwLock<HashMap<Cow<'static, str>, Arc<Namespace<A>>>>,
+    ns: Arc<NsMap<A>>,
+    /// The patterns of the dynamic namespaces, checked in registration order
+    dynamic_ns: RwLock<Vec<DynamicNs<A>>>,
+    /// Set when a graceful shutdown starts, new connections are then refused
+    closing: AtomicBool,
+    transport_hook: Option<TransportEventHook>,
+    protocol_hook: Option<ProtocolHook>,
+    engine_connect_hook: Option<EngineConnectHook>,
+    engine_disconnect_hook: Option<EngineDisconnectHook>,
+    /// Set while inbound events are paused
+    paused: Mutex<Option<PausedEvents>>,
+    /// Whether `paused` is set, so that the lock is only taken while events are paused
+    is_paused: AtomicBool,
+}
+
+/// Inbound events withheld while events are paused
+#[derive(Debug)]
+struct PausedEvents {
+    policy: PauseEventsPolicy,
+    buffer: VecDeque<(Sid, Packet<'static>)>,
 }
 
 impl<A: Adapter> Client<A> {
@@ -32,7 +77,45 @@ impl<A: Adapter> Client<A> {
 
         Self {
             config,
-            ns: RwLock::new(HashMap::new()),
+            ns: Arc::new(RwLock::new(HashMap::new())),
+            dynamic_ns: RwLock::new(Vec::new()),
+            closing: AtomicBool::new(false),
+            transport_hook: None,
+            protocol_hook: None,
+            engine_connect_hook: None,
+            engine_disconnect_hook: None,
+            paused: Mutex::new(None),
+            is_paused: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the callback called for each engine.io transport event
+    pub(crate) fn with_transport_hook(mut self, hook: TransportEventHook) -> Self {
+        self.transport_hook = Some(hook);
+        self
+    }
+
+    /// Sets the callback called with the protocol versions of each new connection
+    pub(crate) fn with_protocol_hook(mut self, hook: ProtocolHook) -> Self {
+        self.protocol_hook = Some(hook);
+        self
+    }
+
+    /// Sets the callback called once when an engine.io connection is opened
+    pub(crate) fn with_engine_connect_hook(mut self, hook: EngineConnectHook) -> Self {
+        self.engine_connect_hook = Some(hook);
+        self
+    }
+
+    /// Sets the callback called once when an engine.io connection is closed
+    pub(crate) fn with_engine_disconnect_hook(mut self, hook: EngineDisconnectHook) -> Self {
+        self.engine_disconnect_hook = Some(hook);
+        self
+    }
+
+    fn emit_transport_event(&self, sid: Sid, event: TransportEvent) {
+        if let Some(hook) = &self.transport_hook {
+            hook.call(sid, event);
         }
     }
 
@@ -47,8 +130,15 @@ impl<A: Adapter> Client<A> {
         tracing::debug!("auth: {:?}", auth);
 
         let sid = esocket.id;
-        if let Some(ns) = self.get_ns(ns_path) {
-            ns.connect(sid, esocket.clone(), auth, self.config.clone())?;
+        if self.is_closing() {
+            esocket.close(EIoDisconnectReason::ClosingServer);
+            Ok(())
+        } else if let Some(ns) = self.get_ns_for_connect(ns_path) {
+            let res = ns
+                .clone()
+                .connect(sid, esocket.clone(), auth, self.config.clone());
+            ns.end_connect();
+            res?;
 
             // cancel the connect timeout task for v5
             if let Some(tx) = esocket.data.connect_recv_tx.lock().unwrap().take() {
@@ -73,8 +163,43 @@ impl<A: Adapter> Client<A> {
         }
     }
 
-    /// Propagate a packet to a its target namespace
-    fn sock_propagate_packet(&self, packet: Packet<'_>, sid: Sid) -> Result<(), Error> {
+    /// Propagate a packet to a its target namespace, unless it is an event and events are paused
+    fn sock_propagate_packet(&self, packet: Packet<'static>, sid: Sid) -> Result<(), Error> {
+        match self.withhold_event(packet, sid) {
+            Some(packet) => self.dispatch_packet(packet, sid),
+            None => Ok(()),
+        }
+    }
+
+    /// Buffers or drops an event packet if events are paused, otherwise gives it back
+    fn withhold_event(&self, packet: Packet<'static>, sid: Sid) -> Option<Packet<'static>> {
+        if !matches!(
+            packet.inner,
+            PacketData::Event(..) | PacketData::BinaryEvent(..)
+        ) {
+            return Some(packet);
+        }
+        if !self.is_paused.load(Ordering::SeqCst) {
+            return Some(packet);
+        }
+        let mut paused = self.paused.lock().unwrap();
+        let paused = match paused.as_mut() {
+            Some(paused) => paused,
+            None => return Some(packet),
+        };
+        match paused.policy {
+            PauseEventsPolicy::Buffer(max) if paused.buffer.len() < max => {
+                paused.buffer.push_back((sid, packet))
+            }
+            _ => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("events paused, dropping event from socket {}", sid);
+            }
+        }
+        None
+    }
+
+    fn dispatch_packet(&self, packet: Packet<'_>, sid: Sid) -> Result<(), Error> {
         if let Some(ns) = self.get_ns(&packet.ns) {
             ns.recv(sid, packet.inner)
         } else {
@@ -92,13 +217,14 @@ impl<A: Adapter> Client<A> {
         let (tx, rx) = oneshot::channel();
         socket.data.connect_recv_tx.lock().unwrap().replace(tx);
 
-        tokio::spawn(
-            tokio::time::timeout(self.config.connect_timeout, rx).map_err(move |_| {
+        let timeout = self.config.clock.sleep(self.config.connect_timeout);
+        tokio::spawn(async move {
+            if let Either::Right(_) = futures::future::select(rx, timeout).await {
                 #[cfg(feature = "tracing")]
                 tracing::debug!("connect timeout for socket {}", socket.id);
                 socket.close(EIoDisconnectReason::TransportClose);
-            }),
-        );
+            }
+        });
     }
 
     /// Adds a new namespace handler
@@ -109,10 +235,80 @@ impl<A: Adapter> Client<A> {
     {
         #[cfg(feature = "tracing")]
         tracing::debug!("adding namespace {}", path);
-        let ns = Namespace::new(path.clone(), callback);
+        let ns = Namespace::new(path.clone(), callback, &self.config);
         self.ns.write().unwrap().insert(path, ns);
     }
 
+    /// Adds a new namespace handler with a first middleware, set before any socket can connect
+    pub fn add_ns_with_middleware<C, T>(
+        &self,
+        path: Cow<'static, str>,
+        callback: C,
+        middleware: BoxedMiddleware<A>,
+    ) where
+        C: ConnectHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("adding namespace {} with a middleware", path);
+        let ns = Namespace::new(path.clone(), callback, &self.config);
+        ns.push_middleware(middleware);
+        self.ns.write().unwrap().insert(path, ns);
+    }
+
+    /// Adds a dynamic namespace handler, creating a namespace for each path matching the pattern
+    pub fn add_dynamic_ns<C, T>(&self, pattern: Cow<'static, str>, callback: C)
+    where
+        C: ConnectHandler<A, T> + Clone,
+        T: Send + Sync + 'static,
+    {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("adding dynamic namespace {}", pattern);
+        let config = self.config.clone();
+        let registry = self
+            .config
+            .ephemeral_dynamic_ns
+            .then(|| Arc::downgrade(&self.ns));
+        let factory: DynamicNsFactory<A> = Box::new(move |path, params| {
+            Namespace::new_dynamic(path, callback.clone(), &config, params, registry.clone())
+        });
+        self.dynamic_ns.write().unwrap().push(DynamicNs {
+            pattern: NsPattern::new(pattern),
+            factory,
+        });
+    }
+
+    /// Gets the namespace a socket connects to, creating it from a dynamic namespace pattern if needed.
+    /// The namespace is marked as connecting while the lock of the namespace map is held
+    /// so that an ephemeral namespace can't be removed before the socket is added to it.
+    fn get_ns_for_connect(&self, path: &str) -> Option<Arc<Namespace<A>>> {
+        if let Some(ns) = self.ns.read().unwrap().get(path) {
+            ns.begin_connect();
+            return Some(ns.clone());
+        }
+        self.create_dynamic_ns(path)
+    }
+
+    /// Creates the namespace of the first dynamic namespace pattern matching the path, if any,
+    /// and marks it as connecting
+    fn create_dynamic_ns(&self, path: &str) -> Option<Arc<Namespace<A>>> {
+        let dynamic_ns = self.dynamic_ns.read().unwrap();
+        let (params, factory) = dynamic_ns
+            .iter()
+            .find_map(|d| Some((d.pattern.matches(path)?, &d.factory)))?;
+        // Another socket may have created the namespace concurrently
+        let mut ns = self.ns.write().unwrap();
+        let ns = ns
+            .entry(Cow::Owned(path.to_string()))
+            .or_insert_with_key(|path| {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("creating dynamic namespace {}", path);
+                factory(path.clone(), params)
+            });
+        ns.begin_connect();
+        Some(ns.clone())
+    }
+
     /// Deletes a namespace handler
     pub fn delete_ns(&self, path: &str) {
         #[cfg(feature = "tracing")]
@@ -124,16 +320,134 @@ impl<A: Adapter> Client<A> {
         self.ns.read().unwrap().get(path).cloned()
     }
 
+    pub(crate) fn get_all_ns(&self) -> Vec<Arc<Namespace<A>>> {
+        self.ns.read().unwrap().values().cloned().collect()
+    }
+
+    /// Counts an incoming packet in the received bytes of its socket and in the throughput of its namespace
+    fn record_in(&self, ns: &str, sid: Sid, _event: bool, bytes: usize) {
+        if let Some(ns) = self.get_ns(ns) {
+            #[cfg(feature = "metrics")]
+            ns.throughput
+                .record_in(self.config.clock.now(), _event, bytes);
+            if let Ok(socket) = ns.get_socket(sid) {
+                socket.record_received(bytes);
+            }
+        }
+    }
+
     /// Closes all engine.io connections and all clients
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     pub(crate) async fn close(&self) {
         #[cfg(feature = "tracing")]
         tracing::debug!("closing all namespaces");
         let ns = self.ns.read().unwrap().clone();
-        futures::future::join_all(ns.values().map(|ns| ns.close())).await;
+        let policy = self.config.shutdown_disconnect_policy;
+        let permits = match policy {
+            ShutdownDisconnectPolicy::RunBounded(n) => Some(Arc::new(Semaphore::new(n.max(1)))),
+            _ => None,
+        };
+        futures::future::join_all(ns.values().map(|ns| ns.close(policy, permits.clone()))).await;
         #[cfg(feature = "tracing")]
         tracing::debug!("all namespaces closed");
     }
+
+    /// Stops dispatching inbound events, see [`SocketIo::pause_events`](crate::SocketIo)
+    pub(crate) fn pause_events(&self, policy: PauseEventsPolicy) {
+        let mut paused = self.paused.lock().unwrap();
+        match paused.as_mut() {
+            Some(paused) => paused.policy = policy,
+            None => {
+                *paused = Some(PausedEvents {
+                    policy,
+                    buffer: VecDeque::new(),
+                });
+                self.is_paused.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Dispatches the buffered events in order and stops withholding new ones
+    pub(crate) fn resume_events(&self) {
+        loop {
+            // Events received while the buffer is being drained are queued behind it
+            // so the lock is only held to pop the next one
+            let (sid, packet) = {
+                let mut paused = self.paused.lock().unwrap();
+                match paused.as_mut().map(|p| p.buffer.pop_front()) {
+                    Some(Some(next)) => next,
+                    Some(None) => {
+                        *paused = None;
+                        self.is_paused.store(false, Ordering::SeqCst);
+                        return;
+                    }
+                    None => return,
+                }
+            };
+            if let Err(_e) = self.dispatch_packet(packet, sid) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("error while dispatching buffered event to {}: {}", sid, _e);
+            }
+        }
+    }
+
+    fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+
+    /// Closes the server in successive phases, see [`SocketIo::graceful_shutdown`](crate::SocketIo)
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    pub(crate) async fn graceful_shutdown(&self, config: StagedShutdownConfig) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("refusing new connections");
+        self.closing.store(true, Ordering::SeqCst);
+        let ns = self.ns.read().unwrap().clone();
+
+        let drained = futures::future::join_all(
+            ns.values()
+                .map(|ns| ns.pending_acks_drained(ACK_DRAIN_RECHECK)),
+        );
+        if !self
+            .run_with_timeout(config.ack_drain_timeout, drained)
+            .await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("timeout while waiting for pending acks");
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!("sending disconnect packets");
+        ns.values().for_each(|ns| ns.send_disconnect());
+
+        if !self
+            .run_with_timeout(config.close_timeout, self.close())
+            .await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("timeout while waiting for transports to close");
+        }
+    }
+
+    /// Closes the connections of all the sockets of the given namespaces, see [`SocketIo::disconnect_all`].
+    /// Returns `false` if the timeout elapsed before every transport was closed.
+    ///
+    /// [`SocketIo::disconnect_all`]: crate::SocketIo#method.disconnect_all
+    pub(crate) async fn disconnect_all(&self, ns: &[Arc<Namespace<A>>], timeout: Duration) -> bool {
+        let closed = futures::future::join_all(ns.iter().map(|ns| ns.disconnect_all()));
+        self.run_with_timeout(timeout, closed).await
+    }
+
+    /// Runs the future until it completes or the duration elapses, according to the configured clock.
+    /// Returns `false` if the duration elapsed first.
+    async fn run_with_timeout(
+        &self,
+        duration: Duration,
+        fut: impl Future<Output = impl Sized>,
+    ) -> bool {
+        futures::pin_mut!(fut);
+        let timeout = self.config.clock.sleep(duration);
+        matches!(futures::future::select(fut, timeout).await, Either::Left(_))
+    }
 }
 
 #[derive(Debug, Default)]
@@ -144,6 +458,10 @@ pub struct SocketData {
 
     /// Channel used to notify the socket that it has been connected to a namespace for v5
     pub connect_recv_tx: Mutex<Option<oneshot::Sender<()>>>,
+
+    /// Extensions shared by all the namespace sockets of this connection
+    #[cfg(feature = "extensions")]
+    pub extensions: crate::extensions::Extensions,
 }
 
 impl<A: Adapter> EngineIoHandler for Client<A> {
@@ -153,8 +471,22 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
     fn on_connect(&self, socket: Arc<EIoSocket<SocketData>>) {
         #[cfg(feature = "tracing")]
         tracing::debug!("eio socket connect");
+        self.emit_transport_event(socket.id, TransportEvent::Open(socket.transport_type()));
+        if let Some(hook) = &self.engine_connect_hook {
+            hook.call(socket.id, &socket.req_parts);
+        }
+
+        if self.is_closing() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("server is closing, refusing connection");
+            socket.close(EIoDisconnectReason::ClosingServer);
+            return;
+        }
 
         let protocol: ProtocolVersion = socket.protocol.into();
+        if let Some(hook) = &self.protocol_hook {
+            hook.call(socket.id, protocol, socket.protocol);
+        }
 
         // Connecting the client to the default namespace is mandatory if the SocketIO protocol is v4.
         // Because we connect by default to the root namespace, we should ensure before that the root namespace is defined
@@ -174,6 +506,7 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
     fn on_disconnect(&self, socket: Arc<EIoSocket<SocketData>>, reason: EIoDisconnectReason) {
         #[cfg(feature = "tracing")]
         tracing::debug!("eio socket disconnected");
+        self.emit_transport_event(socket.id, TransportEvent::Closed(reason.clone().into()));
         let _res: Result<Vec<_>, _> = self
             .ns
             .read()
@@ -192,12 +525,16 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
                 tracing::debug!("error while disconnecting socket: {}", _e)
             }
         }
+        if let Some(hook) = &self.engine_disconnect_hook {
+            hook.call(socket.id, reason.into());
+        }
     }
 
     fn on_message(&self, msg: String, socket: Arc<EIoSocket<SocketData>>) {
         #[cfg(feature = "tracing")]
         tracing::debug!("Received message: {:?}", msg);
-        let packet = match Packet::try_from(msg) {
+        let len = msg.len();
+        let packet = match Packet::decode(msg, socket.protocol.into()) {
             Ok(packet) => packet,
             Err(_e) => {
                 #[cfg(feature = "tracing")]
@@ -208,12 +545,15 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
         };
         #[cfg(feature = "tracing")]
         tracing::debug!("Packet: {:?}", packet);
+        self.record_in(&packet.ns, socket.id, packet.inner.is_event(), len);
 
         let res: Result<(), Error> = match packet.inner {
             PacketData::Connect(auth) => self
                 .sock_connect(auth, &packet.ns, &socket)
                 .map_err(Into::into),
-            PacketData::BinaryEvent(_, _, _) | PacketData::BinaryAck(_, _) => {
+            PacketData::BinaryEvent(_, _, _) | PacketData::BinaryAck(_, _)
+                if packet.inner.payload_count() > 0 =>
+            {
                 // Cache-in the socket data until all the binary payloads are received
                 socket
                     .data
@@ -242,6 +582,12 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
     ///
     /// If the packet is complete, it is propagated to the namespace
     fn on_binary(&self, data: Vec<u8>, socket: Arc<EIoSocket<SocketData>>) {
+        {
+            let partial = socket.data.partial_bin_packet.lock().unwrap();
+            if let Some(packet) = partial.as_ref() {
+                self.record_in(&packet.ns, socket.id, false, data.len());
+            }
+        }
         if apply_payload_on_packet(data, &socket) {
             if let Some(packet) = socket.data.partial_bin_packet.lock().unwrap().take() {
                 if let Err(ref err) = self.sock_propagate_packet(packet, socket.id) {
@@ -258,6 +604,14 @@ impl<A: Adapter> EngineIoHandler for Client<A> {
             }
         }
     }
+
+    fn on_upgrade_start(&self, socket: Arc<EIoSocket<SocketData>>) {
+        self.emit_transport_event(socket.id, TransportEvent::UpgradeStarted);
+    }
+
+    fn on_upgrade(&self, socket: Arc<EIoSocket<SocketData>>) {
+        self.emit_transport_event(socket.id, TransportEvent::UpgradeCompleted);
+    }
 }
 
 /// Utility that applies an incoming binary payload to a partial binary packet
@@ -0,0 +1,156 @@
+//! A [`Clock`] abstraction used by every time-dependent part of socketioxide
+//! (ack timeouts, connect timeouts...).
+//!
+//! By default the [`TokioClock`] is used. A custom clock can be set with [`SocketIoBuilder::with_clock`]
+//! to control time in tests, for example with the [`ManualClock`] (available with the `test-utils` feature).
+//!
+//! [`SocketIoBuilder::with_clock`]: crate::SocketIoBuilder#method.with_clock
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+
+/// A source of time for socketioxide timers.
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// Returns a future that resolves once `duration` has elapsed according to this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+
+    /// Returns the current instant according to this clock.
+    ///
+    /// Defaults to [`Instant::now`].
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The default [`Clock`], backed by [`tokio::time`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn now(&self) -> Instant {
+        tokio::time::Instant::now().into_std()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+#[cfg(any(test, feature = "test-utils"))]
+pub use manual::ManualClock;
+
+#[cfg(any(test, feature = "test-utils"))]
+mod manual {
+    use std::{
+        sync::{Arc, Mutex},
+        time::{Duration, Instant},
+    };
+
+    use futures::future::BoxFuture;
+    use tokio::sync::oneshot;
+
+    use super::Clock;
+
+    /// A [`Clock`] that only advances when [`ManualClock::advance`] is called.
+    ///
+    /// It can be cloned and shared between the server and the test code.
+    #[derive(Debug, Clone)]
+    pub struct ManualClock {
+        inner: Arc<Mutex<Inner>>,
+        start: Instant,
+    }
+
+    impl Default for ManualClock {
+        fn default() -> Self {
+            Self {
+                inner: Default::default(),
+                start: Instant::now(),
+            }
+        }
+    }
+
+    #[derive(Debug, Default)]
+    struct Inner {
+        elapsed: Duration,
+        sleepers: Vec<(Duration, oneshot::Sender<()>)>,
+    }
+
+    impl ManualClock {
+        /// Creates a new [`ManualClock`] starting at zero.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Advances the clock by `duration`, waking every sleep that has expired.
+        pub fn advance(&self, duration: Duration) {
+            let mut inner = self.inner.lock().unwrap();
+            inner.elapsed += duration;
+            let elapsed = inner.elapsed;
+            let (expired, pending) = std::mem::take(&mut inner.sleepers)
+                .into_iter()
+                .partition(|(deadline, _)| *deadline <= elapsed);
+            inner.sleepers = pending;
+            drop(inner);
+            for (_, tx) in expired {
+                tx.send(()).ok();
+            }
+        }
+
+        /// Gets the total amount of time this clock has been advanced by.
+        pub fn elapsed(&self) -> Duration {
+            self.inner.lock().unwrap().elapsed
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            if duration.is_zero() {
+                return Box::pin(std::future::ready(()));
+            }
+            let mut inner = self.inner.lock().unwrap();
+            let (tx, rx) = oneshot::channel();
+            let deadline = inner.elapsed + duration;
+            inner.sleepers.push((deadline, tx));
+            Box::pin(async move {
+                // If the clock is dropped, the time will never advance
+                if rx.await.is_err() {
+                    std::future::pending::<()>().await;
+                }
+            })
+        }
+
+        fn now(&self) -> Instant {
+            self.start + self.elapsed()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+
+    #[tokio::test]
+    async fn manual_clock_advance() {
+        let clock = ManualClock::new();
+        let mut short = clock.sleep(Duration::from_secs(1));
+        let mut long = clock.sleep(Duration::from_secs(10));
+        assert!((&mut short).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(1));
+        assert!((&mut short).now_or_never().is_some());
+        assert!((&mut long).now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(9));
+        assert!(long.now_or_never().is_some());
+        assert_eq!(clock.elapsed(), Duration::from_secs(10));
+
+        let now = clock.now();
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(clock.now() - now, Duration::from_secs(2));
+    }
+}
@@ -2,6 +2,8 @@ use engineioxide::{sid::Sid, socket::DisconnectReason as EIoDisconnectReason};
 use std::fmt::{Debug, Display};
 use tokio::{sync::mpsc::error::TrySendError, time::error::Elapsed};
 
+use crate::{adapter::Room, ProtocolVersion};
+
 /// Error type for socketio
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -22,6 +24,43 @@ pub enum Error {
 
     #[error("adapter error: {0}")]
     Adapter(#[from] AdapterError),
+
+    #[error("packet decode error: {0}")]
+    Decode(#[from] PacketDecodeError),
+}
+
+/// Error type for the packets that do not match the framing of the protocol version negotiated with the client.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum PacketDecodeError {
+    /// The binary packet header does not contain a valid attachment count (`<count>-`).
+    #[error("binary packet without a valid attachment count for protocol {0:?}")]
+    InvalidAttachmentCount(ProtocolVersion),
+
+    /// The number of binary payloads received does not match the attachment count of the packet header.
+    #[error(
+        "binary packet announces {announced} attachments but has {found} for protocol {protocol:?}"
+    )]
+    AttachmentMismatch {
+        /// The protocol version of the client
+        protocol: ProtocolVersion,
+        /// The attachment count of the packet header
+        announced: usize,
+        /// The number of placeholders or payloads actually found
+        found: usize,
+    },
+
+    /// The packet uses a framing that only exists in another protocol version.
+    #[error(
+        "{packet} packet framed for protocol {expected:?} received from a {protocol:?} client"
+    )]
+    ProtocolMismatch {
+        /// The protocol version of the client
+        protocol: ProtocolVersion,
+        /// The protocol version the packet framing belongs to
+        expected: ProtocolVersion,
+        /// The type of the packet
+        packet: &'static str,
+    },
 }
 
 /// Error type for ack operations.
@@ -35,6 +74,19 @@ pub enum AckError<T> {
     #[error("ack timeout error")]
     Timeout,
 
+    /// The socket already awaits the maximum number of ack responses
+    /// (see [`SocketIoBuilder::max_pending_acks_per_socket`]), the packet was not sent.
+    ///
+    /// [`SocketIoBuilder::max_pending_acks_per_socket`]: crate::SocketIoBuilder#method.max_pending_acks_per_socket
+    #[error("too many pending acks for this socket")]
+    TooManyPending,
+
+    /// The ack was cancelled with [`Socket::cancel_ack`] before the client responded.
+    ///
+    /// [`Socket::cancel_ack`]: crate::socket::Socket#method.cancel_ack
+    #[error("ack cancelled")]
+    Cancelled,
+
     /// An error happened while broadcasting to other socket.io nodes
     #[error("adapter error: {0}")]
     Adapter(#[from] AdapterError),
@@ -58,6 +110,10 @@ pub enum BroadcastError {
     /// An error occured while broadcasting to other nodes.
     #[error("Adapter error: {0}")]
     Adapter(#[from] AdapterError),
+
+    /// The namespace selected with [`SocketIo::to_ns`](crate::SocketIo#method.to_ns) is not registered.
+    #[error("namespace {0} not found")]
+    NamespaceNotFound(String),
 }
 /// Error type for sending operations.
 #[derive(thiserror::Error, Debug)]
@@ -69,6 +125,38 @@ pub enum SendError<T> {
     /// Error sending/receiving data through the engine.io socket
     #[error("Error sending data through the engine.io socket: {0:?}")]
     Socket(#[from] SocketError<T>),
+
+    /// No socket with this id is connected to the namespace
+    #[error("socket {0} not found")]
+    SocketGone(Sid),
+}
+
+/// Error type for the [`Socket::try_emit`](crate::socket::Socket#method.try_emit) method.
+///
+/// The data is given back in the [`WouldBlock`](TryEmitError::WouldBlock) and
+/// [`Closed`](TryEmitError::Closed) variants so that it can be sent again later.
+#[derive(thiserror::Error)]
+pub enum TryEmitError<T> {
+    /// An error occurred while serializing the JSON packet.
+    #[error("Error serializing JSON packet: {0:?}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// The packet buffer of the socket is temporarily full, the emit can be retried later.
+    #[error("socket buffer full")]
+    WouldBlock(T),
+
+    /// The socket is closed, the emit will never succeed.
+    #[error("socket closed")]
+    Closed(T),
+}
+impl<T> Debug for TryEmitError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serialize(e) => f.debug_tuple("Serialize").field(e).finish(),
+            Self::WouldBlock(_) => f.debug_tuple("WouldBlock").finish(),
+            Self::Closed(_) => f.debug_tuple("Closed").finish(),
+        }
+    }
 }
 
 /// Error type when using the underlying engine.io socket
@@ -104,11 +192,72 @@ pub enum DisconnectError {
     #[error("internal channel full error")]
     InternalChannelFull,
 
+    /// An error occurred while serializing the payload of
+    /// [`Socket::disconnect_with`](crate::socket::Socket#method.disconnect_with).
+    #[error("Error serializing JSON packet: {0:?}")]
+    Serialize(#[from] serde_json::Error),
+
     /// An error occured while broadcasting to other nodes.
     #[error("adapter error: {0:?}")]
     Adapter(#[from] AdapterError),
 }
 
+/// Error type for the [`Socket::relay_to`](crate::socket::Socket#method.relay_to) method.
+#[derive(thiserror::Error, Debug)]
+pub enum RelayError {
+    /// The peer socket is not connected to the namespace.
+    #[error("peer socket not found")]
+    PeerNotFound,
+
+    /// The relay was rejected by the relay policy.
+    #[error("relay to this peer is forbidden")]
+    Forbidden,
+
+    /// An error occurred while serializing the JSON packet.
+    #[error("Error serializing JSON packet: {0:?}")]
+    Serialize(#[from] serde_json::Error),
+
+    /// Error sending data through the engine.io socket of the peer
+    #[error("Error sending data through the engine.io socket: {0:?}")]
+    Socket(#[from] SocketError<()>),
+
+    /// An error occured while fetching the rooms of the sockets.
+    #[error("adapter error: {0}")]
+    Adapter(#[from] AdapterError),
+}
+
+/// Error type for the [`Socket::join`](crate::socket::Socket#method.join) method.
+#[derive(thiserror::Error, Debug)]
+pub enum JoinError<E> {
+    /// The join was refused by the guard set with
+    /// [`SocketIo::on_room_join_request`](crate::SocketIo#method.on_room_join_request).
+    /// None of the requested rooms were joined.
+    #[error("join of room {0} refused")]
+    Refused(Room),
+
+    /// The room reached the capacity set in its [`RoomMeta`](crate::room::RoomMeta).
+    /// None of the requested rooms were joined.
+    #[error("room {0} is full")]
+    Full(Room),
+
+    /// An error returned by the adapter.
+    #[error("adapter error: {0}")]
+    Adapter(#[from] E),
+}
+
+/// Error type for the [`Socket::kick_from_room`](crate::socket::Socket#method.kick_from_room) method.
+/// In both cases, the socket is left in the room and the client is not notified.
+#[derive(thiserror::Error, Debug)]
+pub enum KickError<E> {
+    /// The notification cannot be sent to the client.
+    #[error("Error sending data through the engine.io socket: {0:?}")]
+    Socket(SocketError<()>),
+
+    /// An error returned by the adapter.
+    #[error("adapter error: {0}")]
+    Adapter(#[from] E),
+}
+
 /// Error type for the [`Adapter`](crate::adapter::Adapter) trait.
 #[derive(Debug, thiserror::Error)]
 pub struct AdapterError(#[from] pub Box<dyn std::error::Error + Send + Sync>);
@@ -119,13 +268,21 @@ impl Display for AdapterError {
 }
 
 impl SocketError<()> {
-    pub(crate) fn with_value<T>(self, value: T) -> SocketError<T> {
+    pub(crate) fn with_value<T>(&self, value: T) -> SocketError<T> {
         match self {
             Self::InternalChannelFull(_) => SocketError::InternalChannelFull(value),
             Self::Closed(_) => SocketError::Closed(value),
         }
     }
 }
+impl<T> From<SocketError<T>> for TryEmitError<T> {
+    fn from(value: SocketError<T>) -> Self {
+        match value {
+            SocketError::InternalChannelFull(data) => Self::WouldBlock(data),
+            SocketError::Closed(data) => Self::Closed(data),
+        }
+    }
+}
 impl<T> From<TrySendError<T>> for SocketError<()> {
     fn from(value: TrySendError<T>) -> Self {
         match value {
@@ -165,9 +322,10 @@ impl From<&Error> for Option<EIoDisconnectReason> {
         use EIoDisconnectReason::*;
         match value {
             Error::SocketGone(_) => Some(TransportClose),
-            Error::Serialize(_) | Error::InvalidPacketType | Error::InvalidEventName => {
-                Some(PacketParsingError)
-            }
+            Error::Serialize(_)
+            | Error::InvalidPacketType
+            | Error::InvalidEventName
+            | Error::Decode(_) => Some(PacketParsingError),
             Error::Adapter(_) | Error::InvalidNamespace => None,
         }
     }
@@ -237,6 +237,25 @@ impl Extensions {
         self.map.clear();
     }
 
+    /// Moves every extension of `other` into this set, replacing the extensions of the same type.
+    pub(crate) fn extend(&self, other: Extensions) {
+        for (id, val) in other.map {
+            self.map.insert(id, val);
+        }
+    }
+
+    /// Moves every extension out of this set.
+    pub(crate) fn take(&self) -> Extensions {
+        let taken = Extensions::new();
+        let ids: Vec<_> = self.map.iter().map(|e| *e.key()).collect();
+        for id in ids {
+            if let Some((id, val)) = self.map.remove(&id) {
+                taken.map.insert(id, val);
+            }
+        }
+        taken
+    }
+
     /// Check whether the extension set is empty or not.
     ///
     /// # Example
@@ -57,6 +57,7 @@
 use std::sync::Arc;
 
 use futures::Future;
+use tokio::sync::OwnedSemaphorePermit;
 
 use crate::{
     adapter::Adapter,
@@ -69,6 +70,12 @@ use super::MakeErasedHandler;
 pub(crate) type BoxedDisconnectHandler<A> = Box<dyn ErasedDisconnectHandler<A>>;
 pub(crate) trait ErasedDisconnectHandler<A: Adapter>: Send + Sync + 'static {
     fn call(&self, s: Arc<Socket<A>>, reason: DisconnectReason);
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        reason: DisconnectReason,
+        permit: OwnedSemaphorePermit,
+    );
 }
 
 impl<A: Adapter, T, H> MakeErasedHandler<H, A, T>
@@ -90,6 +97,16 @@ where
     fn call(&self, s: Arc<Socket<A>>, reason: DisconnectReason) {
         self.handler.call(s, reason);
     }
+
+    #[inline(always)]
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        reason: DisconnectReason,
+        permit: OwnedSemaphorePermit,
+    ) {
+        self.handler.call_with_permit(s, reason, permit);
+    }
 }
 
 /// A trait used to extract the arguments from the disconnect event.
@@ -119,6 +136,19 @@ pub trait DisconnectHandler<A: Adapter, T>: Send + Sync + 'static {
     /// Call the handler with the given arguments.
     fn call(&self, s: Arc<Socket<A>>, reason: DisconnectReason);
 
+    /// Call the handler while holding the given permit.
+    /// The permit is released once the handler has completed.
+    #[doc(hidden)]
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        reason: DisconnectReason,
+        permit: OwnedSemaphorePermit,
+    ) {
+        self.call(s, reason);
+        drop(permit);
+    }
+
     #[doc(hidden)]
     fn phantom(&self) -> std::marker::PhantomData<T> {
         std::marker::PhantomData
@@ -160,6 +190,30 @@ macro_rules! impl_handler_async {
                 tokio::spawn(fut);
 
             }
+
+            fn call_with_permit(
+                &self,
+                s: Arc<Socket<A>>,
+                reason: DisconnectReason,
+                permit: OwnedSemaphorePermit,
+            ) {
+                $(
+                    let $ty = match $ty::from_disconnect_parts(&s, reason) {
+                        Ok(v) => v,
+                        Err(_e) => {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("Error while extracting data: {}", _e);
+                            return;
+                        },
+                    };
+                )*
+
+                let fut = (self.clone())($($ty,)*);
+                tokio::spawn(async move {
+                    fut.await;
+                    drop(permit);
+                });
+            }
         }
     };
 }
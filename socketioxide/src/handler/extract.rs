@@ -5,14 +5,26 @@
 //! * [`Data`]: extracts and deserialize to json any data, if a deserialization error occurs the handler won't be called:
 //!     - for [`ConnectHandler`](super::ConnectHandler): extracts and deserialize to json the auth data
 //!     - for [`MessageHandler`](super::MessageHandler): extracts and deserialize to json the message data
+//!     - the binary payloads referenced by a nested placeholder of the message data can be deserialized
+//!       to the [`Attachment`] fields of the data type
 //! * [`TryData`]: extracts and deserialize to json any data but with a `Result` type in case of error:
 //!     - for [`ConnectHandler`](super::ConnectHandler): extracts and deserialize to json the auth data
 //!     - for [`MessageHandler`](super::MessageHandler): extracts and deserialize to json the message data
+//! * [`AuthData`]: extracts and deserialize to json the auth payload of the connect packet,
+//!   a missing payload is deserialized from `null`
+//! * [`NsParam`]: extracts and parses the parameter matched by the pattern of a
+//!   [`SocketIo::dynamic_ns`](crate::SocketIo#method.dynamic_ns) namespace
 //! * [`SocketRef`]: extracts a reference to the [`Socket`]
 //! * [`Bin`]: extract a binary payload for a given message. Because it consumes the event it should be the last argument
-//! * [`AckSender`]: Can be used to send an ack response to the current message event
+//! * [`AckSender`]: Can be used to send an ack response to the current message event,
+//!   it can be converted to a [`DeferredAck`] to send the response after the handler returned
+//! * [`Reply`]: Can be used to emit a response event to the current message event, even if the client didn't request an ack
+//! * [`AckId`]: extracts the ack id of the current message event, to send the ack later with [`Socket::send_manual_ack`]
+//! * [`EventName`]: extracts the name of the current message event, mostly useful in a [`Socket::on_fallback`] handler
 //! * [`ProtocolVersion`](crate::ProtocolVersion): extracts the protocol version
 //! * [`TransportType`](crate::TransportType): extracts the transport type
+//! * [`TraceContext`]: extracts the W3C trace context sent by the client
+//! * [`Handshake`]: extracts the handshake details of the socket: auth payload, query params, headers and address
 //! * [`DisconnectReason`]: extracts the reason of the disconnection
 //! * [`State`]: extracts a reference to a state previously set with [`SocketIoBuilder::with_state`](crate::io::SocketIoBuilder).
 //!
@@ -82,14 +94,17 @@
 //! let (svc, io) = SocketIo::new_svc();
 //! io.ns("/", handler);
 //! // Use the service with your favorite http server
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::convert::Infallible;
-use std::sync::Arc;
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
 
 use super::message::FromMessageParts;
 use super::FromDisconnectParts;
 use super::{connect::FromConnectParts, message::FromMessage};
 use crate::errors::{DisconnectError, SendError};
-use crate::socket::DisconnectReason;
+use crate::socket::{DisconnectReason, Handshake, Sid};
 use crate::{
     adapter::{Adapter, LocalAdapter},
     packet::Packet,
@@ -112,9 +127,80 @@ fn upwrap_array(v: &mut Value) {
     }
 }
 
+thread_local! {
+    /// The binary payloads of the message whose data is being deserialized on this thread,
+    /// read by the [`Attachment`] fields
+    static CURRENT_BINARY: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Deserializes the message data, with its binary payloads available to the [`Attachment`] fields
+fn from_message_data<T: DeserializeOwned>(
+    v: &mut Value,
+    bin: &mut Vec<Vec<u8>>,
+) -> Result<T, serde_json::Error> {
+    struct Restore<'a>(&'a mut Vec<Vec<u8>>);
+    impl Drop for Restore<'_> {
+        fn drop(&mut self) {
+            CURRENT_BINARY.with(|b| std::mem::swap(&mut *b.borrow_mut(), self.0));
+        }
+    }
+    upwrap_array(v);
+    if bin.is_empty() {
+        return T::deserialize(&*v);
+    }
+    // The payloads are moved in and out of the thread local, they are restored for the next extractors
+    CURRENT_BINARY.with(|b| std::mem::swap(&mut *b.borrow_mut(), bin));
+    let _restore = Restore(bin);
+    T::deserialize(&*v)
+}
+
+/// A binary payload of a message, deserialized from the placeholder that references it in the message data.
+///
+/// It is only filled by the [`Data`] and [`TryData`] extractors of a [`MessageHandler`](super::MessageHandler),
+/// so that a binary attachment nested in the data can be deserialized to a named field.
+/// The binary payloads stay available to the [`Bin`] extractor.
+///
+/// #### Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*};
+/// #[derive(serde::Deserialize)]
+/// struct Upload {
+///     name: String,
+///     // The client sends `{ name: "clip", video: <Buffer> }`
+///     video: Attachment,
+/// }
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef| {
+///     socket.on("upload", |Data::<Upload>(upload)| {
+///         println!("received {}: {} bytes", upload.name, upload.video.0.len());
+///     });
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment(pub Vec<u8>);
+
+impl<'de> serde::Deserialize<'de> for Attachment {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        struct Placeholder {
+            #[serde(rename = "_placeholder")]
+            _placeholder: bool,
+            num: usize,
+        }
+        let Placeholder { num, .. } = Placeholder::deserialize(deserializer)?;
+        CURRENT_BINARY
+            .with(|b| b.borrow().get(num).cloned())
+            .map(Attachment)
+            .ok_or_else(|| serde::de::Error::custom(format_args!("missing binary payload {num}")))
+    }
+}
+
 /// An Extractor that returns the serialized auth data without checking errors.
 /// If a deserialization error occurs, the [`ConnectHandler`](super::ConnectHandler) won't be called
 /// and an error log will be print if the `tracing` feature is enabled.
+///
+/// For a binary message, the placeholders nested in the data can be deserialized
+/// to the [`Attachment`] fields of `T`.
 pub struct Data<T: DeserializeOwned>(pub T);
 impl<T, A> FromConnectParts<A> for Data<T>
 where
@@ -122,11 +208,8 @@ where
     A: Adapter,
 {
     type Error = serde_json::Error;
-    fn from_connect_parts(_: &Arc<Socket<A>>, auth: &Option<String>) -> Result<Self, Self::Error> {
-        auth.as_ref()
-            .map(|a| serde_json::from_str::<T>(a))
-            .unwrap_or(serde_json::from_str::<T>("{}"))
-            .map(Data)
+    fn from_connect_parts(s: &Arc<Socket<A>>, auth: &Option<String>) -> Result<Self, Self::Error> {
+        auth_data(s, auth).map(Data)
     }
 }
 impl<T, A> FromMessageParts<A> for Data<T>
@@ -138,11 +221,23 @@ where
     fn from_message_parts(
         _: &Arc<Socket<A>>,
         v: &mut serde_json::Value,
-        _: &mut Vec<Vec<u8>>,
+        bin: &mut Vec<Vec<u8>>,
         _: &Option<i64>,
     ) -> Result<Self, Self::Error> {
-        upwrap_array(v);
-        serde_json::from_value(v.clone()).map(Data)
+        from_message_data(v, bin).map(Data)
+    }
+}
+
+/// Deserializes the auth payload from the value parsed when the socket was created.
+/// An invalid payload was parsed to `null`, it is parsed again only to get the error.
+fn auth_data<T: DeserializeOwned, A: Adapter>(
+    s: &Arc<Socket<A>>,
+    auth: &Option<String>,
+) -> Result<T, serde_json::Error> {
+    match auth {
+        None => serde_json::from_str("{}"),
+        Some(a) if s.handshake().auth.is_null() => serde_json::from_str(a),
+        Some(_) => T::deserialize(&s.handshake().auth),
     }
 }
 
@@ -155,12 +250,8 @@ where
     A: Adapter,
 {
     type Error = Infallible;
-    fn from_connect_parts(_: &Arc<Socket<A>>, auth: &Option<String>) -> Result<Self, Infallible> {
-        let v: Result<T, serde_json::Error> = auth
-            .as_ref()
-            .map(|a| serde_json::from_str(a))
-            .unwrap_or(serde_json::from_str("{}"));
-        Ok(TryData(v))
+    fn from_connect_parts(s: &Arc<Socket<A>>, auth: &Option<String>) -> Result<Self, Infallible> {
+        Ok(TryData(auth_data(s, auth)))
     }
 }
 impl<T, A> FromMessageParts<A> for TryData<T>
@@ -172,13 +263,107 @@ where
     fn from_message_parts(
         _: &Arc<Socket<A>>,
         v: &mut serde_json::Value,
-        _: &mut Vec<Vec<u8>>,
+        bin: &mut Vec<Vec<u8>>,
         _: &Option<i64>,
     ) -> Result<Self, Infallible> {
-        upwrap_array(v);
-        Ok(TryData(serde_json::from_value(v.clone())))
+        Ok(TryData(from_message_data(v, bin)))
+    }
+}
+/// An Extractor that deserializes the auth payload sent by the client in its connect packet.
+///
+/// Unlike the [`Data`] extractor in a connect handler, a missing auth payload is deserialized from `null`,
+/// so extracting an `AuthData<Option<T>>` yields `None` if the client didn't send any auth.
+/// It can also be used in a message handler to read the auth payload of the socket.
+///
+/// If the deserialization fails, the handler is not called.
+///
+/// #### Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*};
+/// #[derive(serde::Deserialize)]
+/// struct Auth {
+///     token: String,
+/// }
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef, AuthData(auth): AuthData<Option<Auth>>| {
+///     match auth {
+///         Some(auth) => println!("token: {}", auth.token),
+///         None => socket.disconnect().unwrap(),
+///     }
+/// });
+/// ```
+pub struct AuthData<T: DeserializeOwned>(pub T);
+impl<T, A> FromConnectParts<A> for AuthData<T>
+where
+    T: DeserializeOwned,
+    A: Adapter,
+{
+    type Error = serde_json::Error;
+    fn from_connect_parts(s: &Arc<Socket<A>>, _: &Option<String>) -> Result<Self, Self::Error> {
+        T::deserialize(&s.handshake().auth).map(AuthData)
     }
 }
+impl<T, A> FromMessageParts<A> for AuthData<T>
+where
+    T: DeserializeOwned,
+    A: Adapter,
+{
+    type Error = serde_json::Error;
+    fn from_message_parts(
+        s: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Self::Error> {
+        T::deserialize(&s.handshake().auth).map(AuthData)
+    }
+}
+
+/// An Extractor that parses the first parameter matched by the pattern of a namespace created with
+/// [`SocketIo::dynamic_ns`](crate::SocketIo#method.dynamic_ns).
+/// Use [`Socket::ns_param`] to get the parameters of a pattern with several parameters.
+///
+/// If the namespace has no parameter or if the parsing fails, the handler is not called.
+///
+/// #### Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*};
+/// let (_, io) = SocketIo::new_svc();
+/// io.dynamic_ns("/room/:name", |socket: SocketRef, NsParam(name): NsParam| {
+///     println!("socket {} connected to room namespace {}", socket.id, name);
+/// });
+/// ```
+pub struct NsParam<T: FromStr = String>(pub T);
+/// The namespace of the socket has no parameter or it couldn't be parsed,
+/// therefore the handler won't be called.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid or missing namespace parameter")]
+pub struct NsParamRejection;
+
+impl<T: FromStr> NsParam<T> {
+    fn extract<A: Adapter>(s: &Socket<A>) -> Result<Self, NsParamRejection> {
+        let (_, param) = s.ns.params.first().ok_or(NsParamRejection)?;
+        param.parse().map(NsParam).map_err(|_| NsParamRejection)
+    }
+}
+impl<T: FromStr, A: Adapter> FromConnectParts<A> for NsParam<T> {
+    type Error = NsParamRejection;
+    fn from_connect_parts(s: &Arc<Socket<A>>, _: &Option<String>) -> Result<Self, Self::Error> {
+        Self::extract(s)
+    }
+}
+impl<T: FromStr, A: Adapter> FromMessageParts<A> for NsParam<T> {
+    type Error = NsParamRejection;
+    fn from_message_parts(
+        s: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Self::Error> {
+        Self::extract(s)
+    }
+}
+
 /// An Extractor that returns a reference to a [`Socket`].
 #[derive(Debug)]
 pub struct SocketRef<A: Adapter = LocalAdapter>(Arc<Socket<A>>);
@@ -237,9 +422,24 @@ impl<A: Adapter> SocketRef<A> {
     /// Disconnect the socket from the current namespace,
     ///
     /// It will also call the disconnect handler if it is set.
+    /// The [`SocketRef`] is not consumed, so it can still be used afterwards, e.g. to log the socket id.
+    #[inline(always)]
+    pub fn disconnect(&self) -> Result<(), DisconnectError> {
+        self.0.clone().disconnect()
+    }
+
+    /// Disconnect the socket from the current namespace on behalf of the adapter,
+    /// see [`Socket::adapter_disconnect`].
+    #[inline(always)]
+    pub fn adapter_disconnect(&self) -> Result<(), DisconnectError> {
+        self.0.clone().adapter_disconnect()
+    }
+
+    /// Disconnect the socket from the current namespace after sending a payload to the client,
+    /// see [`Socket::disconnect_with`].
     #[inline(always)]
-    pub fn disconnect(self) -> Result<(), DisconnectError> {
-        self.0.disconnect()
+    pub fn disconnect_with<T: Serialize>(&self, payload: T) -> Result<(), DisconnectError> {
+        self.0.clone().disconnect_with(payload)
     }
 }
 
@@ -294,7 +494,6 @@ impl<A: Adapter> AckSender<A> {
 
     /// Send the ack response to the client.
     pub fn send<T: Serialize>(self, data: T) -> Result<(), SendError<T>> {
-        use crate::socket::PermitIteratorExt;
         if let Some(ack_id) = self.ack_id {
             let permits = match self.socket.reserve(1 + self.binary.len()) {
                 Ok(permits) => permits,
@@ -311,12 +510,357 @@ impl<A: Adapter> AckSender<A> {
             } else {
                 Packet::bin_ack(ns, data, self.binary, ack_id)
             };
-            permits.emit(packet);
+            self.socket.send_with_permits(packet, permits);
             Ok(())
         } else {
             Ok(())
         }
     }
+
+    /// Converts the ack sender into a [`DeferredAck`] that can be stored and sent
+    /// after the handler returned, e.g. from a spawned task.
+    ///
+    /// Unlike the [`AckSender`], the [`DeferredAck`] doesn't keep the socket alive once it is disconnected.
+    pub fn into_deferred(self) -> DeferredAck<A> {
+        DeferredAck {
+            binary: self.binary,
+            sid: self.socket.id,
+            socket: Arc::downgrade(&self.socket),
+            ack_id: self.ack_id,
+        }
+    }
+}
+
+/// An owned handle to send an ack response to an event long after its handler returned,
+/// created with [`AckSender::into_deferred`].
+///
+/// It only holds a weak reference to the socket, so it can be stored without preventing
+/// the socket from being dropped when it disconnects.
+///
+/// #### Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*};
+/// # use serde_json::Value;
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef| {
+///     socket.on("job", |Data::<Value>(job), ack: AckSender| {
+///         let ack = ack.into_deferred();
+///         tokio::spawn(async move {
+///             tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+///             // Fails with `SendError::SocketGone` if the socket disconnected in the meantime
+///             ack.send(job).ok();
+///         });
+///     });
+/// });
+/// ```
+#[derive(Debug)]
+pub struct DeferredAck<A: Adapter = LocalAdapter> {
+    binary: Vec<Vec<u8>>,
+    sid: Sid,
+    socket: Weak<Socket<A>>,
+    ack_id: Option<i64>,
+}
+impl<A: Adapter> DeferredAck<A> {
+    pub(crate) fn new(socket: &Arc<Socket<A>>, ack_id: i64) -> Self {
+        Self {
+            binary: Vec::new(),
+            sid: socket.id,
+            socket: Arc::downgrade(socket),
+            ack_id: Some(ack_id),
+        }
+    }
+
+    /// Send the ack response to the client.
+    ///
+    /// If the socket disconnected from the namespace, nothing is sent and a [`SendError::SocketGone`]
+    /// is returned. Otherwise it behaves like [`AckSender::send`].
+    pub fn send<T: Serialize>(self, data: T) -> Result<(), SendError<T>> {
+        let socket = self
+            .socket
+            .upgrade()
+            .filter(|s| s.ns.get_socket(self.sid).is_ok())
+            .ok_or(SendError::SocketGone(self.sid))?;
+        AckSender {
+            binary: self.binary,
+            socket,
+            ack_id: self.ack_id,
+        }
+        .send(data)
+    }
+}
+
+thread_local! {
+    /// The name of the event whose handler is being called on this thread
+    static CURRENT_EVENT: RefCell<Option<Cow<'static, str>>> = const { RefCell::new(None) };
+}
+
+/// Calls `f` with `event` set as the current event, so that it can be extracted by the [`Reply`]
+/// and [`EventName`] extractors.
+///
+/// Extractors are always run synchronously when the handler is called, before any async handler is spawned.
+pub(crate) fn with_current_event<R>(event: Cow<'static, str>, f: impl FnOnce() -> R) -> R {
+    struct Reset(Option<Cow<'static, str>>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            let prev = self.0.take();
+            CURRENT_EVENT.with(|e| *e.borrow_mut() = prev);
+        }
+    }
+    let _reset = Reset(CURRENT_EVENT.with(|e| e.borrow_mut().replace(event)));
+    f()
+}
+
+/// An Extractor to emit a response event to the socket that sent the current event.
+///
+/// Unlike the [`AckSender`], the response is a regular event: it doesn't need the client to request
+/// an ack and the client must listen to the response event. Unlike a plain [`Socket::emit`], it is
+/// bound to the current event, whose name is available with [`Reply::event`] and can be sent back
+/// for correlation with [`Reply::send_correlated`].
+///
+/// #### Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*};
+/// # use serde_json::Value;
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef| {
+///     socket.on("query", |Data::<Value>(query), reply: Reply| {
+///         // The client receives ["query-result", <query>]
+///         reply.send("query-result", query).ok();
+///     });
+/// });
+/// ```
+#[derive(Debug)]
+pub struct Reply<A: Adapter = LocalAdapter> {
+    binary: Vec<Vec<u8>>,
+    socket: Arc<Socket<A>>,
+    event: Cow<'static, str>,
+}
+impl<A: Adapter> FromMessageParts<A> for Reply<A> {
+    type Error = Infallible;
+    fn from_message_parts(
+        s: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Infallible> {
+        let event = CURRENT_EVENT
+            .with(|e| e.borrow().clone())
+            .unwrap_or_default();
+        Ok(Self {
+            binary: vec![],
+            socket: s.clone(),
+            event,
+        })
+    }
+}
+impl<A: Adapter> Reply<A> {
+    /// The name of the event this reply responds to.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Add binary data to the reply.
+    pub fn bin(mut self, bin: Vec<Vec<u8>>) -> Self {
+        self.binary = bin;
+        self
+    }
+
+    /// Emits the `event` response with `data` to the client.
+    ///
+    /// If you provide array-like data (tuple, vec, arrays), it will be considered as multiple arguments.
+    pub fn send<T: Serialize>(
+        self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        self.socket.bin(self.binary).emit(event, data)
+    }
+
+    /// Emits the `event` response with `data` to the client, followed by the name of the current event
+    /// as an **extra trailing argument**, so that the client can tell which event it responds to.
+    pub fn send_correlated<T: Serialize>(
+        self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        let permits = match self.socket.reserve(1 + self.binary.len()) {
+            Ok(permits) => permits,
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("sending error during emit message: {e:?}");
+                return Err(e.with_value(data).into());
+            }
+        };
+        let args = match serde_json::to_value(data)? {
+            Value::Array(mut args) => {
+                args.push(self.event.to_string().into());
+                args
+            }
+            data => vec![data, self.event.to_string().into()],
+        };
+        let ns = self.socket.ns.path.clone();
+        let packet = if self.binary.is_empty() {
+            Packet::event(ns, event.into(), Value::Array(args))
+        } else {
+            Packet::bin_event(ns, event.into(), Value::Array(args), self.binary)
+        };
+        self.socket.send_with_permits(packet, permits);
+        Ok(())
+    }
+}
+
+/// An Extractor that returns the name of the current event.
+///
+/// It is mostly useful in a handler registered with [`Socket::on_fallback`],
+/// which is called for several event names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventName(pub String);
+impl<A: Adapter> FromMessageParts<A> for EventName {
+    type Error = Infallible;
+    fn from_message_parts(
+        _: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Infallible> {
+        let event = CURRENT_EVENT.with(|e| e.borrow().as_deref().map(str::to_string));
+        Ok(EventName(event.unwrap_or_default()))
+    }
+}
+
+/// An Extractor that returns the ack id of the current event, if the client expects an ack.
+///
+/// It can be used with [`Socket::send_manual_ack`] to send the ack response later,
+/// without holding the [`AckSender`]. If the ack is never sent, the client callback never resolves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AckId(pub Option<i64>);
+impl<A: Adapter> FromMessageParts<A> for AckId {
+    type Error = Infallible;
+    fn from_message_parts(
+        _: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        ack_id: &Option<i64>,
+    ) -> Result<Self, Infallible> {
+        Ok(AckId(*ack_id))
+    }
+}
+
+/// A W3C [`traceparent`](https://www.w3.org/TR/trace-context/#traceparent-header) sent by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceParent {
+    trace_id: String,
+    parent_id: String,
+    flags: u8,
+}
+
+impl TraceParent {
+    /// Parses a `traceparent` value in the form `{version}-{trace-id}-{parent-id}-{flags}`.
+    ///
+    /// Returns `None` if it is invalid.
+    pub fn parse(value: &str) -> Option<Self> {
+        fn is_hex(s: &str, len: usize) -> bool {
+            s.len() == len && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        }
+        let mut parts = value.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let parent_id = parts.next()?;
+        let flags = parts.next()?;
+        // Future versions may append fields, version 00 must not
+        if !is_hex(version, 2) || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        if !is_hex(trace_id, 32) || trace_id.bytes().all(|b| b == b'0') {
+            return None;
+        }
+        if !is_hex(parent_id, 16) || parent_id.bytes().all(|b| b == b'0') || !is_hex(flags, 2) {
+            return None;
+        }
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            parent_id: parent_id.to_string(),
+            flags: u8::from_str_radix(flags, 16).ok()?,
+        })
+    }
+
+    /// The id of the whole trace, as 32 hex characters
+    pub fn trace_id(&self) -> &str {
+        &self.trace_id
+    }
+
+    /// The id of the client span that made the request, as 16 hex characters
+    pub fn parent_id(&self) -> &str {
+        &self.parent_id
+    }
+
+    /// The trace flags
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Returns `true` if the client recorded the trace
+    pub fn sampled(&self) -> bool {
+        self.flags & 0x01 == 0x01
+    }
+}
+
+/// An Extractor that returns the [`TraceParent`] sent by the client when connecting, if any.
+///
+/// It is read from the `traceparent` header of the handshake request or, because browsers cannot set
+/// headers on websocket connections, from the `traceparent` field of the auth payload.
+///
+/// With the `tracing` feature, each message event is dispatched in a `socketio.event` span
+/// carrying the `trace_id` and `parent_id` fields, so handler logs can be correlated
+/// with the client trace. Async handlers are run in this span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceContext(pub Option<TraceParent>);
+impl<A: Adapter> FromConnectParts<A> for TraceContext {
+    type Error = Infallible;
+    fn from_connect_parts(s: &Arc<Socket<A>>, _: &Option<String>) -> Result<Self, Infallible> {
+        Ok(TraceContext(s.trace_parent().cloned()))
+    }
+}
+impl<A: Adapter> FromMessageParts<A> for TraceContext {
+    type Error = Infallible;
+    fn from_message_parts(
+        s: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Infallible> {
+        Ok(TraceContext(s.trace_parent().cloned()))
+    }
+}
+impl<A: Adapter> FromDisconnectParts<A> for TraceContext {
+    type Error = Infallible;
+    fn from_disconnect_parts(s: &Arc<Socket<A>>, _: DisconnectReason) -> Result<Self, Infallible> {
+        Ok(TraceContext(s.trace_parent().cloned()))
+    }
+}
+
+impl<A: Adapter> FromConnectParts<A> for Handshake {
+    type Error = Infallible;
+    fn from_connect_parts(s: &Arc<Socket<A>>, _: &Option<String>) -> Result<Self, Infallible> {
+        Ok(s.handshake().clone())
+    }
+}
+impl<A: Adapter> FromMessageParts<A> for Handshake {
+    type Error = Infallible;
+    fn from_message_parts(
+        s: &Arc<Socket<A>>,
+        _: &mut serde_json::Value,
+        _: &mut Vec<Vec<u8>>,
+        _: &Option<i64>,
+    ) -> Result<Self, Infallible> {
+        Ok(s.handshake().clone())
+    }
+}
+impl<A: Adapter> FromDisconnectParts<A> for Handshake {
+    type Error = Infallible;
+    fn from_disconnect_parts(s: &Arc<Socket<A>>, _: DisconnectReason) -> Result<Self, Infallible> {
+        Ok(s.handshake().clone())
+    }
 }
 
 impl<A: Adapter> FromConnectParts<A> for crate::ProtocolVersion {
@@ -71,21 +71,73 @@
 //!     s.on("event_2", on_event);
 //! });
 //! ```
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use futures::Future;
 use serde_json::Value;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 use crate::adapter::Adapter;
 use crate::socket::Socket;
 
-use super::MakeErasedHandler;
+use super::{extract::with_current_event, MakeErasedHandler};
 
-/// A Type Erased [`MessageHandler`] so it can be stored in a HashMap
-pub(crate) type BoxedMessageHandler<A> = Box<dyn ErasedMessageHandler<A>>;
+/// A Type Erased [`MessageHandler`] so it can be stored in a HashMap.
+/// It is shared so that it can be called without holding the lock of the handlers map.
+pub(crate) type BoxedMessageHandler<A> = Arc<dyn ErasedMessageHandler<A>>;
+
+/// The id of a message handler registered with [`Socket::on_with_id`],
+/// used to remove it with [`Socket::off_by_id`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(pub(crate) u64);
+
+/// The options of a message handler registered with [`Socket::on_with_opts`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandlerOpts {
+    /// The maximum number of invocations of the handler running at the same time for a socket.
+    /// An async handler is running until its future completes.
+    ///
+    /// Defaults to `None`: every event is dispatched immediately.
+    pub max_concurrent: Option<usize>,
+    /// The policy applied to the events received while `max_concurrent` invocations are running.
+    ///
+    /// Defaults to an unbounded [`ConcurrencyOverflowPolicy::Queue`].
+    pub overflow: ConcurrencyOverflowPolicy,
+}
+impl Default for HandlerOpts {
+    fn default() -> Self {
+        Self {
+            max_concurrent: None,
+            overflow: ConcurrencyOverflowPolicy::Queue(usize::MAX),
+        }
+    }
+}
+
+/// Policy applied to the events received while a handler already runs its
+/// [`HandlerOpts::max_concurrent`] invocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConcurrencyOverflowPolicy {
+    /// Up to `n` events are queued and dispatched as soon as a running invocation completes.
+    /// Events received when the queue is full are dropped.
+    Queue(usize),
+    /// Every event received while the limit is reached is dropped.
+    Drop,
+}
 
 pub(crate) trait ErasedMessageHandler<A: Adapter>: Send + Sync + 'static {
     fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>);
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        v: Value,
+        p: Vec<Vec<u8>>,
+        ack_id: Option<i64>,
+        permit: OwnedSemaphorePermit,
+    );
 }
 
 /// Define a handler for the connect event.
@@ -103,6 +155,21 @@ pub trait MessageHandler<A: Adapter, T>: Send + Sync + 'static {
     /// Call the handler with the given arguments
     fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>);
 
+    /// Call the handler while holding the given permit, if any.
+    /// The permit is released once the handler has completed.
+    #[doc(hidden)]
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        v: Value,
+        p: Vec<Vec<u8>>,
+        ack_id: Option<i64>,
+        permit: Option<OwnedSemaphorePermit>,
+    ) {
+        self.call(s, v, p, ack_id);
+        drop(permit);
+    }
+
     #[doc(hidden)]
     fn phantom(&self) -> std::marker::PhantomData<T> {
         std::marker::PhantomData
@@ -115,8 +182,8 @@ where
     H: MessageHandler<A, T>,
     A: Adapter,
 {
-    pub fn new_message_boxed(inner: H) -> Box<dyn ErasedMessageHandler<A>> {
-        Box::new(MakeErasedHandler::new(inner))
+    pub fn new_message_boxed(inner: H) -> BoxedMessageHandler<A> {
+        Arc::new(MakeErasedHandler::new(inner))
     }
 }
 
@@ -130,6 +197,87 @@ where
     fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>) {
         self.handler.call(s, v, p, ack_id);
     }
+
+    #[inline(always)]
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        v: Value,
+        p: Vec<Vec<u8>>,
+        ack_id: Option<i64>,
+        permit: OwnedSemaphorePermit,
+    ) {
+        self.handler.call_with_permit(s, v, p, ack_id, Some(permit));
+    }
+}
+
+/// A message handler whose concurrent invocations are limited, see [`HandlerOpts::max_concurrent`].
+pub(crate) struct LimitedMessageHandler<A: Adapter> {
+    inner: BoxedMessageHandler<A>,
+    event: Cow<'static, str>,
+    semaphore: Arc<Semaphore>,
+    overflow: ConcurrencyOverflowPolicy,
+    queued: Arc<AtomicUsize>,
+}
+
+impl<A: Adapter> LimitedMessageHandler<A> {
+    pub fn new_boxed(
+        inner: BoxedMessageHandler<A>,
+        event: Cow<'static, str>,
+        max_concurrent: usize,
+        overflow: ConcurrencyOverflowPolicy,
+    ) -> BoxedMessageHandler<A> {
+        Arc::new(Self {
+            inner,
+            event,
+            semaphore: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            overflow,
+            queued: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+}
+
+impl<A: Adapter> ErasedMessageHandler<A> for LimitedMessageHandler<A> {
+    fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>) {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            self.inner.call_with_permit(s, v, p, ack_id, permit);
+            return;
+        }
+        let max = match self.overflow {
+            ConcurrencyOverflowPolicy::Queue(max) => max,
+            ConcurrencyOverflowPolicy::Drop => 0,
+        };
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= max {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            #[cfg(feature = "tracing")]
+            tracing::debug!("event {} dropped, too many running handlers", self.event);
+            return;
+        }
+        let inner = self.inner.clone();
+        let event = self.event.clone();
+        let semaphore = self.semaphore.clone();
+        let queued = self.queued.clone();
+        spawn_in_current_span(async move {
+            // The semaphore is never closed
+            let Ok(permit) = semaphore.acquire_owned().await else {
+                return;
+            };
+            queued.fetch_sub(1, Ordering::SeqCst);
+            with_current_event(event, || inner.call_with_permit(s, v, p, ack_id, permit));
+        });
+    }
+
+    fn call_with_permit(
+        &self,
+        s: Arc<Socket<A>>,
+        v: Value,
+        p: Vec<Vec<u8>>,
+        ack_id: Option<i64>,
+        permit: OwnedSemaphorePermit,
+    ) {
+        // The caller already limits the invocations to one at a time with its permit
+        self.inner.call_with_permit(s, v, p, ack_id, permit);
+    }
 }
 
 mod private {
@@ -212,6 +360,13 @@ where
     }
 }
 
+/// Spawns the future of an async handler, in the current span if the `tracing` feature is enabled
+fn spawn_in_current_span<F: Future<Output = ()> + Send + 'static>(fut: F) {
+    #[cfg(feature = "tracing")]
+    let fut = tracing::Instrument::in_current_span(fut);
+    tokio::spawn(fut);
+}
+
 /// Empty Async handler
 impl<A, F, Fut> MessageHandler<A, (private::Async,)> for F
 where
@@ -219,9 +374,23 @@ where
     Fut: Future<Output = ()> + Send + 'static,
     A: Adapter,
 {
-    fn call(&self, _: Arc<Socket<A>>, _: Value, _: Vec<Vec<u8>>, _: Option<i64>) {
+    fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>) {
+        self.call_with_permit(s, v, p, ack_id, None);
+    }
+
+    fn call_with_permit(
+        &self,
+        _: Arc<Socket<A>>,
+        _: Value,
+        _: Vec<Vec<u8>>,
+        _: Option<i64>,
+        permit: Option<OwnedSemaphorePermit>,
+    ) {
         let fut = (self.clone())();
-        tokio::spawn(fut);
+        spawn_in_current_span(async move {
+            fut.await;
+            drop(permit);
+        });
     }
 }
 
@@ -249,7 +418,18 @@ macro_rules! impl_async_handler {
             $( $ty: FromMessageParts<A> + Send, )*
             $last: FromMessage<A, M> + Send,
         {
-            fn call(&self, s: Arc<Socket<A>>, mut v: Value, mut p: Vec<Vec<u8>>, ack_id: Option<i64>) {
+            fn call(&self, s: Arc<Socket<A>>, v: Value, p: Vec<Vec<u8>>, ack_id: Option<i64>) {
+                self.call_with_permit(s, v, p, ack_id, None);
+            }
+
+            fn call_with_permit(
+                &self,
+                s: Arc<Socket<A>>,
+                mut v: Value,
+                mut p: Vec<Vec<u8>>,
+                ack_id: Option<i64>,
+                permit: Option<OwnedSemaphorePermit>,
+            ) {
                 $(
                     let $ty = match $ty::from_message_parts(&s, &mut v, &mut p, &ack_id) {
                         Ok(v) => v,
@@ -270,7 +450,10 @@ macro_rules! impl_async_handler {
                 };
 
                 let fut = (self.clone())($($ty,)* last);
-                tokio::spawn(fut);
+                spawn_in_current_span(async move {
+                    fut.await;
+                    drop(permit);
+                });
             }
         }
     };
@@ -0,0 +1,104 @@
+//! Namespace middlewares, run when a socket connects, before the [`ConnectHandler`](super::ConnectHandler).
+//!
+//! A middleware receives the connecting [`Socket`] and its auth payload and can refuse the connection
+//! by returning an error. The client then receives a connect error packet with the error message
+//! and the connect handler is never called.
+//!
+//! Middlewares are added at runtime with [`SocketIo::push_middleware`] and run in the order they were added.
+//! They can be removed with [`SocketIo::remove_middleware`]. Adding or removing a middleware only affects
+//! the sockets connecting afterwards, already connected sockets are left untouched.
+//!
+//! [`SocketIo::push_middleware`]: crate::SocketIo#method.push_middleware
+//! [`SocketIo::remove_middleware`]: crate::SocketIo#method.remove_middleware
+//!
+//! #### Example
+//! ```
+//! # use socketioxide::{SocketIo, extract::*};
+//! let (_, io) = SocketIo::new_svc();
+//! io.ns("/", |socket: SocketRef| {});
+//!
+//! // Enable a stricter auth during an incident
+//! let id = io
+//!     .push_middleware("/", |_, auth| match auth {
+//!         Some(auth) if auth.contains("token") => Ok(()),
+//!         _ => Err("missing token".into()),
+//!     })
+//!     .unwrap();
+//!
+//! // Later on
+//! io.remove_middleware("/", id);
+//! ```
+use std::sync::Arc;
+
+use crate::{adapter::Adapter, socket::Socket};
+
+/// The error returned by a middleware to refuse a connection.
+/// Its message is sent to the client in the connect error packet.
+pub type MiddlewareError = Box<dyn std::error::Error + Send + Sync>;
+
+/// The id of a middleware added with [`SocketIo::push_middleware`],
+/// used to remove it with [`SocketIo::remove_middleware`].
+///
+/// [`SocketIo::push_middleware`]: crate::SocketIo#method.push_middleware
+/// [`SocketIo::remove_middleware`]: crate::SocketIo#method.remove_middleware
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MiddlewareId(pub(crate) u64);
+
+pub(crate) type BoxedMiddleware<A> =
+    Arc<dyn Fn(&Socket<A>, Option<&str>) -> Result<(), MiddlewareError> + Send + Sync>;
+
+/// The middleware of [`SocketIo::ns_require_auth`], refusing the connections without auth payload.
+/// A `null` payload, an empty string or an empty object are considered missing.
+///
+/// [`SocketIo::ns_require_auth`]: crate::SocketIo#method.ns_require_auth
+pub(crate) fn require_auth<A: Adapter>(
+    _: &Socket<A>,
+    auth: Option<&str>,
+) -> Result<(), MiddlewareError> {
+    let present = match auth.map(serde_json::from_str::<serde_json::Value>) {
+        Some(Ok(serde_json::Value::Null)) | None => false,
+        Some(Ok(serde_json::Value::Object(obj))) => !obj.is_empty(),
+        Some(Ok(serde_json::Value::String(s))) => !s.is_empty(),
+        Some(_) => true,
+    };
+    if present {
+        Ok(())
+    } else {
+        Err("missing auth payload".into())
+    }
+}
+
+/// The ordered list of the middlewares of a namespace.
+pub(crate) struct Middlewares<A: Adapter> {
+    list: Vec<(MiddlewareId, BoxedMiddleware<A>)>,
+    next_id: u64,
+}
+
+impl<A: Adapter> Default for Middlewares<A> {
+    fn default() -> Self {
+        Self {
+            list: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<A: Adapter> Middlewares<A> {
+    pub fn push(&mut self, middleware: BoxedMiddleware<A>) -> MiddlewareId {
+        let id = MiddlewareId(self.next_id);
+        self.next_id += 1;
+        self.list.push((id, middleware));
+        id
+    }
+
+    pub fn remove(&mut self, id: MiddlewareId) -> bool {
+        let len = self.list.len();
+        self.list.retain(|(i, _)| *i != id);
+        self.list.len() != len
+    }
+
+    /// Gets a snapshot of the middlewares, so that they can be run without holding any lock
+    pub fn snapshot(&self) -> Vec<BoxedMiddleware<A>> {
+        self.list.iter().map(|(_, m)| m.clone()).collect()
+    }
+}
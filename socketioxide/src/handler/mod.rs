@@ -5,13 +5,18 @@ pub mod connect;
 pub mod disconnect;
 pub mod extract;
 pub mod message;
+pub mod middleware;
 
 pub(crate) use connect::BoxedConnectHandler;
 pub use connect::{ConnectHandler, FromConnectParts};
 pub(crate) use disconnect::BoxedDisconnectHandler;
 pub use disconnect::{DisconnectHandler, FromDisconnectParts};
-pub(crate) use message::BoxedMessageHandler;
-pub use message::{FromMessage, FromMessageParts, MessageHandler};
+pub(crate) use message::{BoxedMessageHandler, LimitedMessageHandler};
+pub use message::{
+    ConcurrencyOverflowPolicy, FromMessage, FromMessageParts, HandlerId, HandlerOpts,
+    MessageHandler,
+};
+pub use middleware::{MiddlewareError, MiddlewareId};
 /// A struct used to erase the type of a [`ConnectHandler`] or [`MessageHandler`] so it can be stored in a map
 pub(crate) struct MakeErasedHandler<H, A, T> {
     handler: H,
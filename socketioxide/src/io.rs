@@ -1,4 +1,9 @@
-use std::{borrow::Cow, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
 use engineioxide::{
     config::{EngineIoConfig, EngineIoConfigBuilder},
@@ -6,17 +11,24 @@ use engineioxide::{
     sid::Sid,
     TransportType,
 };
+use futures::{Sink, Stream};
+use serde_json::Value;
 
 use crate::{
     ack::AckStream,
-    adapter::{Adapter, LocalAdapter, Room},
+    adapter::{Adapter, LocalAdapter, Room, RoomChange},
     client::Client,
+    clock::{Clock, TokioClock},
     extract::SocketRef,
-    handler::ConnectHandler,
+    handler::{ConnectHandler, MiddlewareError, MiddlewareId},
     layer::SocketIoLayer,
     operators::{BroadcastOperators, RoomParam},
+    packet::Packet,
+    room::{RoomHandle, RoomMeta},
     service::SocketIoService,
-    BroadcastError, DisconnectError,
+    socket::{DisconnectReason, DisconnectRecord, RelayRequest, Socket, UndeliveredPacket},
+    transport::Frame,
+    BroadcastError, DisconnectError, EngineIoVersion, ProtocolVersion, SendError,
 };
 
 /// Configuration for Socket.IO & Engine.IO
@@ -34,6 +46,112 @@ pub struct SocketIoConfig {
     ///
     /// Defaults to 45 seconds.
     pub connect_timeout: Duration,
+
+    /// How the disconnect handlers are run when the server is closed with [`SocketIo::close`].
+    ///
+    /// Defaults to [`ShutdownDisconnectPolicy::Run`].
+    pub shutdown_disconnect_policy: ShutdownDisconnectPolicy,
+
+    /// The [`Clock`] used to measure every timeout (acknowledgements, connection...).
+    ///
+    /// Defaults to the [`TokioClock`].
+    pub clock: Arc<dyn Clock>,
+
+    /// Whether every event emitted to a socket is stamped with a per-socket sequence number.
+    /// See [`SocketIoBuilder::sequence_events`] for the client contract.
+    ///
+    /// Defaults to `false`.
+    pub sequence_events: bool,
+
+    /// Whether the `null` fields of the emitted data are removed before being sent.
+    /// See [`SocketIoBuilder::omit_none_fields`].
+    ///
+    /// Defaults to `false`.
+    pub omit_none_fields: bool,
+
+    /// Whether a `null` data is omitted from the emitted binary events and acks.
+    /// See [`SocketIoBuilder::omit_null_binary_data`].
+    ///
+    /// Defaults to `false`.
+    pub omit_null_binary_data: bool,
+
+    /// The maximum number of ack responses a socket can await at the same time.
+    /// See [`SocketIoBuilder::max_pending_acks_per_socket`].
+    ///
+    /// Defaults to `None` (unlimited).
+    pub max_pending_acks: Option<usize>,
+
+    /// The number of sockets a chunked broadcast sends to before yielding to the scheduler.
+    /// See [`BroadcastOperators::emit_chunked`].
+    ///
+    /// Defaults to 256.
+    pub broadcast_chunk_size: usize,
+
+    /// The policy deciding if a socket can relay a message to another one with [`Socket::relay_to`].
+    /// See [`SocketIoBuilder::relay_policy`].
+    ///
+    /// Defaults to `None`: relays are only allowed between sockets sharing a room.
+    ///
+    /// [`Socket::relay_to`]: crate::socket::Socket#method.relay_to
+    pub relay_policy: Option<RelayPolicy>,
+
+    /// The callback receiving a [`DisconnectRecord`] for each disconnected socket.
+    /// See [`SocketIoBuilder::on_disconnect_record`].
+    ///
+    /// Defaults to `None`.
+    pub disconnect_record_hook: Option<DisconnectRecordHook>,
+
+    /// The callback receiving an [`UndeliveredPacket`] for each event that could not be sent to a socket.
+    /// See [`SocketIoBuilder::on_undelivered`].
+    ///
+    /// Defaults to `None`.
+    pub undelivered_hook: Option<UndeliveredHook>,
+
+    /// The function transforming the serialized data of the acknowledgements.
+    /// See [`SocketIoBuilder::ack_serializer`].
+    ///
+    /// Defaults to `None`: the ack data is sent as serialized by [`serde_json`].
+    pub ack_serializer: Option<AckSerializer>,
+
+    /// The number of recent events kept for each socket to be replayed on the client request.
+    /// See [`SocketIoBuilder::enable_replay`].
+    ///
+    /// Defaults to `None`: the replay is disabled.
+    pub replay_buffer_size: Option<usize>,
+
+    /// The maximum number of events held for each socket until the client is ready.
+    /// See [`SocketIoBuilder::max_deferred_events`].
+    ///
+    /// Defaults to 64.
+    pub max_deferred_events: usize,
+
+    /// The application-level liveness checks run on each socket.
+    /// See [`SocketIoBuilder::app_keepalive`].
+    ///
+    /// Defaults to `None`: only the engine.io heartbeat is used.
+    pub app_keepalive: Option<AppKeepalive>,
+
+    /// How the events received from a socket are dispatched to the async handlers.
+    /// See [`SocketIoBuilder::event_ordering`].
+    ///
+    /// Defaults to [`EventOrdering::Concurrent`].
+    pub event_ordering: EventOrdering,
+
+    /// Whether the namespaces created from a [`SocketIo::dynamic_ns`] pattern are removed
+    /// once their last socket disconnects. See [`SocketIoBuilder::ephemeral_dynamic_ns`].
+    ///
+    /// Defaults to `true`.
+    pub ephemeral_dynamic_ns: bool,
+
+    /// How long a session is remembered after its last socket disconnected.
+    /// See [`SocketIoBuilder::session_retention`].
+    ///
+    /// Defaults to `None`: the sessions are not indexed.
+    pub session_retention: Option<Duration>,
+
+    /// The callback called on each room membership change, set with [`SocketIo::on_room_change`].
+    /// It is shared by all the namespaces of the server.
+    pub room_change_hook: RoomChangeHook,
 }
 
 impl Default for SocketIoConfig {
@@ -45,6 +163,246 @@ impl Default for SocketIoConfig {
             },
             ack_timeout: Duration::from_secs(5),
             connect_timeout: Duration::from_secs(45),
+            shutdown_disconnect_policy: ShutdownDisconnectPolicy::default(),
+            clock: Arc::new(TokioClock),
+            sequence_events: false,
+            omit_none_fields: false,
+            omit_null_binary_data: false,
+            max_pending_acks: None,
+            broadcast_chunk_size: 256,
+            relay_policy: None,
+            disconnect_record_hook: None,
+            undelivered_hook: None,
+            ack_serializer: None,
+            replay_buffer_size: None,
+            max_deferred_events: 64,
+            app_keepalive: None,
+            event_ordering: EventOrdering::default(),
+            ephemeral_dynamic_ns: true,
+            session_retention: None,
+            room_change_hook: RoomChangeHook::default(),
+        }
+    }
+}
+
+/// Policy applied to the inbound events received while events are paused
+/// with [`SocketIo::pause_events`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseEventsPolicy {
+    /// Up to `n` events are buffered and dispatched in order when [`SocketIo::resume_events`] is called.
+    /// Events received when the buffer is full are dropped.
+    Buffer(usize),
+    /// Every event received while paused is dropped.
+    Drop,
+}
+
+/// How the events received from a socket are dispatched to the handlers,
+/// see [`SocketIoBuilder::event_ordering`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventOrdering {
+    /// The handlers are called as soon as the events are received,
+    /// the async handlers of successive events run concurrently.
+    #[default]
+    Concurrent,
+    /// The events of a socket are processed one at a time, in arrival order:
+    /// a handler is called once the handlers of the previous events have completed.
+    Fifo,
+}
+
+/// Policy applied to the disconnect handlers of the sockets closed with the
+/// [`DisconnectReason::ClosingServer`](crate::socket::DisconnectReason::ClosingServer) reason,
+/// when the server is closed with [`SocketIo::close`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShutdownDisconnectPolicy {
+    /// Every disconnect handler is called.
+    #[default]
+    Run,
+    /// No disconnect handler is called.
+    Skip,
+    /// Every disconnect handler is called but at most `n` of them can run at the same time.
+    /// For async handlers, a slot is held until the returned future completes.
+    ///
+    /// A value of `0` is treated as `1`.
+    RunBounded(usize),
+}
+
+/// A lifecycle event of an underlying engine.io transport, see [`SocketIoBuilder::on_transport_event`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransportEvent {
+    /// A new engine.io connection was opened with the given transport.
+    Open(TransportType),
+    /// The connection started upgrading from polling to websocket.
+    UpgradeStarted,
+    /// The connection was successfully upgraded from polling to websocket.
+    UpgradeCompleted,
+    /// The engine.io connection was closed.
+    Closed(DisconnectReason),
+}
+
+/// A type erased [`SocketIoBuilder::on_transport_event`] callback.
+#[derive(Clone)]
+pub(crate) struct TransportEventHook(Arc<dyn Fn(Sid, TransportEvent) + Send + Sync>);
+impl TransportEventHook {
+    pub(crate) fn call(&self, sid: Sid, event: TransportEvent) {
+        (self.0)(sid, event)
+    }
+}
+impl std::fmt::Debug for TransportEventHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("TransportEventHook").finish()
+    }
+}
+
+type EngineConnectFn = dyn Fn(Sid, &http::request::Parts) + Send + Sync;
+
+/// A type erased [`SocketIoBuilder::on_engine_connect`] callback.
+#[derive(Clone)]
+pub(crate) struct EngineConnectHook(Arc<EngineConnectFn>);
+impl EngineConnectHook {
+    pub(crate) fn call(&self, sid: Sid, parts: &http::request::Parts) {
+        (self.0)(sid, parts)
+    }
+}
+impl std::fmt::Debug for EngineConnectHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EngineConnectHook").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::on_engine_disconnect`] callback.
+#[derive(Clone)]
+pub(crate) struct EngineDisconnectHook(Arc<dyn Fn(Sid, DisconnectReason) + Send + Sync>);
+impl EngineDisconnectHook {
+    pub(crate) fn call(&self, sid: Sid, reason: DisconnectReason) {
+        (self.0)(sid, reason)
+    }
+}
+impl std::fmt::Debug for EngineDisconnectHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("EngineDisconnectHook").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::relay_policy`] callback.
+#[derive(Clone)]
+pub struct RelayPolicy(Arc<dyn Fn(&mut RelayRequest<'_>) -> bool + Send + Sync>);
+impl RelayPolicy {
+    pub(crate) fn call(&self, req: &mut RelayRequest<'_>) -> bool {
+        (self.0)(req)
+    }
+}
+impl std::fmt::Debug for RelayPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RelayPolicy").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::on_disconnect_record`] callback.
+#[derive(Clone)]
+pub struct DisconnectRecordHook(Arc<dyn Fn(DisconnectRecord) + Send + Sync>);
+impl DisconnectRecordHook {
+    pub(crate) fn call(&self, record: DisconnectRecord) {
+        (self.0)(record)
+    }
+}
+impl std::fmt::Debug for DisconnectRecordHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("DisconnectRecordHook").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::on_undelivered`] callback.
+#[derive(Clone)]
+pub struct UndeliveredHook(Arc<dyn Fn(UndeliveredPacket) + Send + Sync>);
+impl UndeliveredHook {
+    pub(crate) fn call(&self, packet: UndeliveredPacket) {
+        (self.0)(packet)
+    }
+}
+impl std::fmt::Debug for UndeliveredHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("UndeliveredHook").finish()
+    }
+}
+
+/// The callback set with [`SocketIo::on_room_change`], shared by all the namespaces of the server.
+#[derive(Clone, Default)]
+pub struct RoomChangeHook(Arc<RwLock<Option<BoxedRoomChangeHook>>>);
+type BoxedRoomChangeHook = Arc<dyn Fn(Sid, &Room, RoomChange) + Send + Sync>;
+impl RoomChangeHook {
+    pub(crate) fn set(&self, hook: BoxedRoomChangeHook) {
+        *self.0.write().unwrap() = Some(hook);
+    }
+    pub(crate) fn call(&self, sid: Sid, room: &Room, change: RoomChange) {
+        let hook = self.0.read().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(sid, room, change);
+        }
+    }
+}
+impl std::fmt::Debug for RoomChangeHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RoomChangeHook").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::ack_serializer`] function.
+#[derive(Clone)]
+pub struct AckSerializer(Arc<dyn Fn(Value) -> Value + Send + Sync>);
+impl AckSerializer {
+    pub(crate) fn call(&self, data: Value) -> Value {
+        (self.0)(data)
+    }
+}
+impl std::fmt::Debug for AckSerializer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AckSerializer").finish()
+    }
+}
+
+/// A type erased [`SocketIoBuilder::on_protocol_negotiated`] callback.
+#[derive(Clone)]
+pub(crate) struct ProtocolHook(Arc<dyn Fn(Sid, ProtocolVersion, EngineIoVersion) + Send + Sync>);
+impl ProtocolHook {
+    pub(crate) fn call(&self, sid: Sid, protocol: ProtocolVersion, eio: EngineIoVersion) {
+        (self.0)(sid, protocol, eio)
+    }
+}
+impl std::fmt::Debug for ProtocolHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ProtocolHook").finish()
+    }
+}
+
+/// The application-level keepalive checks, see [`SocketIoBuilder::app_keepalive`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppKeepalive {
+    /// The interval between two checks, which is also the time the client has to answer a check.
+    pub interval: Duration,
+    /// The number of consecutive checks a client can miss before being disconnected.
+    /// A value of `0` is treated as `1`.
+    pub missed_threshold: usize,
+}
+
+/// Timeouts of the phases of a [`SocketIo::graceful_shutdown`].
+#[derive(Debug, Clone)]
+pub struct StagedShutdownConfig {
+    /// The maximum amount of time to wait for the pending acknowledgements to be received.
+    ///
+    /// Defaults to 5 seconds.
+    pub ack_drain_timeout: Duration,
+
+    /// The maximum amount of time to wait for the underlying transports to close.
+    ///
+    /// Defaults to 10 seconds.
+    pub close_timeout: Duration,
+}
+
+impl Default for StagedShutdownConfig {
+    fn default() -> Self {
+        Self {
+            ack_drain_timeout: Duration::from_secs(5),
+            close_timeout: Duration::from_secs(10),
         }
     }
 }
@@ -55,6 +413,10 @@ impl Default for SocketIoConfig {
 pub struct SocketIoBuilder<A: Adapter = LocalAdapter> {
     config: SocketIoConfig,
     engine_config_builder: EngineIoConfigBuilder,
+    transport_hook: Option<TransportEventHook>,
+    protocol_hook: Option<ProtocolHook>,
+    engine_connect_hook: Option<EngineConnectHook>,
+    engine_disconnect_hook: Option<EngineDisconnectHook>,
     adapter: std::marker::PhantomData<A>,
 }
 
@@ -64,6 +426,10 @@ impl<A: Adapter> SocketIoBuilder<A> {
         Self {
             config: SocketIoConfig::default(),
             engine_config_builder: EngineIoConfigBuilder::new().req_path("/socket.io".to_string()),
+            transport_hook: None,
+            protocol_hook: None,
+            engine_connect_hook: None,
+            engine_disconnect_hook: None,
             adapter: std::marker::PhantomData,
         }
     }
@@ -129,7 +495,7 @@ impl<A: Adapter> SocketIoBuilder<A> {
 
     /// The amount of time the server will wait for an acknowledgement from the client before closing the connection.
     ///
-    /// Defaults to 5 seconds.
+    /// Defaults to 5 seconds. It can be overridden per namespace with [`SocketIo::set_ack_timeout`].
     #[inline]
     pub fn ack_timeout(mut self, ack_timeout: Duration) -> Self {
         self.config.ack_timeout = ack_timeout;
@@ -145,6 +511,467 @@ impl<A: Adapter> SocketIoBuilder<A> {
         self
     }
 
+    /// How the disconnect handlers are run when the server is closed with [`SocketIo::close`].
+    ///
+    /// Running every disconnect handler at once during a mass shutdown may overload
+    /// the resources they depend on. You can either skip them or cap their concurrency.
+    ///
+    /// Defaults to [`ShutdownDisconnectPolicy::Run`].
+    #[inline]
+    pub fn disconnect_on_shutdown(mut self, policy: ShutdownDisconnectPolicy) -> Self {
+        self.config.shutdown_disconnect_policy = policy;
+        self
+    }
+
+    /// Sets a custom [`Clock`] used to measure every timeout (acknowledgements, connection...).
+    ///
+    /// It is mainly useful in tests to control time deterministically,
+    /// for example with the [`ManualClock`](crate::clock::ManualClock) (`test-utils` feature).
+    ///
+    /// Defaults to the [`TokioClock`].
+    #[inline]
+    pub fn with_clock(mut self, clock: impl Clock) -> Self {
+        self.config.clock = Arc::new(clock);
+        self
+    }
+
+    /// Stamps every event emitted to a socket with a sequence number, so that the client
+    /// can detect dropped packets and request a replay.
+    ///
+    /// Each socket has its own counter, starting at 1 and incremented for each event sent to it,
+    /// including broadcasts. The number is sent as an **extra trailing argument** of the event,
+    /// in the form `{ "_seq": <number> }`, after every other argument and binary placeholder.
+    /// The client is expected to strip this argument and to compare it with the last received number:
+    /// any gap means that some events were lost. Acknowledgement responses are not stamped.
+    ///
+    /// Defaults to `false`.
+    #[inline]
+    pub fn sequence_events(mut self, enabled: bool) -> Self {
+        self.config.sequence_events = enabled;
+        self
+    }
+
+    /// Keeps the last `buffer_size` events emitted to each socket, so that a client detecting a gap
+    /// can request the events it missed. It enables the [`sequence_events`] option.
+    ///
+    /// To request a replay, the client emits the reserved [`REPLAY_EVENT`] event with the
+    /// sequence number of the last event it received: `socket.emit("_replay", 42)`.
+    /// Every buffered event with a greater sequence number is then sent again to this socket only,
+    /// in order and with its original sequence number. The events older than the buffer are not replayed,
+    /// the client can detect it with the sequence number of the first replayed event.
+    /// The reserved event never reaches the message handlers.
+    ///
+    /// Each buffered event is a copy of the emitted packet, including its binary payloads,
+    /// so the memory used grows with `buffer_size` times the number of sockets.
+    ///
+    /// [`sequence_events`]: SocketIoBuilder#method.sequence_events
+    /// [`REPLAY_EVENT`]: crate::socket::REPLAY_EVENT
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder().enable_replay(64).build_svc();
+    /// ```
+    #[inline]
+    pub fn enable_replay(mut self, buffer_size: usize) -> Self {
+        self.config.replay_buffer_size = Some(buffer_size);
+        self.config.sequence_events = true;
+        self
+    }
+
+    /// Sets the maximum number of events held for each socket with [`Socket::defer_until_ready`]
+    /// until [`Socket::mark_ready`] is called. Once it is reached, the deferred emits fail
+    /// with a [`SocketError::InternalChannelFull`] error.
+    ///
+    /// Defaults to 64.
+    ///
+    /// [`Socket::defer_until_ready`]: crate::socket::Socket::defer_until_ready
+    /// [`Socket::mark_ready`]: crate::socket::Socket::mark_ready
+    /// [`SocketError::InternalChannelFull`]: crate::SocketError::InternalChannelFull
+    #[inline]
+    pub fn max_deferred_events(mut self, max: usize) -> Self {
+        self.config.max_deferred_events = max;
+        self
+    }
+
+    /// Checks the liveness of each socket at the application level, in addition to the engine.io heartbeat,
+    /// to detect the half-open connections silently dropped by some NATs or firewalls.
+    ///
+    /// Every `interval`, the reserved [`KEEPALIVE_EVENT`] event is emitted to each socket and the client
+    /// has `interval` to acknowledge it: `socket.on("_ping", (ack) => ack())`.
+    /// A socket missing `missed_threshold` consecutive checks is disconnected with the
+    /// [`DisconnectReason::KeepaliveTimeout`] reason. The time is measured with the configured [`Clock`].
+    ///
+    /// [`KEEPALIVE_EVENT`]: crate::socket::KEEPALIVE_EVENT
+    /// [`DisconnectReason::KeepaliveTimeout`]: crate::socket::DisconnectReason::KeepaliveTimeout
+    ///
+    /// Defaults to disabled.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::builder()
+    ///     .app_keepalive(Duration::from_secs(15), 2)
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn app_keepalive(mut self, interval: Duration, missed_threshold: usize) -> Self {
+        self.config.app_keepalive = Some(AppKeepalive {
+            interval,
+            missed_threshold,
+        });
+        self
+    }
+
+    /// Sets how the events received from a socket are dispatched to the handlers.
+    ///
+    /// With [`EventOrdering::Fifo`], the events of each socket go through a queue and are processed
+    /// one at a time in arrival order: the handlers of an event are called once the (async) handlers
+    /// of the previous events have completed. It is useful when the handlers drive a state machine.
+    ///
+    /// The tradeoff is latency: a slow handler delays every following event of the socket,
+    /// and the queue grows while the client sends events faster than they are handled.
+    /// The queue holds up to [`EVENT_QUEUE_CAPACITY`](crate::socket::EVENT_QUEUE_CAPACITY) events:
+    /// rather than dropping an event, a socket overflowing it is **disconnected** with
+    /// [`DisconnectReason::EventQueueFull`](crate::socket::DisconnectReason::EventQueueFull).
+    /// The events of different sockets are still processed concurrently.
+    ///
+    /// Defaults to [`EventOrdering::Concurrent`].
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, EventOrdering};
+    /// let (_, io) = SocketIo::builder()
+    ///     .event_ordering(EventOrdering::Fifo)
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn event_ordering(mut self, ordering: EventOrdering) -> Self {
+        self.config.event_ordering = ordering;
+        self
+    }
+
+    /// Removes the namespaces created from a [`SocketIo::dynamic_ns`] pattern once their last socket
+    /// disconnects, dropping their rooms and adapter state. A namespace is created again
+    /// the next time a client connects to it.
+    ///
+    /// When disabled, each namespace created from a pattern is kept until it is deleted with [`SocketIo::delete_ns`],
+    /// so the memory grows with every distinct namespace the clients connect to.
+    ///
+    /// Defaults to `true`.
+    #[inline]
+    pub fn ephemeral_dynamic_ns(mut self, enabled: bool) -> Self {
+        self.config.ephemeral_dynamic_ns = enabled;
+        self
+    }
+
+    /// Enables the session index of the namespaces: the last socket of each session id is indexed,
+    /// see [`SocketIo::session_sid`], and the session is remembered for the `retention` duration
+    /// after its last socket disconnected, so that a client reconnecting with the same session id can restore it.
+    /// See [`Socket::session_id`](crate::socket::Socket::session_id).
+    ///
+    /// With the `extensions` feature, the [`extensions`](crate::socket::Socket::extensions) of the last socket
+    /// of a session are moved out of it when it disconnects, so that they can be restored.
+    /// The tasks still holding this socket then see empty extensions.
+    ///
+    /// Each session costs an entry in the index and a timer task for the retention duration after the disconnection.
+    ///
+    /// Disabled by default.
+    #[inline]
+    pub fn session_retention(mut self, retention: Duration) -> Self {
+        self.config.session_retention = Some(retention);
+        self
+    }
+
+    /// Removes the `null` fields from the data of every emitted event and acknowledgement,
+    /// for clients expecting absent optional fields to be omitted rather than set to `null`.
+    ///
+    /// The fields are removed after serialization, at any depth of the data.
+    /// `null` elements of arrays are kept, so that the event arguments keep their position.
+    ///
+    /// This has a cost: the whole serialized value is walked each time a packet is sent to a socket,
+    /// so a broadcast walks it once per receiving socket.
+    /// Prefer `#[serde(skip_serializing_if = "Option::is_none")]` when you control the emitted types.
+    ///
+    /// Defaults to `false`.
+    #[inline]
+    pub fn omit_none_fields(mut self, enabled: bool) -> Self {
+        self.config.omit_none_fields = enabled;
+        self
+    }
+
+    /// Omits the `null` data of the emitted binary events and acks, so that only the placeholders
+    /// of their binary payloads are sent, like the binary-only events of the socket.io client:
+    /// `socket.bin(bin).emit("event", Value::Null)` is then sent as `["event", <placeholder>]`
+    /// instead of `["event", null, <placeholder>]`.
+    ///
+    /// The clients must expect this shape: the binary payloads are received one argument earlier.
+    /// The `null` elements of an array data are kept.
+    ///
+    /// Defaults to `false`.
+    #[inline]
+    pub fn omit_null_binary_data(mut self, enabled: bool) -> Self {
+        self.config.omit_null_binary_data = enabled;
+        self
+    }
+
+    /// Limits the number of ack responses a socket can await at the same time,
+    /// so that a client that never answers can't make the server memory grow.
+    ///
+    /// When the limit is reached, the packet is not sent and the returned [`AckStream`]
+    /// **immediately** yields an [`AckError::TooManyPending`] for this socket.
+    /// Acks that timed out or whose [`AckStream`] was dropped don't count anymore.
+    ///
+    /// Defaults to unlimited.
+    ///
+    /// [`AckStream`]: crate::ack::AckStream
+    /// [`AckError::TooManyPending`]: crate::AckError::TooManyPending
+    #[inline]
+    pub fn max_pending_acks_per_socket(mut self, n: usize) -> Self {
+        self.config.max_pending_acks = Some(n);
+        self
+    }
+
+    /// Sets the number of sockets a chunked broadcast sends to before yielding to the scheduler,
+    /// see [`BroadcastOperators::emit_chunked`].
+    ///
+    /// A value of `0` is treated as `1`.
+    ///
+    /// Defaults to 256.
+    #[inline]
+    pub fn broadcast_chunk_size(mut self, size: usize) -> Self {
+        self.config.broadcast_chunk_size = size.max(1);
+        self
+    }
+
+    /// Sets the policy called for every [`Socket::relay_to`] call. It returns whether the relay is allowed
+    /// and can validate or transform the relayed data through [`RelayRequest::data`].
+    ///
+    /// Without a policy, relays are only allowed between sockets sharing a room
+    /// (see [`RelayRequest::shares_room`]).
+    ///
+    /// [`Socket::relay_to`]: crate::socket::Socket#method.relay_to
+    /// [`RelayRequest::data`]: crate::socket::RelayRequest#structfield.data
+    /// [`RelayRequest::shares_room`]: crate::socket::RelayRequest#method.shares_room
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder()
+    ///     // Only relay webrtc signaling messages between peers of the same call
+    ///     .relay_policy(|req| {
+    ///         matches!(req.event, "offer" | "answer" | "ice-candidate")
+    ///             && req.shares_room()
+    ///             && req.data.is_object()
+    ///     })
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn relay_policy(
+        mut self,
+        policy: impl Fn(&mut RelayRequest<'_>) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.config.relay_policy = Some(RelayPolicy(Arc::new(policy)));
+        self
+    }
+
+    /// Sets a function transforming the data of the acknowledgement packets once serialized by [`serde_json`],
+    /// for example to encrypt them differently from the regular events. It is applied to:
+    /// * the ack responses sent with the [`AckSender`] or with [`Socket::send_manual_ack`],
+    /// * the data of the events emitted with `emit_with_ack`, which request an acknowledgement.
+    ///
+    /// The regular emits are left untouched. The binary packets are not transformed either,
+    /// as the client needs to read their placeholders to reassemble the binary payloads.
+    ///
+    /// The client must apply the inverse function to the ack responses it receives and to the events
+    /// carrying an ack id, the acknowledgements it sends back are received as is.
+    ///
+    /// [`AckSender`]: crate::extract::AckSender
+    /// [`Socket::send_manual_ack`]: crate::socket::Socket#method.send_manual_ack
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// # use serde_json::Value;
+    /// # fn encrypt(data: String) -> String { data }
+    /// let (_, io) = SocketIo::builder()
+    ///     .ack_serializer(|data| Value::String(encrypt(data.to_string())))
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn ack_serializer(
+        mut self,
+        serializer: impl Fn(Value) -> Value + Send + Sync + 'static,
+    ) -> Self {
+        self.config.ack_serializer = Some(AckSerializer(Arc::new(serializer)));
+        self
+    }
+
+    /// Registers a callback receiving a [`DisconnectRecord`] each time a socket disconnects from a namespace,
+    /// whatever the reason. It centralizes the disconnection analytics without having to register
+    /// a disconnect handler on each socket.
+    ///
+    /// The record is assembled before the disconnect handler of the socket is called,
+    /// so it contains the rooms the socket was in when it disconnected.
+    /// The callback is called synchronously so it should not block.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder()
+    ///     .on_disconnect_record(|record| {
+    ///         println!(
+    ///             "socket {} left {} after {:?} ({}), rooms: {:?}",
+    ///             record.sid, record.ns, record.connected_for, record.reason, record.rooms
+    ///         )
+    ///     })
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn on_disconnect_record(
+        mut self,
+        callback: impl Fn(DisconnectRecord) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.disconnect_record_hook = Some(DisconnectRecordHook(Arc::new(callback)));
+        self
+    }
+
+    /// Registers a callback receiving an [`UndeliveredPacket`] each time an event cannot be delivered
+    /// to a socket, because its buffer is full or because it is closed. It is called for the emits
+    /// to a single socket as well as for each socket a broadcast fails to reach, so the delivery
+    /// failures can be accounted for without checking the result of every emit.
+    ///
+    /// Only the events are reported, not the acks or the protocol packets.
+    /// The callback is called synchronously from the emitting task so it should not block.
+    ///
+    /// The events that were queued in the buffer of a socket but not yet written to the transport
+    /// when it closes are dropped without being reported: only the failures to queue an event are covered.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder()
+    ///     .on_undelivered(|packet| {
+    ///         println!(
+    ///             "event {} not delivered to {} on {}: {:?}",
+    ///             packet.event, packet.sid, packet.ns, packet.reason
+    ///         )
+    ///     })
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn on_undelivered(
+        mut self,
+        callback: impl Fn(UndeliveredPacket) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.undelivered_hook = Some(UndeliveredHook(Arc::new(callback)));
+        self
+    }
+
+    /// Registers a callback called for each lifecycle event of the underlying engine.io transports,
+    /// independently of any namespace connection. See [`TransportEvent`].
+    ///
+    /// It is mainly useful to diagnose upgrade failures or polling issues that never reach a namespace handler.
+    /// The callback is called synchronously so it should not block.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder()
+    ///     .on_transport_event(|sid, event| println!("transport event for {sid}: {event:?}"))
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn on_transport_event(
+        mut self,
+        callback: impl Fn(Sid, TransportEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.transport_hook = Some(TransportEventHook(Arc::new(callback)));
+        self
+    }
+
+    /// Registers a callback called when a new connection is opened, with the socket.io
+    /// [`ProtocolVersion`] and the [`EngineIoVersion`] negotiated with the client.
+    ///
+    /// It can be used to log or alert on unexpected legacy clients.
+    /// It is not called for connections refused with [`SocketIoBuilder::min_protocol_version`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, ProtocolVersion};
+    /// let (_, io) = SocketIo::builder()
+    ///     .on_protocol_negotiated(|sid, protocol, _| {
+    ///         if protocol == ProtocolVersion::V4 {
+    ///             println!("legacy client connected: {sid}");
+    ///         }
+    ///     })
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn on_protocol_negotiated(
+        mut self,
+        callback: impl Fn(Sid, ProtocolVersion, EngineIoVersion) + Send + Sync + 'static,
+    ) -> Self {
+        self.protocol_hook = Some(ProtocolHook(Arc::new(callback)));
+        self
+    }
+
+    /// Registers a callback called once when an engine.io connection is opened,
+    /// before the client connects to any namespace, with the request parts of the connection.
+    ///
+    /// Unlike a namespace connect handler, it runs once per physical connection, however many namespaces
+    /// the client joins through it. It is always followed by a call to the [`on_engine_disconnect`] callback
+    /// when the connection closes, even if the connection is refused because the server is closing.
+    /// The callback is called synchronously so it should not block.
+    ///
+    /// [`on_engine_disconnect`]: SocketIoBuilder#method.on_engine_disconnect
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// let (_, io) = SocketIo::builder()
+    ///     .on_engine_connect(|sid, parts| println!("connection {sid} opened from {}", parts.uri))
+    ///     .on_engine_disconnect(|sid, reason| println!("connection {sid} closed: {reason}"))
+    ///     .build_svc();
+    /// ```
+    #[inline]
+    pub fn on_engine_connect(
+        mut self,
+        callback: impl Fn(Sid, &http::request::Parts) + Send + Sync + 'static,
+    ) -> Self {
+        self.engine_connect_hook = Some(EngineConnectHook(Arc::new(callback)));
+        self
+    }
+
+    /// Registers a callback called once when an engine.io connection is closed,
+    /// after the client was disconnected from all its namespaces. See [`on_engine_connect`].
+    ///
+    /// [`on_engine_connect`]: SocketIoBuilder#method.on_engine_connect
+    #[inline]
+    pub fn on_engine_disconnect(
+        mut self,
+        callback: impl Fn(Sid, DisconnectReason) + Send + Sync + 'static,
+    ) -> Self {
+        self.engine_disconnect_hook = Some(EngineDisconnectHook(Arc::new(callback)));
+        self
+    }
+
+    /// The minimum socket.io protocol version accepted on this server.
+    ///
+    /// Clients using an older version are refused during the engine.io handshake with a
+    /// `400 Bad Request` "Unsupported protocol version" error.
+    ///
+    /// Defaults to [`ProtocolVersion::V4`], every supported version is accepted.
+    /// The V4 protocol is only available with the `v4` feature flag.
+    #[inline]
+    pub fn min_protocol_version(mut self, min_protocol_version: ProtocolVersion) -> Self {
+        self.engine_config_builder = self
+            .engine_config_builder
+            .min_protocol_version(min_protocol_version.into());
+        self
+    }
+
     /// Sets a custom [`SocketIoConfig`] created previously for this [`SocketIoBuilder`]
     #[inline]
     pub fn with_config(mut self, config: SocketIoConfig) -> Self {
@@ -157,6 +984,10 @@ impl<A: Adapter> SocketIoBuilder<A> {
         SocketIoBuilder {
             config: self.config,
             engine_config_builder: self.engine_config_builder,
+            transport_hook: self.transport_hook,
+            protocol_hook: self.protocol_hook,
+            engine_connect_hook: self.engine_connect_hook,
+            engine_disconnect_hook: self.engine_disconnect_hook,
             adapter: std::marker::PhantomData,
         }
     }
@@ -175,34 +1006,47 @@ impl<A: Adapter> SocketIoBuilder<A> {
     /// Builds a [`SocketIoLayer`] and a [`SocketIo`] instance
     ///
     /// The layer can be used as a tower layer
-    pub fn build_layer(mut self) -> (SocketIoLayer<A>, SocketIo<A>) {
-        self.config.engine_config = self.engine_config_builder.build();
-
-        let (layer, client) = SocketIoLayer::from_config(Arc::new(self.config));
-        (layer, SocketIo(client))
+    pub fn build_layer(self) -> (SocketIoLayer<A>, SocketIo<A>) {
+        let client = self.build_client();
+        (SocketIoLayer::from_client(client.clone()), SocketIo(client))
     }
 
     /// Builds a [`SocketIoService`] and a [`SocketIo`] instance
     ///
     /// This service will be a _standalone_ service that return a 404 error for every non-socket.io request
     /// It can be used as a hyper service
-    pub fn build_svc(mut self) -> (SocketIoService<NotFoundService>, SocketIo) {
-        self.config.engine_config = self.engine_config_builder.build();
-
-        let (svc, client) =
-            SocketIoService::with_config_inner(NotFoundService, Arc::new(self.config));
+    pub fn build_svc(self) -> (SocketIoService<NotFoundService>, SocketIo) {
+        let client = self.build_client();
+        let svc = SocketIoService::with_client(NotFoundService, client.clone());
         (svc, SocketIo(client))
     }
 
     /// Builds a [`SocketIoService`] and a [`SocketIo`] instance with an inner service
     ///
     /// It can be used as a hyper service
-    pub fn build_with_inner_svc<S: Clone>(mut self, svc: S) -> (SocketIoService<S>, SocketIo) {
-        self.config.engine_config = self.engine_config_builder.build();
-
-        let (svc, client) = SocketIoService::with_config_inner(svc, Arc::new(self.config));
+    pub fn build_with_inner_svc<S: Clone>(self, svc: S) -> (SocketIoService<S>, SocketIo) {
+        let client = self.build_client();
+        let svc = SocketIoService::with_client(svc, client.clone());
         (svc, SocketIo(client))
     }
+
+    fn build_client<B: Adapter>(mut self) -> Arc<Client<B>> {
+        self.config.engine_config = self.engine_config_builder.build();
+        let mut client = Client::new(Arc::new(self.config));
+        if let Some(hook) = self.transport_hook {
+            client = client.with_transport_hook(hook);
+        }
+        if let Some(hook) = self.protocol_hook {
+            client = client.with_protocol_hook(hook);
+        }
+        if let Some(hook) = self.engine_connect_hook {
+            client = client.with_engine_connect_hook(hook);
+        }
+        if let Some(hook) = self.engine_disconnect_hook {
+            client = client.with_engine_disconnect_hook(hook);
+        }
+        Arc::new(client)
+    }
 }
 
 impl Default for SocketIoBuilder {
@@ -341,6 +1185,74 @@ impl<A: Adapter> SocketIo<A> {
         self.0.add_ns(path.into(), callback);
     }
 
+    /// Registers a namespace handler like [`SocketIo::ns`], for a namespace refusing every connection
+    /// without auth payload.
+    ///
+    /// A client connecting without auth payload, or with a `null`, empty string or empty object payload,
+    /// receives a connect error packet with the `"missing auth payload"` message and the connect handler
+    /// is not called. The content of the payload is not checked, use [`SocketIo::push_middleware`]
+    /// or the connect handler to validate it.
+    ///
+    /// **Note**: the v4 protocol clients never send an auth payload so they are always refused.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Auth {
+    ///     token: String,
+    /// }
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns_require_auth("/secure", |socket: SocketRef, Data(auth): Data<Auth>| {
+    ///     println!("socket {} connected with token {}", socket.id, auth.token);
+    /// });
+    /// ```
+    #[inline]
+    pub fn ns_require_auth<C, T>(&self, path: impl Into<Cow<'static, str>>, callback: C)
+    where
+        C: ConnectHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        self.0.add_ns_with_middleware(
+            path.into(),
+            callback,
+            Arc::new(crate::handler::middleware::require_auth),
+        );
+    }
+
+    /// Registers a connect handler for every namespace matching a path pattern.
+    ///
+    /// Each `:name` segment of the pattern matches any non-empty path segment, e.g. `/tenant/:id`
+    /// matches `/tenant/1` but neither `/tenant` nor `/tenant/1/room`. When a client connects to a matching
+    /// namespace that doesn't exist yet, the namespace is created and the handler is called like the one of
+    /// a namespace registered with [`SocketIo::ns`]. The matched segments can be extracted with the
+    /// [`NsParam`](crate::extract::NsParam) extractor or [`Socket::ns_param`](crate::socket::Socket::ns_param).
+    ///
+    /// Namespaces registered with [`SocketIo::ns`] take precedence over the patterns,
+    /// and the patterns are checked in registration order.
+    /// The namespaces that don't match any pattern are still refused.
+    ///
+    /// By default a namespace created from a pattern is removed once its last socket disconnects,
+    /// see [`SocketIoBuilder::ephemeral_dynamic_ns`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.dynamic_ns("/tenant/:id", |socket: SocketRef, NsParam(id): NsParam<u32>| {
+    ///     println!("socket {} connected to tenant {}", socket.id, id);
+    /// });
+    /// ```
+    #[inline]
+    pub fn dynamic_ns<C, T>(&self, pattern: impl Into<Cow<'static, str>>, callback: C)
+    where
+        C: ConnectHandler<A, T> + Clone,
+        T: Send + Sync + 'static,
+    {
+        self.0.add_dynamic_ns(pattern.into(), callback);
+    }
+
     /// Deletes the namespace with the given path
     #[inline]
     pub fn delete_ns<'a>(&self, path: impl Into<&'a str>) {
@@ -355,6 +1267,61 @@ impl<A: Adapter> SocketIo<A> {
         self.0.close().await;
     }
 
+    /// Gracefully closes the server in successive phases:
+    /// 1. New connections are refused.
+    /// 2. Waits for every pending acknowledgement to be received,
+    ///    at most [`StagedShutdownConfig::ack_drain_timeout`].
+    /// 3. Sends a disconnect packet to every socket.
+    /// 4. Closes all the connections like [`SocketIo::close`],
+    ///    waiting at most [`StagedShutdownConfig::close_timeout`].
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, StagedShutdownConfig};
+    /// # use std::time::Duration;
+    /// # async fn doc() {
+    /// let (_, io) = SocketIo::new_svc();
+    /// let config = StagedShutdownConfig {
+    ///     ack_drain_timeout: Duration::from_secs(2),
+    ///     ..Default::default()
+    /// };
+    /// io.graceful_shutdown(config).await;
+    /// # }
+    /// ```
+    #[inline]
+    pub async fn graceful_shutdown(&self, config: StagedShutdownConfig) {
+        self.0.graceful_shutdown(config).await;
+    }
+
+    /// Stops dispatching application events received from the clients, in every namespace,
+    /// until [`SocketIo::resume_events`] is called. The `policy` decides whether the events
+    /// are buffered or dropped in the meantime.
+    ///
+    /// Connections are kept open: heartbeats, namespace connections, disconnections
+    /// and acknowledgement responses are still processed.
+    ///
+    /// Calling it while already paused only updates the policy.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, PauseEventsPolicy};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.pause_events(PauseEventsPolicy::Buffer(1024));
+    /// // ... maintenance
+    /// io.resume_events();
+    /// ```
+    #[inline]
+    pub fn pause_events(&self, policy: PauseEventsPolicy) {
+        self.0.pause_events(policy);
+    }
+
+    /// Resumes the dispatch of application events paused with [`SocketIo::pause_events`].
+    /// Buffered events are dispatched in the order they were received.
+    #[inline]
+    pub fn resume_events(&self) {
+        self.0.resume_events();
+    }
+
     // Chaining operators fns
 
     /// Selects a specific namespace to perform operations on
@@ -404,6 +1371,41 @@ impl<A: Adapter> SocketIo<A> {
         self.get_default_op().to(rooms)
     }
 
+    /// Selects all sockets in the given rooms on the given namespace, for example to broadcast
+    /// from a socket to the rooms of another namespace.
+    ///
+    /// Alias for `io.of(path).to(rooms)`, returning an error if the namespace is not found.
+    ///
+    /// ## Errors
+    /// If the namespace is not registered, a [`BroadcastError::NamespaceNotFound`] is returned.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/notifications", |socket: SocketRef| {});
+    /// let io2 = io.clone();
+    /// io.ns("/admin", move |socket: SocketRef| {
+    ///     let io = io2.clone();
+    ///     socket.on("announce", move || {
+    ///         io.to_ns("/notifications", "all")
+    ///             .and_then(|op| op.emit("announcement", "maintenance at noon"))
+    ///             .ok();
+    ///     });
+    /// });
+    /// ```
+    pub fn to_ns<'a>(
+        &self,
+        path: impl Into<&'a str>,
+        rooms: impl RoomParam,
+    ) -> Result<BroadcastOperators<A>, BroadcastError> {
+        let path = path.into();
+        match self.get_op(path) {
+            Some(op) => Ok(op.to(rooms)),
+            None => Err(BroadcastError::NamespaceNotFound(path.to_string())),
+        }
+    }
+
     /// Selects all sockets in the given rooms on the root namespace.
     ///
     /// Alias for :
@@ -461,14 +1463,111 @@ impl<A: Adapter> SocketIo<A> {
     ///   println!("found socket on / ns in room1 with id: {}", socket.id);
     /// }
     #[inline]
-    pub fn except(&self, rooms: impl RoomParam) -> BroadcastOperators<A> {
-        self.get_default_op().except(rooms)
+    pub fn except(&self, rooms: impl RoomParam) -> BroadcastOperators<A> {
+        self.get_default_op().except(rooms)
+    }
+
+    /// Selects all sockets on the root namespace except the given socket,
+    /// like the operators built from a socket exclude it.
+    ///
+    /// Alias for `io.of("/").unwrap().exclude(sid)`
+    ///
+    /// ## Panics
+    /// If the **default namespace "/" is not found** this fn will panic!
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef, socket::Sid};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// // Later in your code you can notify all the other sockets of the one that sent an update
+    /// # let sender = Sid::new();
+    /// io.exclude(sender).emit("update", "data");
+    /// ```
+    #[inline]
+    pub fn exclude(&self, sid: Sid) -> BroadcastOperators<A> {
+        self.get_default_op().exclude(sid)
+    }
+
+    /// Selects all sockets of the root namespace having at least one of the given tags,
+    /// see [`BroadcastOperators::with_tag`].
+    ///
+    /// Alias for `io.of("/").unwrap().with_tag(tags)`
+    ///
+    /// ## Panics
+    /// If the **default namespace "/" is not found** this fn will panic!
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.add_tag("beta");
+    /// });
+    ///
+    /// // Later in your code you can reach the beta testers without making them join a room
+    /// io.with_tag("beta").emit("feature", "new-ui");
+    /// ```
+    #[inline]
+    pub fn with_tag(&self, tags: impl RoomParam) -> BroadcastOperators<A> {
+        self.get_default_op().with_tag(tags)
+    }
+
+    /// Broadcasts to all sockets only connected on this node (when using multiple nodes).
+    /// When using the default in-memory adapter, this operator is a no-op.
+    ///
+    /// Alias for `io.of("/").unwrap().local()`
+    ///
+    /// ## Panics
+    /// If the **default namespace "/" is not found** this fn will panic!
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     println!("Socket connected on / namespace with id: {}", socket.id);
+    /// });
+    ///
+    /// // Later in your code you can select all sockets in the local node and on the root namespace
+    /// // and for example show all sockets connected to it
+    /// let sockets = io.local().sockets().unwrap();
+    /// for socket in sockets {
+    ///   println!("found socket on / ns in room1 with id: {}", socket.id);
+    /// }
+    #[inline]
+    pub fn local(&self) -> BroadcastOperators<A> {
+        self.get_default_op().local()
+    }
+
+    /// Selects all sockets connected at or after the given instant on the root namespace.
+    ///
+    /// Alias for `io.of("/").unwrap().connected_after(instant)`
+    ///
+    /// ## Panics
+    /// If the **default namespace "/" is not found** this fn will panic!
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// # use std::time::Instant;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// let rollout_start = Instant::now();
+    /// // Later in your code you can target the sockets of the beta room connected since the rollout started
+    /// io.to("beta").connected_after(rollout_start).emit("feature", "new-ui");
+    /// ```
+    #[inline]
+    pub fn connected_after(&self, instant: Instant) -> BroadcastOperators<A> {
+        self.get_default_op().connected_after(instant)
     }
 
-    /// Broadcasts to all sockets only connected on this node (when using multiple nodes).
-    /// When using the default in-memory adapter, this operator is a no-op.
+    /// Selects all sockets connected within the given duration on the root namespace,
+    /// see [`BroadcastOperators::connected_within`].
     ///
-    /// Alias for `io.of("/").unwrap().local()`
+    /// Alias for `io.of("/").unwrap().connected_within(duration)`
     ///
     /// ## Panics
     /// If the **default namespace "/" is not found** this fn will panic!
@@ -476,20 +1575,19 @@ impl<A: Adapter> SocketIo<A> {
     /// ## Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// # use std::time::Duration;
     /// let (_, io) = SocketIo::new_svc();
-    /// io.ns("/", |socket: SocketRef| {
-    ///     println!("Socket connected on / namespace with id: {}", socket.id);
-    /// });
+    /// io.ns("/", |socket: SocketRef| {});
     ///
-    /// // Later in your code you can select all sockets in the local node and on the root namespace
-    /// // and for example show all sockets connected to it
-    /// let sockets = io.local().sockets().unwrap();
+    /// // Later in your code you can show the sockets connected in the last 10 minutes
+    /// let sockets = io.connected_within(Duration::from_secs(10 * 60)).sockets().unwrap();
     /// for socket in sockets {
-    ///   println!("found socket on / ns in room1 with id: {}", socket.id);
+    ///   println!("found recently connected socket with id: {}", socket.id);
     /// }
+    /// ```
     #[inline]
-    pub fn local(&self) -> BroadcastOperators<A> {
-        self.get_default_op().local()
+    pub fn connected_within(&self, duration: Duration) -> BroadcastOperators<A> {
+        self.get_default_op().connected_within(duration)
     }
 
     /// Sets a custom timeout when broadcasting a message with an acknowledgement.
@@ -498,6 +1596,7 @@ impl<A: Adapter> SocketIo<A> {
     ///
     /// # Panics
     /// If the **default namespace "/" is not found** this fn will panic!
+    /// It also panics if the timeout is [`Duration::ZERO`].
     ///
     /// See [`SocketIoBuilder::ack_timeout`](crate::SocketIoBuilder) for the default timeout.
     ///
@@ -594,6 +1693,545 @@ impl<A: Adapter> SocketIo<A> {
         self.get_default_op().emit(event, data)
     }
 
+    /// Gets a [`RoomHandle`] for the given room of the given namespace.
+    /// It caches the room member set, see the [`room`](crate::room) module for the staleness guarantees.
+    ///
+    /// Returns `Ok(None)` if the namespace is not found.
+    ///
+    /// ## Errors
+    /// When using a distributed adapter, it can return an [`Adapter::Error`] while fetching the initial members.
+    /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("lobby").ok();
+    /// });
+    ///
+    /// let lobby = io.room("/", "lobby").unwrap().unwrap();
+    /// println!("{} players in the lobby", lobby.size());
+    /// lobby.emit("hello", "Welcome!").ok();
+    /// ```
+    pub fn room(&self, ns: &str, room: impl Into<Room>) -> Result<Option<RoomHandle<A>>, A::Error> {
+        self.0
+            .get_ns(ns)
+            .map(|ns| RoomHandle::new(ns, room.into()))
+            .transpose()
+    }
+
+    /// Attaches a connection made over a custom transport, see the [`transport`] module.
+    /// The connection is then handled like the engine.io ones: the client connects to the namespaces
+    /// by sending connect packets.
+    ///
+    /// The connection is closed when the `incoming` stream ends or when the socket is closed by the server.
+    /// Returns the id of the underlying connection.
+    ///
+    /// [`transport`]: crate::transport
+    pub fn attach_transport(
+        &self,
+        incoming: impl Stream<Item = Frame> + Send + 'static,
+        outgoing: impl Sink<Frame> + Send + 'static,
+    ) -> Sid {
+        crate::transport::attach(self.0.clone(), incoming, outgoing)
+    }
+
+    /// Gets the number of sockets connected to the given namespace, see [`Adapter::sockets_count`].
+    /// Returns `None` if the namespace is not found.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    /// assert_eq!(io.sockets_count("/").unwrap(), Some(0));
+    /// ```
+    pub fn sockets_count(&self, ns: &str) -> Result<Option<usize>, A::Error> {
+        self.0
+            .get_ns(ns)
+            .map(|ns| ns.adapter.sockets_count())
+            .transpose()
+    }
+
+    /// Returns `true` if at least one socket of the given namespace is in the given room,
+    /// see [`Adapter::room_exists`]. Returns `None` if the namespace is not found.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    /// assert_eq!(io.room_exists("/", "lobby").unwrap(), Some(false));
+    /// ```
+    pub fn room_exists(&self, ns: &str, room: impl Into<Room>) -> Result<Option<bool>, A::Error> {
+        let room = room.into();
+        self.0
+            .get_ns(ns)
+            .map(|ns| ns.adapter.room_exists(&room))
+            .transpose()
+    }
+
+    /// Attaches metadata to a room of the given namespace, replacing the previous one.
+    /// The room does not need to exist yet, e.g. to set its capacity before the first join.
+    /// See the [`room`](crate::room) module for more details.
+    ///
+    /// If [`RoomMeta::created_at`] is `None`, it is set to the creation instant of the previous metadata
+    /// of the room, or to now. Once the room is populated, the metadata is removed when the last socket
+    /// leaves, unless [`RoomMeta::keep_when_empty`] is set.
+    ///
+    /// Returns `false` if the namespace is not found.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, room::RoomMeta, JoinError};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     if let Err(JoinError::Full(_)) = socket.join("game-1") {
+    ///         socket.emit("game-full", ()).ok();
+    ///     }
+    /// });
+    /// let meta = RoomMeta {
+    ///     capacity: Some(4),
+    ///     data: [("map".to_string(), "desert".into())].into(),
+    ///     ..Default::default()
+    /// };
+    /// io.set_room_meta("/", "game-1", meta).unwrap();
+    /// assert_eq!(io.room_meta("/", "game-1").unwrap().capacity, Some(4));
+    /// ```
+    pub fn set_room_meta(
+        &self,
+        ns: &str,
+        room: impl Into<Room>,
+        meta: RoomMeta,
+    ) -> Result<bool, A::Error> {
+        match self.0.get_ns(ns) {
+            Some(ns) => ns.set_room_meta(room.into(), meta).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Gets the metadata attached to a room of the given namespace with [`SocketIo::set_room_meta`].
+    /// Returns `None` if the namespace is not found or if the room has no metadata.
+    pub fn room_meta(&self, ns: &str, room: impl Into<Room>) -> Option<RoomMeta> {
+        self.0.get_ns(ns)?.room_meta(&room.into())
+    }
+
+    /// Removes the metadata attached to a room of the given namespace and returns it.
+    /// Returns `None` if the namespace is not found or if the room has no metadata.
+    pub fn remove_room_meta(&self, ns: &str, room: impl Into<Room>) -> Option<RoomMeta> {
+        self.0.get_ns(ns)?.remove_room_meta(&room.into())
+    }
+
+    /// Gets the number of sockets connected to all the namespaces, see [`Adapter::sockets_count`].
+    /// A connection attached to several namespaces is counted once per namespace,
+    /// use [`connection_count()`](#method.connection_count) to count it once.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    /// io.ns("/chat", |socket: SocketRef| {});
+    /// assert_eq!(io.socket_count().unwrap(), 0);
+    /// ```
+    pub fn socket_count(&self) -> Result<usize, A::Error> {
+        self.0
+            .get_all_ns()
+            .iter()
+            .map(|ns| ns.adapter.sockets_count())
+            .sum()
+    }
+
+    /// Gets the number of distinct connections attached to at least one namespace.
+    ///
+    /// The sockets of a connection share its id in every namespace, see [`Adapter::socket_ids`],
+    /// so a client connected to several namespaces is counted once.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    /// io.ns("/chat", |socket: SocketRef| {});
+    /// println!("{} clients online", io.connection_count().unwrap());
+    /// ```
+    pub fn connection_count(&self) -> Result<usize, A::Error> {
+        let mut ids = HashSet::new();
+        for ns in self.0.get_all_ns() {
+            ids.extend(ns.adapter.socket_ids()?);
+        }
+        Ok(ids.len())
+    }
+
+    /// Adds a middleware to the given namespace, run after the already added ones when a socket connects,
+    /// before the connect handler. If it returns an error, the connection is refused and the client receives
+    /// a connect error packet with the error message. See the [`middleware`] module.
+    ///
+    /// It only affects the sockets connecting afterwards. Returns `None` if the namespace is not found.
+    ///
+    /// [`middleware`]: crate::handler::middleware
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/admin", |socket: SocketRef| {});
+    /// io.push_middleware("/admin", |socket, _| {
+    ///     match socket.req_parts().headers.get("x-admin") {
+    ///         Some(_) => Ok(()),
+    ///         None => Err("forbidden".into()),
+    ///     }
+    /// });
+    /// ```
+    pub fn push_middleware(
+        &self,
+        ns: &str,
+        middleware: impl Fn(&Socket<A>, Option<&str>) -> Result<(), MiddlewareError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Option<MiddlewareId> {
+        let ns = self.0.get_ns(ns)?;
+        Some(ns.push_middleware(Arc::new(middleware)))
+    }
+
+    /// Removes a middleware previously added with [`SocketIo::push_middleware`] from the given namespace.
+    ///
+    /// It only affects the sockets connecting afterwards.
+    /// Returns `false` if the namespace or the middleware is not found.
+    pub fn remove_middleware(&self, ns: &str, id: MiddlewareId) -> bool {
+        self.0
+            .get_ns(ns)
+            .map_or(false, |ns| ns.remove_middleware(id))
+    }
+
+    /// Sets a guard consulted each time a socket of the given namespace calls [`Socket::join`],
+    /// before the adapter adds the membership. If it returns `false` for one of the rooms,
+    /// the join fails with a [`JoinError::Refused`] and none of the rooms are joined.
+    /// It replaces the previous guard of the namespace.
+    ///
+    /// Only [`Socket::join`] is guarded: the rooms set with [`Socket::set_rooms`] and the joins
+    /// made through the broadcast operators (e.g. `io.to("a").join("b")`) are server decisions and are not checked.
+    ///
+    /// Returns `false` if the namespace is not found.
+    ///
+    /// [`JoinError::Refused`]: crate::JoinError::Refused
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data::<String>(room)| {
+    ///     if socket.join(room).is_err() {
+    ///         socket.emit("forbidden", ()).ok();
+    ///     }
+    /// });
+    /// io.on_room_join_request("/", |socket, room| {
+    ///     !room.starts_with("admin:") || socket.req_parts().headers.contains_key("x-admin")
+    /// });
+    /// ```
+    pub fn on_room_join_request(
+        &self,
+        ns: &str,
+        guard: impl Fn(&Socket<A>, &Room) -> bool + Send + Sync + 'static,
+    ) -> bool {
+        match self.0.get_ns(ns) {
+            Some(ns) => {
+                ns.set_room_join_guard(Arc::new(guard));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets a callback called each time a socket joins or leaves a room, in any namespace,
+    /// whatever the code path: [`Socket::join`], [`Socket::leave`], [`Socket::set_rooms`],
+    /// the broadcast operators or the disconnection of the socket.
+    /// It applies to every namespace, including the ones created later from a [`SocketIo::dynamic_ns`] pattern,
+    /// and replaces the previous callback.
+    ///
+    /// The callback is only called for the actual changes: joining a room the socket is already in
+    /// or leaving a room it is not in is not reported. With the [`LocalAdapter`](crate::adapter::LocalAdapter),
+    /// it is called synchronously right after the membership is changed.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, adapter::RoomChange, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("lobby").ok();
+    /// });
+    /// io.on_room_change(|sid, room, change| match change {
+    ///     RoomChange::Join => println!("{sid} joined {room}"),
+    ///     RoomChange::Leave => println!("{sid} left {room}"),
+    /// });
+    /// ```
+    pub fn on_room_change(
+        &self,
+        callback: impl Fn(Sid, &Room, RoomChange) + Send + Sync + 'static,
+    ) {
+        self.0.config.room_change_hook.set(Arc::new(callback));
+    }
+
+    /// Sets the default ack timeout of the sockets of the given namespace, overriding the
+    /// [`SocketIoBuilder::ack_timeout`] one. It applies to the acknowledgements requested afterwards
+    /// without a [`timeout()`](crate::operators::ConfOperators#method.timeout) operator.
+    /// `None` removes the override.
+    ///
+    /// Returns `false` if the namespace is not found.
+    ///
+    /// # Panics
+    /// In debug builds, if the timeout is zero. In release builds, a zero timeout removes the override.
+    /// A timeout longer than [`MAX_ACK_TIMEOUT`](crate::operators::MAX_ACK_TIMEOUT) is clamped.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/chat", |socket: SocketRef| {});
+    /// io.ns("/jobs", |socket: SocketRef| {});
+    /// io.set_ack_timeout("/chat", Some(Duration::from_secs(2)));
+    /// io.set_ack_timeout("/jobs", Some(Duration::from_secs(60)));
+    /// ```
+    pub fn set_ack_timeout(&self, ns: &str, timeout: Option<Duration>) -> bool {
+        let timeout = timeout.and_then(crate::operators::validate_timeout);
+        match self.0.get_ns(ns) {
+            Some(ns) => {
+                ns.set_ack_timeout(timeout);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Gets the throughput of a namespace over the last [`THROUGHPUT_WINDOW`], see the [`metrics`] module.
+    /// Returns `None` if the namespace is not found.
+    ///
+    /// **Note**: only the traffic of this server node is measured.
+    ///
+    /// [`THROUGHPUT_WINDOW`]: crate::metrics::THROUGHPUT_WINDOW
+    /// [`metrics`]: crate::metrics
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// let throughput = io.throughput("/").unwrap();
+    /// println!("{} events/s in, {} bytes/s out", throughput.events_in, throughput.bytes_out);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+    #[cfg(feature = "metrics")]
+    pub fn throughput(&self, ns: &str) -> Option<crate::metrics::ThroughputSnapshot> {
+        let ns = self.0.get_ns(ns)?;
+        Some(ns.throughput.snapshot(self.0.config.clock.now()))
+    }
+
+    /// Disconnects every socket of the given namespace that didn't send anything for at least `idle`
+    /// (see [`Socket::idle_for`]). Their disconnect handlers are called with
+    /// [`DisconnectReason::IdleTimeout`](crate::socket::DisconnectReason::IdleTimeout).
+    ///
+    /// Returns the ids of the disconnected sockets, or `None` if the namespace is not found.
+    ///
+    /// **Note**: it only reaches sockets connected to this server node, not through the [`Adapter`].
+    ///
+    /// ## Errors
+    /// The errors of the sockets that could not be disconnected are returned,
+    /// the other idle sockets are still disconnected.
+    ///
+    /// [`Socket::idle_for`]: crate::socket::Socket#method.idle_for
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// // Periodically reap the sockets idle for more than 10 minutes
+    /// async fn reap_idle(io: SocketIo) {
+    ///     loop {
+    ///         tokio::time::sleep(Duration::from_secs(60)).await;
+    ///         io.disconnect_idle("/", Duration::from_secs(600)).ok();
+    ///     }
+    /// }
+    /// ```
+    pub fn disconnect_idle(
+        &self,
+        ns: &str,
+        idle: Duration,
+    ) -> Result<Option<Vec<Sid>>, Vec<DisconnectError>> {
+        let Some(ns) = self.0.get_ns(ns) else {
+            return Ok(None);
+        };
+        let mut disconnected = Vec::new();
+        let mut errors = Vec::new();
+        for socket in ns.get_sockets() {
+            if socket.idle_for() < idle {
+                continue;
+            }
+            let sid = socket.id;
+            match socket.disconnect_with_reason(DisconnectReason::IdleTimeout) {
+                Ok(()) => disconnected.push(sid),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.is_empty() {
+            Ok(Some(disconnected))
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Closes the connection of every socket of every namespace, so that the clients reconnect,
+    /// for example to a healthy node when rolling a deployment. Unlike [`SocketIo::close`],
+    /// the namespaces are kept and new sockets can still connect.
+    ///
+    /// The disconnect handlers are called with [`DisconnectReason::ClosingServer`].
+    /// Returns once every transport is closed, or `false` if the `timeout` elapsed before.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::SocketIo;
+    /// # use std::time::Duration;
+    /// # async fn doc() {
+    /// let (_, io) = SocketIo::new_svc();
+    /// if !io.disconnect_all(Duration::from_secs(5)).await {
+    ///     println!("some connections are still open");
+    /// }
+    /// # }
+    /// ```
+    pub async fn disconnect_all(&self, timeout: Duration) -> bool {
+        let ns = self.0.get_all_ns();
+        self.0.disconnect_all(&ns, timeout).await
+    }
+
+    /// Closes the connection of every socket of the given namespace, see [`SocketIo::disconnect_all`].
+    ///
+    /// The other namespaces the clients are connected to are disconnected as well,
+    /// because they share the same underlying connection.
+    /// Returns `None` if the namespace is not found, `Some(false)` if the `timeout` elapsed before
+    /// every transport was closed.
+    pub async fn disconnect_all_ns(&self, ns: &str, timeout: Duration) -> Option<bool> {
+        let ns = self.0.get_ns(ns)?;
+        Some(self.0.disconnect_all(&[ns], timeout).await)
+    }
+
+    /// Makes many sockets of the given namespace join the given rooms at once,
+    /// with a single [`Adapter::add_many`] call rather than one call per socket.
+    ///
+    /// The sockets that are not connected to the namespace are skipped.
+    /// Returns the ids of the sockets that joined, none of them if the namespace is not found.
+    ///
+    /// The join guard set with [`SocketIo::on_room_join_request`] and the capacity of the rooms
+    /// set in their [`RoomMeta`] are not checked: the rooms can exceed their capacity.
+    ///
+    /// ## Errors
+    /// The sockets that could not join are returned with the adapter error,
+    /// the other sockets still join the rooms.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| socket.join("lobby").unwrap());
+    ///
+    /// // Move every lobby socket to the match room when the game starts
+    /// let lobby: Vec<_> = io.within("lobby").sockets().unwrap().iter().map(|s| s.id).collect();
+    /// io.join_all("/", lobby, "match").unwrap();
+    /// ```
+    pub fn join_all(
+        &self,
+        ns: &str,
+        sids: impl IntoIterator<Item = Sid>,
+        rooms: impl RoomParam,
+    ) -> Result<Vec<Sid>, Vec<(Sid, A::Error)>> {
+        let Some(ns) = self.0.get_ns(ns) else {
+            return Ok(Vec::new());
+        };
+        let sids: Vec<Sid> = sids
+            .into_iter()
+            .filter(|&sid| ns.get_socket(sid).is_ok())
+            .collect();
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        let res = ns.adapter.add_many(&sids, rooms.clone());
+        let failed: HashSet<Sid> = match &res {
+            Ok(()) => HashSet::new(),
+            Err(errors) => errors.iter().map(|(sid, _)| *sid).collect(),
+        };
+        let joined: Vec<Sid> = sids
+            .into_iter()
+            .filter(|sid| !failed.contains(sid))
+            .collect();
+        for &sid in &joined {
+            ns.rooms_joined(sid, &rooms);
+        }
+        res.map(|()| joined)
+    }
+
+    /// Gets the id of the last socket that connected to the given namespace with the given session id,
+    /// see [`Socket::session_id`]. The socket may be disconnected, its session is remembered for the
+    /// [`SocketIoBuilder::session_retention`] duration.
+    ///
+    /// Returns `None` if the namespace is not found, if the session is unknown
+    /// or if [`SocketIoBuilder::session_retention`] is not set.
+    ///
+    /// [`Socket::session_id`]: crate::socket::Socket#method.session_id
+    #[inline]
+    pub fn session_sid(&self, ns: &str, session_id: &str) -> Option<Sid> {
+        self.0.get_ns(ns)?.session_sid(session_id)
+    }
+
+    /// Emits a message to every socket associated with the given user id in the given namespace.
+    /// Sockets are associated with a user id with [`Socket::set_user_id`].
+    ///
+    /// If the namespace is not found or if the user has no connected socket, nothing is sent.
+    ///
+    /// **Note**: it only reaches sockets connected to this server node, not through the [`Adapter`].
+    ///
+    /// [`Socket::set_user_id`]: crate::socket::Socket#method.set_user_id
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data(user_id): Data<String>| {
+    ///     socket.set_user_id(user_id);
+    /// });
+    ///
+    /// // Later in your code you can reach every tab/device opened by the user
+    /// io.emit_to_user("/", "user-1", "notification", "Hello!").ok();
+    /// ```
+    pub fn emit_to_user<T: serde::Serialize>(
+        &self,
+        ns: &str,
+        user_id: &str,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), BroadcastError> {
+        let Some(ns) = self.0.get_ns(ns) else {
+            return Ok(());
+        };
+        let sockets = ns.get_user_sockets(user_id);
+        if sockets.is_empty() {
+            return Ok(());
+        }
+
+        let packet = Packet::event(ns.path.clone(), event.into(), serde_json::to_value(data)?);
+        ns.mirror_to_observers(&packet, false, |sid| sockets.iter().any(|s| s.id == sid));
+        let errors: Vec<_> = sockets
+            .iter()
+            .filter_map(|socket| socket.send(packet.clone()).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+
     /// Emits a message to all sockets selected with the previous operators and
     /// waits for the acknowledgement(s).
     ///
@@ -783,6 +2421,35 @@ impl<A: Adapter> SocketIo<A> {
         self.get_default_op().get_socket(sid)
     }
 
+    /// Emits a message to the socket with the given id on the root namespace,
+    /// without knowing its rooms.
+    ///
+    /// Alias for `io.of("/").unwrap().emit_to_sid(sid, event, data)`,
+    /// see [`BroadcastOperators::emit_to_sid`] for the errors.
+    ///
+    /// ## Panics
+    /// If the **default namespace "/" is not found** this fn will panic!
+    ///
+    /// ### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::SocketRef};
+    /// let (_, io) = SocketIo::new_svc();
+    /// let io2 = io.clone();
+    /// io.ns("/", move |socket: SocketRef| {
+    ///     // Only the id is kept, e.g. in an external store
+    ///     let sid = socket.id;
+    ///     io2.emit_to_sid(sid, "welcome", "hello").ok();
+    /// });
+    /// ```
+    pub fn emit_to_sid<T: serde::Serialize>(
+        &self,
+        sid: Sid,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        self.get_default_op().emit_to_sid(sid, event, data)
+    }
+
     /// Returns a new operator on the given namespace
     #[inline(always)]
     fn get_op(&self, path: &str) -> Option<BroadcastOperators<A>> {
@@ -851,4 +2518,34 @@ mod tests {
         assert!(io.get_socket(sid).is_some());
         assert!(io.get_socket(Sid::new()).is_none());
     }
+
+    #[test]
+    fn join_all() {
+        use engineioxide::Socket;
+        let (_, io) = SocketIo::builder().build_svc();
+        io.ns("/", || {});
+        let ns = io.0.get_ns("/").unwrap();
+        let mut sids: Vec<Sid> = (0..500).map(|_| Sid::new()).collect();
+        for &sid in &sids {
+            let socket = Socket::new_dummy(sid, Box::new(|_, _| {})).into();
+            let config = SocketIoConfig::default().into();
+            ns.clone().connect(sid, socket, None, config).unwrap();
+        }
+
+        // A socket that is gone in the middle of the batch doesn't abort the other ones
+        let gone = Sid::new();
+        sids.insert(250, gone);
+        let joined = io.join_all("/", sids.clone(), ["match", "game"]).unwrap();
+        sids.remove(250);
+        assert_eq!(joined, sids);
+
+        let mut members: Vec<Sid> = ns.adapter.sockets("match").unwrap();
+        members.sort();
+        sids.sort();
+        assert_eq!(members, sids);
+        assert_eq!(ns.adapter.sockets("game").unwrap().len(), 500);
+        assert!(ns.adapter.socket_rooms(gone).unwrap().is_empty());
+
+        assert!(io.join_all("/nope", sids, "match").unwrap().is_empty());
+    }
 }
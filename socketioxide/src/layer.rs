@@ -43,7 +43,6 @@ use crate::{
     adapter::{Adapter, LocalAdapter},
     client::Client,
     service::SocketIoService,
-    SocketIoConfig,
 };
 
 /// A [`Layer`] for [`SocketIoService`], acting as a middleware.
@@ -60,12 +59,8 @@ impl<A: Adapter> Clone for SocketIoLayer<A> {
 }
 
 impl<A: Adapter> SocketIoLayer<A> {
-    pub(crate) fn from_config(config: Arc<SocketIoConfig>) -> (Self, Arc<Client<A>>) {
-        let client = Arc::new(Client::new(config.clone()));
-        let layer = Self {
-            client: client.clone(),
-        };
-        (layer, client)
+    pub(crate) fn from_client(client: Arc<Client<A>>) -> Self {
+        Self { client }
     }
 }
 
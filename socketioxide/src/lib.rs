@@ -151,11 +151,16 @@
 //! * [`TryData`](extract::TryData): extracts and deserialize to json any data but with a `Result` type in case of error
 //!     - for [`ConnectHandler`](handler::ConnectHandler): extracts and deserialize to json the auth data
 //!     - for [`MessageHandler`](handler::MessageHandler): extracts and deserialize to json the message data
+//! * [`AuthData`](extract::AuthData): extracts and deserialize to json the auth payload sent by the client when connecting
+//! * [`NsParam`](extract::NsParam): extracts the parameter matched by the pattern of a dynamic namespace
 //! * [`SocketRef`](extract::SocketRef): extracts a reference to the [`Socket`](socket::Socket)
 //! * [`Bin`](extract::Bin): extract a binary payload for a given message. Because it consumes the event it should be the last argument
 //! * [`AckSender`](extract::AckSender): Can be used to send an ack response to the current message event
+//! * [`Reply`](extract::Reply): Can be used to emit a response event to the current message event
+//! * [`AckId`](extract::AckId): extracts the ack id of the current message event
 //! * [`ProtocolVersion`]: extracts the protocol version of the socket
 //! * [`TransportType`]: extracts the transport type of the socket
+//! * [`TraceContext`](extract::TraceContext): extracts the W3C trace context sent by the client when connecting
 //! * [`DisconnectReason`](crate::socket::DisconnectReason): extracts the reason of the disconnection
 //! * [`State`](extract::State): extracts a reference to a state previously set with [`SocketIoBuilder::with_state`](crate::io::SocketIoBuilder).
 //! ### Extractor order
@@ -211,6 +216,8 @@
 //! They are implemented with the [`AckSender`](extract::AckSender) extractor.
 //! You can send an ack response with an optional binary payload with the [`AckSender::send`](extract::AckSender) method.
 //! If the client doesn't send an ack response, the [`AckSender::send`](extract::AckSender) method will do nothing.
+//! To defer the ack response outside of the handler, extract the ack id with [`AckId`](extract::AckId)
+//! and send it later with [`SocketRef::send_manual_ack`](crate::socket::Socket::send_manual_ack).
 //!
 //! #### Client acknowledgements
 //! If you want to emit/broadcast a message and await for a/many client(s) acknowledgment(s) you can use:
@@ -218,6 +225,9 @@
 //! * [`BroadcastOperators::emit_with_ack`] for broadcasting or [emit configuration](#emiting-data).
 //! * [`SocketIo::emit_with_ack`] for broadcasting.
 //!
+//! The ack arguments sent by the client are received as an array. A multi-argument ack
+//! (`callback(a, b)` on the JS side) can be deserialized into a tuple such as `(A, B)`.
+//!
 //! [`SocketRef::emit_with_ack`]: crate::extract::SocketRef#method.emit_with_ack
 //! [`BroadcastOperators::emit_with_ack`]: crate::operators::BroadcastOperators#method.emit_with_ack
 //! [`SocketIo::emit_with_ack`]: SocketIo#method.emit_with_ack
@@ -254,6 +264,8 @@
 //! * `tracing`: enable logging with [`tracing`] calls
 //! * `extensions`: enable per-socket state with the [`extensions`] module
 //! * `state`: enable global state management
+//! * `test-utils`: enable the [`ManualClock`](clock) to control time in tests
+//! * `metrics`: enable the per-namespace throughput counters of the [`metrics`] module
 //!
 pub mod adapter;
 
@@ -263,18 +275,33 @@ pub mod extensions;
 #[cfg(feature = "state")]
 mod state;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "metrics")))]
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 pub mod ack;
+pub mod clock;
 pub mod handler;
 pub mod layer;
 pub mod operators;
 pub mod packet;
+pub mod room;
 pub mod service;
 pub mod socket;
+pub mod transport;
 
+pub use engineioxide::ProtocolVersion as EngineIoVersion;
 pub use engineioxide::TransportType;
-pub use errors::{AckError, AdapterError, BroadcastError, DisconnectError, SendError, SocketError};
+pub use errors::{
+    AckError, AdapterError, BroadcastError, DisconnectError, JoinError, KickError,
+    PacketDecodeError, RelayError, SendError, SocketError, TryEmitError,
+};
 pub use handler::extract;
-pub use io::{SocketIo, SocketIoBuilder, SocketIoConfig};
+pub use io::{
+    AckSerializer, AppKeepalive, DisconnectRecordHook, EventOrdering, PauseEventsPolicy,
+    RelayPolicy, RoomChangeHook, ShutdownDisconnectPolicy, SocketIo, SocketIoBuilder,
+    SocketIoConfig, StagedShutdownConfig, TransportEvent, UndeliveredHook,
+};
 
 mod client;
 mod errors;
@@ -285,7 +312,7 @@ mod ns;
 /// It is accessible with the [`Socket::protocol`](socket::Socket) method or as an extractor
 ///
 /// **Note**: The socket.io protocol version does not correspond to the engine.io protocol version.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
 pub enum ProtocolVersion {
     /// The socket.io protocol version 4, only available with the feature flag `v4`
     V4 = 4,
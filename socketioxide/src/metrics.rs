@@ -0,0 +1,176 @@
+//! Per-namespace throughput metrics, available with the `metrics` feature.
+//!
+//! Every namespace counts the events and bytes it receives from and sends to its sockets.
+//! The counters are sampled in one second buckets, and the rates of the last [`THROUGHPUT_WINDOW`]
+//! are returned by [`SocketIo::throughput`].
+//!
+//! The bytes are the ones of the encoded socket.io packets and binary payloads,
+//! without the engine.io framing.
+//!
+//! [`SocketIo::throughput`]: crate::SocketIo#method.throughput
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The duration over which the throughput rates are computed.
+pub const THROUGHPUT_WINDOW: Duration = Duration::from_secs(10);
+
+const BUCKET_DURATION: Duration = Duration::from_secs(1);
+/// One more bucket than the window, so that the current second never overwrites the oldest one.
+const BUCKET_COUNT: usize = THROUGHPUT_WINDOW.as_secs() as usize + 1;
+
+/// The throughput of a namespace, in units per second, see [`SocketIo::throughput`].
+///
+/// [`SocketIo::throughput`]: crate::SocketIo#method.throughput
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ThroughputSnapshot {
+    /// The events received from the clients per second
+    pub events_in: f64,
+    /// The events sent to the clients per second
+    pub events_out: f64,
+    /// The bytes received from the clients per second
+    pub bytes_in: f64,
+    /// The bytes sent to the clients per second
+    pub bytes_out: f64,
+    /// The duration over which the rates were computed.
+    /// It is shorter than the [`THROUGHPUT_WINDOW`] until the namespace has existed for that long.
+    pub window: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    /// The index of the second this bucket counts, plus one so that `0` means unused
+    epoch: AtomicU64,
+    events_in: AtomicU64,
+    events_out: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+/// The rolling window sampler of a namespace.
+///
+/// The counters are updated without locking: a few packets recorded while a bucket is recycled
+/// can be lost, so the rates are approximate.
+#[derive(Debug)]
+pub(crate) struct Throughput {
+    origin: Instant,
+    buckets: [Bucket; BUCKET_COUNT],
+}
+
+impl Throughput {
+    pub fn new(origin: Instant) -> Self {
+        Self {
+            origin,
+            buckets: Default::default(),
+        }
+    }
+
+    /// Records an incoming packet of `bytes` bytes
+    pub fn record_in(&self, now: Instant, event: bool, bytes: usize) {
+        let bucket = self.bucket(now);
+        bucket.events_in.fetch_add(event as u64, Ordering::Relaxed);
+        bucket.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Records an outgoing packet of `bytes` bytes
+    pub fn record_out(&self, now: Instant, event: bool, bytes: usize) {
+        let bucket = self.bucket(now);
+        bucket.events_out.fetch_add(event as u64, Ordering::Relaxed);
+        bucket.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Computes the rates over the last completed buckets, the current second is not included.
+    pub fn snapshot(&self, now: Instant) -> ThroughputSnapshot {
+        let current = self.epoch(now);
+        let first = current.saturating_sub(THROUGHPUT_WINDOW.as_secs());
+        let window = BUCKET_DURATION * (current - first) as u32;
+        if window.is_zero() {
+            return ThroughputSnapshot::default();
+        }
+
+        let mut totals = [0u64; 4];
+        for bucket in &self.buckets {
+            let epoch = bucket.epoch.load(Ordering::Acquire).checked_sub(1);
+            if !epoch.map_or(false, |epoch| (first..current).contains(&epoch)) {
+                continue;
+            }
+            totals[0] += bucket.events_in.load(Ordering::Relaxed);
+            totals[1] += bucket.events_out.load(Ordering::Relaxed);
+            totals[2] += bucket.bytes_in.load(Ordering::Relaxed);
+            totals[3] += bucket.bytes_out.load(Ordering::Relaxed);
+        }
+        let secs = window.as_secs_f64();
+        ThroughputSnapshot {
+            events_in: totals[0] as f64 / secs,
+            events_out: totals[1] as f64 / secs,
+            bytes_in: totals[2] as f64 / secs,
+            bytes_out: totals[3] as f64 / secs,
+            window,
+        }
+    }
+
+    fn epoch(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.origin).as_secs() / BUCKET_DURATION.as_secs()
+    }
+
+    /// Gets the bucket of the current second, recycling it if it still holds an older second
+    fn bucket(&self, now: Instant) -> &Bucket {
+        let epoch = self.epoch(now);
+        let bucket = &self.buckets[(epoch % BUCKET_COUNT as u64) as usize];
+        let current = bucket.epoch.load(Ordering::Acquire);
+        if current != epoch + 1
+            && bucket
+                .epoch
+                .compare_exchange(current, epoch + 1, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+        {
+            bucket.events_in.store(0, Ordering::Relaxed);
+            bucket.events_out.store(0, Ordering::Relaxed);
+            bucket.bytes_in.store(0, Ordering::Relaxed);
+            bucket.bytes_out.store(0, Ordering::Relaxed);
+        }
+        bucket
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rolling_window() {
+        let origin = Instant::now();
+        let throughput = Throughput::new(origin);
+        assert_eq!(throughput.snapshot(origin), ThroughputSnapshot::default());
+
+        let record = |sec: u64| {
+            let now = origin + Duration::from_secs(sec) + Duration::from_millis(500);
+            for _ in 0..sec {
+                throughput.record_in(now, true, 10);
+            }
+            throughput.record_out(now, false, 100);
+        };
+        (0..3).for_each(record);
+
+        // Only the first 2 seconds are completed: 0 and 1 events
+        let snapshot = throughput.snapshot(origin + Duration::from_secs(2));
+        assert_eq!(snapshot.window, Duration::from_secs(2));
+        assert_eq!(snapshot.events_in, 0.5);
+        assert_eq!(snapshot.bytes_in, 5.0);
+        assert_eq!(snapshot.events_out, 0.0);
+        assert_eq!(snapshot.bytes_out, 100.0);
+
+        (3..15).for_each(record);
+        // The seconds 5 to 14 are in the window, the current one isn't counted
+        let snapshot = throughput.snapshot(origin + Duration::from_millis(15_200));
+        assert_eq!(snapshot.window, THROUGHPUT_WINDOW);
+        assert_eq!(snapshot.events_in, 9.5);
+        assert_eq!(snapshot.bytes_out, 100.0);
+
+        // Without traffic, the rates fall to zero once the window has passed
+        let snapshot = throughput.snapshot(origin + Duration::from_secs(40));
+        assert_eq!(snapshot.events_in, 0.0);
+        assert_eq!(snapshot.bytes_out, 0.0);
+    }
+}
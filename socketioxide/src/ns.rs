@@ -1,29 +1,115 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    sync::{Arc, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, RwLock, Weak,
+    },
+    time::Duration,
 };
 
+use tokio::sync::Semaphore;
+
 use crate::{
-    adapter::Adapter,
-    errors::Error,
-    handler::{BoxedConnectHandler, ConnectHandler, MakeErasedHandler},
+    adapter::{Adapter, BroadcastOptions, Room, RoomChange},
+    clock::Clock,
+    errors::{Error, JoinError},
+    handler::{
+        middleware::{BoxedMiddleware, Middlewares},
+        BoxedConnectHandler, ConnectHandler, MakeErasedHandler, MiddlewareId,
+    },
     packet::{Packet, PacketData},
-    socket::Socket,
-    SocketIoConfig,
+    room::{RoomMembers, RoomMeta},
+    socket::{DisconnectReason, ObserverScope, Socket},
+    EventOrdering, RoomChangeHook, ShutdownDisconnectPolicy, SocketIoConfig,
 };
 use crate::{client::SocketData, errors::AdapterError};
 use engineioxide::sid::Sid;
 
+/// The guard of the rooms joined with [`Socket::join`], see [`SocketIo::on_room_join_request`](crate::SocketIo#method.on_room_join_request)
+pub(crate) type BoxedRoomGuard<A> = Arc<dyn Fn(&Socket<A>, &Room) -> bool + Send + Sync>;
+
+/// The last socket connected with a session id, see [`Socket::session_id`]
+struct Session {
+    sid: Sid,
+    /// The extensions of the socket, moved here when it disconnected
+    #[cfg(feature = "extensions")]
+    extensions: Option<crate::extensions::Extensions>,
+    /// The user id of the socket when it disconnected, required to restore the extensions
+    #[cfg(feature = "extensions")]
+    user_id: Option<String>,
+}
+
+/// The namespaces of a server, indexed by path
+pub(crate) type NsMap<A> = RwLock<HashMap<Cow<'static, str>, Arc<Namespace<A>>>>;
+
+/// The key of a coalesced broadcast: the user key and the sorted rooms of the broadcast
+type CoalesceKey = (Cow<'static, str>, Vec<Room>);
+
 pub struct Namespace<A: Adapter> {
     pub path: Cow<'static, str>,
     pub(crate) adapter: A,
     handler: BoxedConnectHandler<A>,
     sockets: RwLock<HashMap<Sid, Arc<Socket<A>>>>,
+    /// Index of the sockets belonging to each user id, see [`Socket::set_user_id`]
+    users: RwLock<HashMap<String, HashSet<Sid>>>,
+    /// The last socket of each session id, see [`Socket::session_id`]
+    sessions: RwLock<HashMap<String, Session>>,
+    /// Index of the sockets having each tag, see [`Socket::add_tag`]
+    tags: RwLock<HashMap<String, HashSet<Sid>>>,
+    /// The sockets receiving a mirror of the traffic, see [`Socket::make_observer`]
+    observers: RwLock<HashMap<Sid, ObserverScope>>,
+    /// Member caches of the rooms tracked by a [`RoomHandle`](crate::room::RoomHandle)
+    room_handles: RwLock<HashMap<Room, Weak<RoomMembers>>>,
+    /// Clock used by the emits scheduled on this namespace
+    pub(crate) clock: Arc<dyn Clock>,
+    /// Middlewares run before the connect handler
+    middlewares: RwLock<Middlewares<A>>,
+    /// Guard consulted before a socket joins a room
+    room_join_guard: RwLock<Option<BoxedRoomGuard<A>>>,
+    /// Callback called after each room membership change made by the adapter, shared by all the namespaces
+    pub(crate) room_change_hook: RoomChangeHook,
+    /// The metadata of the rooms with whether the room was populated since it was attached,
+    /// see [`SocketIo::set_room_meta`](crate::SocketIo#method.set_room_meta)
+    room_meta: RwLock<HashMap<Room, (RoomMeta, bool)>>,
+    /// The ack timeout overriding the one of the config, see [`SocketIo::set_ack_timeout`](crate::SocketIo#method.set_ack_timeout)
+    ack_timeout: RwLock<Option<Duration>>,
+    /// Held while joining rooms with a capacity, so that concurrent joins cannot exceed it
+    capacity_lock: Mutex<()>,
+    /// The latest broadcasts coalesced with [`BroadcastOperators::coalesce`](crate::operators::BroadcastOperators::coalesce),
+    /// waiting for the end of their interval
+    coalesced: Mutex<HashMap<CoalesceKey, (Packet<'static>, BroadcastOptions)>>,
+    /// Throughput counters of the traffic of this namespace
+    #[cfg(feature = "metrics")]
+    pub(crate) throughput: crate::metrics::Throughput,
+    /// The parameters matched by the pattern of a dynamic namespace,
+    /// see [`SocketIo::dynamic_ns`](crate::SocketIo#method.dynamic_ns)
+    pub(crate) params: Vec<(Cow<'static, str>, String)>,
+    /// The namespace map this namespace is removed from once it is empty,
+    /// see [`SocketIoBuilder::ephemeral_dynamic_ns`](crate::SocketIoBuilder#method.ephemeral_dynamic_ns)
+    registry: Option<Weak<NsMap<A>>>,
+    /// The number of sockets being connected, preventing the removal of an ephemeral namespace
+    connecting: AtomicUsize,
 }
 
 impl<A: Adapter> Namespace<A> {
-    pub fn new<C, T>(path: Cow<'static, str>, handler: C) -> Arc<Self>
+    pub fn new<C, T>(path: Cow<'static, str>, handler: C, config: &SocketIoConfig) -> Arc<Self>
+    where
+        C: ConnectHandler<A, T> + Send + Sync + 'static,
+        T: Send + Sync + 'static,
+    {
+        Self::new_dynamic(path, handler, config, Vec::new(), None)
+    }
+
+    /// Creates a namespace matched by the pattern of a dynamic namespace.
+    /// If a registry is given, the namespace removes itself from it once its last socket is removed.
+    pub(crate) fn new_dynamic<C, T>(
+        path: Cow<'static, str>,
+        handler: C,
+        config: &SocketIoConfig,
+        params: Vec<(Cow<'static, str>, String)>,
+        registry: Option<Weak<NsMap<A>>>,
+    ) -> Arc<Self>
     where
         C: ConnectHandler<A, T> + Send + Sync + 'static,
         T: Send + Sync + 'static,
@@ -32,6 +118,24 @@ impl<A: Adapter> Namespace<A> {
             path,
             handler: MakeErasedHandler::new_ns_boxed(handler),
             sockets: HashMap::new().into(),
+            users: HashMap::new().into(),
+            sessions: HashMap::new().into(),
+            tags: HashMap::new().into(),
+            observers: HashMap::new().into(),
+            room_handles: HashMap::new().into(),
+            #[cfg(feature = "metrics")]
+            throughput: crate::metrics::Throughput::new(config.clock.now()),
+            clock: config.clock.clone(),
+            middlewares: RwLock::default(),
+            room_join_guard: RwLock::default(),
+            room_change_hook: config.room_change_hook.clone(),
+            room_meta: RwLock::default(),
+            ack_timeout: RwLock::default(),
+            capacity_lock: Mutex::default(),
+            coalesced: Mutex::default(),
+            params,
+            registry,
+            connecting: AtomicUsize::new(0),
             adapter: A::new(ns.clone()),
         })
     }
@@ -44,9 +148,36 @@ impl<A: Adapter> Namespace<A> {
         auth: Option<String>,
         config: Arc<SocketIoConfig>,
     ) -> Result<(), serde_json::Error> {
-        let socket: Arc<Socket<A>> = Socket::new(sid, self.clone(), esocket.clone(), config).into();
+        let mut socket = Socket::new(sid, self.clone(), esocket.clone(), config);
+        socket.set_auth(auth.as_deref());
+        let socket: Arc<Socket<A>> = socket.into();
+
+        // The middlewares are run on a snapshot so that they can modify the list
+        let middlewares = self.middlewares.read().unwrap().snapshot();
+        for middleware in middlewares {
+            if let Err(e) = middleware(&socket, auth.as_deref()) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("connection of socket {sid} refused by middleware: {e}");
+                if let Err(_e) =
+                    socket.send(Packet::connect_error(self.path.clone(), e.to_string()))
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("error sending connect error packet: {:?}", _e);
+                }
+                // The middlewares may have joined rooms or set a disconnect handler
+                if let Err(_e) = socket.close(DisconnectReason::MiddlewareRefused) {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("adapter error when closing refused socket: {:?}", _e);
+                }
+                return Ok(());
+            }
+        }
 
         self.sockets.write().unwrap().insert(sid, socket.clone());
+        if let (Some(session_id), Some(_)) = (socket.session_id(), socket.config.session_retention)
+        {
+            self.set_session(session_id, sid);
+        }
 
         let protocol = esocket.protocol.into();
         if let Err(_e) = socket.send(Packet::connect(&self.path, socket.id, protocol)) {
@@ -56,16 +187,477 @@ impl<A: Adapter> Namespace<A> {
             return Ok(());
         }
 
+        if let Some(keepalive) = socket.config.app_keepalive {
+            socket.spawn_keepalive(keepalive);
+        }
+        if socket.config.event_ordering == EventOrdering::Fifo {
+            socket.spawn_event_queue();
+        }
         self.handler.call(socket, auth);
         Ok(())
     }
 
+    /// Adds a middleware run after the other ones for the sockets connecting afterwards
+    pub fn push_middleware(&self, middleware: BoxedMiddleware<A>) -> MiddlewareId {
+        self.middlewares.write().unwrap().push(middleware)
+    }
+
+    /// Removes a middleware, returns `false` if it was not found
+    pub fn remove_middleware(&self, id: MiddlewareId) -> bool {
+        self.middlewares.write().unwrap().remove(id)
+    }
+
+    /// Sets the guard consulted before a socket joins a room, replacing the previous one
+    pub fn set_room_join_guard(&self, guard: BoxedRoomGuard<A>) {
+        *self.room_join_guard.write().unwrap() = Some(guard);
+    }
+
+    /// Reports a change of the membership of a socket in a room to the callback set with
+    /// [`SocketIo::on_room_change`](crate::SocketIo#method.on_room_change), if any.
+    ///
+    /// It must be called by the [`Adapter`] once the membership is changed, without holding any lock
+    /// of the adapter as the callback may change the rooms of the sockets.
+    pub fn notify_room_change(&self, sid: Sid, room: &Room, change: RoomChange) {
+        self.room_change_hook.call(sid, room, change);
+    }
+
+    /// Sets the ack timeout of the namespace, `None` falls back to the one of the config
+    pub fn set_ack_timeout(&self, timeout: Option<Duration>) {
+        *self.ack_timeout.write().unwrap() = timeout;
+    }
+
+    pub fn ack_timeout(&self) -> Option<Duration> {
+        *self.ack_timeout.read().unwrap()
+    }
+
+    /// Gets the first room that the socket is not allowed to join, if any
+    pub fn refused_room<'a>(&self, socket: &Socket<A>, rooms: &'a [Room]) -> Option<&'a Room> {
+        // The guard is cloned so that it can access the namespace without holding the lock
+        let guard = self.room_join_guard.read().unwrap().clone()?;
+        rooms.iter().find(|room| !guard(socket, room))
+    }
+
+    /// Attaches metadata to a room, replacing the previous one but keeping its creation instant
+    pub fn set_room_meta(&self, room: Room, mut meta: RoomMeta) -> Result<(), A::Error> {
+        let populated = self.adapter.room_exists(&room)?;
+        let mut metas = self.room_meta.write().unwrap();
+        let created_at = metas.get(&room).and_then(|(m, _)| m.created_at);
+        if meta.created_at.is_none() {
+            meta.created_at = Some(created_at.unwrap_or_else(|| self.clock.now()));
+        }
+        metas.insert(room, (meta, populated));
+        Ok(())
+    }
+
+    pub fn room_meta(&self, room: &Room) -> Option<RoomMeta> {
+        self.room_meta
+            .read()
+            .unwrap()
+            .get(room)
+            .map(|(m, _)| m.clone())
+    }
+
+    pub fn remove_room_meta(&self, room: &Room) -> Option<RoomMeta> {
+        self.room_meta.write().unwrap().remove(room).map(|(m, _)| m)
+    }
+
+    /// Checks that the socket can join the rooms without exceeding their capacity.
+    /// If one of the rooms has a capacity, the returned guard must be held until the rooms are joined.
+    pub fn check_capacity(
+        &self,
+        sid: Sid,
+        rooms: &[Room],
+    ) -> Result<Option<MutexGuard<'_, ()>>, JoinError<A::Error>> {
+        let capacities: Vec<(&Room, usize)> = {
+            let metas = self.room_meta.read().unwrap();
+            if metas.is_empty() {
+                return Ok(None);
+            }
+            rooms
+                .iter()
+                .filter_map(|room| Some((room, metas.get(room)?.0.capacity?)))
+                .collect()
+        };
+        if capacities.is_empty() {
+            return Ok(None);
+        }
+        let guard = self.capacity_lock.lock().unwrap();
+        for (room, capacity) in capacities {
+            if !self.adapter.is_in_room(sid, room)?
+                && self.adapter.sockets(room.clone())?.len() >= capacity
+            {
+                return Err(JoinError::Full(room.clone()));
+            }
+        }
+        Ok(Some(guard))
+    }
+
+    /// Updates the metadata of the given rooms, or of every room if `None`, after a membership change.
+    /// The metadata of a room that was populated and is now empty is removed, unless it is kept with
+    /// [`RoomMeta::keep_when_empty`].
+    fn sync_room_meta(&self, rooms: Option<&[Room]>) {
+        if self.room_meta.read().unwrap().is_empty() {
+            return;
+        }
+        let mut metas = self.room_meta.write().unwrap();
+        metas.retain(|room, (meta, populated)| {
+            if rooms.map_or(false, |rooms| !rooms.contains(room)) {
+                return true;
+            }
+            // On adapter errors, the metadata is kept
+            if self.adapter.room_exists(room).unwrap_or(true) {
+                *populated = true;
+                true
+            } else {
+                !*populated || meta.keep_when_empty
+            }
+        });
+    }
+
+    /// Keeps the broadcast as the latest one of its key, it is sent when the interval
+    /// started by the first broadcast of the key elapses.
+    pub fn coalesce_broadcast(
+        self: &Arc<Self>,
+        key: Cow<'static, str>,
+        interval: Duration,
+        packet: Packet<'static>,
+        opts: BroadcastOptions,
+    ) {
+        let mut rooms: Vec<Room> = opts.rooms.iter().cloned().collect();
+        rooms.sort_unstable();
+        let key = (key, rooms);
+
+        let mut coalesced = self.coalesced.lock().unwrap();
+        if let Some(pending) = coalesced.get_mut(&key) {
+            *pending = (packet, opts);
+            return;
+        }
+        coalesced.insert(key.clone(), (packet, opts));
+        let sleep = self.clock.sleep(interval);
+        let ns = Arc::downgrade(self);
+        tokio::spawn(async move {
+            sleep.await;
+            let Some(ns) = ns.upgrade() else {
+                return;
+            };
+            let pending = ns.coalesced.lock().unwrap().remove(&key);
+            if let Some((packet, opts)) = pending {
+                if let Err(_e) = ns.adapter.broadcast(packet, opts) {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("coalesced broadcast error: {_e:?}");
+                }
+            }
+        });
+    }
+
     /// Removes a socket from a namespace and propagate the event to the adapter
     pub fn remove_socket(&self, sid: Sid) -> Result<(), AdapterError> {
-        self.sockets.write().unwrap().remove(&sid);
+        let socket = self.sockets.write().unwrap().remove(&sid);
+        if let Some(socket) = socket {
+            if let Some(user_id) = socket.user_id() {
+                self.remove_user_socket(&user_id, sid);
+            }
+            for tag in socket.tags() {
+                self.remove_tag(sid, &tag);
+            }
+            self.remove_observer(sid);
+        }
         self.adapter
             .del_all(sid)
-            .map_err(|err| AdapterError(Box::new(err)))
+            .map_err(|err| AdapterError(Box::new(err)))?;
+        self.rooms_left_all(sid);
+        self.try_teardown();
+        Ok(())
+    }
+
+    /// Marks a socket as being connected, the namespace is not removed until [`Namespace::end_connect`]
+    /// is called. It must be called while holding a lock of the namespace map,
+    /// so that the namespace cannot be removed concurrently.
+    pub(crate) fn begin_connect(&self) {
+        self.connecting.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Ends a connection started with [`Namespace::begin_connect`],
+    /// removing the namespace if the connection was refused and it is ephemeral.
+    pub(crate) fn end_connect(&self) {
+        self.connecting.fetch_sub(1, Ordering::SeqCst);
+        self.try_teardown();
+    }
+
+    /// Removes an ephemeral namespace from its namespace map if it has no socket left.
+    ///
+    /// The map is locked before checking the sockets so that a socket cannot start connecting in between.
+    fn try_teardown(&self) {
+        let Some(registry) = self.registry.as_ref().and_then(Weak::upgrade) else {
+            return;
+        };
+        let mut registry = registry.write().unwrap();
+        if self.connecting.load(Ordering::SeqCst) > 0 || !self.sockets.read().unwrap().is_empty() {
+            return;
+        }
+        // The namespace may have been deleted and created again with the same path
+        if matches!(registry.get(&self.path), Some(ns) if std::ptr::eq(Arc::as_ptr(ns), self)) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("removing empty ephemeral namespace {}", self.path);
+            registry.remove(&self.path);
+        }
+    }
+
+    /// Gets the member cache of a room, creating it from the adapter state if it is not tracked yet
+    pub(crate) fn room_members(&self, room: Room) -> Result<Arc<RoomMembers>, A::Error> {
+        // The adapter is read while holding the lock so that concurrent joins and leaves
+        // are applied to the cache after it is populated
+        let mut handles = self.room_handles.write().unwrap();
+        if let Some(members) = handles.get(&room).and_then(Weak::upgrade) {
+            return Ok(members);
+        }
+        handles.retain(|_, members| members.strong_count() > 0);
+        let sids = self.adapter.sockets(room.clone())?;
+        let members = Arc::new(RoomMembers::new(sids.into_iter().collect()));
+        handles.insert(room, Arc::downgrade(&members));
+        Ok(members)
+    }
+
+    /// Applies a socket joining rooms to the tracked room caches
+    pub fn rooms_joined(&self, sid: Sid, rooms: &[Room]) {
+        self.tracked_rooms(rooms).iter().for_each(|m| m.insert(sid));
+        self.sync_room_meta(Some(rooms));
+    }
+
+    /// Applies a socket leaving rooms to the tracked room caches
+    pub fn rooms_left(&self, sid: Sid, rooms: &[Room]) {
+        self.tracked_rooms(rooms).iter().for_each(|m| m.remove(sid));
+        self.sync_room_meta(Some(rooms));
+    }
+
+    /// Applies a socket leaving every room to the tracked room caches
+    pub fn rooms_left_all(&self, sid: Sid) {
+        {
+            let handles = self.room_handles.read().unwrap();
+            handles
+                .values()
+                .filter_map(Weak::upgrade)
+                .for_each(|m| m.remove(sid));
+        }
+        self.sync_room_meta(None);
+    }
+
+    /// Resyncs the tracked room caches with the adapter state,
+    /// used when the changed sockets are not known.
+    pub fn refresh_rooms(&self, rooms: &[Room]) -> Result<(), A::Error> {
+        let handles = self.room_handles.read().unwrap();
+        for room in rooms {
+            if let Some(members) = handles.get(room).and_then(Weak::upgrade) {
+                let sids = self.adapter.sockets(room.clone())?;
+                members.replace(sids.into_iter().collect());
+            }
+        }
+        drop(handles);
+        self.sync_room_meta(Some(rooms));
+        Ok(())
+    }
+
+    fn tracked_rooms(&self, rooms: &[Room]) -> Vec<Arc<RoomMembers>> {
+        let handles = self.room_handles.read().unwrap();
+        if handles.is_empty() {
+            return Vec::new();
+        }
+        rooms
+            .iter()
+            .filter_map(|room| handles.get(room).and_then(Weak::upgrade))
+            .collect()
+    }
+
+    /// Moves a socket from the `old` user id index entry to the `new` one.
+    /// It has no effect if the socket is not connected to this namespace anymore.
+    pub fn set_user(&self, sid: Sid, old: Option<&str>, new: String) {
+        if let Some(old) = old {
+            self.remove_user_socket(old, sid);
+        }
+        if self.has(sid) {
+            self.users
+                .write()
+                .unwrap()
+                .entry(new)
+                .or_default()
+                .insert(sid);
+        }
+    }
+
+    fn remove_user_socket(&self, user_id: &str, sid: Sid) {
+        let mut users = self.users.write().unwrap();
+        if let Some(sids) = users.get_mut(user_id) {
+            sids.remove(&sid);
+            if sids.is_empty() {
+                users.remove(user_id);
+            }
+        }
+    }
+
+    /// Gets all the sockets connected to this namespace with the given user id
+    pub fn get_user_sockets(&self, user_id: &str) -> Vec<Arc<Socket<A>>> {
+        let users = self.users.read().unwrap();
+        let Some(sids) = users.get(user_id) else {
+            return Vec::new();
+        };
+        let sockets = self.sockets.read().unwrap();
+        sids.iter()
+            .filter_map(|sid| sockets.get(sid).cloned())
+            .collect()
+    }
+
+    /// Makes a socket the last socket of a session, keeping the extensions saved for the session
+    fn set_session(&self, session_id: &str, sid: Sid) {
+        let mut sessions = self.sessions.write().unwrap();
+        match sessions.get_mut(session_id) {
+            Some(session) => session.sid = sid,
+            None => {
+                let session = Session {
+                    sid,
+                    #[cfg(feature = "extensions")]
+                    extensions: None,
+                    #[cfg(feature = "extensions")]
+                    user_id: None,
+                };
+                sessions.insert(session_id.to_string(), session);
+            }
+        }
+    }
+
+    /// Saves the state of the last socket of a session when it disconnects, the session is then
+    /// forgotten after the retention duration unless another socket connects with the same session id.
+    pub(crate) fn end_session(self: &Arc<Self>, socket: &Socket<A>, retention: Duration) {
+        let Some(session_id) = socket.session_id() else {
+            return;
+        };
+        match self.sessions.write().unwrap().get_mut(session_id) {
+            Some(_session) if _session.sid == socket.id => {
+                #[cfg(feature = "extensions")]
+                {
+                    _session.extensions = Some(socket.extensions.take());
+                    _session.user_id = socket.user_id();
+                }
+            }
+            // Another socket may have connected with the same session id
+            _ => return,
+        }
+        let ns = Arc::downgrade(self);
+        let (session_id, sid) = (session_id.to_string(), socket.id);
+        let expired = self.clock.sleep(retention);
+        tokio::spawn(async move {
+            expired.await;
+            if let Some(ns) = ns.upgrade() {
+                let mut sessions = ns.sessions.write().unwrap();
+                if matches!(sessions.get(&session_id), Some(s) if s.sid == sid) {
+                    sessions.remove(&session_id);
+                }
+            }
+        });
+    }
+
+    /// Gets the id of the last socket of a session
+    pub fn session_sid(&self, session_id: &str) -> Option<Sid> {
+        self.sessions.read().unwrap().get(session_id).map(|s| s.sid)
+    }
+
+    /// Takes the extensions saved when the previous socket of a session disconnected,
+    /// only if it had the same user id
+    #[cfg(feature = "extensions")]
+    pub(crate) fn take_session_extensions(
+        &self,
+        session_id: &str,
+        user_id: &str,
+    ) -> Option<crate::extensions::Extensions> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions.get_mut(session_id)?;
+        if session.user_id.as_deref() != Some(user_id) {
+            return None;
+        }
+        session.extensions.take()
+    }
+
+    /// Adds a socket to the tag index.
+    /// It has no effect if the socket is not connected to this namespace anymore.
+    pub fn add_tag(&self, sid: Sid, tag: String) {
+        if self.has(sid) {
+            self.tags
+                .write()
+                .unwrap()
+                .entry(tag)
+                .or_default()
+                .insert(sid);
+        }
+    }
+
+    /// Removes a socket from the tag index
+    pub fn remove_tag(&self, sid: Sid, tag: &str) {
+        let mut tags = self.tags.write().unwrap();
+        if let Some(sids) = tags.get_mut(tag) {
+            sids.remove(&sid);
+            if sids.is_empty() {
+                tags.remove(tag);
+            }
+        }
+    }
+
+    /// Registers a socket as an observer of the namespace traffic.
+    /// It has no effect if the socket is not connected to this namespace anymore.
+    pub fn add_observer(&self, sid: Sid, scope: ObserverScope) {
+        if self.has(sid) {
+            self.observers.write().unwrap().insert(sid, scope);
+        }
+    }
+
+    /// Unregisters an observer socket, returns `false` if it was not an observer
+    pub fn remove_observer(&self, sid: Sid) -> bool {
+        self.observers.write().unwrap().remove(&sid).is_some()
+    }
+
+    /// Sends a copy of an event packet to the observers, except those for which `skip` returns `true`.
+    /// Packets emitted to a single socket (`direct`) are only sent to the [`ObserverScope::All`] observers.
+    ///
+    /// The ack id of the copy is removed, so that the observers can't answer in place of the recipients.
+    pub fn mirror_to_observers(
+        &self,
+        packet: &Packet<'_>,
+        direct: bool,
+        skip: impl Fn(Sid) -> bool,
+    ) {
+        if !packet.inner.is_event() {
+            return;
+        }
+        let observers: Vec<Sid> = {
+            let observers = self.observers.read().unwrap();
+            if observers.is_empty() {
+                return;
+            }
+            observers
+                .iter()
+                .filter(|(sid, scope)| (!direct || **scope == ObserverScope::All) && !skip(**sid))
+                .map(|(sid, _)| *sid)
+                .collect()
+        };
+        for sid in observers {
+            let Ok(socket) = self.get_socket(sid) else {
+                continue;
+            };
+            let mut packet = packet.clone();
+            packet.inner.clear_ack_id();
+            if let Err(_e) = socket.send(packet) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("failed to mirror packet to observer {sid}: {_e:?}");
+            }
+        }
+    }
+
+    /// Gets the ids of the sockets having at least one of the given tags
+    pub fn get_tagged_sids<'t>(&self, tags: impl IntoIterator<Item = &'t str>) -> HashSet<Sid> {
+        let index = self.tags.read().unwrap();
+        tags.into_iter()
+            .filter_map(|tag| index.get(tag))
+            .flatten()
+            .copied()
+            .collect()
     }
 
     pub fn has(&self, sid: Sid) -> bool {
@@ -75,7 +667,7 @@ impl<A: Adapter> Namespace<A> {
     pub fn recv(&self, sid: Sid, packet: PacketData<'_>) -> Result<(), Error> {
         match packet {
             PacketData::Connect(_) => unreachable!("connect packets should be handled before"),
-            PacketData::ConnectError => Err(Error::InvalidPacketType),
+            PacketData::ConnectError(_) => Err(Error::InvalidPacketType),
             packet => self.get_socket(sid)?.recv(packet),
         }
     }
@@ -93,16 +685,76 @@ impl<A: Adapter> Namespace<A> {
         self.sockets.read().unwrap().values().cloned().collect()
     }
 
+    pub fn sockets_count(&self) -> usize {
+        self.sockets.read().unwrap().len()
+    }
+
+    pub fn socket_ids(&self) -> Vec<Sid> {
+        self.sockets.read().unwrap().keys().copied().collect()
+    }
+
+    /// Returns a future that resolves when every socket of this namespace has received
+    /// all its pending ack responses.
+    pub async fn pending_acks_drained(&self, recheck: Duration) {
+        let sockets = self.get_sockets();
+        futures::future::join_all(sockets.iter().map(|s| s.pending_acks_drained(recheck))).await;
+    }
+
+    /// Sends a disconnect packet to every socket of this namespace without closing them.
+    pub fn send_disconnect(&self) {
+        for socket in self.get_sockets() {
+            if let Err(_e) = socket.send(Packet::disconnect(&self.path)) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("error sending disconnect packet to {}: {:?}", socket.id, _e);
+            }
+        }
+    }
+
+    /// Closes the underlying connection of every socket of the namespace, without closing the namespace:
+    /// new sockets can still connect afterwards.
+    ///
+    /// The disconnect handlers are called with [`DisconnectReason::ClosingServer`] once the transports are closed.
+    /// Returns when all the underlying transports are closed.
+    pub async fn disconnect_all(&self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("disconnecting all sockets in namespace {}", self.path);
+        let sockets = self.get_sockets();
+        futures::future::join_all(sockets.iter().map(|s| s.close_underlying_transport())).await;
+    }
+
     /// Closes the entire namespace :
     /// * Closes the adapter
     /// * Closes all the sockets and their underlying connections
     /// * Removes all the sockets from the namespace
-    pub async fn close(&self) {
+    ///
+    /// The disconnect handlers are called according to the given [`ShutdownDisconnectPolicy`].
+    /// With [`ShutdownDisconnectPolicy::RunBounded`], the `permits` semaphore caps their concurrency.
+    pub async fn close(&self, policy: ShutdownDisconnectPolicy, permits: Option<Arc<Semaphore>>) {
         self.adapter.close().ok();
         #[cfg(feature = "tracing")]
         tracing::debug!("closing all sockets in namespace {}", self.path);
         let sockets = self.sockets.read().unwrap().clone();
-        futures::future::join_all(sockets.values().map(|s| s.close_underlying_transport())).await;
+        futures::future::join_all(sockets.values().map(|s| {
+            // With the default policy, the handler is called when the transport is closed.
+            let handler = match policy {
+                ShutdownDisconnectPolicy::Run => None,
+                _ => s.take_disconnect_handler(),
+            };
+            let permits = permits.clone();
+            async move {
+                s.close_underlying_transport().await;
+                if let (Some(handler), Some(permits)) = (handler, permits) {
+                    if let Ok(permit) = permits.acquire_owned().await {
+                        handler.call_with_permit(
+                            s.clone(),
+                            DisconnectReason::ClosingServer,
+                            permit,
+                        );
+                    }
+                }
+            }
+        }))
+        .await;
         self.sockets.write().unwrap().shrink_to_fit();
         #[cfg(feature = "tracing")]
         tracing::debug!("all sockets in namespace {} closed", self.path);
@@ -112,7 +764,7 @@ impl<A: Adapter> Namespace<A> {
 #[cfg(test)]
 impl<A: Adapter> Namespace<A> {
     pub fn new_dummy<const S: usize>(sockets: [Sid; S]) -> Arc<Self> {
-        let ns = Namespace::new(Cow::Borrowed("/"), || {});
+        let ns = Namespace::new(Cow::Borrowed("/"), || {}, &SocketIoConfig::default());
         for sid in sockets {
             ns.sockets
                 .write()
@@ -136,3 +788,33 @@ impl<A: Adapter + std::fmt::Debug> std::fmt::Debug for Namespace<A> {
             .finish()
     }
 }
+
+/// The path pattern of a dynamic namespace, e.g. `/tenant/:id`,
+/// where each `:name` segment matches any non-empty segment
+#[derive(Debug, Clone)]
+pub(crate) struct NsPattern {
+    pattern: Cow<'static, str>,
+}
+
+impl NsPattern {
+    pub fn new(pattern: Cow<'static, str>) -> Self {
+        Self { pattern }
+    }
+
+    /// Matches a namespace path against the pattern, returning the matched parameters
+    pub fn matches(&self, path: &str) -> Option<Vec<(Cow<'static, str>, String)>> {
+        let mut pattern = self.pattern.split('/');
+        let mut path = path.split('/');
+        let mut params = Vec::new();
+        loop {
+            match (pattern.next(), path.next()) {
+                (None, None) => return Some(params),
+                (Some(p), Some(s)) if p.starts_with(':') && !s.is_empty() => {
+                    params.push((Cow::Owned(p[1..].to_string()), s.to_string()));
+                }
+                (Some(p), Some(s)) if p == s => (),
+                _ => return None,
+            }
+        }
+    }
+}
@@ -6,16 +6,31 @@
 //! There is two types of operators:
 //! * [`ConfOperators`]: Chainable operators to configure the message to be sent.
 //! * [`BroadcastOperators`]: Chainable operators to select sockets to send a message to and to configure the message to be sent.
+//!
+//! ## Ordering
+//! Every packet sent to a socket, whether it is emitted directly to it or broadcast to one of its rooms,
+//! is pushed to the single outgoing queue of this socket when the emit is called.
+//! The socket therefore receives the packets in the order they were emitted on the server.
+//! The only exceptions are the emits that complete later by design, such as
+//! [`emit_after`](BroadcastOperators::emit_after) or the `async` emits that are not awaited.
 use std::borrow::Cow;
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use engineioxide::sid::Sid;
+use futures::future::Either;
+use futures::{Future, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::sync::oneshot;
 
 use crate::ack::{AckInnerStream, AckStream};
 use crate::adapter::LocalAdapter;
-use crate::errors::{BroadcastError, DisconnectError};
+use crate::errors::{BroadcastError, DisconnectError, JoinError};
 use crate::extract::SocketRef;
-use crate::socket::Socket;
+use crate::socket::{Event, Socket};
 use crate::SendError;
 use crate::{
     adapter::{Adapter, BroadcastFlags, BroadcastOptions, Room},
@@ -23,6 +38,59 @@ use crate::{
     packet::Packet,
 };
 
+/// The maximum timeout that can be set with the `timeout()` operators.
+/// Longer timeouts are clamped to this value.
+pub const MAX_ACK_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Validates a timeout set with an operator.
+///
+/// A zero timeout would make every acknowledgement immediately time out: it panics in debug builds,
+/// and is ignored in release builds (`None` is returned so that the default timeout is used).
+pub(crate) fn validate_timeout(timeout: Duration) -> Option<Duration> {
+    debug_assert!(
+        !timeout.is_zero(),
+        "ack timeout must be greater than zero, otherwise every acknowledgement would immediately time out"
+    );
+    if timeout.is_zero() {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("ack timeout of zero is ignored, using the default timeout");
+        None
+    } else if timeout > MAX_ACK_TIMEOUT {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            "ack timeout of {:?} is too long, clamping it to {:?}",
+            timeout,
+            MAX_ACK_TIMEOUT
+        );
+        Some(MAX_ACK_TIMEOUT)
+    } else {
+        Some(timeout)
+    }
+}
+
+/// Splits an array into the events of a [`BroadcastOperators::chunked`] emit,
+/// followed by the completion event.
+fn chunk_events(
+    event: Cow<'static, str>,
+    data: Value,
+    chunk_size: usize,
+) -> Result<Vec<(Cow<'static, str>, Value)>, serde_json::Error> {
+    let Value::Array(items) = data else {
+        return Err(serde::ser::Error::custom("chunked data must be an array"));
+    };
+    let batch = Sid::new().to_string();
+    let mut items = items.into_iter().peekable();
+    let mut events = Vec::new();
+    while items.peek().is_some() {
+        let chunk: Vec<Value> = items.by_ref().take(chunk_size).collect();
+        let data = serde_json::json!({ "batch": batch, "index": events.len(), "items": chunk });
+        events.push((event.clone(), data));
+    }
+    let complete = serde_json::json!({ "batch": batch, "complete": true, "chunks": events.len() });
+    events.push((event, complete));
+    Ok(events)
+}
+
 /// A trait for types that can be used as a room parameter.
 ///
 /// [`String`], [`Vec<String>`], [`Vec<&str>`], [`&'static str`](str) and const arrays are implemented by default.
@@ -105,12 +173,32 @@ impl RoomParam for Sid {
 pub struct ConfOperators<'a, A: Adapter = LocalAdapter> {
     binary: Vec<Vec<u8>>,
     timeout: Option<Duration>,
+    fallback: Option<Value>,
     socket: &'a Socket<A>,
 }
+/// A handle to a message scheduled with [`BroadcastOperators::emit_after`].
+#[derive(Debug)]
+pub struct ScheduledEmit {
+    cancel: oneshot::Sender<()>,
+}
+
+impl ScheduledEmit {
+    /// Cancels the scheduled emit.
+    ///
+    /// Returns `false` if the message was already sent or about to be sent.
+    pub fn cancel(self) -> bool {
+        self.cancel.send(()).is_ok()
+    }
+}
+
 /// Chainable operators to select sockets to send a message to and to configure the message to be sent.
 pub struct BroadcastOperators<A: Adapter = LocalAdapter> {
     binary: Vec<Vec<u8>>,
     timeout: Option<Duration>,
+    /// The key and interval of [`BroadcastOperators::coalesce`]
+    coalesce: Option<(Cow<'static, str>, Duration)>,
+    /// The number of items of each event of [`BroadcastOperators::chunked`]
+    chunk_size: Option<usize>,
     ns: Arc<Namespace<A>>,
     opts: BroadcastOptions,
 }
@@ -124,6 +212,8 @@ impl<A: Adapter> From<ConfOperators<'_, A>> for BroadcastOperators<A> {
         Self {
             binary: conf.binary,
             timeout: conf.timeout,
+            coalesce: None,
+            chunk_size: None,
             ns: conf.socket.ns.clone(),
             opts,
         }
@@ -136,6 +226,7 @@ impl<'a, A: Adapter> ConfOperators<'a, A> {
         Self {
             binary: vec![],
             timeout: None,
+            fallback: None,
             socket: sender,
         }
     }
@@ -244,6 +335,72 @@ impl<'a, A: Adapter> ConfOperators<'a, A> {
         BroadcastOperators::from(self).broadcast()
     }
 
+    /// Selects all sockets having at least one of the given tags (except the current socket).
+    ///
+    /// See [`BroadcastOperators::with_tag`] for more details.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // Only the other beta testers, with a binary payload
+    ///         socket
+    ///             .bin(vec![vec![1, 2, 3]])
+    ///             .with_tag("beta")
+    ///             .emit("test", data);
+    ///     });
+    /// });
+    pub fn with_tag(self, tags: impl RoomParam) -> BroadcastOperators<A> {
+        BroadcastOperators::from(self).with_tag(tags)
+    }
+
+    /// Selects all sockets connected at or after the given instant (except the current socket).
+    /// It is a shorthand for `broadcast().connected_after(instant)` and can be combined with the room operators.
+    ///
+    /// [`Socket::connected_at`] gives the connection instant of a socket.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // Only the sockets that connected after the current one, with a binary payload
+    ///         socket
+    ///             .bin(vec![vec![1, 2, 3]])
+    ///             .connected_after(socket.connected_at())
+    ///             .emit("test", data);
+    ///     });
+    /// });
+    pub fn connected_after(self, instant: Instant) -> BroadcastOperators<A> {
+        self.broadcast().connected_after(instant)
+    }
+
+    /// Selects all sockets connected within the given duration (except the current socket).
+    /// It is a shorthand for `broadcast().connected_within(duration)` and can be combined with the room operators.
+    ///
+    /// See [`BroadcastOperators::connected_within`] for more details.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // Only the sockets that connected in the last 10 minutes, with a binary payload
+    ///         socket
+    ///             .bin(vec![vec![1, 2, 3]])
+    ///             .connected_within(Duration::from_secs(10 * 60))
+    ///             .emit("test", data);
+    ///     });
+    /// });
+    pub fn connected_within(self, duration: Duration) -> BroadcastOperators<A> {
+        self.broadcast().connected_within(duration)
+    }
+
     /// Sets a custom timeout when sending a message with an acknowledgement.
     ///
     /// See [`SocketIoBuilder::ack_timeout`](crate::SocketIoBuilder) for the default timeout.
@@ -252,6 +409,12 @@ impl<'a, A: Adapter> ConfOperators<'a, A> {
     ///
     /// [`emit_with_ack()`]: #method.emit_with_ack
     ///
+    /// Timeouts longer than [`MAX_ACK_TIMEOUT`] are clamped to it.
+    ///
+    /// # Panics
+    /// In debug builds, if the timeout is [`Duration::ZERO`].
+    /// In release builds, a zero timeout is ignored and the default timeout is used.
+    ///
     /// # Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::*};
@@ -280,7 +443,7 @@ impl<'a, A: Adapter> ConfOperators<'a, A> {
     /// });
     ///
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = Some(timeout);
+        self.timeout = validate_timeout(timeout);
         self
     }
 
@@ -300,6 +463,34 @@ impl<'a, A: Adapter> ConfOperators<'a, A> {
         self.binary = binary;
         self
     }
+
+    /// Sends the `fallback` data instead of the message data if the client is still on the polling transport,
+    /// for messages that are too large or too binary-heavy to be sent efficiently with long-polling.
+    ///
+    /// The fallback is sent with the same event name and without the binary payloads,
+    /// so that the client can, for example, request the full data again once it has upgraded to websocket.
+    /// Websocket clients receive the message unchanged.
+    ///
+    /// The transport is checked when the message is emitted and the emit never waits for an upgrade.
+    ///
+    /// This operator only applies to the messages emitted to the current socket,
+    /// it is ignored when broadcasting.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::json;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let history = vec![json!({ "msg": "hello" }); 10_000];
+    ///     socket
+    ///         .prefer_websocket(json!({ "truncated": true }))
+    ///         .emit("history", [history])
+    ///         .ok();
+    /// });
+    pub fn prefer_websocket(mut self, fallback: Value) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
 }
 
 // ==== impl ConfOperators consume fns ====
@@ -345,8 +536,9 @@ impl<A: Adapter> ConfOperators<'_, A> {
         event: impl Into<Cow<'static, str>>,
         data: T,
     ) -> Result<(), SendError<T>> {
-        use crate::socket::PermitIteratorExt;
-        let permits = match self.socket.reserve(1 + self.binary.len()) {
+        let event = event.into();
+        let fallback = self.take_polling_fallback();
+        let permits = match self.socket.reserve_event(1 + self.binary.len(), &event) {
             Ok(permits) => permits,
             Err(e) => {
                 #[cfg(feature = "tracing")]
@@ -354,8 +546,11 @@ impl<A: Adapter> ConfOperators<'_, A> {
                 return Err(e.with_value(data).into());
             }
         };
-        let packet = self.get_packet(event, data)?;
-        permits.emit(packet);
+        let packet = match fallback {
+            Some(fallback) => self.get_packet(event, fallback)?,
+            None => self.get_packet(event, data)?,
+        };
+        self.socket.send_with_permits(packet, permits);
 
         Ok(())
     }
@@ -416,7 +611,9 @@ impl<A: Adapter> ConfOperators<'_, A> {
         event: impl Into<Cow<'static, str>>,
         data: T,
     ) -> Result<AckStream<V>, SendError<T>> {
-        let permits = match self.socket.reserve(1 + self.binary.len()) {
+        let event = event.into();
+        let fallback = self.take_polling_fallback();
+        let permits = match self.socket.reserve_event(1 + self.binary.len(), &event) {
             Ok(permits) => permits,
             Err(e) => {
                 #[cfg(feature = "tracing")]
@@ -424,11 +621,19 @@ impl<A: Adapter> ConfOperators<'_, A> {
                 return Err(e.with_value(data).into());
             }
         };
-        let timeout = self.timeout.unwrap_or(self.socket.config.ack_timeout);
-        let packet = self.get_packet(event, data)?;
-        let rx = self.socket.send_with_ack_permit(packet, permits);
-        let stream = AckInnerStream::send(rx, timeout, self.socket.id);
-        Ok(AckStream::<V>::from(stream))
+        let timeout = self.timeout.unwrap_or_else(|| self.socket.ack_timeout());
+        let packet = match fallback {
+            Some(fallback) => self.get_packet(event, fallback)?,
+            None => self.get_packet(event, data)?,
+        };
+        let (rx, ack_id) = self.socket.send_with_ack_permit(packet, permits);
+        let stream = AckInnerStream::send(
+            rx,
+            timeout,
+            self.socket.id,
+            self.socket.config.clock.as_ref(),
+        );
+        Ok(AckStream::<V>::from(stream).with_ack_id(ack_id))
     }
 
     /// Makes all sockets selected with the previous operators join the given room(s).
@@ -443,7 +648,7 @@ impl<A: Adapter> ConfOperators<'_, A> {
     ///     socket.within("room1").within("room3").join(["room4", "room5"]).unwrap();
     ///   });
     /// });
-    pub fn join(self, rooms: impl RoomParam) -> Result<(), A::Error> {
+    pub fn join(self, rooms: impl RoomParam) -> Result<(), JoinError<A::Error>> {
         self.socket.join(rooms)
     }
 
@@ -468,6 +673,18 @@ impl<A: Adapter> ConfOperators<'_, A> {
         self.socket.rooms()
     }
 
+    /// Gets the fallback data to send if the client is on the polling transport.
+    /// The binary payloads are dropped in this case.
+    fn take_polling_fallback(&mut self) -> Option<Value> {
+        let fallback = self.fallback.take()?;
+        if self.socket.transport_type() == crate::TransportType::Polling {
+            self.binary.clear();
+            Some(fallback)
+        } else {
+            None
+        }
+    }
+
     /// Creates a packet with the given event and data.
     fn get_packet(
         &mut self,
@@ -491,6 +708,8 @@ impl<A: Adapter> BroadcastOperators<A> {
         Self {
             binary: vec![],
             timeout: None,
+            coalesce: None,
+            chunk_size: None,
             ns,
             opts: BroadcastOptions::default(),
         }
@@ -499,6 +718,8 @@ impl<A: Adapter> BroadcastOperators<A> {
         Self {
             binary: vec![],
             timeout: None,
+            coalesce: None,
+            chunk_size: None,
             ns,
             opts: BroadcastOptions {
                 sid: Some(sid),
@@ -581,6 +802,46 @@ impl<A: Adapter> BroadcastOperators<A> {
         self.broadcast()
     }
 
+    /// Filters out the given socket from the sockets selected with the previous operators.
+    ///
+    /// It is useful outside of a message handler to reproduce the "broadcast except the sender" behavior
+    /// of the operators built from a socket.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Sid};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// // Later in your code, with the id of the socket that triggered an update
+    /// # let sender = Sid::new();
+    /// io.of("/").unwrap().to("room1").exclude(sender).emit("update", "data");
+    /// ```
+    pub fn exclude(mut self, sid: Sid) -> Self {
+        self.opts.except_sids.insert(sid);
+        self.broadcast()
+    }
+
+    /// Selects all sockets having at least one of the given tags, see [`Socket::add_tag`].
+    /// It can be combined with the room operators, the selected sockets are then the union of both.
+    ///
+    /// Tags are only indexed on the current node, so this operator only reaches local sockets
+    /// even when using an adapter spanning multiple nodes.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.add_tag("beta");
+    /// });
+    ///
+    /// // Later in your code
+    /// io.of("/").unwrap().with_tag("beta").emit("feature", "new-ui");
+    /// ```
+    pub fn with_tag(mut self, tags: impl RoomParam) -> Self {
+        self.opts.tags.extend(tags.into_room_iter());
+        self.broadcast()
+    }
+
     /// Broadcasts to all sockets only connected on this node (when using multiple nodes).
     /// When using the default in-memory adapter, this operator is a no-op.
     /// #### Example
@@ -616,6 +877,61 @@ impl<A: Adapter> BroadcastOperators<A> {
         self
     }
 
+    /// Only keeps the selected sockets that connected at or after the given instant.
+    /// It filters the selection of the other operators without changing it: the current socket is
+    /// only excluded by the [`to()`](#method.to) or [`broadcast()`](#method.broadcast) operators.
+    ///
+    /// When called multiple times, the latest instant is kept.
+    /// [`Socket::connected_at`] gives the connection instant of a socket.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // Only the sockets of room1 that connected after the current one
+    ///         socket
+    ///             .to("room1")
+    ///             .connected_after(socket.connected_at())
+    ///             .emit("test", data);
+    ///     });
+    /// });
+    pub fn connected_after(mut self, instant: Instant) -> Self {
+        let instant = self
+            .opts
+            .connected_after
+            .map_or(instant, |i| i.max(instant));
+        self.opts.connected_after = Some(instant);
+        self
+    }
+
+    /// Only keeps the selected sockets that connected within the given duration,
+    /// see [`connected_after()`](#method.connected_after).
+    ///
+    /// The window is computed with the configured [`Clock`](crate::clock::Clock) when this operator is called,
+    /// the sockets are then filtered when the message is emitted.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {});
+    ///
+    /// // Roll out a feature to the sockets that connected in the last 10 minutes
+    /// io.of("/")
+    ///     .unwrap()
+    ///     .connected_within(Duration::from_secs(10 * 60))
+    ///     .emit("feature", "new-ui");
+    /// ```
+    pub fn connected_within(self, duration: Duration) -> Self {
+        match self.ns.clock.now().checked_sub(duration) {
+            Some(instant) => self.connected_after(instant),
+            // The window starts before the clock origin, no socket can be filtered out
+            None => self,
+        }
+    }
+
     /// Sets a custom timeout when sending a message with an acknowledgement.
     ///
     /// See [`SocketIoBuilder::ack_timeout`](crate::SocketIoBuilder) for the default timeout.
@@ -624,6 +940,12 @@ impl<A: Adapter> BroadcastOperators<A> {
     ///
     /// [`emit_with_ack()`]: #method.emit_with_ack
     ///
+    /// Timeouts longer than [`MAX_ACK_TIMEOUT`] are clamped to it.
+    ///
+    /// # Panics
+    /// In debug builds, if the timeout is [`Duration::ZERO`].
+    /// In release builds, a zero timeout is ignored and the default timeout is used.
+    ///
     /// # Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::*};
@@ -652,7 +974,7 @@ impl<A: Adapter> BroadcastOperators<A> {
     /// });
     ///
     pub fn timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = Some(timeout);
+        self.timeout = validate_timeout(timeout);
         self
     }
 
@@ -672,6 +994,106 @@ impl<A: Adapter> BroadcastOperators<A> {
         self.binary = binary;
         self
     }
+
+    /// Coalesces the messages emitted with [`emit()`](#method.emit) with the same `key`
+    /// to the same rooms: only the latest message emitted during `interval` is sent,
+    /// at the end of the interval. The interval starts with the first message emitted
+    /// after the previous flush and is measured with the configured [`Clock`](crate::clock::Clock).
+    ///
+    /// It is useful for rapidly changing values such as live counters,
+    /// where the clients only need the last state.
+    ///
+    /// The selected sockets are resolved when the message is sent, with the operators of the latest message.
+    /// Errors happening when the message is sent are only logged.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("click", |socket: SocketRef, Data::<u64>(count)| {
+    ///         // At most one update every 100ms for the sockets of the room
+    ///         socket
+    ///             .within("game-1")
+    ///             .coalesce("counter", Duration::from_millis(100))
+    ///             .emit("counter", count)
+    ///             .ok();
+    ///     });
+    /// });
+    pub fn coalesce(mut self, key: impl Into<Cow<'static, str>>, interval: Duration) -> Self {
+        self.coalesce = Some((key.into(), interval));
+        self
+    }
+
+    /// Splits the array emitted with [`emit()`](#method.emit) into sequential events of at most
+    /// `chunk_size` items, so that the clients can process a large payload incrementally.
+    ///
+    /// The data must serialize to an array, its items are split instead of being sent as multiple arguments.
+    /// All the events share the event name and a random batch id, the clients reassemble them as follows:
+    /// * Each chunk is sent as `{ "batch": "<id>", "index": <n>, "items": [...] }`,
+    ///   with `index` starting at 0 and incremented for each chunk.
+    /// * Once all the chunks are sent, a final `{ "batch": "<id>", "complete": true, "chunks": <count> }`
+    ///   event marks the end of the batch. An empty array is sent as this event only.
+    ///
+    /// The chunks are sent like with [`emit_many()`](#method.emit_many): each selected socket receives
+    /// them in order and they are attached the binary payloads set with [`bin()`](#method.bin).
+    /// A chunked emit is never [coalesced](#method.coalesce).
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let history: Vec<u32> = (0..100_000).collect();
+    ///     // 100 "history" events of 1000 items followed by the completion event
+    ///     socket.within(socket.id).chunked(1000).emit("history", history).ok();
+    /// });
+    pub fn chunked(mut self, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be greater than zero");
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Limits to `max` the number of acknowledgements awaited at the same time by
+    /// [`emit_with_ack()`](#method.emit_with_ack).
+    ///
+    /// The message is sent to the first `max` sockets right away, and to the next ones as the
+    /// [`AckStream`] yields the previous acknowledgements. The [`timeout()`](#method.timeout)
+    /// of each socket starts when the message is sent to it. The remaining sockets are not sent
+    /// the message if the [`AckStream`] is dropped before the end.
+    ///
+    /// By default, the message is sent to all the sockets at once.
+    ///
+    /// # Panics
+    /// If `max` is zero.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use futures::stream::StreamExt;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("survey", |socket: SocketRef| async move {
+    ///         // Ask every socket, with at most 100 answers awaited at the same time
+    ///         socket.broadcast()
+    ///             .max_in_flight(100)
+    ///             .emit_with_ack::<Value>("survey", "how are you?")
+    ///             .unwrap()
+    ///             .for_each(|(id, ack)| async move {
+    ///                 println!("Ack of socket {}: {:?}", id, ack);
+    ///             }).await;
+    ///     });
+    /// });
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        assert!(
+            max > 0,
+            "max in flight acknowledgements must be greater than zero"
+        );
+        self.opts.max_in_flight = Some(max);
+        self
+    }
 }
 
 // ==== impl BroadcastOperators consume fns ====
@@ -719,7 +1141,15 @@ impl<A: Adapter> BroadcastOperators<A> {
         event: impl Into<Cow<'static, str>>,
         data: T,
     ) -> Result<(), BroadcastError> {
+        if let Some(chunk_size) = self.chunk_size {
+            let events = chunk_events(event.into(), serde_json::to_value(data)?, chunk_size)?;
+            return self.emit_many(events);
+        }
         let packet = self.get_packet(event, data)?;
+        if let Some((key, interval)) = self.coalesce.take() {
+            self.ns.coalesce_broadcast(key, interval, packet, self.opts);
+            return Ok(());
+        }
         if let Err(e) = self.ns.adapter.broadcast(packet, self.opts) {
             #[cfg(feature = "tracing")]
             tracing::debug!("broadcast error: {e:?}");
@@ -728,6 +1158,295 @@ impl<A: Adapter> BroadcastOperators<A> {
         Ok(())
     }
 
+    /// Emits a typed event to all sockets selected with the previous operators,
+    /// the event name is the [`Event::NAME`] of the payload type.
+    ///
+    /// The errors are the same as the [`emit()`](#method.emit) ones.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Event};
+    /// #[derive(serde::Serialize)]
+    /// struct Joined(String);
+    /// impl Event for Joined {
+    ///     const NAME: &'static str = "room:joined";
+    /// }
+    ///
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("lobby").ok();
+    ///     socket.to("lobby").emit_typed(Joined(socket.id.to_string())).ok();
+    /// });
+    #[inline]
+    pub fn emit_typed<E: Event>(self, payload: E) -> Result<(), BroadcastError> {
+        self.emit(E::NAME, payload)
+    }
+
+    /// Emits a message to the socket with the given id in this namespace,
+    /// without knowing its rooms. The rooms selected with the previous operators are ignored,
+    /// the binary payloads set with [`bin()`](#method.bin) are sent.
+    ///
+    /// ## Errors
+    /// * If no socket with this id is connected to the namespace, a [`SendError::SocketGone`] is returned.
+    /// * Otherwise the errors are the same as the [`Socket::emit()`] ones.
+    ///
+    /// [`SendError::SocketGone`]: crate::SendError::SocketGone
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Sid};
+    /// # use std::str::FromStr;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/chat", |socket: SocketRef| {});
+    ///
+    /// // The id is read from an external store
+    /// let sid = Sid::from_str("AAAAAAAAAAAAAAAA").unwrap();
+    /// if let Err(err) = io.of("/chat").unwrap().emit_to_sid(sid, "dm", "hello") {
+    ///     println!("cannot reach {sid}: {err}");
+    /// }
+    /// ```
+    pub fn emit_to_sid<T: serde::Serialize>(
+        mut self,
+        sid: Sid,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        let Ok(socket) = self.ns.get_socket(sid) else {
+            return Err(SendError::SocketGone(sid));
+        };
+        let event = event.into();
+        let permits = match socket.reserve_event(1 + self.binary.len(), &event) {
+            Ok(permits) => permits,
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("sending error during emit message: {e:?}");
+                return Err(e.with_value(data).into());
+            }
+        };
+        let packet = self.get_packet(event, data)?;
+        socket.send_with_permits(packet, permits);
+        Ok(())
+    }
+
+    /// Emits several events to all sockets selected with the previous operators.
+    ///
+    /// The sockets are resolved once, with the [`Adapter::fetch_sockets`] method of this server,
+    /// and every event is then sent to this fixed set: a socket joining a selected room in the meantime
+    /// receives none of the events, and each socket receives them in order.
+    /// The binary payloads set with [`bin()`](#method.bin) are attached to every event.
+    ///
+    /// ## Errors
+    /// All the events are sent before returning, the sockets that could not receive one of them
+    /// are reported in a [`BroadcastError::Socket`]. When fetching the sockets,
+    /// a [`BroadcastError::Adapter`] may also be returned.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::json;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("score", |socket: SocketRef, Data::<u32>(score)| {
+    ///         socket.within("game").emit_many([
+    ///             ("score", json!(score)),
+    ///             ("leaderboard:dirty", json!(true)),
+    ///         ]).ok();
+    ///     });
+    /// });
+    /// ```
+    pub fn emit_many<E: Into<Cow<'static, str>>>(
+        self,
+        events: impl IntoIterator<Item = (E, Value)>,
+    ) -> Result<(), BroadcastError> {
+        let sender = self.opts.sid;
+        let sockets = self
+            .ns
+            .adapter
+            .fetch_sockets(self.opts)
+            .map_err(|e| BroadcastError::Adapter(e.into()))?;
+
+        let mut errors = Vec::new();
+        for (event, data) in events {
+            let (ns, event) = (self.ns.path.clone(), event.into());
+            let packet = if self.binary.is_empty() {
+                Packet::event(ns, event, data)
+            } else {
+                Packet::bin_event(ns, event, data, self.binary.clone())
+            };
+            self.ns.mirror_to_observers(&packet, false, |sid| {
+                sender == Some(sid) || sockets.iter().any(|s| s.id == sid)
+            });
+            errors.extend(sockets.iter().filter_map(|s| s.send(packet.clone()).err()));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("broadcast error: {errors:?}");
+            Err(errors.into())
+        }
+    }
+
+    /// Emits a message to all sockets selected with the previous operators, like [`emit()`](#method.emit),
+    /// but yields to the scheduler after every chunk of sockets so that broadcasting to a very large room
+    /// doesn't starve the other tasks. The chunk size is set with [`SocketIoBuilder::broadcast_chunk_size`].
+    ///
+    /// The sockets are the ones of this server returned by the [`Adapter::fetch_sockets`] method
+    /// when the broadcast starts.
+    ///
+    /// ## Errors
+    /// The same errors as [`emit()`](#method.emit) are returned, once the message was sent to every socket.
+    /// When fetching the sockets, a [`BroadcastError::Adapter`] may also be returned.
+    ///
+    /// [`SocketIoBuilder::broadcast_chunk_size`]: crate::SocketIoBuilder#method.broadcast_chunk_size
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef| async move {
+    ///         socket.within("lobby").emit_chunked("news", "hello").await.ok();
+    ///     });
+    /// });
+    pub async fn emit_chunked<T: serde::Serialize>(
+        mut self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), BroadcastError> {
+        let packet = self.get_packet(event, data)?;
+        let sender = self.opts.sid;
+        let sockets = self
+            .ns
+            .adapter
+            .fetch_sockets(self.opts)
+            .map_err(|e| BroadcastError::Adapter(e.into()))?;
+        let Some(chunk_size) = sockets.first().map(|s| s.config.broadcast_chunk_size) else {
+            return Ok(());
+        };
+        self.ns.mirror_to_observers(&packet, false, |sid| {
+            sender == Some(sid) || sockets.iter().any(|s| s.id == sid)
+        });
+
+        let mut errors = Vec::new();
+        for (i, chunk) in sockets.chunks(chunk_size.max(1)).enumerate() {
+            if i > 0 {
+                tokio::task::yield_now().await;
+            }
+            errors.extend(chunk.iter().filter_map(|s| s.send(packet.clone()).err()));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("broadcast error: {errors:?}");
+            Err(errors.into())
+        }
+    }
+
+    /// Emits a message to all sockets selected with the previous operators, like [`emit()`](#method.emit),
+    /// but the data is serialized on the blocking thread pool with [`tokio::task::spawn_blocking`]
+    /// so that serializing a very large payload doesn't add latency to the other tasks.
+    ///
+    /// It is only worth it for large payloads, small ones are cheaper to serialize inline.
+    ///
+    /// ## Ordering
+    /// The message is only enqueued once the serialization completes. Messages emitted on the same sockets
+    /// in the meantime, for example with [`emit()`](#method.emit), may therefore be sent before this one.
+    /// Await this method before emitting the next message if the order matters.
+    ///
+    /// ## Errors
+    /// The same errors as [`emit()`](#method.emit) are returned.
+    /// If the serialization panics, the panic is propagated.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("snapshot", |socket: SocketRef| async move {
+    ///         let snapshot = vec![0u32; 1_000_000];
+    ///         socket.within("viewers").emit_blocking("snapshot", snapshot).await.ok();
+    ///     });
+    /// });
+    pub async fn emit_blocking<T: serde::Serialize + Send + 'static>(
+        mut self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), BroadcastError> {
+        let event = event.into();
+        let data = match tokio::task::spawn_blocking(move || serde_json::to_value(data)).await {
+            Ok(data) => data?,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        };
+        // The data is already serialized, it must not be walked again on the async worker
+        let packet = self.value_packet(event, data);
+        let res = self.ns.adapter.broadcast(packet, self.opts);
+        #[cfg(feature = "tracing")]
+        if let Err(e) = &res {
+            tracing::debug!("broadcast error: {e:?}");
+        }
+        res
+    }
+
+    /// Schedules a message to be emitted to the sockets selected with the previous operators
+    /// once `delay` has elapsed, according to the configured [`Clock`](crate::clock::Clock).
+    ///
+    /// The selected sockets are resolved when the message is sent, not when it is scheduled:
+    /// a socket joining a selected room in the meantime will receive it, one leaving it will not.
+    ///
+    /// The returned [`ScheduledEmit`] can be used to cancel the emit.
+    /// Dropping it does **not** cancel the emit.
+    ///
+    /// ## Errors
+    /// When encoding the data into JSON a [`BroadcastError::Serialize`] is **immediately** returned.
+    /// Errors happening when the message is sent are only logged.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("ready", |socket: SocketRef| async move {
+    ///         socket.within("game-1").emit("announce", "game starts in 30s").ok();
+    ///         let start = socket
+    ///             .within("game-1")
+    ///             .emit_after(Duration::from_secs(30), "start", ())
+    ///             .unwrap();
+    ///         // Later, if the game is aborted:
+    ///         start.cancel();
+    ///     });
+    /// });
+    pub fn emit_after<T: serde::Serialize>(
+        mut self,
+        delay: Duration,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<ScheduledEmit, BroadcastError> {
+        let packet = self.get_packet(event, data)?;
+        let (tx, mut rx) = oneshot::channel();
+        let sleep = self.ns.clock.sleep(delay);
+        tokio::spawn(async move {
+            match futures::future::select(&mut rx, sleep).await {
+                Either::Left((Ok(()), _)) => return,
+                // The handle was dropped without cancelling
+                Either::Left((Err(_), sleep)) => sleep.await,
+                Either::Right(_) => {
+                    // Prevent cancellation once the emit is decided
+                    rx.close();
+                    if rx.try_recv().is_ok() {
+                        return;
+                    }
+                }
+            }
+            if let Err(_e) = self.ns.adapter.broadcast(packet, self.opts) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("scheduled broadcast error: {_e:?}");
+            }
+        });
+        Ok(ScheduledEmit { cancel: tx })
+    }
+
     /// Emits a message to all sockets selected with the previous operators and
     /// waits for the acknowledgement(s).
     ///
@@ -800,6 +1519,100 @@ impl<A: Adapter> BroadcastOperators<A> {
         Ok(stream)
     }
 
+    /// Emits a message to all sockets selected with the previous operators and aggregates their acknowledgements
+    /// with the `reduce` function, starting from `init`, as they are received.
+    ///
+    /// The returned future resolves once every socket answered or the [`timeout()`](#method.timeout) elapsed,
+    /// with the reduced value and the ids of the sockets whose acknowledgement is missing:
+    /// the ones that timed out, but also the ones that disconnected or answered data that is not deserializable as `V`.
+    ///
+    /// If the packet encoding failed a [`serde_json::Error`] is **immediately** returned.
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("start-vote", |socket: SocketRef| async move {
+    ///         let (yes, missing) = socket.to("players")
+    ///             .emit_with_ack_reduce::<bool, usize>("vote", "continue?", 0, |yes, (_, vote)| {
+    ///                 yes + vote as usize
+    ///             })
+    ///             .unwrap()
+    ///             .await;
+    ///         println!("{yes} players voted yes, {} did not vote", missing.len());
+    ///     });
+    /// });
+    pub fn emit_with_ack_reduce<V: DeserializeOwned, R>(
+        self,
+        event: impl Into<Cow<'static, str>>,
+        data: impl serde::Serialize,
+        init: R,
+        mut reduce: impl FnMut(R, (Sid, V)) -> R,
+    ) -> Result<impl Future<Output = (R, Vec<Sid>)>, serde_json::Error> {
+        let stream = self.emit_with_ack::<V>(event, data)?;
+        let reduced = stream.fold((init, Vec::new()), move |(acc, mut missing), (sid, ack)| {
+            let acc = match ack {
+                Ok(ack) => reduce(acc, (sid, ack.data)),
+                Err(_) => {
+                    missing.push(sid);
+                    acc
+                }
+            };
+            futures::future::ready((acc, missing))
+        });
+        Ok(reduced)
+    }
+
+    /// Emits a message to all sockets selected with the previous operators and waits for their acknowledgements,
+    /// like [`emit_with_ack()`](#method.emit_with_ack), except that the data is computed for each recipient
+    /// by calling `data` with its socket id when the message is sent.
+    ///
+    /// The sockets are the ones of this server returned by the [`Adapter::fetch_sockets`] method.
+    /// Any binary payload set with [`bin()`](#method.bin) is sent to every socket.
+    ///
+    /// If the data of a socket cannot be serialized, nothing is sent to it and the [`AckStream`]
+    /// yields an [`AckError::Serde`] for this socket only.
+    ///
+    /// [`AckError::Serde`]: crate::AckError::Serde
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use futures::stream::StreamExt;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef| async move {
+    ///         // Send its own id to each socket of the room1 room
+    ///         let ack_stream = socket.to("room1")
+    ///             .emit_with_ack_each::<_, String>("whoami", |sid| sid.to_string())
+    ///             .unwrap();
+    ///
+    ///         ack_stream.for_each(|(id, ack)| async move {
+    ///             println!("Ack of socket {}: {:?}", id, ack);
+    ///         }).await;
+    ///     });
+    /// });
+    pub fn emit_with_ack_each<T: serde::Serialize, V>(
+        self,
+        event: impl Into<Cow<'static, str>>,
+        data: impl Fn(Sid) -> T,
+    ) -> Result<AckStream<V>, A::Error> {
+        let sockets = self.ns.adapter.fetch_sockets(self.opts)?;
+        let (ns, event) = (self.ns.path.clone(), event.into());
+        let binary = self.binary;
+        let stream = AckInnerStream::broadcast_each(sockets, self.timeout, |sid| {
+            let data = serde_json::to_value(data(sid))?;
+            Ok(if binary.is_empty() {
+                Packet::event(ns.clone(), event.clone(), data)
+            } else {
+                Packet::bin_event(ns.clone(), event.clone(), data, binary.clone())
+            })
+        });
+        Ok(stream.into())
+    }
+
     /// Gets all sockets selected with the previous operators.
     ///
     /// It can be used to retrieve any extension data (with the `extensions` feature enabled) from the sockets or to make some sockets join other rooms.
@@ -839,6 +1652,10 @@ impl<A: Adapter> BroadcastOperators<A> {
 
     /// Makes all sockets selected with the previous operators join the given room(s).
     ///
+    /// The join guard set with [`SocketIo::on_room_join_request`](crate::SocketIo#method.on_room_join_request)
+    /// and the capacity of the rooms set in their [`RoomMeta`](crate::room::RoomMeta) are not checked:
+    /// the rooms can exceed their capacity.
+    ///
     /// ### Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::*};
@@ -850,7 +1667,9 @@ impl<A: Adapter> BroadcastOperators<A> {
     ///   });
     /// });
     pub fn join(self, rooms: impl RoomParam) -> Result<(), A::Error> {
-        self.ns.adapter.add_sockets(self.opts, rooms)
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        self.ns.adapter.add_sockets(self.opts, rooms.clone())?;
+        self.ns.refresh_rooms(&rooms)
     }
 
     /// Makes all sockets selected with the previous operators leave the given room(s).
@@ -866,7 +1685,9 @@ impl<A: Adapter> BroadcastOperators<A> {
     ///   });
     /// });
     pub fn leave(self, rooms: impl RoomParam) -> Result<(), A::Error> {
-        self.ns.adapter.del_sockets(self.opts, rooms)
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        self.ns.adapter.del_sockets(self.opts, rooms.clone())?;
+        self.ns.refresh_rooms(&rooms)
     }
 
     /// Gets all room names for a given namespace
@@ -885,14 +1706,130 @@ impl<A: Adapter> BroadcastOperators<A> {
         event: impl Into<Cow<'static, str>>,
         data: impl serde::Serialize,
     ) -> Result<Packet<'static>, serde_json::Error> {
-        let ns = self.ns.path.clone();
         let data = serde_json::to_value(data)?;
-        let packet = if self.binary.is_empty() {
-            Packet::event(ns, event.into(), data)
+        Ok(self.value_packet(event.into(), data))
+    }
+
+    /// Creates a packet with the given event and already serialized data.
+    fn value_packet(&mut self, event: Cow<'static, str>, data: Value) -> Packet<'static> {
+        let ns = self.ns.path.clone();
+        if self.binary.is_empty() {
+            Packet::event(ns, event, data)
         } else {
             let binary = std::mem::take(&mut self.binary);
-            Packet::bin_event(ns, event.into(), data, binary)
-        };
-        Ok(packet)
+            Packet::bin_event(ns, event, data, binary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_socket() -> Arc<Socket<LocalAdapter>> {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        Socket::new_dummy(sid, ns).into()
+    }
+
+    #[tokio::test]
+    async fn exclude_with_sender() {
+        let sids = [Sid::new(), Sid::new(), Sid::new()];
+        let ns = Namespace::<LocalAdapter>::new_dummy(sids);
+
+        // The sender and the excluded socket are both filtered out
+        let sockets = BroadcastOperators::from_sock(ns.clone(), sids[0])
+            .exclude(sids[1])
+            .sockets()
+            .unwrap();
+        assert_eq!(sockets.len(), 1);
+        assert_eq!(sockets[0].id, sids[2]);
+    }
+
+    #[tokio::test]
+    async fn emit_chunked_yields() {
+        // The dummy sockets can hold 200 packets
+        const MAX_PACKETS: usize = 200;
+        let sids: [Sid; 600] = std::array::from_fn(|_| Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy(sids);
+
+        let yielded = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        tokio::spawn({
+            let yielded = yielded.clone();
+            async move { yielded.store(true, std::sync::atomic::Ordering::SeqCst) }
+        });
+        BroadcastOperators::new(ns.clone())
+            .broadcast()
+            .emit_chunked("test", "foo")
+            .await
+            .unwrap();
+        assert!(yielded.load(std::sync::atomic::Ordering::SeqCst));
+
+        // Every socket received exactly one packet
+        for sid in sids {
+            let socket = ns.get_socket(sid).unwrap();
+            assert!(socket.reserve(MAX_PACKETS - 1).is_ok());
+            assert!(socket.reserve(MAX_PACKETS).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn emit_blocking_off_thread() {
+        struct Large(std::sync::mpsc::Sender<std::thread::ThreadId>);
+        impl serde::Serialize for Large {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                self.0.send(std::thread::current().id()).unwrap();
+                s.serialize_str(&"a".repeat(1 << 20))
+            }
+        }
+
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let (tx, rx) = std::sync::mpsc::channel();
+        BroadcastOperators::new(ns.clone())
+            .broadcast()
+            .emit_blocking("test", Large(tx))
+            .await
+            .unwrap();
+        assert_ne!(rx.recv().unwrap(), std::thread::current().id());
+
+        // The packet was enqueued to the socket
+        let socket = ns.get_socket(sid).unwrap();
+        assert!(socket.reserve(200).is_err());
+        assert!(socket.reserve(199).is_ok());
+    }
+
+    #[test]
+    fn timeout_accepted() {
+        let socket = create_socket();
+        let op = ConfOperators::new(&socket).timeout(Duration::from_secs(1));
+        assert_eq!(op.timeout, Some(Duration::from_secs(1)));
+
+        let op = BroadcastOperators::from_sock(socket.ns.clone(), socket.id)
+            .timeout(Duration::from_millis(10));
+        assert_eq!(op.timeout, Some(Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn timeout_clamped() {
+        let socket = create_socket();
+        let op = ConfOperators::new(&socket).timeout(Duration::MAX);
+        assert_eq!(op.timeout, Some(MAX_ACK_TIMEOUT));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "ack timeout must be greater than zero")]
+    fn conf_timeout_zero() {
+        let socket = create_socket();
+        let _ = ConfOperators::new(&socket).timeout(Duration::ZERO);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "ack timeout must be greater than zero")]
+    fn broadcast_timeout_zero() {
+        let socket = create_socket();
+        let _ = BroadcastOperators::from_sock(socket.ns.clone(), socket.id).timeout(Duration::ZERO);
     }
 }
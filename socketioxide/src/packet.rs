@@ -7,7 +7,7 @@ use crate::ProtocolVersion;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use crate::errors::Error;
+use crate::errors::{Error, PacketDecodeError};
 use engineioxide::sid::Sid;
 
 /// The socket.io packet type.
@@ -69,11 +69,24 @@ impl<'a> Packet<'a> {
 }
 
 impl<'a> Packet<'a> {
+    /// Converts the packet into an owned one, so that it can be stored
+    pub(crate) fn into_owned(self) -> Packet<'static> {
+        Packet {
+            inner: self.inner.into_owned(),
+            ns: Cow::Owned(self.ns.into_owned()),
+        }
+    }
+
     /// Create a connect error packet for the given namespace
     pub fn invalid_namespace(ns: &'a str) -> Self {
+        Self::connect_error(ns, "Invalid namespace")
+    }
+
+    /// Create a connect error packet for the given namespace with the given message
+    pub fn connect_error(ns: impl Into<Cow<'a, str>>, message: impl Into<String>) -> Self {
         Self {
-            inner: PacketData::ConnectError,
-            ns: Cow::Borrowed(ns),
+            inner: PacketData::ConnectError(message.into()),
+            ns: ns.into(),
         }
     }
 
@@ -154,7 +167,8 @@ impl<'a> Packet<'a> {
                     + ACK_PUNCTUATION_SIZE
                     + BINARY_PUNCTUATION_SIZE
             }
-            ConnectError => 31,
+            // {"message":"..."}
+            ConnectError(message) => message.len() + 14,
         };
 
         let nsp_size = if self.ns == "/" {
@@ -187,8 +201,8 @@ pub enum PacketData<'a> {
     Event(Cow<'a, str>, Value, Option<i64>),
     /// Event ack packet, to acknowledge an event
     EventAck(Value, i64),
-    /// Connect error packet, sent when the namespace is invalid
-    ConnectError,
+    /// Connect error packet with a message, sent when the namespace is invalid or the connection is refused
+    ConnectError(String),
     /// Binary event packet with optional ack id, to request an ack from the other side
     BinaryEvent(Cow<'a, str>, BinaryPacket, Option<i64>),
     /// Binary ack packet, to acknowledge an event with binary data
@@ -203,7 +217,20 @@ pub struct BinaryPacket {
     /// Binary payload
     pub bin: Vec<Vec<u8>>,
     /// The number of expected payloads (used when receiving data)
-    payload_count: usize,
+    pub(crate) payload_count: usize,
+    /// Whether the outgoing data was `null`, see [`SocketIoConfig::omit_null_binary_data`](crate::SocketIoConfig::omit_null_binary_data)
+    pub(crate) null_data: bool,
+}
+
+fn strip_null_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_null_fields);
+        }
+        Value::Array(arr) => arr.iter_mut().for_each(strip_null_fields),
+        _ => (),
+    }
 }
 
 impl<'a> PacketData<'a> {
@@ -213,7 +240,7 @@ impl<'a> PacketData<'a> {
             PacketData::Disconnect => '1',
             PacketData::Event(_, _, _) => '2',
             PacketData::EventAck(_, _) => '3',
-            PacketData::ConnectError => '4',
+            PacketData::ConnectError(_) => '4',
             PacketData::BinaryEvent(_, _, _) => '5',
             PacketData::BinaryAck(_, _) => '6',
         }
@@ -230,6 +257,98 @@ impl<'a> PacketData<'a> {
         };
     }
 
+    /// Gets the data of a non-binary ack packet or of a non-binary event packet requesting an ack
+    pub(crate) fn ack_data_mut(&mut self) -> Option<&mut Value> {
+        match self {
+            PacketData::EventAck(data, _) | PacketData::Event(_, data, Some(_)) => Some(data),
+            _ => None,
+        }
+    }
+
+    /// Converts the packet data into an owned one, that does not borrow the event name
+    pub(crate) fn into_owned(self) -> PacketData<'static> {
+        match self {
+            PacketData::Connect(data) => PacketData::Connect(data),
+            PacketData::Disconnect => PacketData::Disconnect,
+            PacketData::Event(e, data, ack) => {
+                PacketData::Event(Cow::Owned(e.into_owned()), data, ack)
+            }
+            PacketData::EventAck(data, ack) => PacketData::EventAck(data, ack),
+            PacketData::ConnectError(msg) => PacketData::ConnectError(msg),
+            PacketData::BinaryEvent(e, packet, ack) => {
+                PacketData::BinaryEvent(Cow::Owned(e.into_owned()), packet, ack)
+            }
+            PacketData::BinaryAck(packet, ack) => PacketData::BinaryAck(packet, ack),
+        }
+    }
+
+    /// Removes the ack id of an event packet, so that the other side doesn't answer it
+    pub(crate) fn clear_ack_id(&mut self) {
+        if let PacketData::Event(_, _, ack) | PacketData::BinaryEvent(_, _, ack) = self {
+            *ack = None;
+        }
+    }
+
+    /// Append a `{ "_seq": seq }` argument to the packet data
+    /// It will only be set for event packets
+    pub(crate) fn set_seq(&mut self, seq: u64) {
+        let data = match self {
+            PacketData::Event(_, data, _) => data,
+            PacketData::BinaryEvent(_, BinaryPacket { data, .. }, _) => data,
+            _ => return,
+        };
+        let seq = json!({ "_seq": seq });
+        match data {
+            Value::Array(v) => v.push(seq),
+            d => *d = Value::Array(vec![d.take(), seq]),
+        }
+    }
+
+    /// Removes the `null` fields of every object of the packet data, recursively.
+    /// Array elements are left untouched so that the arguments keep their position.
+    /// It will only have an effect on event and ack packets.
+    pub(crate) fn strip_null_fields(&mut self) {
+        let data = match self {
+            PacketData::Event(_, data, _) | PacketData::EventAck(data, _) => data,
+            PacketData::BinaryEvent(_, BinaryPacket { data, .. }, _)
+            | PacketData::BinaryAck(BinaryPacket { data, .. }, _) => data,
+            _ => return,
+        };
+        strip_null_fields(data);
+    }
+
+    /// Removes the `null` data of an outgoing binary event or ack, so that only the placeholders
+    /// of the binary payloads are sent, like the binary-only events of the socket.io client.
+    pub(crate) fn omit_null_binary_data(&mut self) {
+        match self {
+            PacketData::BinaryEvent(_, packet, _) | PacketData::BinaryAck(packet, _)
+                if packet.null_data =>
+            {
+                if let Some(data) = packet.data.as_array_mut() {
+                    data.remove(0);
+                }
+                packet.null_data = false;
+            }
+            _ => (),
+        }
+    }
+
+    /// Check if the packet is an event packet (either binary or not)
+    pub(crate) fn is_event(&self) -> bool {
+        matches!(
+            self,
+            PacketData::Event(_, _, _) | PacketData::BinaryEvent(_, _, _)
+        )
+    }
+
+    /// Gets the event name of an event packet (either binary or not)
+    pub(crate) fn event_name(&self) -> Option<&str> {
+        match self {
+            PacketData::Event(e, _, _) | PacketData::BinaryEvent(e, _, _) => Some(e),
+            _ => None,
+        }
+    }
+
     /// Check if the packet is a binary packet (either binary event or binary ack)
     pub(crate) fn is_binary(&self) -> bool {
         matches!(
@@ -245,6 +364,14 @@ impl<'a> PacketData<'a> {
             _ => 0,
         }
     }
+
+    /// Get the number of binary payloads attached to an outgoing packet or 0 if it is not a binary packet
+    pub(crate) fn bin_count(&self) -> usize {
+        match self {
+            PacketData::BinaryEvent(_, bin, _) | PacketData::BinaryAck(bin, _) => bin.bin.len(),
+            _ => 0,
+        }
+    }
 }
 
 impl BinaryPacket {
@@ -274,11 +401,13 @@ impl BinaryPacket {
             data,
             bin: Vec::new(),
             payload_count,
+            null_data: false,
         }
     }
 
     /// Create a binary packet from outgoing data and a payload
     pub fn outgoing(data: Value, bin: Vec<Vec<u8>>) -> Self {
+        let null_data = data.is_null();
         let mut data = match data {
             Value::Array(v) => Value::Array(v),
             d => Value::Array(vec![d]),
@@ -294,6 +423,7 @@ impl BinaryPacket {
             data,
             bin,
             payload_count,
+            null_data,
         }
     }
 
@@ -377,7 +507,12 @@ impl<'a> From<Packet<'a>> for String {
                 res.push_str(itoa_buf.format(ack));
                 res.push_str(&data.unwrap())
             }
-            PacketData::ConnectError => res.push_str("{\"message\":\"Invalid namespace\"}"),
+            PacketData::ConnectError(message) => {
+                let message = serde_json::to_string(&message).unwrap();
+                res.push_str("{\"message\":");
+                res.push_str(&message);
+                res.push('}');
+            }
             PacketData::BinaryEvent(_, bin, ack) => {
                 res.push_str(itoa_buf.format(bin.payload_count));
                 res.push('-');
@@ -438,35 +573,75 @@ fn deserialize_packet<T: DeserializeOwned>(data: &str) -> Result<Option<T>, serd
     Ok(packet)
 }
 
-/// Deserialize a packet from a string
-/// The string should be in the format of:
-/// ```text
-/// <packet type>[<# of binary attachments>-][<namespace>,][<acknowledgment id>][JSON-stringified payload without binary]
-/// + binary attachments extracted
-/// ```
-impl<'a> TryFrom<String> for Packet<'a> {
-    type Error = Error;
+/// Counts the binary placeholders left in the data, at any depth
+fn count_placeholders(data: &Value) -> usize {
+    match data {
+        Value::Array(v) => v.iter().map(count_placeholders).sum(),
+        Value::Object(o) if o.contains_key("_placeholder") => 1,
+        Value::Object(o) => o.values().map(count_placeholders).sum(),
+        _ => 0,
+    }
+}
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
+/// Checks that the binary packet placeholders match the attachment count of the packet header.
+/// The header count is the one used to know when all the payloads are received.
+fn check_attachments(
+    mut packet: BinaryPacket,
+    announced: usize,
+    protocol: ProtocolVersion,
+) -> Result<BinaryPacket, PacketDecodeError> {
+    let found = packet.payload_count + count_placeholders(&packet.data);
+    if found != announced {
+        return Err(PacketDecodeError::AttachmentMismatch {
+            protocol,
+            announced,
+            found,
+        });
+    }
+    packet.payload_count = announced;
+    Ok(packet)
+}
+
+impl<'a> Packet<'a> {
+    /// Decodes a packet received from a client with the framing of the given protocol version.
+    ///
+    /// The string should be in the format of:
+    /// ```text
+    /// <packet type>[<# of binary attachments>-][<namespace>,][<acknowledgment id>][JSON-stringified payload without binary]
+    /// + binary attachments extracted
+    /// ```
+    /// With the protocol V4, the connect packet namespace may end with the query of the client
+    /// (e.g. `0/admin?token=1,`), it is stripped. The connect payload only exists since the protocol V5.
+    pub fn decode(value: String, protocol: ProtocolVersion) -> Result<Self, Error> {
         // It is possible to parse the packet from a byte slice because separators are only ASCII
         let chars = value.as_bytes();
         let mut i = 1;
-        let index = (b'0'..=b'6')
-            .contains(&chars[0])
-            .then_some(chars[0])
+        let index = chars
+            .first()
+            .filter(|c| (b'0'..=b'6').contains(c))
+            .copied()
             .ok_or(Error::InvalidPacketType)?;
 
-        // Move the cursor to skip the payload count if it is a binary packet
-        if index == b'5' || index == b'6' {
-            while chars.get(i) != Some(&b'-') {
+        // Parse the attachment count if it is a binary packet
+        let attachments = if index == b'5' || index == b'6' {
+            while chars.get(i).map_or(false, u8::is_ascii_digit) {
                 i += 1;
             }
+            if chars.get(i) != Some(&b'-') {
+                return Err(PacketDecodeError::InvalidAttachmentCount(protocol).into());
+            }
+            let count = value[1..i]
+                .parse()
+                .map_err(|_| PacketDecodeError::InvalidAttachmentCount(protocol))?;
             i += 1;
-        }
+            count
+        } else {
+            0
+        };
 
         let start_index = i;
         // Custom nsps will start with a slash
-        let ns = if chars.get(i) == Some(&b'/') {
+        let mut ns = if chars.get(i) == Some(&b'/') {
             loop {
                 match chars.get(i) {
                     Some(b',') => {
@@ -485,6 +660,11 @@ impl<'a> TryFrom<String> for Packet<'a> {
         } else {
             Cow::Borrowed("/")
         };
+        if protocol == ProtocolVersion::V4 && index == b'0' {
+            if let Some((path, _query)) = ns.split_once('?') {
+                ns = Cow::Owned(path.to_string());
+            }
+        }
 
         let start_index = i;
         let ack: Option<i64> = loop {
@@ -497,6 +677,14 @@ impl<'a> TryFrom<String> for Packet<'a> {
 
         let data = &value[i..];
         let inner = match index {
+            b'0' if protocol == ProtocolVersion::V4 && !data.is_empty() => {
+                return Err(PacketDecodeError::ProtocolMismatch {
+                    protocol,
+                    expected: ProtocolVersion::V5,
+                    packet: "connect",
+                }
+                .into())
+            }
             b'0' => PacketData::Connect((!data.is_empty()).then(|| data.to_string())),
             b'1' => PacketData::Disconnect,
             b'2' => {
@@ -509,14 +697,15 @@ impl<'a> TryFrom<String> for Packet<'a> {
             }
             b'5' => {
                 let (event, payload) = deserialize_event_packet(data)?;
-                PacketData::BinaryEvent(event.into(), BinaryPacket::incoming(payload), ack)
+                let packet = BinaryPacket::incoming(payload);
+                let packet = check_attachments(packet, attachments, protocol)?;
+                PacketData::BinaryEvent(event.into(), packet, ack)
             }
             b'6' => {
                 let packet = deserialize_packet(data)?.ok_or(Error::InvalidPacketType)?;
-                PacketData::BinaryAck(
-                    BinaryPacket::incoming(packet),
-                    ack.ok_or(Error::InvalidPacketType)?,
-                )
+                let packet =
+                    check_attachments(BinaryPacket::incoming(packet), attachments, protocol)?;
+                PacketData::BinaryAck(packet, ack.ok_or(Error::InvalidPacketType)?)
             }
             _ => return Err(Error::InvalidPacketType),
         };
@@ -525,6 +714,15 @@ impl<'a> TryFrom<String> for Packet<'a> {
     }
 }
 
+/// Deserialize a packet from a string with the framing of the protocol V5, see [`Packet::decode`].
+impl<'a> TryFrom<String> for Packet<'a> {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Packet::decode(value, ProtocolVersion::V5)
+    }
+}
+
 /// Connect packet sent by the client
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectPacket {
@@ -669,6 +867,34 @@ mod test {
         assert_eq!(packet, payload);
     }
 
+    #[test]
+    fn packet_encode_event_with_seq() {
+        let payload = format!("2{}", json!(["event", { "data": "value™" }, { "_seq": 1 }]));
+        let mut packet = Packet::event("/", "event", json!({ "data": "value™" }));
+        packet.inner.set_seq(1);
+        let packet: String = packet.into();
+        assert_eq!(packet, payload);
+
+        let payload = format!("2{}", json!(["event", 1, 2, { "_seq": 2 }]));
+        let mut packet = Packet::event("/", "event", json!([1, 2]));
+        packet.inner.set_seq(2);
+        let packet: String = packet.into();
+        assert_eq!(packet, payload);
+
+        let json = json!(["event", "data", { "_placeholder": true, "num": 0 }, { "_seq": 3 }]);
+        let payload = format!("51-{}", json);
+        let mut packet = Packet::bin_event("/", "event", json!("data"), vec![vec![1]]);
+        packet.inner.set_seq(3);
+        let packet: String = packet.into();
+        assert_eq!(packet, payload);
+
+        // Acks are not stamped
+        let mut packet = Packet::ack("/", json!("data"), 54);
+        packet.inner.set_seq(4);
+        let packet: String = packet.into();
+        assert_eq!(packet, "354[\"data\"]");
+    }
+
     // EventAck(Value, i64),
     #[test]
     fn packet_decode_event_ack() {
@@ -705,6 +931,10 @@ mod test {
         let payload = format!("4/admin™,{}", json!({ "message": "Invalid namespace" }));
         let packet: String = Packet::invalid_namespace("/admin™").try_into().unwrap();
         assert_eq!(packet, payload);
+
+        let payload = format!("4{}", json!({ "message": "not \"authorized\"" }));
+        let packet: String = Packet::connect_error("/", "not \"authorized\"").into();
+        assert_eq!(packet, payload);
     }
 
     // BinaryEvent(String, BinaryPacket, Option<i64>),
@@ -755,6 +985,39 @@ mod test {
         assert_eq!(packet, payload);
     }
 
+    #[test]
+    fn packet_encode_binary_null_event() {
+        // The null data is kept by default
+        let payload =
+            r#"52-["event",null,{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}]"#;
+        let packet = Packet::bin_event("/", "event", Value::Null, vec![vec![1], vec![2]]);
+        assert_eq!(String::from(packet), payload);
+
+        let payload = r#"61-54[null,{"_placeholder":true,"num":0}]"#;
+        let packet = Packet::bin_ack("/", Value::Null, vec![vec![1]], 54);
+        assert_eq!(String::from(packet), payload);
+    }
+
+    #[test]
+    fn packet_encode_binary_only_event() {
+        let payload = r#"52-["event",{"_placeholder":true,"num":0},{"_placeholder":true,"num":1}]"#;
+        let mut packet = Packet::bin_event("/", "event", Value::Null, vec![vec![1], vec![2]]);
+        packet.inner.omit_null_binary_data();
+        assert!(matches!(packet.inner, PacketData::BinaryEvent(..)));
+        assert_eq!(String::from(packet), payload);
+
+        let payload = r#"61-54[{"_placeholder":true,"num":0}]"#;
+        let mut packet = Packet::bin_ack("/", Value::Null, vec![vec![1]], 54);
+        packet.inner.omit_null_binary_data();
+        assert_eq!(String::from(packet), payload);
+
+        // Only a null data is omitted, not a null argument
+        let payload = r#"51-["event",null,"data",{"_placeholder":true,"num":0}]"#;
+        let mut packet = Packet::bin_event("/", "event", json!([null, "data"]), vec![vec![1]]);
+        packet.inner.omit_null_binary_data();
+        assert_eq!(String::from(packet), payload);
+    }
+
     #[test]
     fn packet_decode_binary_event() {
         let json = json!(["event", { "data": "value™" }, { "_placeholder": true, "num": 0}]);
@@ -765,6 +1028,7 @@ mod test {
                     bin: vec![vec![1]],
                     data: json!([{"data": "value™"}]),
                     payload_count: 1,
+                    null_data: false,
                 },
                 ack,
             ),
@@ -841,6 +1105,7 @@ mod test {
                     bin: vec![vec![1]],
                     data: json!([{"data": "value™"}]),
                     payload_count: 1,
+                    null_data: false,
                 },
                 ack,
             ),
@@ -867,6 +1132,78 @@ mod test {
         assert_eq!(packet, comparison_packet(54, "/admin™"));
     }
 
+    #[test]
+    fn packet_decode_binary_protocols() {
+        let decode_err = |payload: &str, protocol| match Packet::decode(payload.into(), protocol) {
+            Err(Error::Decode(e)) => e,
+            res => panic!("unexpected decode result: {res:?}"),
+        };
+        for protocol in [ProtocolVersion::V4, ProtocolVersion::V5] {
+            // Nested placeholders are counted with the attachment header
+            let json = json!(["event", { "file": { "_placeholder": true, "num": 0 } }]);
+            let packet = Packet::decode(format!("51-{}", json), protocol).unwrap();
+            assert_eq!(packet.inner.payload_count(), 1);
+
+            let json =
+                json!([{ "_placeholder": true, "num": 0 }, { "_placeholder": true, "num": 1 }]);
+            let packet = Packet::decode(format!("62-/admin,12{}", json), protocol).unwrap();
+            assert_eq!(packet.ns, "/admin");
+            assert!(matches!(packet.inner, PacketData::BinaryAck(_, 12)));
+            assert_eq!(packet.inner.payload_count(), 2);
+
+            // A binary packet without attachments is complete as soon as it is decoded
+            let packet = Packet::decode(r#"50-["event",1]"#.into(), protocol).unwrap();
+            assert_eq!(packet.inner.payload_count(), 0);
+
+            let json = json!(["event", { "_placeholder": true, "num": 0 }]);
+            assert_eq!(
+                decode_err(&format!("52-{}", json), protocol),
+                PacketDecodeError::AttachmentMismatch {
+                    protocol,
+                    announced: 2,
+                    found: 1
+                }
+            );
+            assert_eq!(
+                decode_err(&format!("5{}", json), protocol),
+                PacketDecodeError::InvalidAttachmentCount(protocol)
+            );
+            assert_eq!(
+                decode_err(&format!("6-12{}", json), protocol),
+                PacketDecodeError::InvalidAttachmentCount(protocol)
+            );
+        }
+    }
+
+    #[test]
+    fn packet_decode_connect_v4() {
+        let comparison_packet = Packet {
+            inner: PacketData::Connect(None),
+            ns: "/admin".into(),
+        };
+        let packet = Packet::decode("0/admin?token=123,".into(), ProtocolVersion::V4).unwrap();
+        assert_eq!(packet, comparison_packet);
+        let packet = Packet::decode("0/admin,".into(), ProtocolVersion::V4).unwrap();
+        assert_eq!(packet, comparison_packet);
+
+        // The connect payload only exists with the protocol V5
+        let payload = r#"0/admin,{"token":"123"}"#;
+        let packet = Packet::decode(payload.into(), ProtocolVersion::V5).unwrap();
+        assert_eq!(
+            packet.inner,
+            PacketData::Connect(Some(r#"{"token":"123"}"#.into()))
+        );
+        let err = Packet::decode(payload.into(), ProtocolVersion::V4).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Decode(PacketDecodeError::ProtocolMismatch {
+                protocol: ProtocolVersion::V4,
+                expected: ProtocolVersion::V5,
+                packet: "connect"
+            })
+        ));
+    }
+
     #[test]
     fn packet_size_hint() {
         let sid = Sid::new();
@@ -912,4 +1249,34 @@ mod test {
         let packet = Packet::bin_ack("/", json!("data"), vec![vec![1]], 54);
         assert_eq!(packet.get_size_hint(), 5);
     }
+
+    #[test]
+    fn packet_strip_null_fields() {
+        let data =
+            json!([{ "a": null, "b": { "c": null, "d": 1 }, "e": [null, { "f": null }] }, null]);
+        let mut packet = Packet::event("/", "event", data.clone());
+        packet.inner.strip_null_fields();
+        match packet.inner {
+            PacketData::Event(_, data, _) => {
+                assert_eq!(data, json!([{ "b": { "d": 1 }, "e": [null, {}] }, null]))
+            }
+            _ => panic!("unexpected packet"),
+        }
+
+        let mut packet = Packet::bin_ack("/", data, vec![vec![1]], 54);
+        packet.inner.strip_null_fields();
+        match packet.inner {
+            PacketData::BinaryAck(bin, _) => {
+                assert_eq!(
+                    bin.data,
+                    json!([
+                        { "b": { "d": 1 }, "e": [null, {}] },
+                        null,
+                        { "_placeholder": true, "num": 0 }
+                    ])
+                )
+            }
+            _ => panic!("unexpected packet"),
+        }
+    }
 }
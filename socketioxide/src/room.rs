@@ -0,0 +1,323 @@
+//! A [`RoomHandle`] gives a cached view of the members of a room.
+//!
+//! It is obtained with [`SocketIo::room`](crate::SocketIo#method.room) and avoids resolving the room
+//! membership through the [`Adapter`] each time it is needed.
+//!
+//! ## Staleness
+//! The member set is fetched from the [`Adapter`] when the first handle of a room is created.
+//! It is then updated synchronously, right after the adapter, for every change made on this server:
+//! * [`Socket::join`], [`Socket::leave`] and [`Socket::leave_all`],
+//! * the `join` and `leave` operators,
+//! * socket disconnections.
+//!
+//! Changes made by other server nodes through a distributed adapter are not observed,
+//! use [`RoomHandle::refresh`] to resync the set with the adapter.
+//!
+//! [`Socket::join`]: crate::socket::Socket#method.join
+//! [`Socket::leave`]: crate::socket::Socket#method.leave
+//! [`Socket::leave_all`]: crate::socket::Socket#method.leave_all
+//!
+//! ## Metadata
+//! A [`RoomMeta`] can be attached to a room with [`SocketIo::set_room_meta`](crate::SocketIo#method.set_room_meta).
+//! It is stored on this server only and is not shared through the [`Adapter`].
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use engineioxide::sid::Sid;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    adapter::{Adapter, LocalAdapter, Room},
+    ns::Namespace,
+    packet::Packet,
+    BroadcastError,
+};
+
+/// Metadata attached to a room with [`SocketIo::set_room_meta`](crate::SocketIo#method.set_room_meta).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoomMeta {
+    /// The instant at which the metadata was first attached to the room, according to the configured
+    /// [`Clock`](crate::clock::Clock). It is set when the metadata is attached if it is `None`.
+    pub created_at: Option<Instant>,
+    /// The socket owning the room
+    pub owner: Option<Sid>,
+    /// The maximum number of sockets in the room. Once it is reached,
+    /// [`Socket::join`](crate::socket::Socket#method.join), [`Socket::try_join`](crate::socket::Socket#method.try_join)
+    /// and [`Socket::set_rooms`](crate::socket::Socket#method.set_rooms) fail with a [`JoinError::Full`](crate::JoinError::Full).
+    ///
+    /// It is not enforced by the server side joins of many sockets at once:
+    /// [`SocketIo::join_all`](crate::SocketIo#method.join_all) and the `join` of the
+    /// [`BroadcastOperators`](crate::operators::BroadcastOperators) can exceed it.
+    pub capacity: Option<usize>,
+    /// Keeps the metadata when the last socket leaves the room, by default it is removed
+    pub keep_when_empty: bool,
+    /// Custom key-values
+    pub data: HashMap<String, Value>,
+}
+
+/// Cached members of a room, shared by every [`RoomHandle`] of the same room.
+///
+/// The set is copied on write so that [`RoomHandle::members`] can hand out snapshots for free.
+#[derive(Debug, Default)]
+pub(crate) struct RoomMembers(RwLock<Arc<HashSet<Sid>>>);
+
+impl RoomMembers {
+    pub fn new(members: HashSet<Sid>) -> Self {
+        Self(RwLock::new(Arc::new(members)))
+    }
+
+    pub fn insert(&self, sid: Sid) {
+        Arc::make_mut(&mut self.0.write().unwrap()).insert(sid);
+    }
+
+    pub fn remove(&self, sid: Sid) {
+        let mut members = self.0.write().unwrap();
+        if members.contains(&sid) {
+            Arc::make_mut(&mut members).remove(&sid);
+        }
+    }
+
+    pub fn replace(&self, members: HashSet<Sid>) {
+        *self.0.write().unwrap() = Arc::new(members);
+    }
+
+    pub fn get(&self) -> Arc<HashSet<Sid>> {
+        self.0.read().unwrap().clone()
+    }
+}
+
+/// A handle to a room of a namespace, caching its member set.
+///
+/// See the [module level documentation](self) for the staleness guarantees.
+pub struct RoomHandle<A: Adapter = LocalAdapter> {
+    ns: Arc<Namespace<A>>,
+    room: Room,
+    members: Arc<RoomMembers>,
+}
+
+impl<A: Adapter> RoomHandle<A> {
+    pub(crate) fn new(ns: Arc<Namespace<A>>, room: Room) -> Result<Self, A::Error> {
+        let members = ns.room_members(room.clone())?;
+        Ok(Self { ns, room, members })
+    }
+
+    /// Gets the name of the room
+    pub fn name(&self) -> &Room {
+        &self.room
+    }
+
+    /// Gets a snapshot of the members of the room.
+    ///
+    /// The snapshot is not updated afterwards, call this method again to get the current members.
+    pub fn members(&self) -> Arc<HashSet<Sid>> {
+        self.members.get()
+    }
+
+    /// Gets the number of members of the room
+    pub fn size(&self) -> usize {
+        self.members.get().len()
+    }
+
+    /// Checks if the given socket is a member of the room
+    pub fn contains(&self, sid: Sid) -> bool {
+        self.members.get().contains(&sid)
+    }
+
+    /// Emits a message to every cached member of the room connected to this server.
+    ///
+    /// **Note**: it does not go through the [`Adapter`], to broadcast to the room on every server node
+    /// use [`SocketIo::to`](crate::SocketIo#method.to).
+    pub fn emit<T: Serialize>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), BroadcastError> {
+        let members = self.members.get();
+        if members.is_empty() {
+            return Ok(());
+        }
+        let packet = Packet::event(
+            self.ns.path.clone(),
+            event.into(),
+            serde_json::to_value(data)?,
+        );
+        self.ns
+            .mirror_to_observers(&packet, false, |sid| members.contains(&sid));
+        let errors: Vec<_> = members
+            .iter()
+            .filter_map(|sid| self.ns.get_socket(*sid).ok())
+            .filter_map(|socket| socket.send(packet.clone()).err())
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+
+    /// Resyncs the cached member set with the [`Adapter`].
+    pub fn refresh(&self) -> Result<(), A::Error> {
+        self.ns.refresh_rooms(std::slice::from_ref(&self.room))
+    }
+}
+
+impl<A: Adapter> Clone for RoomHandle<A> {
+    fn clone(&self) -> Self {
+        Self {
+            ns: self.ns.clone(),
+            room: self.room.clone(),
+            members: self.members.clone(),
+        }
+    }
+}
+
+impl<A: Adapter> Debug for RoomHandle<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RoomHandle")
+            .field("ns", &self.ns.path)
+            .field("room", &self.room)
+            .field("members", &self.members.get())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::operators::BroadcastOperators;
+
+    fn adapter_members(ns: &Namespace<LocalAdapter>, room: &'static str) -> HashSet<Sid> {
+        ns.adapter.sockets(room).unwrap().into_iter().collect()
+    }
+
+    #[tokio::test]
+    async fn room_handle_initial_members() {
+        let (sid1, sid2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2]);
+        ns.get_socket(sid1).unwrap().join("lobby").unwrap();
+
+        let handle = RoomHandle::new(ns.clone(), "lobby".into()).unwrap();
+        assert_eq!(handle.size(), 1);
+        assert!(handle.contains(sid1));
+        assert_eq!(*handle.members(), adapter_members(&ns, "lobby"));
+    }
+
+    #[tokio::test]
+    async fn room_handle_join_leave() {
+        let (sid1, sid2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2]);
+        let handle = RoomHandle::new(ns.clone(), "lobby".into()).unwrap();
+        let snapshot = handle.members();
+
+        let socket1 = ns.get_socket(sid1).unwrap();
+        let socket2 = ns.get_socket(sid2).unwrap();
+        socket1.join(["lobby", "other"]).unwrap();
+        socket2.join("lobby").unwrap();
+        assert_eq!(handle.size(), 2);
+        assert_eq!(*handle.members(), adapter_members(&ns, "lobby"));
+        assert!(snapshot.is_empty());
+
+        socket1.leave("lobby").unwrap();
+        assert_eq!(*handle.members(), HashSet::from([sid2]));
+        socket2.leave_all().unwrap();
+        assert_eq!(handle.size(), 0);
+        assert_eq!(*handle.members(), adapter_members(&ns, "lobby"));
+    }
+
+    #[tokio::test]
+    async fn room_handle_operators_and_disconnect() {
+        let (sid1, sid2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2]);
+        let handle = RoomHandle::new(ns.clone(), "lobby".into()).unwrap();
+        let handle2 = RoomHandle::new(ns.clone(), "lobby".into()).unwrap();
+
+        BroadcastOperators::new(ns.clone())
+            .broadcast()
+            .join("lobby")
+            .unwrap();
+        assert_eq!(*handle.members(), HashSet::from([sid1, sid2]));
+        assert_eq!(*handle2.members(), adapter_members(&ns, "lobby"));
+
+        ns.remove_socket(sid1).unwrap();
+        assert_eq!(*handle.members(), HashSet::from([sid2]));
+
+        BroadcastOperators::new(ns.clone())
+            .broadcast()
+            .leave("lobby")
+            .unwrap();
+        assert_eq!(handle.size(), 0);
+        assert_eq!(*handle.members(), adapter_members(&ns, "lobby"));
+    }
+
+    #[tokio::test]
+    async fn room_handle_refresh() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let handle = RoomHandle::new(ns.clone(), "lobby".into()).unwrap();
+
+        // Simulate a change made by another node by bypassing the socket
+        ns.adapter.add_all(sid, "lobby").unwrap();
+        assert_eq!(handle.size(), 0);
+        handle.refresh().unwrap();
+        assert_eq!(*handle.members(), HashSet::from([sid]));
+    }
+
+    #[tokio::test]
+    async fn room_meta_capacity() {
+        use crate::JoinError;
+        let (sid1, sid2, sid3) = (Sid::new(), Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2, sid3]);
+        let meta = RoomMeta {
+            owner: Some(sid1),
+            capacity: Some(2),
+            data: HashMap::from([("map".to_string(), Value::from("desert"))]),
+            ..Default::default()
+        };
+        ns.set_room_meta("game".into(), meta).unwrap();
+
+        let stored = ns.room_meta(&"game".into()).unwrap();
+        assert!(stored.created_at.is_some());
+        assert_eq!(stored.owner, Some(sid1));
+        assert_eq!(stored.data["map"], "desert");
+
+        let [s1, s2, s3] = [sid1, sid2, sid3].map(|sid| ns.get_socket(sid).unwrap());
+        s1.join("game").unwrap();
+        s2.join("game").unwrap();
+        let err = s3.join(["other", "game"]).unwrap_err();
+        assert!(matches!(err, JoinError::Full(room) if room == "game"));
+        // None of the rooms are joined
+        assert!(s3.rooms().unwrap().is_empty());
+        // A member can join again
+        s2.join("game").unwrap();
+
+        s2.leave("game").unwrap();
+        s3.join("game").unwrap();
+    }
+
+    #[tokio::test]
+    async fn room_meta_cleanup() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        let kept = RoomMeta {
+            keep_when_empty: true,
+            ..Default::default()
+        };
+        ns.set_room_meta("a".into(), RoomMeta::default()).unwrap();
+        ns.set_room_meta("b".into(), kept).unwrap();
+        ns.set_room_meta("c".into(), RoomMeta::default()).unwrap();
+
+        socket.join(["a", "b"]).unwrap();
+        socket.leave_all().unwrap();
+        assert!(ns.room_meta(&"a".into()).is_none());
+        assert!(ns.room_meta(&"b".into()).is_some());
+        // The room never populated keeps its metadata
+        assert!(ns.room_meta(&"c".into()).is_some());
+    }
+}
@@ -56,7 +56,6 @@ use tower::Service as TowerSvc;
 use crate::{
     adapter::{Adapter, LocalAdapter},
     client::Client,
-    SocketIoConfig,
 };
 
 /// A [`Tower`](TowerSvc)/[`Hyper`](HyperSvc) Service that wraps [`EngineIoService`] and
@@ -116,17 +115,6 @@ impl<A: Adapter, S: Clone> SocketIoService<S, A> {
         self.engine_svc.into_make_service()
     }
 
-    /// Creates a new [`EngineIoService`] with a custom inner service and a custom config.
-    pub(crate) fn with_config_inner(
-        inner: S,
-        config: Arc<SocketIoConfig>,
-    ) -> (Self, Arc<Client<A>>) {
-        let engine_config = config.engine_config.clone();
-        let client = Arc::new(Client::new(config));
-        let svc = EngineIoService::with_config_inner(inner, client.clone(), engine_config);
-        (Self { engine_svc: svc }, client)
-    }
-
     /// Creates a new [`EngineIoService`] with a custom inner service and an existing client
     /// It is mainly used with a [`SocketIoLayer`](crate::layer::SocketIoLayer) that owns the client
     pub(crate) fn with_client(inner: S, client: Arc<Client<A>>) -> Self {
@@ -2,20 +2,27 @@
 //! The socket struct itself should not be used directly, but through a [`SocketRef`](crate::extract::SocketRef).
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     fmt::Debug,
     sync::Mutex,
     sync::{
-        atomic::{AtomicI64, Ordering},
+        atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
         Arc, RwLock,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use engineioxide::socket::{DisconnectReason as EIoDisconnectReason, Permit, PermitIterator};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
 use serde_json::Value;
-use tokio::sync::oneshot::{self, Receiver};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot::{self, Receiver},
+    Notify, Semaphore,
+};
 
 #[cfg(feature = "extensions")]
 use crate::extensions::Extensions;
@@ -23,15 +30,18 @@ use crate::extensions::Extensions;
 use crate::{
     ack::{AckInnerStream, AckResponse, AckResult, AckStream},
     adapter::{Adapter, LocalAdapter, Room},
-    errors::{DisconnectError, Error, SendError},
+    errors::{
+        DisconnectError, Error, JoinError, KickError, PacketDecodeError, SendError, TryEmitError,
+    },
     handler::{
-        BoxedDisconnectHandler, BoxedMessageHandler, DisconnectHandler, MakeErasedHandler,
-        MessageHandler,
+        extract::{with_current_event, AckId, DeferredAck, TraceParent},
+        BoxedDisconnectHandler, BoxedMessageHandler, DisconnectHandler, HandlerId, HandlerOpts,
+        LimitedMessageHandler, MakeErasedHandler, MessageHandler,
     },
     ns::Namespace,
     operators::{BroadcastOperators, ConfOperators, RoomParam},
     packet::{BinaryPacket, Packet, PacketData},
-    AckError, SocketIoConfig,
+    AckError, AppKeepalive, BroadcastError, EventOrdering, RelayError, SocketIoConfig,
 };
 use crate::{
     client::SocketData,
@@ -40,6 +50,47 @@ use crate::{
 
 pub use engineioxide::sid::Sid;
 
+/// The reserved event emitted by a client to request a replay of the events it missed,
+/// see [`SocketIoBuilder::enable_replay`](crate::SocketIoBuilder#method.enable_replay).
+pub const REPLAY_EVENT: &str = "_replay";
+
+/// The reserved event sent to the clients to check their liveness,
+/// see [`SocketIoBuilder::app_keepalive`](crate::SocketIoBuilder#method.app_keepalive).
+pub const KEEPALIVE_EVENT: &str = "_ping";
+
+/// The reserved event carrying the payload sent to the client by [`Socket::disconnect_with`],
+/// right before the disconnect packet.
+pub const DISCONNECT_PAYLOAD_EVENT: &str = "_disconnect";
+
+/// The field of the auth payload carrying the session id of the client, see [`Socket::session_id`].
+pub const SESSION_ID_AUTH_KEY: &str = "sessionId";
+
+/// The maximum length in bytes of a session id, the longer ones are ignored, see [`Socket::session_id`].
+pub const MAX_SESSION_ID_LEN: usize = 128;
+
+/// The number of events buffered by the stream returned by [`Socket::events`].
+pub const EVENT_STREAM_CAPACITY: usize = 256;
+
+/// The number of events of a socket waiting to be processed with [`EventOrdering::Fifo`].
+pub const EVENT_QUEUE_CAPACITY: usize = 256;
+
+/// An event received from the client, yielded by [`Socket::events`]:
+/// the event name, its arguments, its binary payloads and its ack id.
+pub type InboundEvent = (String, Value, Vec<Vec<u8>>, AckId);
+
+/// An event received from the client without message handler, yielded by [`Socket::event_stream`].
+#[derive(Debug)]
+pub struct IncomingEvent<A: Adapter = LocalAdapter> {
+    /// The name of the event
+    pub event: String,
+    /// The arguments of the event, with the binary placeholders removed
+    pub data: Value,
+    /// The binary payloads of the event
+    pub bin: Vec<Vec<u8>>,
+    /// The handle to send the ack response, if the client expects one
+    pub ack: Option<DeferredAck<A>>,
+}
+
 /// All the possible reasons for a [`Socket`] to be disconnected from a namespace.
 ///
 /// It can be used as an extractor in the [`on_disconnect`](crate::handler::disconnect) handler.
@@ -66,8 +117,308 @@ pub enum DisconnectReason {
     /// The socket was forcefully disconnected from the namespace with [`Socket::disconnect`]
     ServerNSDisconnect,
 
+    /// The socket was evicted by the adapter with [`Socket::adapter_disconnect`],
+    /// for example when a distributed adapter receives a forced logout from another node
+    ServerAdapterClose,
+
     /// The server is being closed
     ClosingServer,
+
+    /// The socket was disconnected by the server because it didn't send anything for too long,
+    /// see [`SocketIo::disconnect_idle`](crate::SocketIo#method.disconnect_idle)
+    IdleTimeout,
+
+    /// The client missed too many application-level keepalive checks,
+    /// see [`SocketIoBuilder::app_keepalive`](crate::SocketIoBuilder#method.app_keepalive)
+    KeepaliveTimeout,
+
+    /// The connection was refused by a namespace middleware, see [`SocketIo::push_middleware`].
+    /// The connect handler is never called for this socket, this reason is mostly seen
+    /// by the [`SocketIoBuilder::on_disconnect_record`] hook.
+    ///
+    /// [`SocketIo::push_middleware`]: crate::SocketIo#method.push_middleware
+    /// [`SocketIoBuilder::on_disconnect_record`]: crate::SocketIoBuilder#method.on_disconnect_record
+    MiddlewareRefused,
+
+    /// The client sent events faster than they were processed and overflowed the queue of the socket,
+    /// see [`EventOrdering::Fifo`] and [`EVENT_QUEUE_CAPACITY`]
+    EventQueueFull,
+}
+
+/// A relay submitted to the [`SocketIoBuilder::relay_policy`], see [`Socket::relay_to`].
+///
+/// [`SocketIoBuilder::relay_policy`]: crate::SocketIoBuilder#method.relay_policy
+#[derive(Debug)]
+pub struct RelayRequest<'a> {
+    /// The socket sending the message
+    pub from: Sid,
+    /// The peer socket the message is relayed to
+    pub to: Sid,
+    /// The event name
+    pub event: &'a str,
+    /// The rooms of the sending socket
+    pub from_rooms: &'a [Room],
+    /// The rooms of the peer socket
+    pub to_rooms: &'a [Room],
+    /// The relayed data, it can be modified before being sent to the peer
+    pub data: &'a mut Value,
+}
+
+impl RelayRequest<'_> {
+    /// Returns `true` if both sockets are in at least one common room.
+    pub fn shares_room(&self) -> bool {
+        self.from_rooms.iter().any(|r| self.to_rooms.contains(r))
+    }
+}
+
+/// A summary of a socket disconnection, given to the [`SocketIoBuilder::on_disconnect_record`] callback.
+///
+/// [`SocketIoBuilder::on_disconnect_record`]: crate::SocketIoBuilder#method.on_disconnect_record
+#[derive(Debug, Clone)]
+pub struct DisconnectRecord {
+    /// The id of the disconnected socket
+    pub sid: Sid,
+    /// The namespace the socket was connected to
+    pub ns: Cow<'static, str>,
+    /// The reason of the disconnection
+    pub reason: DisconnectReason,
+    /// The rooms the socket was in when it disconnected
+    pub rooms: Vec<Room>,
+    /// How long the socket stayed connected, measured with the configured [`Clock`](crate::clock::Clock)
+    pub connected_for: Duration,
+    /// The number of bytes sent to the socket, binary payloads included
+    pub bytes_sent: u64,
+    /// The number of bytes received from the socket, binary payloads included
+    pub bytes_received: u64,
+}
+
+/// The handshake details of a socket, assembled when it connects, see [`Socket::handshake`].
+///
+/// It mirrors the `socket.handshake` object of the JS server.
+/// It is also an extractor, available in the connect, message and disconnect handlers.
+#[derive(Debug, Clone)]
+pub struct Handshake {
+    /// The auth payload sent by the client when connecting to the namespace,
+    /// or [`Value::Null`] if there is none.
+    pub auth: Value,
+    /// The query params of the handshake request, percent-decoded.
+    /// If a param is repeated, the last value is kept.
+    pub query: HashMap<String, String>,
+    /// The headers of the handshake request
+    pub headers: http::HeaderMap,
+    /// The address of the client.
+    ///
+    /// It is read from a [`SocketAddr`](std::net::SocketAddr) in the extensions of the handshake request,
+    /// which must be inserted by the server or by a middleware before the request reaches the socket.io service.
+    /// It is `None` otherwise.
+    pub address: Option<std::net::SocketAddr>,
+}
+
+impl Handshake {
+    fn new(req: &http::request::Parts) -> Self {
+        let query = req
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| match param.split_once('=') {
+                Some((key, value)) => (percent_decode(key), percent_decode(value)),
+                None => (percent_decode(param), String::new()),
+            })
+            .collect();
+        Self {
+            auth: Value::Null,
+            query,
+            headers: req.headers.clone(),
+            address: req.extensions.get().copied(),
+        }
+    }
+}
+
+/// Decodes a `application/x-www-form-urlencoded` query component,
+/// the invalid escape sequences are kept as is.
+fn percent_decode(s: &str) -> String {
+    let hex = |b: Option<&u8>| (*b? as char).to_digit(16);
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match (hex(bytes.get(i + 1)), hex(bytes.get(i + 2))) {
+                (Some(hi), Some(lo)) => {
+                    decoded.push((hi * 16 + lo) as u8);
+                    i += 2;
+                }
+                _ => decoded.push(b'%'),
+            },
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// An event that could not be delivered to a socket,
+/// given to the [`SocketIoBuilder::on_undelivered`] callback.
+///
+/// [`SocketIoBuilder::on_undelivered`]: crate::SocketIoBuilder#method.on_undelivered
+#[derive(Debug, Clone)]
+pub struct UndeliveredPacket {
+    /// The id of the target socket
+    pub sid: Sid,
+    /// The namespace of the target socket
+    pub ns: Cow<'static, str>,
+    /// The name of the event that was not delivered
+    pub event: String,
+    /// Why the event was not delivered
+    pub reason: UndeliveredReason,
+}
+
+/// The reason an event was not delivered, see [`UndeliveredPacket`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndeliveredReason {
+    /// The internal buffer of the socket was full, the event was dropped.
+    BufferFull,
+    /// The socket was closed before the event could be queued.
+    SocketClosed,
+}
+
+impl From<&SocketError<()>> for UndeliveredReason {
+    fn from(err: &SocketError<()>) -> Self {
+        match err {
+            SocketError::InternalChannelFull(_) => Self::BufferFull,
+            SocketError::Closed(_) => Self::SocketClosed,
+        }
+    }
+}
+
+/// A set of message handlers swapped in at once with [`Socket::replace_handlers`].
+pub struct HandlersBuilder<A: Adapter = LocalAdapter> {
+    handlers: HashMap<Cow<'static, str>, BoxedMessageHandler<A>>,
+}
+
+impl<A: Adapter> HandlersBuilder<A> {
+    /// Registers a message handler for the given event, see [`Socket::on`].
+    /// If a handler is already registered for this event, it is replaced.
+    pub fn on<H, T>(&mut self, event: impl Into<Cow<'static, str>>, handler: H) -> &mut Self
+    where
+        H: MessageHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        self.handlers
+            .insert(event.into(), MakeErasedHandler::new_message_boxed(handler));
+        self
+    }
+}
+
+impl<A: Adapter> std::fmt::Debug for HandlersBuilder<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HandlersBuilder")
+            .field("events", &self.handlers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// The traffic of a namespace mirrored to an observer socket, see [`Socket::make_observer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObserverScope {
+    /// Only the events broadcast with the operators, whatever the selected rooms.
+    Broadcasts,
+    /// The broadcast events and the events emitted to a single socket.
+    All,
+}
+
+/// A payload type bound to an event name, to emit it with [`Socket::emit_typed`].
+///
+/// Each event name is declared once, with its payload type, so that a payload can't be emitted
+/// with the wrong event name.
+///
+/// # Example
+/// ```
+/// # use socketioxide::{SocketIo, extract::*, socket::Event};
+/// #[derive(serde::Serialize)]
+/// struct ChatMessage {
+///     author: String,
+///     text: String,
+/// }
+/// impl Event for ChatMessage {
+///     const NAME: &'static str = "chat:message";
+/// }
+///
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef| {
+///     let msg = ChatMessage {
+///         author: "bot".into(),
+///         text: "Welcome!".into(),
+///     };
+///     socket.emit_typed(msg).ok();
+/// });
+/// ```
+///
+/// Only the types implementing [`Event`] can be emitted, so any other payload is rejected at compile time:
+/// ```compile_fail
+/// # use socketioxide::{SocketIo, extract::*};
+/// let (_, io) = SocketIo::new_svc();
+/// io.ns("/", |socket: SocketRef| {
+///     socket.emit_typed("Welcome!").ok();
+/// });
+/// ```
+pub trait Event: Serialize {
+    /// The name of the event emitted with this payload
+    const NAME: &'static str;
+}
+
+/// The configuration of a sequence sent with [`Socket::emit_sequence`].
+#[derive(Debug, Clone, Default)]
+pub struct SequenceConfig {
+    timeout: Option<Duration>,
+    retries: usize,
+}
+
+impl SequenceConfig {
+    /// Creates a configuration waiting for each step acknowledgement with the default ack timeout, without retries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the time to wait for the acknowledgement of each step.
+    ///
+    /// Defaults to the [`SocketIoBuilder::ack_timeout`](crate::SocketIoBuilder#method.ack_timeout).
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many times a step is sent again when its acknowledgement times out
+    /// before aborting the sequence.
+    ///
+    /// Defaults to 0.
+    #[inline]
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+}
+
+/// How far a sequence sent with [`Socket::emit_sequence`] got.
+#[derive(Debug)]
+pub struct SequenceProgress {
+    /// The number of steps acknowledged by the client
+    pub acked: usize,
+    /// The number of steps of the sequence
+    pub total: usize,
+    /// The error that stopped the sequence at the step `acked`, if it did not complete
+    pub error: Option<AckError<()>>,
+}
+
+impl SequenceProgress {
+    /// Returns `true` if every step of the sequence was acknowledged.
+    pub fn is_complete(&self) -> bool {
+        self.acked == self.total
+    }
 }
 
 impl std::fmt::Display for DisconnectReason {
@@ -81,7 +432,12 @@ impl std::fmt::Display for DisconnectReason {
             HeartbeatTimeout => "client did not send a PONG packet in time",
             ClientNSDisconnect => "client has manually disconnected the socket from the namespace",
             ServerNSDisconnect => "socket was forcefully disconnected from the namespace",
+            ServerAdapterClose => "socket was evicted from the namespace by the adapter",
             ClosingServer => "server is being closed",
+            IdleTimeout => "socket was disconnected by the server after being idle for too long",
+            KeepaliveTimeout => "client missed too many keepalive checks",
+            MiddlewareRefused => "connection was refused by a namespace middleware",
+            EventQueueFull => "client sent events faster than they were processed",
         };
         f.write_str(str)
     }
@@ -104,7 +460,8 @@ impl From<EIoDisconnectReason> for DisconnectReason {
 pub(crate) trait PermitIteratorExt<'a>:
     ExactSizeIterator<Item = Permit<'a>> + Sized
 {
-    fn emit(mut self, mut packet: Packet<'_>) {
+    /// Emits the packet and its binary payloads, returns the number of bytes sent.
+    fn emit(mut self, mut packet: Packet<'_>) -> usize {
         debug_assert!(self.len() > 0, "No permits available to send the message");
 
         let bin_payloads = match packet.inner {
@@ -114,7 +471,8 @@ pub(crate) trait PermitIteratorExt<'a>:
             _ => None,
         };
 
-        let msg = packet.into();
+        let msg: String = packet.into();
+        let mut bytes = msg.len();
         self.next().unwrap().emit(msg);
 
         if let Some(bin_payloads) = bin_payloads {
@@ -123,12 +481,32 @@ pub(crate) trait PermitIteratorExt<'a>:
                 "Not enough permits available to send the message with the binary payload"
             );
             for bin in bin_payloads {
+                bytes += bin.len();
                 self.next().unwrap().emit_binary(bin);
             }
         }
+        bytes
+    }
+}
+impl<'a, I: ExactSizeIterator<Item = Permit<'a>>> PermitIteratorExt<'a> for I {}
+
+/// A middleware added with [`Socket::use_outbound`] or [`Socket::use_outbound_with_control`]
+struct OutboundMiddleware {
+    f: Box<dyn Fn(&mut Packet<'_>) + Send + Sync>,
+    /// Whether the control packets are transformed too
+    control: bool,
+}
+impl OutboundMiddleware {
+    fn new(f: impl Fn(&mut Packet<'_>) + Send + Sync + 'static, control: bool) -> Self {
+        Self {
+            f: Box::new(f),
+            control,
+        }
     }
 }
-impl<'a> PermitIteratorExt<'a> for PermitIterator<'a> {}
+
+/// The message handlers of a socket by event, with the id returned by [`Socket::on_with_id`]
+type MessageHandlers<A> = HashMap<Cow<'static, str>, Vec<(HandlerId, BoxedMessageHandler<A>)>>;
 
 /// A Socket represents a client connected to a namespace.
 /// It is used to send and receive messages from the client, join and leave rooms, etc.
@@ -136,10 +514,51 @@ impl<'a> PermitIteratorExt<'a> for PermitIterator<'a> {}
 pub struct Socket<A: Adapter = LocalAdapter> {
     pub(crate) config: Arc<SocketIoConfig>,
     pub(crate) ns: Arc<Namespace<A>>,
-    message_handlers: RwLock<HashMap<Cow<'static, str>, BoxedMessageHandler<A>>>,
+    /// The message handlers of each event, called in registration order
+    message_handlers: RwLock<MessageHandlers<A>>,
+    handler_counter: AtomicU64,
+    /// The handler of the events without a message handler, see [`Socket::on_fallback`]
+    fallback_handler: RwLock<Option<BoxedMessageHandler<A>>>,
+    /// The middlewares applied to the outgoing packets, see [`Socket::use_outbound`]
+    outbound: RwLock<Vec<OutboundMiddleware>>,
     disconnect_handler: Mutex<Option<BoxedDisconnectHandler<A>>>,
     ack_message: Mutex<HashMap<i64, oneshot::Sender<AckResult<Value>>>>,
     ack_counter: AtomicI64,
+    /// The last sequence number, locked until the sequenced packet is emitted so that the
+    /// packets are sent in the order of their sequence numbers
+    seq_counter: Mutex<u64>,
+    /// The last events sent with their sequence number, if the replay is enabled
+    replay_buffer: Option<Mutex<VecDeque<(u64, Packet<'static>)>>>,
+    /// The events held until the client is ready, `None` once [`Socket::mark_ready`] is called
+    deferred: Mutex<Option<VecDeque<Packet<'static>>>>,
+    /// Notified each time an ack response is received
+    ack_received: Notify,
+    /// The sender of the stream returned by [`Socket::events`]
+    event_stream: Mutex<Option<mpsc::Sender<InboundEvent>>>,
+    /// The sender of the stream returned by [`Socket::event_stream`]
+    unhandled_stream: Mutex<Option<mpsc::Sender<IncomingEvent<A>>>>,
+    /// Whether `event_stream` is set, so that its lock is only taken while the stream is alive
+    has_event_stream: AtomicBool,
+    /// Whether `unhandled_stream` is set, so that its lock is only taken while the stream is alive
+    has_unhandled_stream: AtomicBool,
+    /// The queue of the events processed in order with [`EventOrdering::Fifo`]
+    event_queue: Mutex<Option<mpsc::Sender<InboundEvent>>>,
+    user_id: Mutex<Option<String>>,
+    /// The session id sent by the client in the auth payload, see [`Socket::session_id`]
+    session_id: Option<String>,
+    /// The local tags of the socket, see [`Socket::add_tag`]
+    tags: Mutex<HashSet<String>>,
+    /// The trace context sent by the client when connecting
+    trace_parent: Option<TraceParent>,
+    /// The handshake details, see [`Socket::handshake`]
+    handshake: Handshake,
+    /// The instant at which the socket was created, according to the configured clock
+    connected_at: Instant,
+    /// The time of the last packet received, in nanoseconds since `connected_at`
+    last_activity: AtomicU64,
+    /// The bytes sent to and received from the client, reported in the [`DisconnectRecord`]
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
     /// The socket id
     pub id: Sid,
 
@@ -164,9 +583,37 @@ impl<A: Adapter> Socket<A> {
         Self {
             ns,
             message_handlers: RwLock::new(HashMap::new()),
+            handler_counter: AtomicU64::new(0),
+            fallback_handler: RwLock::new(None),
+            outbound: RwLock::new(Vec::new()),
             disconnect_handler: Mutex::new(None),
             ack_message: Mutex::new(HashMap::new()),
             ack_counter: AtomicI64::new(0),
+            seq_counter: Mutex::new(0),
+            replay_buffer: config
+                .replay_buffer_size
+                .map(|size| Mutex::new(VecDeque::with_capacity(size))),
+            deferred: Mutex::new(Some(VecDeque::new())),
+            ack_received: Notify::new(),
+            event_stream: Mutex::new(None),
+            unhandled_stream: Mutex::new(None),
+            has_event_stream: AtomicBool::new(false),
+            has_unhandled_stream: AtomicBool::new(false),
+            event_queue: Mutex::new(None),
+            user_id: Mutex::new(None),
+            session_id: None,
+            tags: Mutex::new(HashSet::new()),
+            connected_at: config.clock.now(),
+            last_activity: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            handshake: Handshake::new(&esocket.req_parts),
+            trace_parent: esocket
+                .req_parts
+                .headers
+                .get("traceparent")
+                .and_then(|v| v.to_str().ok())
+                .and_then(TraceParent::parse),
             id: sid,
             #[cfg(feature = "extensions")]
             extensions: Extensions::new(),
@@ -232,10 +679,339 @@ impl<A: Adapter> Socket<A> {
         H: MessageHandler<A, T>,
         T: Send + Sync + 'static,
     {
+        let handler = MakeErasedHandler::new_message_boxed(handler);
+        let handlers = vec![(self.next_handler_id(), handler)];
         self.message_handlers
             .write()
             .unwrap()
-            .insert(event.into(), MakeErasedHandler::new_message_boxed(handler));
+            .insert(event.into(), handlers);
+    }
+
+    /// Registers a message handler for the given event alongside the ones already registered,
+    /// and returns its [`HandlerId`] to remove it later with [`Socket::off_by_id`].
+    ///
+    /// Unlike [`Socket::on`], the other handlers of the event are kept:
+    /// they are all called, in registration order. It is useful when several subsystems
+    /// listen to the same event and only one of them should be removed afterwards.
+    /// Registering a handler with [`Socket::on`] replaces all the handlers of the event.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on_with_id("move", || println!("game logic"));
+    ///     let audit = socket.on_with_id("move", || println!("audit"));
+    ///
+    ///     // Only the audit handler is removed
+    ///     socket.off_by_id(audit);
+    /// });
+    /// ```
+    pub fn on_with_id<H, T>(&self, event: impl Into<Cow<'static, str>>, handler: H) -> HandlerId
+    where
+        H: MessageHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        let handler = MakeErasedHandler::new_message_boxed(handler);
+        let id = self.next_handler_id();
+        self.message_handlers
+            .write()
+            .unwrap()
+            .entry(event.into())
+            .or_default()
+            .push((id, handler));
+        id
+    }
+
+    /// Registers a message handler for the given event like [`Socket::on`], with [`HandlerOpts`].
+    ///
+    /// With [`HandlerOpts::max_concurrent`], at most this number of invocations of the handler
+    /// run at the same time for this socket. The events received while the limit is reached
+    /// are queued or dropped according to the [`HandlerOpts::overflow`] policy.
+    /// It protects the server from a client firing many expensive events at once.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use socketioxide::handler::{ConcurrencyOverflowPolicy, HandlerOpts};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let opts = HandlerOpts {
+    ///         max_concurrent: Some(4),
+    ///         overflow: ConcurrencyOverflowPolicy::Queue(16),
+    ///     };
+    ///     socket.on_with_opts(
+    ///         "upload",
+    ///         |Data::<Value>(data)| async move {
+    ///             // Heavy work
+    ///         },
+    ///         opts,
+    ///     );
+    /// });
+    /// ```
+    pub fn on_with_opts<H, T>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        handler: H,
+        opts: HandlerOpts,
+    ) where
+        H: MessageHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        let event = event.into();
+        let mut handler = MakeErasedHandler::new_message_boxed(handler);
+        if let Some(max) = opts.max_concurrent {
+            handler = LimitedMessageHandler::new_boxed(handler, event.clone(), max, opts.overflow);
+        }
+        let handlers = vec![(self.next_handler_id(), handler)];
+        self.message_handlers
+            .write()
+            .unwrap()
+            .insert(event, handlers);
+    }
+
+    /// Registers a fallback handler, called for the events that have no handler registered with [`Socket::on`].
+    /// It can be used to log or proxy the unknown events. The name of the event is available
+    /// with the [`EventName`](crate::extract::EventName) extractor, along with the usual message extractors.
+    ///
+    /// You can register only one fallback handler per socket. If you register multiple handlers,
+    /// only the last one will be used. Without a fallback handler, the unknown events are ignored.
+    /// While a stream returned by [`Socket::event_stream`] is alive, the unknown events are sent to it instead.
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("chat", || println!("chat message"));
+    ///     socket.on_fallback(|EventName(event), Data::<Value>(data)| {
+    ///         println!("unhandled event {event}: {data}");
+    ///     });
+    /// });
+    /// ```
+    pub fn on_fallback<H, T>(&self, handler: H)
+    where
+        H: MessageHandler<A, T>,
+        T: Send + Sync + 'static,
+    {
+        let handler = MakeErasedHandler::new_message_boxed(handler);
+        self.fallback_handler.write().unwrap().replace(handler);
+    }
+
+    /// Removes the message handlers registered for the given event.
+    /// Afterwards, the event is dispatched to the [fallback handler](Socket::on_fallback), if any.
+    ///
+    /// Returns `true` if at least one handler was registered for this event.
+    /// It can be called from a message handler, even from the one being removed.
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     // A one-shot handler
+    ///     socket.on("init", |socket: SocketRef| {
+    ///         println!("socket initialized");
+    ///         socket.off("init");
+    ///     });
+    /// });
+    /// ```
+    pub fn off(&self, event: &str) -> bool {
+        self.message_handlers
+            .write()
+            .unwrap()
+            .remove(event)
+            .is_some()
+    }
+
+    /// Removes the message handler registered with [`Socket::on_with_id`] under the given id,
+    /// the other handlers of the event are kept.
+    /// Once the last handler of an event is removed, the event is dispatched to the
+    /// [fallback handler](Socket::on_fallback), if any.
+    ///
+    /// Returns `true` if the handler was still registered.
+    pub fn off_by_id(&self, id: HandlerId) -> bool {
+        let mut handlers = self.message_handlers.write().unwrap();
+        let Some((event, list)) = handlers
+            .iter_mut()
+            .find(|(_, list)| list.iter().any(|(i, _)| *i == id))
+        else {
+            return false;
+        };
+        list.retain(|(i, _)| *i != id);
+        if list.is_empty() {
+            let event = event.clone();
+            handlers.remove(&event);
+        }
+        true
+    }
+
+    /// Removes every message handler of the socket, see [`Socket::off`].
+    /// The fallback and disconnect handlers are left untouched.
+    pub fn off_all(&self) {
+        self.message_handlers.write().unwrap().clear();
+    }
+
+    /// Adds a middleware called with every event and ack packet sent to this socket, right before it is
+    /// written to the transport, for example to inject a per-socket field or to encrypt the data
+    /// with a session key. The middlewares are applied in the order they were added.
+    ///
+    /// The control packets (connect, disconnect and connect error packets) are skipped,
+    /// use [`Socket::use_outbound_with_control`] to transform them as well.
+    ///
+    /// The packet is transformed after its permits were reserved: if the middleware adds binary payloads,
+    /// their permits are reserved afterwards and the packet is dropped if the socket buffer is full.
+    /// The middleware is called while a lock is held and must not add other middlewares. When
+    /// [`SocketIoBuilder::sequence_events`] is enabled, it must not emit to the socket either,
+    /// including with [`Socket::emit_with_ack`].
+    ///
+    /// [`SocketIoBuilder::sequence_events`]: crate::SocketIoBuilder#method.sequence_events
+    ///
+    /// #### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, packet::PacketData};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.use_outbound(|packet| {
+    ///         if let PacketData::Event(_, data, _) = &mut packet.inner {
+    ///             data["locale"] = "fr".into();
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn use_outbound<F>(&self, middleware: F)
+    where
+        F: Fn(&mut Packet<'_>) + Send + Sync + 'static,
+    {
+        self.outbound
+            .write()
+            .unwrap()
+            .push(OutboundMiddleware::new(middleware, false));
+    }
+
+    /// Adds a middleware called with every packet sent to this socket, control packets included.
+    /// See [`Socket::use_outbound`].
+    pub fn use_outbound_with_control<F>(&self, middleware: F)
+    where
+        F: Fn(&mut Packet<'_>) + Send + Sync + 'static,
+    {
+        self.outbound
+            .write()
+            .unwrap()
+            .push(OutboundMiddleware::new(middleware, true));
+    }
+
+    /// Replaces all the message handlers of the socket at once with the ones registered on the given builder.
+    ///
+    /// The new handlers are registered without holding any lock, then swapped in one step:
+    /// every event is dispatched either against the previous handlers or against the new ones, never a mix of both.
+    /// The events without handler in the new set are ignored afterwards.
+    /// The disconnect handler is left untouched.
+    /// ### Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("move", || println!("legacy move"));
+    ///     socket.on("chat", || println!("legacy chat"));
+    ///
+    ///     // The feature flag was flipped
+    ///     socket.replace_handlers(|handlers| {
+    ///         handlers
+    ///             .on("move", || println!("new move"))
+    ///             .on("chat", || println!("new chat"));
+    ///     });
+    /// });
+    /// ```
+    pub fn replace_handlers(&self, build: impl FnOnce(&mut HandlersBuilder<A>)) {
+        let mut builder = HandlersBuilder {
+            handlers: HashMap::new(),
+        };
+        build(&mut builder);
+        let handlers = builder
+            .handlers
+            .into_iter()
+            .map(|(event, handler)| (event, vec![(self.next_handler_id(), handler)]))
+            .collect();
+        *self.message_handlers.write().unwrap() = handlers;
+    }
+
+    fn next_handler_id(&self) -> HandlerId {
+        HandlerId(self.handler_counter.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Gets a stream of every event received from the client, for code forwarding the events
+    /// without knowing their names.
+    ///
+    /// The stream complements the message handlers: each event is yielded by the stream
+    /// **and** dispatched to the handler registered for its name, if any. The reserved events
+    /// (e.g. [`REPLAY_EVENT`]) are not yielded. The arguments are the array sent by the client,
+    /// with the binary placeholders removed. If the ack id is set, the client expects an ack
+    /// that can be sent with [`Socket::send_manual_ack`].
+    ///
+    /// Only one stream is fed at a time: calling this method again ends the previous stream.
+    /// The stream ends when the socket is disconnected.
+    ///
+    /// The stream buffers up to [`EVENT_STREAM_CAPACITY`] events. If it is not consumed fast enough,
+    /// the events received while the buffer is full are **dropped** from the stream,
+    /// they are still dispatched to the handlers.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use futures::StreamExt;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let mut events = socket.events();
+    ///     tokio::spawn(async move {
+    ///         while let Some((event, data, _bin, _ack_id)) = events.next().await {
+    ///             println!("received {event}: {data}");
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn events(&self) -> impl futures::Stream<Item = InboundEvent> {
+        let (tx, mut rx) = mpsc::channel(EVENT_STREAM_CAPACITY);
+        *self.event_stream.lock().unwrap() = Some(tx);
+        self.has_event_stream.store(true, Ordering::SeqCst);
+        futures::stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
+    /// Gets a stream of the events received from the client that have no handler registered
+    /// with [`Socket::on`], to process them in a single loop instead of registering a handler per event.
+    ///
+    /// Unlike [`Socket::events`], the events are **moved** to the stream: while the stream is alive,
+    /// the [`Socket::on_fallback`] handler is not called. If the client expects an ack,
+    /// the [`IncomingEvent::ack`] handle can be used to send it.
+    ///
+    /// Only one stream is fed at a time: calling this method again ends the previous stream.
+    /// The stream ends when the socket is disconnected.
+    ///
+    /// The stream buffers up to [`EVENT_STREAM_CAPACITY`] events. If it is not consumed fast enough,
+    /// the events received while the buffer is full are **dropped**, except the ones expecting an ack:
+    /// they are given to the [`Socket::on_fallback`] handler as if there was no stream.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use futures::StreamExt;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let mut events = socket.event_stream();
+    ///     tokio::spawn(async move {
+    ///         while let Some(ev) = events.next().await {
+    ///             println!("received {}: {}", ev.event, ev.data);
+    ///             if let Some(ack) = ev.ack {
+    ///                 ack.send("ok").ok();
+    ///             }
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn event_stream(&self) -> impl futures::Stream<Item = IncomingEvent<A>> {
+        let (tx, mut rx) = mpsc::channel(EVENT_STREAM_CAPACITY);
+        *self.unhandled_stream.lock().unwrap() = Some(tx);
+        self.has_unhandled_stream.store(true, Ordering::SeqCst);
+        futures::stream::poll_fn(move |cx| rx.poll_recv(cx))
     }
 
     /// ## Registers a disconnect handler.
@@ -313,7 +1089,8 @@ impl<A: Adapter> Socket<A> {
         event: impl Into<Cow<'static, str>>,
         data: T,
     ) -> Result<(), SendError<T>> {
-        let permits = match self.reserve(1) {
+        let event = event.into();
+        let permits = match self.reserve_event(1, &event) {
             Ok(permits) => permits,
             Err(e) => {
                 #[cfg(feature = "tracing")]
@@ -324,41 +1101,502 @@ impl<A: Adapter> Socket<A> {
 
         let ns = self.ns();
         let data = serde_json::to_value(data)?;
-        permits.emit(Packet::event(ns, event.into(), data));
+        self.send_with_permits(Packet::event(ns, event, data), permits);
         Ok(())
     }
 
-    /// Emits a message to the client and wait for acknowledgement.
-    ///
-    /// The acknowledgement has a timeout specified in the config (5s by default)
-    /// (see [`SocketIoBuilder::ack_timeout`]) or with the [`timeout()`] operator.
-    ///
-    /// To get acknowledgements, an [`AckStream`] is returned.
-    /// It can be used in two ways:
-    /// * As a [`Stream`]: It will yield all the [`AckResponse`] with their corresponding socket id
-    /// received from the client. It can useful when broadcasting to multiple sockets and therefore expecting
-    /// more than one acknowledgement. If you want to get the socket from this id, use [`io::get_socket()`].
-    /// * As a [`Future`]: It will yield the first [`AckResponse`] received from the client.
-    /// Useful when expecting only one acknowledgement.
-    ///
-    /// # Errors
+    /// Emits a message to the client without ever waiting for buffer space.
     ///
-    /// If the packet encoding failed a [`serde_json::Error`] is **immediately** returned.
+    /// It behaves like [`emit()`](Socket::emit) but surfaces a full packet buffer as a distinct,
+    /// retryable [`TryEmitError::WouldBlock`] error so that a custom drop/retry policy can be implemented.
     ///
-    /// If the socket is full or if it has been closed before receiving the acknowledgement,
-    /// an [`SendError::Socket`] will be **immediately returned** and the value to send will be given back.
+    /// ## Errors
+    /// * When encoding the data into JSON a [`TryEmitError::Serialize`] may be returned.
+    /// * If the packet buffer is full, a [`TryEmitError::WouldBlock`] will be returned
+    ///   and the provided data to be send will be given back in the error.
+    /// * If the underlying engine.io connection is closed a [`TryEmitError::Closed`]
+    ///   will be returned and the provided data to be send will be given back in the error.
     ///
-    /// If the client didn't respond before the timeout, the [`AckStream`] will yield
+    /// [`TryEmitError::Serialize`]: crate::TryEmitError::Serialize
+    /// [`TryEmitError::WouldBlock`]: crate::TryEmitError::WouldBlock
+    /// [`TryEmitError::Closed`]: crate::TryEmitError::Closed
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, TryEmitError, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         match socket.try_emit("test", data) {
+    ///             Err(TryEmitError::WouldBlock(data)) => {
+    ///                 // Retry later with the returned data
+    ///             }
+    ///             _ => {}
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn try_emit<T: Serialize>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), TryEmitError<T>> {
+        let event = event.into();
+        let permits = match self.reserve_event(1, &event) {
+            Ok(permits) => permits,
+            Err(e) => return Err(e.with_value(data).into()),
+        };
+
+        let data = serde_json::to_value(data)?;
+        self.send_with_permits(Packet::event(self.ns(), event, data), permits);
+        Ok(())
+    }
+
+    /// Emits several messages to the client at once, to reduce the number of transport frames
+    /// when emitting many small events.
+    ///
+    /// Every item is serialized before any packet is sent, then the packet buffer space of all the
+    /// serialized items is reserved at once and they are queued back to back: with the polling transport
+    /// they are sent in the same payload, and the websocket transport writes them before flushing.
+    ///
+    /// A result is returned for each item, in order.
+    ///
+    /// ## Errors
+    /// * An item that cannot be encoded into JSON gets a [`SendError::Serialize`], the other items are still sent.
+    /// * If the underlying engine.io connection is closed or if the packet buffer doesn't have space for
+    ///   all the serialized items, none of them is sent and each of them gets a [`SendError::Socket`] error
+    ///   with its data given back.
+    ///
+    /// [`SendError::Serialize`]: crate::SendError::Serialize
+    /// [`SendError::Socket`]: crate::SendError::Socket
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let updates = (0..10).map(|i| ("position", [i, i * 2]));
+    ///     for res in socket.emit_batched(updates) {
+    ///         res.ok();
+    ///     }
+    /// });
+    /// ```
+    pub fn emit_batched<E, T>(
+        &self,
+        items: impl IntoIterator<Item = (E, T)>,
+    ) -> Vec<Result<(), SendError<T>>>
+    where
+        E: Into<Cow<'static, str>>,
+        T: Serialize,
+    {
+        let mut results = Vec::new();
+        let mut packets = Vec::new();
+        for (event, data) in items {
+            match serde_json::to_value(&data) {
+                Ok(value) => {
+                    results.push(Ok(()));
+                    packets.push((results.len() - 1, event.into(), value, data));
+                }
+                Err(e) => results.push(Err(SendError::Serialize(e))),
+            }
+        }
+        if packets.is_empty() {
+            return results;
+        }
+
+        let mut permits = match self.reserve(packets.len()) {
+            Ok(permits) => permits,
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("sending error during batched emit: {e:?}");
+                for (i, event, _, data) in packets {
+                    self.report_undelivered(&event, &e);
+                    results[i] = Err(e.with_value(data).into());
+                }
+                return results;
+            }
+        };
+        let ns = self.ns();
+        for (_, event, value, _) in packets {
+            self.send_with_permits(Packet::event(ns, event, value), permits.by_ref().take(1));
+        }
+        results
+    }
+
+    /// Emits a message to the client, waiting for space in the packet buffer instead of
+    /// failing with [`SocketError::InternalChannelFull`].
+    ///
+    /// The data is serialized eagerly, so a serialization error is returned before any wait.
+    /// The future completes once the packet is queued or the socket is closed.
+    ///
+    /// This method is cancel safe: dropping the future before it completes never sends the packet,
+    /// even partially.
+    ///
+    /// ## Errors
+    /// * When encoding the data into JSON a [`SendError::Serialize`] may be returned.
+    /// * If the underlying engine.io connection is closed a [`SendError::Socket(SocketError::Closed)`]
+    ///   will be returned and the provided data to be send will be given back in the error.
+    ///
+    /// [`SendError::Serialize`]: crate::SendError::Serialize
+    /// [`SendError::Socket(SocketError::Closed)`]: crate::SocketError::Closed
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Vec<Value>>(items)| async move {
+    ///         // Send each item, waiting for the client to keep up
+    ///         for item in items {
+    ///             if socket.emit_async("item", item).await.is_err() {
+    ///                 break;
+    ///             }
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub async fn emit_async<T: Serialize>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        let event = event.into();
+        let value = serde_json::to_value(&data)?;
+        let permits = match self.esocket.reserve_async(1).await {
+            Ok(permits) => permits,
+            Err(_) => {
+                let err = SocketError::Closed(());
+                self.report_undelivered(&event, &err);
+                return Err(err.with_value(data).into());
+            }
+        };
+
+        let ns = self.ns();
+        self.send_with_permits(Packet::event(ns, event, value), permits);
+        Ok(())
+    }
+
+    /// Emits a message to the client once it is ready, see [`Socket::mark_ready`].
+    ///
+    /// Until then, the event is held in a buffer of [`max_deferred_events`] events for this socket.
+    /// The deferred events are sent in order when the socket is marked as ready,
+    /// and once it is, this method behaves like [`emit()`](Socket::emit).
+    ///
+    /// ## Errors
+    /// * When encoding the data into JSON a [`SendError::Serialize`] may be returned.
+    /// * If the deferred buffer is full, a [`SendError::Socket(SocketError::InternalChannelFull)`]
+    ///   will be returned and the provided data to be send will be given back in the error.
+    /// * Once the socket is ready, the errors are the same as the [`emit()`](Socket::emit) ones.
+    ///
+    /// [`max_deferred_events`]: crate::SocketIoBuilder#method.max_deferred_events
+    /// [`SendError::Serialize`]: crate::SendError::Serialize
+    /// [`SendError::Socket(SocketError::InternalChannelFull)`]: crate::SocketError::InternalChannelFull
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     // The client emits "ready" once its local initialization is done
+    ///     socket.defer_until_ready("news", "Hello!").ok();
+    ///     socket.on("ready", |socket: SocketRef| socket.mark_ready());
+    /// });
+    /// ```
+    pub fn defer_until_ready<T: Serialize>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), SendError<T>> {
+        let mut deferred = self.deferred.lock().unwrap();
+        let Some(queue) = deferred.as_mut() else {
+            drop(deferred);
+            return self.emit(event, data);
+        };
+        let event = event.into();
+        if queue.len() >= self.config.max_deferred_events {
+            let err = SocketError::InternalChannelFull(());
+            self.report_undelivered(&event, &err);
+            return Err(err.with_value(data).into());
+        }
+        let data = serde_json::to_value(data)?;
+        queue.push_back(Packet::event(self.ns.path.clone(), event, data));
+        Ok(())
+    }
+
+    /// Marks the client as ready and sends the events deferred with [`Socket::defer_until_ready`], in order.
+    ///
+    /// A deferred event that cannot be sent is dropped and reported to the [`on_undelivered`] callback.
+    /// Calling it again has no effect.
+    ///
+    /// [`on_undelivered`]: crate::SocketIoBuilder#method.on_undelivered
+    pub fn mark_ready(&self) {
+        // The lock is held while flushing so that concurrent deferred emits are sent afterwards
+        let mut deferred = self.deferred.lock().unwrap();
+        let Some(queue) = deferred.take() else {
+            return;
+        };
+        for packet in queue {
+            if let Err(_e) = self.send_direct(packet) {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("deferred event not sent: {_e:?}");
+            }
+        }
+    }
+
+    /// Returns `true` once the socket is marked as ready with [`Socket::mark_ready`].
+    pub fn is_ready(&self) -> bool {
+        self.deferred.lock().unwrap().is_none()
+    }
+
+    /// Emits a typed event to the client, the event name is the [`Event::NAME`] of the payload type.
+    ///
+    /// The errors are the same as the [`emit()`](Socket::emit) ones.
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Event};
+    /// #[derive(serde::Serialize)]
+    /// struct Welcome(String);
+    /// impl Event for Welcome {
+    ///     const NAME: &'static str = "welcome";
+    /// }
+    ///
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     // Same as `socket.emit("welcome", "Hello!")`
+    ///     socket.emit_typed(Welcome("Hello!".into())).ok();
+    /// });
+    /// ```
+    #[inline]
+    pub fn emit_typed<E: Event>(&self, payload: E) -> Result<(), SendError<E>> {
+        self.emit(E::NAME, payload)
+    }
+
+    /// Emits a message to the client only if the socket is still in the given room.
+    ///
+    /// The membership check and the reservation of the message in the socket buffer are done atomically
+    /// with respect to the [`Adapter`]: the socket cannot leave the room in between, so room-scoped data
+    /// is never sent to a socket that just left. The message is then written once the membership lock is released,
+    /// so the hooks and middlewares called while sending it can make the socket join or leave rooms.
+    /// With a custom adapter, this guarantee depends on its [`Adapter::run_if_in_room`] implementation.
+    ///
+    /// Returns `Ok(true)` if the message was sent and `Ok(false)` if the socket is not in the room.
+    ///
+    /// ## Errors
+    /// * When encoding the data into JSON a [`BroadcastError::Serialize`] is returned.
+    /// * If the socket is full or if it has been closed, a [`BroadcastError::Socket`] is returned.
+    /// * When using a distributed adapter, a [`BroadcastError::Adapter`] can be returned.
+    ///
+    /// [`BroadcastError::Serialize`]: crate::BroadcastError::Serialize
+    /// [`BroadcastError::Socket`]: crate::BroadcastError::Socket
+    /// [`BroadcastError::Adapter`]: crate::BroadcastError::Adapter
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("game-1").ok();
+    ///     socket.on("state", |socket: SocketRef, Data::<Value>(state)| {
+    ///         let sent = socket.emit_if_in_room("game-1", "state", state).unwrap();
+    ///         println!("state sent: {sent}");
+    ///     });
+    /// });
+    /// ```
+    pub fn emit_if_in_room<T: Serialize>(
+        &self,
+        room: impl Into<Room>,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<bool, BroadcastError> {
+        let event = event.into();
+        let packet = Packet::event(self.ns(), event.clone(), serde_json::to_value(data)?);
+        // Only the permits are reserved under the membership lock, so that no user code runs while it is held
+        let permits = self
+            .ns
+            .adapter
+            .run_if_in_room(self.id, &room.into(), || self.reserve(1))
+            .map_err(|e| AdapterError(Box::new(e)))?;
+        match permits {
+            Some(Ok(permits)) => {
+                self.send_with_permits(packet, permits);
+                Ok(true)
+            }
+            Some(Err(e)) => {
+                self.report_undelivered(&event, &e);
+                Err(vec![e].into())
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Relays a message to exactly one `peer` socket of the same namespace,
+    /// for example to forward WebRTC signaling data between two clients.
+    ///
+    /// Before being sent, the relay is submitted to the policy set with [`SocketIoBuilder::relay_policy`],
+    /// which can reject it or transform the data. Without a policy, the relay is only allowed
+    /// if both sockets share a room.
+    ///
+    /// ## Errors
+    /// * If the peer is not connected to the namespace, a [`RelayError::PeerNotFound`] is returned.
+    /// * If the relay is rejected, a [`RelayError::Forbidden`] is returned.
+    /// * When encoding the data into JSON a [`RelayError::Serialize`] is returned.
+    /// * If the peer socket is full or if it has been closed, a [`RelayError::Socket`] is returned.
+    /// * When using a distributed adapter, a [`RelayError::Adapter`] can be returned.
+    ///
+    /// [`SocketIoBuilder::relay_policy`]: crate::SocketIoBuilder#method.relay_policy
+    /// [`RelayError::PeerNotFound`]: crate::RelayError::PeerNotFound
+    /// [`RelayError::Forbidden`]: crate::RelayError::Forbidden
+    /// [`RelayError::Serialize`]: crate::RelayError::Serialize
+    /// [`RelayError::Socket`]: crate::RelayError::Socket
+    /// [`RelayError::Adapter`]: crate::RelayError::Adapter
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Sid};
+    /// # use serde::Deserialize;
+    /// # use serde_json::Value;
+    /// #[derive(Deserialize)]
+    /// struct Signal {
+    ///     to: Sid,
+    ///     sdp: Value,
+    /// }
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("call-1").ok();
+    ///     socket.on("offer", |socket: SocketRef, Data::<Signal>(signal)| {
+    ///         if let Err(e) = socket.relay_to(signal.to, "offer", signal.sdp) {
+    ///             println!("relay error: {e}");
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn relay_to<T: Serialize>(
+        &self,
+        peer: Sid,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), RelayError> {
+        let peer_socket = self
+            .ns
+            .get_socket(peer)
+            .map_err(|_| RelayError::PeerNotFound)?;
+        let event = event.into();
+        let mut data = serde_json::to_value(data)?;
+        let adapter_err = |e: A::Error| RelayError::Adapter(AdapterError(Box::new(e)));
+        let from_rooms = self.ns.adapter.socket_rooms(self.id).map_err(adapter_err)?;
+        let to_rooms = self.ns.adapter.socket_rooms(peer).map_err(adapter_err)?;
+
+        let mut req = RelayRequest {
+            from: self.id,
+            to: peer,
+            event: &event,
+            from_rooms: &from_rooms,
+            to_rooms: &to_rooms,
+            data: &mut data,
+        };
+        let allowed = match &self.config.relay_policy {
+            Some(policy) => policy.call(&mut req),
+            None => req.shares_room(),
+        };
+        if !allowed {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(from = ?self.id, to = ?peer, "relay forbidden for event {event}");
+            return Err(RelayError::Forbidden);
+        }
+        peer_socket.send_direct(Packet::event(self.ns(), event, data))?;
+        Ok(())
+    }
+
+    /// Sends an ack response to the client for the event with the given ack id.
+    ///
+    /// It can be used to defer an ack response outside of the handler scope without keeping
+    /// the [`AckSender`] around. The id can be retrieved with the [`AckId`] extractor.
+    ///
+    /// Nothing tracks the acks that are still expected: if you never call this method,
+    /// the client callback will never resolve (or will time out on the client side).
+    ///
+    /// ## Errors
+    /// The same errors as the [`emit`](Socket::emit) method can be returned.
+    ///
+    /// [`AckSender`]: crate::extract::AckSender
+    /// [`AckId`]: crate::extract::AckId
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data), AckId(id)| {
+    ///         if let Some(id) = id {
+    ///             tokio::spawn(async move {
+    ///                 tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    ///                 socket.send_manual_ack(id, data).ok();
+    ///             });
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn send_manual_ack<T: Serialize>(&self, id: i64, data: T) -> Result<(), SendError<T>> {
+        let permits = match self.reserve(1) {
+            Ok(permits) => permits,
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!("sending error during manual ack: {e:?}");
+                return Err(e.with_value(data).into());
+            }
+        };
+
+        let data = serde_json::to_value(data)?;
+        self.send_with_permits(Packet::ack(self.ns(), data, id), permits);
+        Ok(())
+    }
+
+    /// Emits a message to the client and wait for acknowledgement.
+    ///
+    /// The acknowledgement has a timeout specified in the config (5s by default)
+    /// (see [`SocketIoBuilder::ack_timeout`]) or with the [`timeout()`] operator.
+    ///
+    /// To get acknowledgements, an [`AckStream`] is returned.
+    /// It can be used in two ways:
+    /// * As a [`Stream`]: It will yield all the [`AckResponse`] with their corresponding socket id
+    /// received from the client. It can useful when broadcasting to multiple sockets and therefore expecting
+    /// more than one acknowledgement. If you want to get the socket from this id, use [`io::get_socket()`].
+    /// * As a [`Future`]: It will yield the first [`AckResponse`] received from the client.
+    /// Useful when expecting only one acknowledgement.
+    ///
+    /// # Errors
+    ///
+    /// If the packet encoding failed a [`serde_json::Error`] is **immediately** returned.
+    ///
+    /// If the socket is full or if it has been closed before receiving the acknowledgement,
+    /// an [`SendError::Socket`] will be **immediately returned** and the value to send will be given back.
+    ///
+    /// If the client didn't respond before the timeout, the [`AckStream`] will yield
     /// an [`AckError::Timeout`]. If the data sent by the client is not deserializable as `V`,
     /// an [`AckError::Serde`] will be yielded.
     ///
+    /// If the socket already awaits the maximum number of acks set with
+    /// [`SocketIoBuilder::max_pending_acks_per_socket`], the packet is not sent and
+    /// the [`AckStream`] will immediately yield an [`AckError::TooManyPending`].
+    ///
+    /// The client ack arguments are always received as an array. When the client acks with
+    /// several arguments, they can be deserialized into a tuple: `emit_with_ack::<_, (A, B)>`.
+    ///
+    /// An ack answering with a tagged union such as `{ status: "ok" | "error", ... }` can be deserialized
+    /// into an internally tagged enum: `emit_with_ack::<_, (Outcome,)>` with `#[serde(tag = "status")]`.
+    /// An unknown tag yields an [`AckError::Serde`] naming the tag and the expected variants.
+    /// Binary attachments sent as separate ack arguments are removed from the data
+    /// and are available in [`AckResponse::binary`].
+    ///
+    /// [`AckResponse::binary`]: crate::ack::AckResponse#structfield.binary
     /// [`timeout()`]: crate::operators::ConfOperators#method.timeout
     /// [`SocketIoBuilder::ack_timeout`]: crate::SocketIoBuilder#method.ack_timeout
+    /// [`SocketIoBuilder::max_pending_acks_per_socket`]: crate::SocketIoBuilder#method.max_pending_acks_per_socket
     /// [`Stream`]: futures::stream::Stream
     /// [`Future`]: futures::future::Future
     /// [`AckError`]: crate::AckError
     /// [`AckError::Serde`]: crate::AckError::Serde
     /// [`AckError::Timeout`]: crate::AckError::Timeout
+    /// [`AckError::TooManyPending`]: crate::AckError::TooManyPending
     /// [`AckError::Socket`]: crate::AckError::Socket
     /// [`AckError::Socket(SocketError::Closed)`]: crate::SocketError::Closed
     /// [`io::get_socket()`]: crate::SocketIo#method.get_socket
@@ -384,7 +1622,47 @@ impl<A: Adapter> Socket<A> {
         event: impl Into<Cow<'static, str>>,
         data: T,
     ) -> Result<AckStream<V>, SendError<T>> {
-        let permits = match self.reserve(1) {
+        self.emit_with_ack_timeout(event, data, self.ack_timeout())
+    }
+
+    /// Gets the default ack timeout of the socket: the one set on its namespace with
+    /// [`SocketIo::set_ack_timeout`](crate::SocketIo#method.set_ack_timeout),
+    /// or the [`ack_timeout`](crate::SocketIoConfig::ack_timeout) of the config.
+    pub(crate) fn ack_timeout(&self) -> Duration {
+        self.ns.ack_timeout().unwrap_or(self.config.ack_timeout)
+    }
+
+    /// Emits a message to the client and waits for an acknowledgement during the given `timeout`,
+    /// instead of the ack timeout of the namespace or of the config.
+    ///
+    /// It is the same as `socket.timeout(timeout).emit_with_ack(event, data)`, see [`Socket::emit_with_ack`]
+    /// for the errors and the acknowledgement stream.
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("sync", |socket: SocketRef| async move {
+    ///         let ack = socket
+    ///             .emit_with_ack_timeout::<_, Value>("sync", "state", Duration::from_millis(500))
+    ///             .unwrap();
+    ///         match ack.await {
+    ///             Ok(data) => println!("synced: {:?}", data),
+    ///             Err(err) => println!("sync failed: {:?}", err),
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn emit_with_ack_timeout<T: Serialize, V: DeserializeOwned>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+        timeout: Duration,
+    ) -> Result<AckStream<V>, SendError<T>> {
+        let event = event.into();
+        let permits = match self.reserve_event(1, &event) {
             Ok(permits) => permits,
             Err(e) => {
                 #[cfg(feature = "tracing")]
@@ -393,10 +1671,139 @@ impl<A: Adapter> Socket<A> {
             }
         };
         let data = serde_json::to_value(data)?;
-        let packet = Packet::event(self.ns(), event.into(), data);
-        let rx = self.send_with_ack_permit(packet, permits);
-        let stream = AckInnerStream::send(rx, self.config.ack_timeout, self.id);
-        Ok(AckStream::<V>::from(stream))
+        let packet = Packet::event(self.ns(), event, data);
+        let (rx, ack_id) = self.send_with_ack_permit(packet, permits);
+        let stream = AckInnerStream::send(rx, timeout, self.id, self.config.clock.as_ref());
+        Ok(AckStream::<V>::from(stream).with_ack_id(ack_id))
+    }
+
+    /// Gets the ids of the acks awaited from the client, in no particular order.
+    ///
+    /// Acks whose [`AckStream`] was dropped are not returned.
+    pub fn pending_acks(&self) -> Vec<i64> {
+        self.ack_message
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, tx)| !tx.is_closed())
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Stops waiting for the ack with the given id, see [`AckStream::ack_id`].
+    /// The corresponding [`AckStream`] immediately yields an [`AckError::Cancelled`]
+    /// and a late response from the client is ignored.
+    ///
+    /// Returns `false` if the ack was not pending anymore.
+    ///
+    /// [`AckError::Cancelled`]: crate::AckError::Cancelled
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| async move {
+    ///     let ack = socket.emit_with_ack::<_, Value>("confirm", "order-1").unwrap();
+    ///     let id = ack.ack_id().unwrap();
+    ///     // Later, the order was cancelled by another client
+    ///     socket.cancel_ack(id);
+    ///     assert!(ack.await.is_err());
+    /// });
+    /// ```
+    pub fn cancel_ack(&self, id: i64) -> bool {
+        let Some(tx) = self.ack_message.lock().unwrap().remove(&id) else {
+            return false;
+        };
+        self.ack_received.notify_waiters();
+        tx.send(Err(AckError::Cancelled)).is_ok()
+    }
+
+    /// Emits a sequence of events, one at a time: each step is only sent once the client acknowledged the
+    /// previous one. The returned future resolves with the [`SequenceProgress`] when the sequence completes
+    /// or stops.
+    ///
+    /// When a step is not acknowledged before the [`SequenceConfig::timeout`], it is sent again
+    /// up to [`SequenceConfig::retries`] times, then the sequence is aborted with an [`AckError::Timeout`].
+    /// A sequence is also aborted if the socket disconnects, if a step cannot be serialized or sent.
+    ///
+    /// [`AckError::Timeout`]: crate::AckError::Timeout
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::SequenceConfig};
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| async move {
+    ///     let steps = vec![("step", "welcome"), ("step", "profile"), ("step", "done")];
+    ///     let config = SequenceConfig::new()
+    ///         .timeout(Duration::from_secs(30))
+    ///         .retries(2);
+    ///     let progress = socket.emit_sequence(steps, config).await;
+    ///     if !progress.is_complete() {
+    ///         println!("tutorial stopped at step {}: {:?}", progress.acked, progress.error);
+    ///     }
+    /// });
+    /// ```
+    pub async fn emit_sequence<E, T>(
+        &self,
+        steps: Vec<(E, T)>,
+        config: SequenceConfig,
+    ) -> SequenceProgress
+    where
+        E: Into<Cow<'static, str>>,
+        T: Serialize,
+    {
+        let mut progress = SequenceProgress {
+            acked: 0,
+            total: steps.len(),
+            error: None,
+        };
+        for (event, data) in steps {
+            let event: Cow<'static, str> = event.into();
+            let mut retries = config.retries;
+            loop {
+                match self.emit_sequence_step(event.clone(), &data, &config).await {
+                    Ok(()) => break,
+                    Err(AckError::Timeout) if retries > 0 => retries -= 1,
+                    Err(e) => {
+                        progress.error = Some(e);
+                        return progress;
+                    }
+                }
+            }
+            progress.acked += 1;
+        }
+        progress
+    }
+
+    /// Sends a step of a sequence and waits for its acknowledgement
+    async fn emit_sequence_step<T: Serialize>(
+        &self,
+        event: Cow<'static, str>,
+        data: &T,
+        config: &SequenceConfig,
+    ) -> Result<(), AckError<()>> {
+        // The socket may have left the namespace while its engine.io connection is still open
+        if self.ns.get_socket(self.id).is_err() {
+            return Err(SocketError::Closed(()).into());
+        }
+        let mut op = ConfOperators::new(self);
+        if let Some(timeout) = config.timeout {
+            op = op.timeout(timeout);
+        }
+        let ack = op
+            .emit_with_ack::<_, IgnoredAny>(event, data)
+            .map_err(|e| match e {
+                SendError::Serialize(e) => AckError::Serde(e),
+                SendError::Socket(SocketError::InternalChannelFull(_)) => {
+                    SocketError::InternalChannelFull(()).into()
+                }
+                SendError::Socket(SocketError::Closed(_)) | SendError::SocketGone(_) => {
+                    SocketError::Closed(()).into()
+                }
+            })?;
+        ack.await.map(|_| ())
     }
 
     // Room actions
@@ -406,36 +1813,187 @@ impl<A: Adapter> Socket<A> {
     /// If the room does not exist, it will be created.
     ///
     /// ## Errors
+    /// If a guard was set with [`SocketIo::on_room_join_request`] and it refuses one of the rooms,
+    /// a [`JoinError::Refused`] is returned and none of the rooms are joined.
+    ///
+    /// If one of the rooms reached the capacity set in its [`RoomMeta`](crate::room::RoomMeta),
+    /// a [`JoinError::Full`] is returned and none of the rooms are joined.
+    ///
+    /// When using a distributed adapter, it can return a [`JoinError::Adapter`] which is mostly related to network errors.
+    ///
+    /// [`SocketIo::on_room_join_request`]: crate::SocketIo#method.on_room_join_request
+    pub fn join(&self, rooms: impl RoomParam) -> Result<(), JoinError<A::Error>> {
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        if let Some(room) = self.ns.refused_room(self, &rooms) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(sid = ?self.id, "join of room {room} refused");
+            return Err(JoinError::Refused(room.clone()));
+        }
+        let capacity = self.ns.check_capacity(self.id, &rooms)?;
+        self.ns.adapter.add_all(self.id, rooms.clone())?;
+        drop(capacity);
+        self.ns.rooms_joined(self.id, &rooms);
+        Ok(())
+    }
+
+    /// Joins the given rooms like [`Socket::join`] and returns the ones the socket was not already in.
+    /// A join retried after a success then returns an empty list.
+    ///
+    /// The errors are the same as the [`join()`](Socket::join) ones.
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     let joined = socket.try_join(["lobby", "game-1"]).unwrap();
+    ///     assert_eq!(joined.len(), 2);
+    ///     // Already a member: nothing to count
+    ///     assert!(socket.try_join("lobby").unwrap().is_empty());
+    /// });
+    /// ```
+    pub fn try_join(&self, rooms: impl RoomParam) -> Result<Vec<Room>, JoinError<A::Error>> {
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        if let Some(room) = self.ns.refused_room(self, &rooms) {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(sid = ?self.id, "join of room {room} refused");
+            return Err(JoinError::Refused(room.clone()));
+        }
+        let capacity = self.ns.check_capacity(self.id, &rooms)?;
+        let joined = self.ns.adapter.add_new(self.id, rooms)?;
+        drop(capacity);
+        self.ns.rooms_joined(self.id, &joined);
+        Ok(joined)
+    }
+
+    /// Leaves the given rooms.
+    ///
+    /// If the room does not exist, it will do nothing
+    /// ## Errors
+    /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
+    /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
+    pub fn leave(&self, rooms: impl RoomParam) -> Result<(), A::Error> {
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        self.ns.adapter.del(self.id, rooms.clone())?;
+        self.ns.rooms_left(self.id, &rooms);
+        Ok(())
+    }
+
+    /// Removes the socket from the given room and emits the given event to its client,
+    /// with the room name as data, so that it can update its state.
+    ///
+    /// The membership check, the removal and the reservation of the notification buffer space happen
+    /// while the membership of the room cannot change (with the [`LocalAdapter`] and the adapters
+    /// implementing [`Adapter::del_if_in_room`]): the socket is never removed without its client being
+    /// notified, and the broadcasts to the room selecting their sockets afterwards don't reach it.
+    ///
+    /// Returns `false` if the socket was not in the room, the client is then not notified.
+    ///
+    /// ## Errors
+    /// * If the packet buffer is full or if the socket is closed, a [`KickError::Socket`] is returned.
+    /// * When using a distributed adapter, it can return a [`KickError::Adapter`]
+    ///   which is mostly related to network errors.
+    ///
+    /// In both cases, the socket is left in the room.
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.join("game-1").unwrap();
+    ///     socket.on("cheat", |socket: SocketRef| {
+    ///         socket.kick_from_room("game-1", "kicked").ok();
+    ///     });
+    /// });
+    /// ```
+    pub fn kick_from_room(
+        &self,
+        room: impl Into<Room>,
+        event: impl Into<Cow<'static, str>>,
+    ) -> Result<bool, KickError<A::Error>> {
+        let (room, event) = (room.into(), event.into());
+        let mut error = None;
+        let permits = self.ns.adapter.del_if_in_room(self.id, &room, || {
+            self.reserve(1).map_err(|e| error = Some(e)).ok()
+        })?;
+        // The undelivered hook runs user code, it is called once the membership lock is released
+        if let Some(e) = error {
+            self.report_undelivered(&event, &e);
+            return Err(KickError::Socket(e));
+        }
+        let Some(permits) = permits else {
+            return Ok(false);
+        };
+        self.ns.rooms_left(self.id, std::slice::from_ref(&room));
+        let data = Value::String(room.into_owned());
+        self.send_with_permits(Packet::event(self.ns(), event, data), permits);
+        Ok(true)
+    }
+
+    /// Replaces the rooms of the socket with exactly the given rooms.
+    ///
+    /// Unlike a [`leave_all`](Socket::leave_all) followed by a [`join`](Socket::join),
+    /// the rooms that are kept are never left and, with the [`LocalAdapter`], the whole change is atomic:
+    /// a concurrent broadcast either targets the socket with its previous rooms or with the new ones.
+    ///
+    /// ## Errors
+    /// If one of the new rooms reached the capacity set in its [`RoomMeta`](crate::room::RoomMeta),
+    /// a [`JoinError::Full`] is returned and the rooms of the socket are left unchanged.
+    ///
+    /// When using a distributed adapter, it can return a [`JoinError::Adapter`] which is mostly related to network errors.
+    pub fn set_rooms(&self, rooms: impl RoomParam) -> Result<(), JoinError<A::Error>> {
+        let rooms: Vec<Room> = rooms.into_room_iter().collect();
+        let capacity = self.ns.check_capacity(self.id, &rooms)?;
+        let (joined, left) = self.ns.adapter.set_rooms(self.id, rooms)?;
+        drop(capacity);
+        self.ns.rooms_joined(self.id, &joined);
+        self.ns.rooms_left(self.id, &left);
+        Ok(())
+    }
+
+    /// Leaves all rooms where the socket is connected.
+    /// ## Errors
+    /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
+    /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
+    pub fn leave_all(&self) -> Result<(), A::Error> {
+        self.ns.adapter.del_all(self.id)?;
+        self.ns.rooms_left_all(self.id);
+        Ok(())
+    }
+
+    /// Gets all rooms where the socket is connected.
+    /// ## Errors
     /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
     /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
-    pub fn join(&self, rooms: impl RoomParam) -> Result<(), A::Error> {
-        self.ns.adapter.add_all(self.id, rooms)
+    pub fn rooms(&self) -> Result<Vec<Room>, A::Error> {
+        self.ns.adapter.socket_rooms(self.id)
     }
 
-    /// Leaves the given rooms.
+    /// Gets the rooms of the socket tracked on this server node, see [`Adapter::local_socket_rooms`].
     ///
-    /// If the room does not exist, it will do nothing
-    /// ## Errors
-    /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
-    /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
-    pub fn leave(&self, rooms: impl RoomParam) -> Result<(), A::Error> {
-        self.ns.adapter.del(self.id, rooms)
+    /// Unlike [`Socket::rooms`], it never makes a network call with a distributed adapter,
+    /// so it can be used on hot paths. The result may be stale relative to the cluster,
+    /// e.g. when another node just made the socket join a room.
+    /// With the [`LocalAdapter`], both methods are equivalent.
+    pub fn local_rooms(&self) -> Vec<Room> {
+        self.ns.adapter.local_socket_rooms(self.id)
     }
 
-    /// Leaves all rooms where the socket is connected.
+    /// Returns `true` if the socket is in the given room, without fetching all its rooms.
     /// ## Errors
     /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
     /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
-    pub fn leave_all(&self) -> Result<(), A::Error> {
-        self.ns.adapter.del_all(self.id)
+    pub fn is_in_room(&self, room: impl Into<Room>) -> Result<bool, A::Error> {
+        self.ns.adapter.is_in_room(self.id, &room.into())
     }
 
-    /// Gets all rooms where the socket is connected.
+    /// Gets the number of sockets connected to the namespace of this socket, itself included.
+    /// A socket in several rooms is counted once.
+    ///
     /// ## Errors
     /// When using a distributed adapter, it can return an [`Adapter::Error`] which is mostly related to network errors.
     /// For the default [`LocalAdapter`] it is always an [`Infallible`](std::convert::Infallible) error
-    pub fn rooms(&self) -> Result<Vec<Room>, A::Error> {
-        self.ns.adapter.socket_rooms(self.id)
+    pub fn sockets_count(&self) -> Result<usize, A::Error> {
+        self.ns.adapter.sockets_count()
     }
 
     // Socket operators
@@ -464,6 +2022,33 @@ impl<A: Adapter> Socket<A> {
         BroadcastOperators::from_sock(self.ns.clone(), self.id).to(rooms)
     }
 
+    /// Emits a message to all clients in the given rooms except the current socket.
+    /// It is a shorthand for `socket.to(rooms).emit(event, data)` and behaves the same way.
+    ///
+    /// # Errors
+    /// If the data cannot be serialized, a [`BroadcastError::Serialize`] is returned.
+    /// Sockets that cannot receive the message are reported in a [`BroadcastError::Socket`].
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("message", |socket: SocketRef, Data::<Value>(data)| {
+    ///         socket.emit_to("chat", "message", data).ok();
+    ///     });
+    /// });
+    /// ```
+    pub fn emit_to<T: Serialize>(
+        &self,
+        rooms: impl RoomParam,
+        event: impl Into<Cow<'static, str>>,
+        data: T,
+    ) -> Result<(), BroadcastError> {
+        self.to(rooms).emit(event, data)
+    }
+
     /// Selects all clients in the given rooms.
     ///
     /// It does include the current socket contrary to the `to()` operator.
@@ -538,6 +2123,11 @@ impl<A: Adapter> Socket<A> {
     ///
     /// [`emit_with_ack()`]: #method.emit_with_ack
     ///
+    /// Timeouts longer than [`MAX_ACK_TIMEOUT`](crate::operators::MAX_ACK_TIMEOUT) are clamped to it.
+    ///
+    /// # Panics
+    /// If the timeout is [`Duration::ZERO`].
+    ///
     /// # Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::*};
@@ -570,6 +2160,9 @@ impl<A: Adapter> Socket<A> {
     }
 
     /// Adds a binary payload to the message.
+    ///
+    /// With [`SocketIoBuilder::omit_null_binary_data`](crate::SocketIoBuilder#method.omit_null_binary_data),
+    /// a `null` data (e.g. `()` or `Value::Null`) is omitted and only the binary payloads are sent.
     /// # Example
     /// ```
     /// # use socketioxide::{SocketIo, extract::*};
@@ -586,6 +2179,28 @@ impl<A: Adapter> Socket<A> {
         ConfOperators::new(self).bin(binary)
     }
 
+    /// Sends the given fallback data instead of the message if the client is still on the polling transport.
+    /// See [`ConfOperators::prefer_websocket`] for more details.
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::json;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("snapshot", |socket: SocketRef| {
+    ///         let snapshot = vec![0u8; 1 << 20];
+    ///         // Polling clients are only notified that the snapshot is available
+    ///         socket
+    ///             .prefer_websocket(json!({ "available": true }))
+    ///             .bin(vec![snapshot])
+    ///             .emit("snapshot", json!({ "size": 1 << 20 }))
+    ///             .ok();
+    ///     });
+    /// });
+    pub fn prefer_websocket(&self, fallback: Value) -> ConfOperators<'_, A> {
+        ConfOperators::new(self).prefer_websocket(fallback)
+    }
+
     /// Broadcasts to all clients without any filtering (except the current socket).
     /// # Example
     /// ```
@@ -603,19 +2218,207 @@ impl<A: Adapter> Socket<A> {
         BroadcastOperators::from_sock(self.ns.clone(), self.id).broadcast()
     }
 
+    /// Broadcasts a message to all clients of the namespace (except the current socket) and waits
+    /// for their acknowledgements, with the ack timeout specified in the config.
+    /// It is a shorthand for `socket.broadcast().emit_with_ack(event, data)`.
+    ///
+    /// The returned [`AckStream`] is a [`Stream`] yielding one item per targeted socket,
+    /// with its [`Sid`], as soon as it responds. A socket that doesn't respond in time yields an
+    /// [`AckError::Timeout`] item, without affecting the other sockets. The stream ends once every
+    /// targeted socket has responded or timed out.
+    ///
+    /// Use [`broadcast()`](#method.broadcast) to customize the timeout or the binary payload.
+    ///
+    /// [`Stream`]: futures::stream::Stream
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use futures::stream::StreamExt;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("poll", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         let acks = socket.broadcast_with_ack::<String>("poll", data).unwrap();
+    ///         acks.for_each(|(id, ack)| async move {
+    ///             match ack {
+    ///                 Ok(ack) => println!("Vote of socket {}: {}", id, ack.data),
+    ///                 Err(err) => println!("No vote from socket {}: {:?}", id, err),
+    ///             }
+    ///         }).await;
+    ///     });
+    /// });
+    pub fn broadcast_with_ack<V>(
+        &self,
+        event: impl Into<Cow<'static, str>>,
+        data: impl Serialize,
+    ) -> Result<AckStream<V>, serde_json::Error> {
+        self.broadcast().emit_with_ack(event, data)
+    }
+
+    /// Selects all clients having the given tags (except the current socket), see [`Socket::add_tag`].
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // This message will be broadcast to all the other beta testers
+    ///         socket.with_tag("beta").emit("test", data);
+    ///     });
+    /// });
+    /// ```
+    pub fn with_tag(&self, tags: impl RoomParam) -> BroadcastOperators<A> {
+        BroadcastOperators::from_sock(self.ns.clone(), self.id).with_tag(tags)
+    }
+
+    /// Selects all clients connected at or after the given instant (except the current socket).
+    /// It is a shorthand for `socket.broadcast().connected_after(instant)`.
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // This message will be sent to the clients that connected after this one
+    ///         socket.connected_after(socket.connected_at()).emit("test", data);
+    ///     });
+    /// });
+    /// ```
+    pub fn connected_after(&self, instant: Instant) -> BroadcastOperators<A> {
+        self.broadcast().connected_after(instant)
+    }
+
+    /// Selects all clients connected within the given duration (except the current socket).
+    /// It is a shorthand for `socket.broadcast().connected_within(duration)`,
+    /// see [`BroadcastOperators::connected_within`].
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::Value;
+    /// # use std::time::Duration;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("test", |socket: SocketRef, Data::<Value>(data)| async move {
+    ///         // This message will be sent to the clients that connected in the last minute
+    ///         socket.connected_within(Duration::from_secs(60)).emit("test", data);
+    ///     });
+    /// });
+    pub fn connected_within(&self, duration: Duration) -> BroadcastOperators<A> {
+        self.broadcast().connected_within(duration)
+    }
+
     /// Disconnects the socket from the current namespace,
     ///
     /// It will also call the disconnect handler if it is set.
     pub fn disconnect(self: Arc<Self>) -> Result<(), DisconnectError> {
+        self.disconnect_with_reason(DisconnectReason::ServerNSDisconnect)
+    }
+
+    /// Disconnects the socket from the current namespace, after handing the client a payload
+    /// describing why with the reserved [`DISCONNECT_PAYLOAD_EVENT`] event.
+    ///
+    /// The payload event and the disconnect packet are queued together,
+    /// so the payload always reaches the client before the disconnect packet.
+    /// It will also call the disconnect handler if it is set.
+    ///
+    /// ## Errors
+    /// * When encoding the payload into JSON a [`DisconnectError::Serialize`] is returned.
+    /// * If the packet buffer cannot hold both packets a [`DisconnectError::InternalChannelFull`] is returned.
+    ///
+    /// In both cases nothing is sent and the socket stays connected.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// # use serde_json::json;
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("logout", |socket: SocketRef| {
+    ///         socket.disconnect_with(json!({ "code": "session_expired" })).ok();
+    ///     });
+    /// });
+    /// ```
+    pub fn disconnect_with<T: Serialize>(
+        self: Arc<Self>,
+        payload: T,
+    ) -> Result<(), DisconnectError> {
+        let data = serde_json::to_value(payload)?;
+        {
+            let event = Packet::event(self.ns(), DISCONNECT_PAYLOAD_EVENT, data);
+            let disconnect = Packet::disconnect(&self.ns.path);
+            // Both packets are reserved before sending any of them so that the payload
+            // is never sent without the disconnect packet
+            let permits = self
+                .reserve_packet(&event)
+                .and_then(|permits| Ok((permits, self.reserve_packet(&disconnect)?)));
+            match permits {
+                Ok((event_permits, disconnect_permits)) => {
+                    self.send_with_permits(event, event_permits);
+                    self.write_packet(disconnect, disconnect_permits);
+                }
+                Err(SocketError::InternalChannelFull(_)) => {
+                    return Err(DisconnectError::InternalChannelFull)
+                }
+                Err(SocketError::Closed(_)) => (),
+            }
+        }
+
+        self.close(DisconnectReason::ServerNSDisconnect)?;
+        Ok(())
+    }
+
+    /// Disconnects the socket from the current namespace on behalf of the [`Adapter`],
+    /// the disconnect handler is called with [`DisconnectReason::ServerAdapterClose`].
+    ///
+    /// It is meant for adapter implementations evicting a socket when told so by another node,
+    /// applications should use [`Socket::disconnect`].
+    pub fn adapter_disconnect(self: Arc<Self>) -> Result<(), DisconnectError> {
+        self.disconnect_with_reason(DisconnectReason::ServerAdapterClose)
+    }
+
+    /// Disconnects the socket from the namespace, calling the disconnect handler with the given reason.
+    pub(crate) fn disconnect_with_reason(
+        self: Arc<Self>,
+        reason: DisconnectReason,
+    ) -> Result<(), DisconnectError> {
         let res = self.send(Packet::disconnect(&self.ns.path));
         if let Err(SocketError::InternalChannelFull(_)) = res {
             return Err(DisconnectError::InternalChannelFull);
         }
 
-        self.close(DisconnectReason::ServerNSDisconnect)?;
+        self.close(reason)?;
         Ok(())
     }
 
+    /// Sends the namespace CONNECT packet to the client again, with the same payload as the one sent
+    /// when the socket connected. It can be used by recovery tooling when the client wrongly
+    /// thinks it is disconnected from the namespace.
+    ///
+    /// The server state is left untouched: no new socket is created and the connect handler is not called.
+    pub fn resend_connect(&self) -> Result<(), SocketError<()>> {
+        self.send(Packet::connect(&self.ns.path, self.id, self.protocol()))
+    }
+
+    /// Gets the instant at which this socket connected, according to the configured [`Clock`](crate::clock::Clock).
+    pub fn connected_at(&self) -> Instant {
+        self.connected_at
+    }
+
+    /// Gets the time elapsed since the client last sent a packet to this socket,
+    /// or since the socket connected if it never did.
+    ///
+    /// The time is measured with the configured [`Clock`](crate::clock::Clock).
+    pub fn idle_for(&self) -> Duration {
+        let last = Duration::from_nanos(self.last_activity.load(Ordering::Relaxed));
+        self.config
+            .clock
+            .now()
+            .saturating_duration_since(self.connected_at + last)
+    }
+
     /// Closes the engine.io connection if it is not already closed.
     /// Return a future that resolves when the underlying transport is closed.
     pub(crate) async fn close_underlying_transport(&self) {
@@ -627,59 +2430,584 @@ impl<A: Adapter> Socket<A> {
         self.esocket.closed().await;
     }
 
-    /// Gets the current namespace path.
-    #[inline]
-    pub fn ns(&self) -> &str {
-        &self.ns.path
+    /// Associates this socket with a user id, so that it can be reached with [`SocketIo::emit_to_user`].
+    ///
+    /// It is typically called in the connect handler, once the auth data has been verified.
+    /// A user can have many sockets (multiple tabs or devices). Calling this method again replaces
+    /// the previous user id. The association is removed when the socket disconnects.
+    ///
+    /// [`SocketIo::emit_to_user`]: crate::SocketIo#method.emit_to_user
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// #[derive(serde::Deserialize)]
+    /// struct Auth {
+    ///     token: String,
+    /// }
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data(auth): Data<Auth>| {
+    ///     // Verify the token and get the user id from it
+    ///     let user_id = auth.token;
+    ///     socket.set_user_id(user_id);
+    /// });
+    /// ```
+    pub fn set_user_id(&self, user_id: impl Into<String>) {
+        let user_id = user_id.into();
+        let mut current = self.user_id.lock().unwrap();
+        self.ns
+            .set_user(self.id, current.as_deref(), user_id.clone());
+        current.replace(user_id);
+    }
+
+    /// Gets the W3C trace context sent by the client when connecting, see the [`TraceContext`] extractor.
+    ///
+    /// [`TraceContext`]: crate::extract::TraceContext
+    pub fn trace_parent(&self) -> Option<&TraceParent> {
+        self.trace_parent.as_ref()
+    }
+
+    /// Gets the [`Handshake`] details of the socket: the auth payload, query params,
+    /// headers and address of the client when it connected.
+    ///
+    /// It can also be accessed as an extractor:
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::Handshake};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, handshake: Handshake| {
+    ///     let room = handshake.query.get("room").cloned().unwrap_or_default();
+    ///     println!("auth: {}, user agent: {:?}", handshake.auth, handshake.headers.get("user-agent"));
+    ///     socket.join(room).ok();
+    ///     assert_eq!(socket.handshake().auth, handshake.auth);
+    /// });
+    /// ```
+    pub fn handshake(&self) -> &Handshake {
+        &self.handshake
+    }
+
+    /// Sets the auth payload of the handshake and the trace context from its `traceparent` field,
+    /// if it was not sent in the handshake headers.
+    pub(crate) fn set_auth(&mut self, auth: Option<&str>) {
+        let auth = auth
+            .and_then(|a| serde_json::from_str::<Value>(a).ok())
+            .unwrap_or_default();
+        if self.trace_parent.is_none() {
+            self.trace_parent = auth
+                .get("traceparent")
+                .and_then(|t| t.as_str())
+                .and_then(TraceParent::parse);
+        }
+        self.session_id = auth
+            .get(SESSION_ID_AUTH_KEY)
+            .and_then(|s| s.as_str())
+            .filter(|s| s.len() <= MAX_SESSION_ID_LEN)
+            .map(str::to_string);
+        self.handshake.auth = auth;
+    }
+
+    /// Gets the session id sent by the client in the `sessionId` field of its auth payload.
+    ///
+    /// Unlike the socket id, the session id is chosen by the client and stays the same when it reconnects.
+    /// A session id longer than [`MAX_SESSION_ID_LEN`] is ignored.
+    /// With [`SocketIoBuilder::session_retention`], the last socket of each session is indexed by the namespace,
+    /// see [`SocketIo::session_sid`], and the session is remembered for the retention duration
+    /// after its last socket disconnected.
+    ///
+    /// **Note**: the session id is not authenticated, a client can send the session id of another client.
+    /// Check that it belongs to the authenticated user before trusting it.
+    ///
+    /// [`SocketIo::session_sid`]: crate::SocketIo#method.session_sid
+    /// [`SocketIoBuilder::session_retention`]: crate::SocketIoBuilder#method.session_retention
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Restores the [`extensions`](Socket::extensions) of the previous socket of the session,
+    /// moved out of it when it disconnected. Returns `false` if there is nothing to restore,
+    /// which is always the case without [`SocketIoBuilder::session_retention`](crate::SocketIoBuilder#method.session_retention).
+    ///
+    /// Because the [`session_id`](Socket::session_id) is chosen by the client, the extensions are only restored
+    /// if both sockets have the same [`user_id`](Socket::user_id). The user id is set by the server with
+    /// [`Socket::set_user_id`], once the client is authenticated: it must be set before calling this method,
+    /// and a session whose previous socket had no user id is never restored.
+    ///
+    /// The extensions are moved after the disconnect handler is called, so an async disconnect handler
+    /// must read them before its first await point.
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// #[derive(serde::Deserialize)]
+    /// struct Auth {
+    ///     token: String,
+    /// }
+    /// struct Cart(Vec<String>);
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data(auth): Data<Auth>| {
+    ///     // Verify the token and get the user id from it
+    ///     let user_id = auth.token;
+    ///     socket.set_user_id(user_id);
+    ///     // The cart of the previous socket of the session is only restored for the same user
+    ///     if !socket.restore_session() {
+    ///         socket.extensions.insert(Cart(Vec::new()));
+    ///     }
+    /// });
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+    #[cfg(feature = "extensions")]
+    pub fn restore_session(&self) -> bool {
+        let Some(user_id) = self.user_id() else {
+            return false;
+        };
+        let extensions = self
+            .session_id()
+            .and_then(|id| self.ns.take_session_extensions(id, &user_id));
+        match extensions {
+            Some(extensions) => {
+                self.extensions.extend(extensions);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Gets the user id previously set with [`Socket::set_user_id`].
+    pub fn user_id(&self) -> Option<String> {
+        self.user_id.lock().unwrap().clone()
+    }
+
+    /// Adds a tag to the socket, to target it with the [`with_tag()`](Socket::with_tag) operators.
+    ///
+    /// Contrary to rooms, tags are local to this server node and are not handled by the [`Adapter`].
+    /// They are lightweight static groups, usually set when the socket connects.
+    /// Returns `false` if the socket already had the tag.
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data(beta): Data<bool>| {
+    ///     if beta {
+    ///         socket.add_tag("beta");
+    ///     }
+    /// });
+    ///
+    /// // Later in your code
+    /// io.with_tag("beta").emit("feature", "new-ui").ok();
+    /// ```
+    pub fn add_tag(&self, tag: impl Into<String>) -> bool {
+        let tag = tag.into();
+        let mut tags = self.tags.lock().unwrap();
+        if tags.contains(&tag) {
+            return false;
+        }
+        self.ns.add_tag(self.id, tag.clone());
+        tags.insert(tag)
+    }
+
+    /// Removes a tag from the socket. Returns `false` if the socket did not have the tag.
+    pub fn remove_tag(&self, tag: &str) -> bool {
+        let removed = self.tags.lock().unwrap().remove(tag);
+        if removed {
+            self.ns.remove_tag(self.id, tag);
+        }
+        removed
+    }
+
+    /// Makes this socket an observer of its namespace: it receives a copy of every event broadcast
+    /// in the namespace, even to rooms it is not part of, and of the events emitted to a single socket
+    /// with the [`ObserverScope::All`] scope. Calling it again replaces the scope.
+    ///
+    /// The copies are sent without ack id, so an observer can't answer in place of the actual recipients.
+    /// Only the local sockets are observed when using an adapter spanning multiple nodes.
+    ///
+    /// **Warning**: an observer can read the whole namespace traffic, this method should only be called
+    /// once the socket has been authorized, for example by verifying an admin token in the connect handler
+    /// or in a middleware.
+    ///
+    /// # Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*, socket::ObserverScope};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef, Data(token): Data<String>| {
+    ///     if token == "admin-secret" {
+    ///         socket.make_observer(ObserverScope::Broadcasts);
+    ///     }
+    /// });
+    /// ```
+    pub fn make_observer(&self, scope: ObserverScope) {
+        self.ns.add_observer(self.id, scope);
+    }
+
+    /// Stops mirroring the namespace traffic to this socket, see [`Socket::make_observer`].
+    /// Returns `false` if the socket was not an observer.
+    pub fn stop_observing(&self) -> bool {
+        self.ns.remove_observer(self.id)
+    }
+
+    /// Gets the tags of the socket, see [`Socket::add_tag`].
+    pub fn tags(&self) -> Vec<String> {
+        self.tags.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Returns a future that resolves when there is no more ack response awaited from the client.
+    ///
+    /// Acks whose [`AckStream`] was dropped (e.g. after a timeout) are discarded every `recheck` interval.
+    pub(crate) async fn pending_acks_drained(&self, recheck: Duration) {
+        loop {
+            let received = self.ack_received.notified();
+            futures::pin_mut!(received);
+            {
+                let mut acks = self.ack_message.lock().unwrap();
+                acks.retain(|_, tx| !tx.is_closed());
+                if acks.is_empty() {
+                    return;
+                }
+            }
+            futures::future::select(received, self.config.clock.sleep(recheck)).await;
+        }
+    }
+
+    /// Gets the current namespace path.
+    #[inline]
+    pub fn ns(&self) -> &str {
+        &self.ns.path
+    }
+
+    /// Gets a parameter matched by the pattern of the namespace, if the namespace was created
+    /// from a [`SocketIo::dynamic_ns`](crate::SocketIo#method.dynamic_ns) pattern.
+    ///
+    /// For a socket connected to `/tenant/1` with the pattern `/tenant/:id`, `ns_param("id")` returns `"1"`.
+    pub fn ns_param(&self, name: &str) -> Option<&str> {
+        self.ns
+            .params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub(crate) fn reserve(&self, n: usize) -> Result<PermitIterator<'_>, SocketError<()>> {
+        Ok(self.esocket.reserve(n)?)
+    }
+
+    /// Reserves the permits of an event, reporting it to the [`SocketIoConfig::undelivered_hook`] on failure.
+    pub(crate) fn reserve_event(
+        &self,
+        n: usize,
+        event: &str,
+    ) -> Result<PermitIterator<'_>, SocketError<()>> {
+        self.reserve(n).map_err(|e| {
+            self.report_undelivered(event, &e);
+            e
+        })
+    }
+
+    /// Reserves the permits of a packet, reporting it to the [`SocketIoConfig::undelivered_hook`]
+    /// on failure if it is an event.
+    fn reserve_packet(&self, packet: &Packet<'_>) -> Result<PermitIterator<'_>, SocketError<()>> {
+        let n = 1 + packet.inner.payload_count();
+        match packet.inner.event_name() {
+            Some(event) => self.reserve_event(n, event),
+            None => self.reserve(n),
+        }
+    }
+
+    fn report_undelivered(&self, event: &str, err: &SocketError<()>) {
+        if let Some(hook) = &self.config.undelivered_hook {
+            hook.call(UndeliveredPacket {
+                sid: self.id,
+                ns: self.ns.path.clone(),
+                event: event.to_string(),
+                reason: err.into(),
+            });
+        }
+    }
+
+    /// Emits a packet to this socket only. Broadcasts use it for each selected socket,
+    /// so the packet is not mirrored to the observers here, see [`Namespace::mirror_to_observers`].
+    pub(crate) fn send(&self, packet: Packet<'_>) -> Result<(), SocketError<()>> {
+        let permits = self.reserve_packet(&packet)?;
+        self.write_packet(packet, permits);
+        Ok(())
+    }
+
+    /// Emits a packet addressed to this single socket and mirrors it to the [`ObserverScope::All`] observers.
+    fn send_direct(&self, packet: Packet<'_>) -> Result<(), SocketError<()>> {
+        let permits = self.reserve_packet(&packet)?;
+        self.send_with_permits(packet, permits);
+        Ok(())
+    }
+
+    /// Emits a packet addressed to this single socket with already reserved permits.
+    /// Events are mirrored to the [`ObserverScope::All`] observers of the namespace.
+    pub(crate) fn send_with_permits<'a>(
+        &'a self,
+        packet: Packet<'_>,
+        permits: impl PermitIteratorExt<'a>,
+    ) {
+        self.ns
+            .mirror_to_observers(&packet, true, |sid| sid == self.id);
+        self.write_packet(packet, permits);
+    }
+
+    /// Writes a packet with already reserved permits.
+    /// Events are stamped with a sequence number if [`SocketIoConfig::sequence_events`] is enabled
+    /// and their `null` fields are removed if [`SocketIoConfig::omit_none_fields`] is enabled.
+    /// The `null` data of the binary packets is removed if [`SocketIoConfig::omit_null_binary_data`] is enabled.
+    /// The data of the acks and of the events requesting one goes through the [`SocketIoConfig::ack_serializer`].
+    fn write_packet<'a>(&'a self, mut packet: Packet<'_>, permits: impl PermitIteratorExt<'a>) {
+        if self.config.omit_none_fields {
+            packet.inner.strip_null_fields();
+        }
+        if self.config.omit_null_binary_data {
+            packet.inner.omit_null_binary_data();
+        }
+        if let Some(serializer) = &self.config.ack_serializer {
+            if let Some(data) = packet.inner.ack_data_mut() {
+                *data = serializer.call(data.take());
+            }
+        }
+        if self.config.sequence_events && packet.inner.is_event() {
+            let mut seq = self.seq_counter.lock().unwrap();
+            *seq += 1;
+            packet.inner.set_seq(*seq);
+            self.buffer_for_replay(*seq, &packet);
+            self.emit_packet(packet, permits);
+        } else {
+            self.emit_packet(packet, permits);
+        }
+    }
+
+    /// Sends an already prepared packet, after the [`Socket::use_outbound`] middlewares,
+    /// and records it in the sent bytes and in the namespace throughput.
+    ///
+    /// The permits of the binary payloads added by the middlewares are reserved afterwards,
+    /// the packet is dropped and reported as undelivered if they cannot be.
+    fn emit_packet<'a>(&'a self, mut packet: Packet<'_>, permits: impl PermitIteratorExt<'a>) {
+        self.apply_outbound(&mut packet);
+        #[cfg(feature = "metrics")]
+        let event = packet.inner.is_event();
+        let missing = (1 + packet.inner.bin_count()).saturating_sub(permits.len());
+        let bytes = if missing == 0 {
+            permits.emit(packet)
+        } else {
+            match self.reserve(missing) {
+                Ok(extra) => permits
+                    .chain(extra)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .emit(packet),
+                Err(e) => {
+                    if let Some(event) = packet.inner.event_name() {
+                        self.report_undelivered(event, &e);
+                    }
+                    return;
+                }
+            }
+        };
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        #[cfg(feature = "metrics")]
+        self.ns
+            .throughput
+            .record_out(self.config.clock.now(), event, bytes);
     }
 
-    pub(crate) fn reserve(&self, n: usize) -> Result<PermitIterator<'_>, SocketError<()>> {
-        Ok(self.esocket.reserve(n)?)
+    /// Records the bytes of a packet received from the client
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
     }
 
-    pub(crate) fn send(&self, packet: Packet<'_>) -> Result<(), SocketError<()>> {
-        let permits = self.reserve(1 + packet.inner.payload_count())?;
-        permits.emit(packet);
-        Ok(())
+    fn apply_outbound(&self, packet: &mut Packet<'_>) {
+        let middlewares = self.outbound.read().unwrap();
+        if middlewares.is_empty() {
+            return;
+        }
+        let control = matches!(
+            packet.inner,
+            PacketData::Connect(_) | PacketData::Disconnect | PacketData::ConnectError(_)
+        );
+        for middleware in middlewares.iter().filter(|m| m.control || !control) {
+            (middleware.f)(packet);
+        }
     }
 
+    /// Emits a packet requesting an ack with already reserved permits.
+    /// The ack id is returned along with the receiver, unless the packet was not sent.
     pub(crate) fn send_with_ack_permit(
         &self,
         mut packet: Packet<'_>,
         permits: PermitIterator<'_>,
-    ) -> Receiver<AckResult<Value>> {
+    ) -> (Receiver<AckResult<Value>>, Option<i64>) {
         let (tx, rx) = oneshot::channel();
 
-        let ack = self.ack_counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let Some(ack) = self.register_ack(tx) else {
+            return (rx, None);
+        };
         packet.inner.set_ack_id(ack);
-        permits.emit(packet);
-        self.ack_message.lock().unwrap().insert(ack, tx);
-        rx
+        self.send_with_permits(packet, permits);
+        (rx, Some(ack))
     }
 
     pub(crate) fn send_with_ack(&self, mut packet: Packet<'_>) -> Receiver<AckResult<Value>> {
         let (tx, rx) = oneshot::channel();
 
-        let ack = self.ack_counter.fetch_add(1, Ordering::SeqCst) + 1;
+        let Some(ack) = self.register_ack(tx) else {
+            return rx;
+        };
         packet.inner.set_ack_id(ack);
-        match self.send(packet) {
-            Ok(()) => {
-                self.ack_message.lock().unwrap().insert(ack, tx);
-            }
-            Err(e) => {
+        if let Err(e) = self.send(packet) {
+            if let Some(tx) = self.ack_message.lock().unwrap().remove(&ack) {
                 tx.send(Err(AckError::Socket(e))).ok();
             }
         }
         rx
     }
 
+    /// Allocates an ack id and registers its sender, unless [`SocketIoConfig::max_pending_acks`]
+    /// is reached, in which case the sender is resolved with [`AckError::TooManyPending`].
+    ///
+    /// The lock is released before returning: the packet is sent afterwards because sending
+    /// runs user code (outbound middlewares, serializer, undelivered hook) that may emit with an ack.
+    fn register_ack(&self, tx: oneshot::Sender<AckResult<Value>>) -> Option<i64> {
+        let mut acks = self.ack_message.lock().unwrap();
+        if self.ack_limit_reached(&mut acks) {
+            tx.send(Err(AckError::TooManyPending)).ok();
+            return None;
+        }
+        let ack = self.ack_counter.fetch_add(1, Ordering::SeqCst) + 1;
+        acks.insert(ack, tx);
+        Some(ack)
+    }
+
+    /// Starts the application-level keepalive checks of the socket, see [`SocketIoConfig::app_keepalive`].
+    /// The first check is scheduled before returning, so that it is measured from the connection.
+    pub(crate) fn spawn_keepalive(self: &Arc<Self>, keepalive: AppKeepalive) {
+        let AppKeepalive {
+            interval,
+            missed_threshold,
+        } = keepalive;
+        let socket = Arc::downgrade(self);
+        let clock = self.config.clock.clone();
+        let mut tick = clock.sleep(interval);
+        tokio::spawn(async move {
+            let mut missed = 0;
+            loop {
+                tick.await;
+                let Some(socket) = socket.upgrade() else {
+                    return;
+                };
+                tick = clock.sleep(interval);
+                let packet = Packet::event(socket.ns.path.clone(), KEEPALIVE_EVENT, Value::Null);
+                let rx = socket.send_with_ack(packet);
+                match AckInnerStream::send(rx, interval, socket.id, clock.as_ref()).await {
+                    Ok(_) => missed = 0,
+                    Err(AckError::Socket(SocketError::Closed(()))) => return,
+                    Err(_e) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(sid = ?socket.id, "keepalive check missed: {_e:?}");
+                        missed += 1;
+                    }
+                }
+                if missed >= missed_threshold.max(1) {
+                    socket
+                        .disconnect_with_reason(DisconnectReason::KeepaliveTimeout)
+                        .ok();
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Spawns the task processing the events of the socket one at a time with [`EventOrdering::Fifo`].
+    ///
+    /// Each handler is called with a permit of a single-permit semaphore, released once the handler
+    /// (or the future of an async handler) has completed. The next handler waits for this permit.
+    pub(crate) fn spawn_event_queue(self: &Arc<Self>) {
+        let (tx, mut rx) = mpsc::channel::<InboundEvent>(EVENT_QUEUE_CAPACITY);
+        self.event_queue.lock().unwrap().replace(tx);
+        let socket = Arc::downgrade(self);
+        let running = Arc::new(Semaphore::new(1));
+        tokio::spawn(async move {
+            while let Some((e, data, bin, AckId(ack))) = rx.recv().await {
+                let Some(socket) = socket.upgrade() else {
+                    return;
+                };
+                let Some((event, handlers)) = socket.event_handlers(&e) else {
+                    continue;
+                };
+                // The data is only cloned for the handlers before the last one
+                let mut args = Some((data, bin));
+                let count = handlers.len();
+                for (i, handler) in handlers.into_iter().enumerate() {
+                    let Ok(permit) = running.clone().acquire_owned().await else {
+                        return;
+                    };
+                    let (data, bin) = match &args {
+                        Some((data, bin)) if i + 1 < count => (data.clone(), bin.clone()),
+                        _ => args.take().unwrap_or_default(),
+                    };
+                    let call = || {
+                        with_current_event(event.clone(), || {
+                            handler.call_with_permit(socket.clone(), data, bin, ack, permit)
+                        })
+                    };
+                    #[cfg(feature = "tracing")]
+                    let call = || socket.dispatch_span(&e).in_scope(call);
+                    call();
+                }
+            }
+        });
+    }
+
+    /// Checks if the socket awaits [`SocketIoConfig::max_pending_acks`] ack responses.
+    /// The acks whose receiver was dropped are discarded before counting.
+    fn ack_limit_reached(
+        &self,
+        acks: &mut HashMap<i64, oneshot::Sender<AckResult<Value>>>,
+    ) -> bool {
+        let Some(max) = self.config.max_pending_acks else {
+            return false;
+        };
+        if acks.len() >= max {
+            acks.retain(|_, tx| !tx.is_closed());
+        }
+        acks.len() >= max
+    }
+
+    /// Removes the disconnect handler so that it is not called when the socket is closed.
+    pub(crate) fn take_disconnect_handler(&self) -> Option<BoxedDisconnectHandler<A>> {
+        self.disconnect_handler.lock().unwrap().take()
+    }
+
     /// Called when the socket is gracefully disconnected from the server or the client
     ///
     /// It maybe also close when the underlying transport is closed or failed.
     pub(crate) fn close(self: Arc<Self>, reason: DisconnectReason) -> Result<(), AdapterError> {
+        // The rooms are read before the disconnect handler can make the socket leave them
+        if let Some(hook) = &self.config.disconnect_record_hook {
+            hook.call(DisconnectRecord {
+                sid: self.id,
+                ns: self.ns.path.clone(),
+                reason,
+                rooms: self.rooms().unwrap_or_default(),
+                connected_for: self.config.clock.now() - self.connected_at,
+                bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+                bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            });
+        }
         if let Some(handler) = self.disconnect_handler.lock().unwrap().take() {
             handler.call(self.clone(), reason);
         }
+        // The pending acks will never be received, they are resolved with a closed socket error
+        self.ack_message.lock().unwrap().clear();
+        self.has_event_stream.store(false, Ordering::SeqCst);
+        self.event_stream.lock().unwrap().take();
+        self.has_unhandled_stream.store(false, Ordering::SeqCst);
+        self.unhandled_stream.lock().unwrap().take();
+        self.event_queue.lock().unwrap().take();
+        if let Some(retention) = self.config.session_retention {
+            self.ns.end_session(&self, retention);
+        }
 
         self.ns.remove_socket(self.id)?;
         Ok(())
@@ -687,6 +3015,9 @@ impl<A: Adapter> Socket<A> {
 
     // Receives data from client:
     pub(crate) fn recv(self: Arc<Self>, packet: PacketData<'_>) -> Result<(), Error> {
+        let since_connect = self.config.clock.now() - self.connected_at;
+        let since_connect = u64::try_from(since_connect.as_nanos()).unwrap_or(u64::MAX);
+        self.last_activity.store(since_connect, Ordering::Relaxed);
         match packet {
             PacketData::Event(e, data, ack) => self.recv_event(&e, data, ack),
             PacketData::EventAck(data, ack_id) => self.recv_ack(data, ack_id),
@@ -720,6 +3051,18 @@ impl<A: Adapter> Socket<A> {
         self.esocket.transport_type()
     }
 
+    /// Gets the last round-trip time of the engine.io heartbeat: the time between the last ping sent
+    /// to the client and its pong. It is updated on each heartbeat cycle, see [`SocketIoBuilder::ping_interval`].
+    ///
+    /// It is `None` before the first pong is received, and always with the v3 protocol
+    /// where the pings are sent by the client.
+    /// All the namespace sockets of a client share the same engine.io connection and therefore the same latency.
+    ///
+    /// [`SocketIoBuilder::ping_interval`]: crate::SocketIoBuilder#method.ping_interval
+    pub fn latency(&self) -> Option<Duration> {
+        self.esocket.latency()
+    }
+
     /// Gets the socket.io [`ProtocolVersion`](crate::ProtocolVersion) used by the client to connect with this [`Socket`]
     ///
     /// It can also be accessed as an extractor:
@@ -735,10 +3078,130 @@ impl<A: Adapter> Socket<A> {
         self.esocket.protocol.into()
     }
 
+    /// A type map shared by all the namespace sockets of the same engine.io connection,
+    /// whereas the [`extensions`](Socket#structfield.extensions) field is specific to this namespace socket.
+    /// It can be used to keep a session across the namespaces joined by a client.
+    /// It is dropped with the underlying connection.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// struct UserId(String);
+    ///
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.engine_extensions().insert(UserId("alice".into()));
+    /// });
+    /// io.ns("/chat", |socket: SocketRef| {
+    ///     let user = socket.engine_extensions().get::<UserId>();
+    /// });
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "extensions")))]
+    #[cfg(feature = "extensions")]
+    pub fn engine_extensions(&self) -> &Extensions {
+        &self.esocket.data.extensions
+    }
+
+    /// Gets the number of packets queued by the engine.io socket and not yet written to the transport.
+    ///
+    /// A growing queue means that the client or the network can't keep up with the emitted packets.
+    /// Once it reaches the [`max_buffer_size`] of the config, emits fail with a [`SendError::Socket`] error.
+    ///
+    /// Binary payloads are counted as separate packets and the engine.io ping/pong packets are included.
+    ///
+    /// [`max_buffer_size`]: crate::SocketIoBuilder#method.max_buffer_size
+    pub fn transport_queue_len(&self) -> usize {
+        self.esocket.queue_len()
+    }
+
+    /// Gets the number of packets buffered for this client, to shed load before emitting.
+    /// It is the same as [`Socket::transport_queue_len`].
+    ///
+    /// Once it reaches the [`send_buffer_capacity`](Socket::send_buffer_capacity), emits fail with a
+    /// [`SocketError::InternalChannelFull`] error.
+    /// The buffer is shared by all the namespaces of the underlying engine.io connection.
+    ///
+    /// ## Example
+    /// ```
+    /// # use socketioxide::{SocketIo, extract::*};
+    /// let (_, io) = SocketIo::new_svc();
+    /// io.ns("/", |socket: SocketRef| {
+    ///     socket.on("tick", |socket: SocketRef| {
+    ///         // Drop the low priority updates when the client is lagging
+    ///         if socket.send_buffer_len() < socket.send_buffer_capacity() / 2 {
+    ///             socket.emit("update", "data").ok();
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn send_buffer_len(&self) -> usize {
+        self.esocket.queue_len()
+    }
+
+    /// Gets the maximum number of packets buffered for this client, see [`Socket::send_buffer_len`].
+    /// It is the [`max_buffer_size`](crate::SocketIoBuilder#method.max_buffer_size) of the config.
+    pub fn send_buffer_capacity(&self) -> usize {
+        self.esocket.queue_capacity()
+    }
+
+    /// Keeps a copy of a stamped event in the replay buffer, dropping the oldest one if it is full
+    fn buffer_for_replay(&self, seq: u64, packet: &Packet<'_>) {
+        let (Some(buffer), Some(size)) = (&self.replay_buffer, self.config.replay_buffer_size)
+        else {
+            return;
+        };
+        if size == 0 {
+            return;
+        }
+        let mut buffer = buffer.lock().unwrap();
+        if buffer.len() >= size {
+            buffer.pop_front();
+        }
+        buffer.push_back((seq, packet.clone().into_owned()));
+    }
+
+    /// Sends again the buffered events with a sequence number greater than the one requested by the client.
+    /// The replay stops if the internal buffer is full, the client can request it again.
+    fn replay(&self, data: Value) {
+        let from = match &data {
+            Value::Array(args) => args.first().and_then(Value::as_u64),
+            data => data.as_u64(),
+        };
+        let Some(from) = from else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(?data, "invalid replay request");
+            return;
+        };
+        let mut packets: Vec<_> = match &self.replay_buffer {
+            Some(buffer) => buffer
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(seq, _)| *seq > from)
+                .cloned()
+                .collect(),
+            None => return,
+        };
+        packets.sort_unstable_by_key(|(seq, _)| *seq);
+        for (_seq, packet) in packets {
+            match self.reserve(1 + packet.inner.payload_count()) {
+                Ok(permits) => self.emit_packet(packet, permits),
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!("replay stopped at seq {_seq}: {_e:?}");
+                    return;
+                }
+            }
+        }
+    }
+
     fn recv_event(self: Arc<Self>, e: &str, data: Value, ack: Option<i64>) -> Result<(), Error> {
-        if let Some(handler) = self.message_handlers.read().unwrap().get(e) {
-            handler.call(self.clone(), data, vec![], ack);
+        if e == REPLAY_EVENT && self.replay_buffer.is_some() {
+            self.replay(data);
+            return Ok(());
         }
+        self.forward_event(e, &data, &[], ack);
+        self.dispatch_event(e, data, vec![], ack);
         Ok(())
     }
 
@@ -748,12 +3211,168 @@ impl<A: Adapter> Socket<A> {
         packet: BinaryPacket,
         ack: Option<i64>,
     ) -> Result<(), Error> {
-        if let Some(handler) = self.message_handlers.read().unwrap().get(e) {
-            handler.call(self.clone(), packet.data, packet.bin, ack);
-        }
+        self.check_bin_payloads(&packet)?;
+        self.forward_event(e, &packet.data, &packet.bin, ack);
+        self.dispatch_event(e, packet.data, packet.bin, ack);
         Ok(())
     }
 
+    /// Gets the handlers registered for the event, or the fallback handler if there is none.
+    fn event_handlers(&self, e: &str) -> Option<(Cow<'static, str>, Vec<BoxedMessageHandler<A>>)> {
+        let handlers = self
+            .message_handlers
+            .read()
+            .unwrap()
+            .get_key_value(e)
+            .map(|(event, list)| (event.clone(), list.iter().map(|(_, h)| h.clone()).collect()));
+        match handlers {
+            Some(handlers) => Some(handlers),
+            None => {
+                let handler = self.fallback_handler.read().unwrap().clone()?;
+                Some((Cow::Owned(e.to_string()), vec![handler]))
+            }
+        }
+    }
+
+    /// Calls the handlers registered for the event, in registration order,
+    /// or the fallback handler if there is none.
+    /// The handlers are called without holding any lock, so that they can register or remove handlers.
+    ///
+    /// With [`EventOrdering::Fifo`], the event is pushed to the queue of the socket instead.
+    fn dispatch_event(self: Arc<Self>, e: &str, data: Value, bin: Vec<Vec<u8>>, ack: Option<i64>) {
+        let Some((data, bin)) = self.stream_unhandled(e, data, bin, ack) else {
+            return;
+        };
+        if self.config.event_ordering == EventOrdering::Fifo {
+            let queue = self.event_queue.lock().unwrap().clone();
+            if let Some(queue) = queue {
+                // Dropping the event would break the ordering, the socket is disconnected instead
+                if let Err(TrySendError::Full(_)) =
+                    queue.try_send((e.to_string(), data, bin, AckId(ack)))
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(sid = ?self.id, "event queue full, disconnecting socket");
+                    self.disconnect_with_reason(DisconnectReason::EventQueueFull)
+                        .ok();
+                }
+                return;
+            }
+        }
+        let Some((event, mut handlers)) = self.event_handlers(e) else {
+            return;
+        };
+        #[cfg(feature = "tracing")]
+        let _span = self.dispatch_span(e).entered();
+        // The data is only cloned for the handlers before the last one
+        let last = handlers.pop();
+        for handler in handlers {
+            let (data, bin) = (data.clone(), bin.clone());
+            with_current_event(event.clone(), || handler.call(self.clone(), data, bin, ack));
+        }
+        if let Some(handler) = last {
+            with_current_event(event, || handler.call(self.clone(), data, bin, ack));
+        }
+    }
+
+    /// Moves an event without message handler to the stream returned by [`Socket::event_stream`].
+    /// The data is given back if there is no stream or if the event has a handler.
+    fn stream_unhandled(
+        self: &Arc<Self>,
+        e: &str,
+        data: Value,
+        bin: Vec<Vec<u8>>,
+        ack: Option<i64>,
+    ) -> Option<(Value, Vec<Vec<u8>>)> {
+        if !self.has_unhandled_stream.load(Ordering::SeqCst) {
+            return Some((data, bin));
+        }
+        let mut stream = self.unhandled_stream.lock().unwrap();
+        let Some(tx) = stream.as_ref() else {
+            return Some((data, bin));
+        };
+        if self.message_handlers.read().unwrap().contains_key(e) {
+            return Some((data, bin));
+        }
+        let item = IncomingEvent {
+            event: e.to_string(),
+            data,
+            bin,
+            ack: ack.map(|ack_id| DeferredAck::new(self, ack_id)),
+        };
+        match tx.try_send(item) {
+            Ok(()) => None,
+            // The client awaits an ack: the event is dispatched as if there was no stream
+            Err(TrySendError::Full(item)) if item.ack.is_some() => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(sid = ?self.id, "event stream full, dispatching event {e}");
+                Some((item.data, item.bin))
+            }
+            Err(TrySendError::Full(_)) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(sid = ?self.id, "event stream full, dropping event {e}");
+                None
+            }
+            Err(TrySendError::Closed(item)) => {
+                self.has_unhandled_stream.store(false, Ordering::SeqCst);
+                *stream = None;
+                Some((item.data, item.bin))
+            }
+        }
+    }
+
+    /// Copies an event to the stream returned by [`Socket::events`], if any
+    fn forward_event(&self, event: &str, data: &Value, bin: &[Vec<u8>], ack: Option<i64>) {
+        if !self.has_event_stream.load(Ordering::SeqCst) {
+            return;
+        }
+        let mut stream = self.event_stream.lock().unwrap();
+        let Some(tx) = stream.as_ref() else {
+            return;
+        };
+        let item = (event.to_string(), data.clone(), bin.to_vec(), AckId(ack));
+        match tx.try_send(item) {
+            Ok(()) => (),
+            Err(TrySendError::Full(_)) => {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(sid = ?self.id, "event stream full, dropping event {event}");
+            }
+            Err(TrySendError::Closed(_)) => {
+                self.has_event_stream.store(false, Ordering::SeqCst);
+                *stream = None;
+            }
+        }
+    }
+
+    /// Checks that all the binary payloads announced by the packet header were received
+    fn check_bin_payloads(&self, packet: &BinaryPacket) -> Result<(), PacketDecodeError> {
+        if packet.is_complete() {
+            Ok(())
+        } else {
+            Err(PacketDecodeError::AttachmentMismatch {
+                protocol: self.protocol(),
+                announced: packet.payload_count,
+                found: packet.bin.len(),
+            })
+        }
+    }
+
+    /// Creates the span in which a message event is dispatched, linked to the client trace context
+    #[cfg(feature = "tracing")]
+    fn dispatch_span(&self, event: &str) -> tracing::Span {
+        let span = tracing::info_span!(
+            "socketio.event",
+            event,
+            sid = %self.id,
+            trace_id = tracing::field::Empty,
+            parent_id = tracing::field::Empty,
+        );
+        if let Some(trace) = &self.trace_parent {
+            span.record("trace_id", trace.trace_id());
+            span.record("parent_id", trace.parent_id());
+        }
+        span
+    }
+
     fn recv_ack(self: Arc<Self>, data: Value, ack: i64) -> Result<(), Error> {
         if let Some(tx) = self.ack_message.lock().unwrap().remove(&ack) {
             let res = AckResponse {
@@ -761,17 +3380,20 @@ impl<A: Adapter> Socket<A> {
                 binary: vec![],
             };
             tx.send(Ok(res)).ok();
+            self.ack_received.notify_waiters();
         }
         Ok(())
     }
 
     fn recv_bin_ack(self: Arc<Self>, packet: BinaryPacket, ack: i64) -> Result<(), Error> {
+        self.check_bin_payloads(&packet)?;
         if let Some(tx) = self.ack_message.lock().unwrap().remove(&ack) {
             let res = AckResponse {
                 data: packet.data,
                 binary: packet.bin,
             };
             tx.send(Ok(res)).ok();
+            self.ack_received.notify_waiters();
         }
         Ok(())
     }
@@ -828,4 +3450,463 @@ mod test {
             Err(SendError::Socket(SocketError::InternalChannelFull(_)))
         ));
     }
+
+    #[tokio::test]
+    async fn cancel_ack() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        let ack1 = socket.emit_with_ack::<_, Value>("test", 1).unwrap();
+        let ack2 = socket.emit_with_ack::<_, Value>("test", 2).unwrap();
+        assert_eq!(ack1.ack_id(), Some(1));
+        assert_eq!(ack2.ack_id(), Some(2));
+        let mut pending = socket.pending_acks();
+        pending.sort_unstable();
+        assert_eq!(pending, [1, 2]);
+
+        assert!(socket.cancel_ack(1));
+        assert!(!socket.cancel_ack(1));
+        assert!(matches!(ack1.await, Err(AckError::Cancelled)));
+        assert_eq!(socket.pending_acks(), [2]);
+
+        // A late response to the cancelled ack is ignored
+        socket
+            .clone()
+            .recv(Packet::ack("/", serde_json::json!(["late"]), 1).inner)
+            .unwrap();
+        socket
+            .clone()
+            .recv(Packet::ack("/", serde_json::json!(["ok"]), 2).inner)
+            .unwrap();
+        assert_eq!(ack2.await.unwrap().data, serde_json::json!(["ok"]));
+    }
+
+    #[tokio::test]
+    async fn max_concurrent_handlers() {
+        use crate::handler::ConcurrencyOverflowPolicy;
+        use tokio::sync::Semaphore;
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel::<&str>();
+        let release = Arc::new(Semaphore::new(0));
+        for (event, overflow) in [
+            ("queue", ConcurrencyOverflowPolicy::Queue(1)),
+            ("drop", ConcurrencyOverflowPolicy::Drop),
+        ] {
+            let (tx, release) = (tx.clone(), release.clone());
+            let opts = HandlerOpts {
+                max_concurrent: Some(2),
+                overflow,
+            };
+            let handler = move || async move {
+                tx.send(event).unwrap();
+                release.acquire().await.unwrap().forget();
+            };
+            socket.on_with_opts(event, handler, opts);
+        }
+        let recv = |event: &'static str| {
+            socket
+                .clone()
+                .recv(Packet::event("/", event, Value::Null).inner)
+                .unwrap()
+        };
+
+        // Only 2 handlers run, 1 event is queued and the 4th one is dropped
+        (0..4).for_each(|_| recv("queue"));
+        // 2 handlers run and the 2 other events are dropped
+        (0..4).for_each(|_| recv("drop"));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let mut started = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            started.push(event);
+        }
+        started.sort();
+        assert_eq!(started, ["drop", "drop", "queue", "queue"]);
+
+        release.add_permits(4);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(rx.try_recv().unwrap(), "queue");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn replace_handlers() {
+        use std::sync::atomic::AtomicBool;
+        const DISPATCHES: usize = 2000;
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        let dispatched = Arc::new(Mutex::new(Vec::new()));
+        let register = |handlers: &mut HandlersBuilder, version: u8| {
+            for event in ["a", "b"] {
+                let dispatched = dispatched.clone();
+                handlers.on(event, move || dispatched.lock().unwrap().push(version));
+            }
+        };
+        socket.replace_handlers(|h| register(h, 1));
+
+        // Every event finds a handler while the sets are swapped concurrently
+        let done = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for _ in 0..DISPATCHES {
+                    for event in ["a", "b"] {
+                        let packet = Packet::event("/", event, Value::Null).inner;
+                        socket.clone().recv(packet).unwrap();
+                    }
+                }
+                done.store(true, Ordering::SeqCst);
+            });
+            let mut version = 1;
+            while !done.load(Ordering::SeqCst) {
+                version = 3 - version;
+                socket.replace_handlers(|h| register(h, version));
+            }
+        });
+        let dispatched = std::mem::take(&mut *dispatched.lock().unwrap());
+        assert_eq!(dispatched.len(), 2 * DISPATCHES);
+        assert!(dispatched.iter().all(|v| *v == 1 || *v == 2));
+
+        // The events missing from the new set are not dispatched anymore
+        let (tx, rx) = std::sync::mpsc::channel();
+        socket.replace_handlers(|h| {
+            h.on("c", move || tx.send(()).unwrap());
+        });
+        for event in ["a", "b", "c"] {
+            let packet = Packet::event("/", event, Value::Null).inner;
+            socket.clone().recv(packet).unwrap();
+        }
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn transport_queue_len() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        assert_eq!(socket.transport_queue_len(), 0);
+
+        // The dummy transport never drains its queue
+        for i in 1..=3 {
+            socket.emit("test", "foo").unwrap();
+            assert_eq!(socket.transport_queue_len(), i);
+        }
+        // A binary packet is queued as the event and its attachment
+        socket.bin(vec![vec![1, 2, 3]]).emit("bin", "foo").unwrap();
+        assert_eq!(socket.transport_queue_len(), 5);
+    }
+
+    #[tokio::test]
+    async fn try_emit_would_block() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        while socket.send_buffer_len() < socket.send_buffer_capacity() {
+            socket.try_emit("test", "foo").unwrap();
+        }
+        let err = socket.try_emit("test", "foo").unwrap_err();
+        assert!(matches!(err, TryEmitError::WouldBlock("foo")));
+    }
+
+    #[tokio::test]
+    async fn emit_batched() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        let capacity = socket.send_buffer_capacity();
+
+        // A serialization error only fails its own item
+        let valid = HashMap::new();
+        let invalid = HashMap::from([((1, 2), 3)]);
+        let res = socket.emit_batched([("a", valid.clone()), ("b", invalid), ("c", valid)]);
+        assert!(matches!(
+            res[..],
+            [Ok(()), Err(SendError::Serialize(_)), Ok(())]
+        ));
+        assert_eq!(socket.send_buffer_len(), 2);
+
+        // Nothing is sent if the buffer can't hold all the items
+        let items = (0..capacity - 1).map(|i| ("test", i));
+        let res = socket.emit_batched(items);
+        assert_eq!(res.len(), capacity - 1);
+        for (i, res) in res.into_iter().enumerate() {
+            match res {
+                Err(SendError::Socket(SocketError::InternalChannelFull(data))) => {
+                    assert_eq!(data, i)
+                }
+                _ => panic!("unexpected result {res:?}"),
+            }
+        }
+        assert_eq!(socket.send_buffer_len(), 2);
+    }
+
+    #[tokio::test]
+    async fn emit_async_waits_for_space() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        let capacity = socket.send_buffer_capacity();
+        while socket.send_buffer_len() < capacity {
+            socket.emit("test", "foo").unwrap();
+        }
+
+        // Serialization errors are returned without waiting
+        let invalid = HashMap::from([((1, 2), 3)]);
+        let res = socket.emit_async("test", invalid).await;
+        assert!(matches!(res, Err(SendError::Serialize(_))));
+
+        let fut = socket.emit_async("test", "foo");
+        tokio::time::timeout(Duration::from_millis(10), fut)
+            .await
+            .unwrap_err();
+        // The cancelled emit did not take any space in the buffer
+        assert_eq!(socket.send_buffer_len(), capacity);
+    }
+
+    #[tokio::test]
+    async fn send_buffer() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        let capacity = socket.send_buffer_capacity();
+        assert!(capacity > 0);
+
+        // The dummy transport never drains its queue
+        while socket.send_buffer_len() < capacity {
+            socket.emit("test", "foo").unwrap();
+        }
+        let err = socket.emit("test", "foo").unwrap_err();
+        assert!(matches!(
+            err,
+            SendError::Socket(SocketError::InternalChannelFull(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn emit_if_in_room() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        assert!(!socket.emit_if_in_room("room1", "test", "foo").unwrap());
+
+        socket.join("room1").unwrap();
+        assert!(socket.emit_if_in_room("room1", "test", "foo").unwrap());
+
+        // The socket leaves concurrently with the emits
+        let leave = tokio::spawn({
+            let socket = socket.clone();
+            async move { socket.leave("room1").unwrap() }
+        });
+        while !leave.is_finished() {
+            socket.emit_if_in_room("room1", "test", "foo").unwrap();
+            tokio::task::yield_now().await;
+        }
+        assert!(!socket.emit_if_in_room("room1", "test", "foo").unwrap());
+    }
+
+    #[tokio::test]
+    async fn emit_if_in_room_with_hook_leaving_room() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        socket.join("room1").unwrap();
+        let weak = Arc::downgrade(&socket);
+        socket.use_outbound(move |_| {
+            if let Some(socket) = weak.upgrade() {
+                socket.leave("room1").unwrap();
+            }
+        });
+
+        // The middleware runs after the membership lock is released
+        assert!(socket.emit_if_in_room("room1", "test", "foo").unwrap());
+        assert!(!socket.emit_if_in_room("room1", "test", "foo").unwrap());
+    }
+
+    #[tokio::test]
+    async fn try_join() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        assert_eq!(
+            socket.try_join(["room1", "room2"]).unwrap(),
+            ["room1", "room2"]
+        );
+        // The retried join is a no-op
+        assert!(socket.try_join(["room1", "room2"]).unwrap().is_empty());
+        assert_eq!(socket.try_join(["room2", "room3"]).unwrap(), ["room3"]);
+
+        let mut rooms = socket.rooms().unwrap();
+        rooms.sort();
+        assert_eq!(rooms, ["room1", "room2", "room3"]);
+    }
+
+    #[tokio::test]
+    async fn is_in_room() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        socket.join("room1").unwrap();
+        assert!(socket.is_in_room("room1").unwrap());
+        assert!(!socket.is_in_room("room2").unwrap());
+        socket.leave("room1").unwrap();
+        assert!(!socket.is_in_room("room1").unwrap());
+    }
+
+    #[tokio::test]
+    async fn set_rooms() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket = ns.get_socket(sid).unwrap();
+        let handle = crate::room::RoomHandle::new(ns.clone(), "room1".into()).unwrap();
+        socket.join(["room1", "room2"]).unwrap();
+
+        socket.set_rooms(["room2", "room3"]).unwrap();
+        let mut rooms = socket.rooms().unwrap();
+        rooms.sort();
+        assert_eq!(rooms, ["room2", "room3"]);
+        assert!(!handle.contains(sid));
+
+        socket.set_rooms(Vec::<Room>::new()).unwrap();
+        assert!(socket.rooms().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn connected_after_keeps_selection() {
+        let (sid1, sid2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2]);
+        let (socket1, socket2) = (ns.get_socket(sid1).unwrap(), ns.get_socket(sid2).unwrap());
+        socket1.join("room1").unwrap();
+        socket2.join("room1").unwrap();
+        let instant = socket1.connected_at().min(socket2.connected_at());
+
+        let sids = |op: BroadcastOperators<LocalAdapter>| {
+            let mut sids: Vec<Sid> = op.sockets().unwrap().iter().map(|s| s.id).collect();
+            sids.sort();
+            sids
+        };
+        let mut all = vec![sid1, sid2];
+        all.sort();
+        // `within` includes the current socket, `to` and the shorthand exclude it
+        assert_eq!(sids(socket1.within("room1").connected_after(instant)), all);
+        assert_eq!(sids(socket1.to("room1").connected_after(instant)), [sid2]);
+        assert_eq!(sids(socket1.connected_after(instant)), [sid2]);
+    }
+
+    #[tokio::test]
+    async fn set_rooms_capacity() {
+        let (sid1, sid2) = (Sid::new(), Sid::new());
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid1, sid2]);
+        let (socket1, socket2) = (ns.get_socket(sid1).unwrap(), ns.get_socket(sid2).unwrap());
+        let meta = crate::room::RoomMeta {
+            capacity: Some(1),
+            ..Default::default()
+        };
+        ns.set_room_meta("full".into(), meta).unwrap();
+        socket1.join("full").unwrap();
+
+        // Keeping a room already joined doesn't count against its capacity
+        socket1.set_rooms(["full", "room1"]).unwrap();
+        assert!(matches!(
+            socket2.set_rooms(["full", "room1"]),
+            Err(JoinError::Full(room)) if room == "full"
+        ));
+        assert!(socket2.rooms().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    async fn dispatch_span_trace_parent() {
+        use tracing::field::{Field, Visit};
+        use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+        /// Collects the fields recorded on the spans after their creation
+        struct Recorded(Arc<Mutex<Vec<(String, String)>>>);
+        impl Visit for Recorded {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                let value = format!("{value:?}");
+                self.0.lock().unwrap().push((field.name().into(), value));
+            }
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((field.name().into(), value.into()));
+            }
+        }
+        impl<S: tracing::Subscriber> Layer<S> for Recorded {
+            fn on_record(
+                &self,
+                _: &tracing::Id,
+                values: &tracing::span::Record<'_>,
+                _: Context<'_, S>,
+            ) {
+                values.record(&mut Recorded(self.0.clone()));
+            }
+        }
+
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let close_fn = Box::new(move |_, _| ());
+        let esocket = engineioxide::Socket::new_dummy(sid, close_fn).into();
+        let mut socket: Socket = Socket::new(sid, ns, esocket, Arc::new(SocketIoConfig::default()));
+        let auth = r#"{"traceparent":"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"}"#;
+        socket.set_auth(Some(auth));
+
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(Recorded(fields.clone()));
+        tracing::subscriber::with_default(subscriber, || socket.dispatch_span("msg"));
+        assert_eq!(
+            *fields.lock().unwrap(),
+            [
+                ("trace_id".into(), "4bf92f3577b34da6a3ce929d0e0e4736".into()),
+                ("parent_id".into(), "00f067aa0ba902b7".into())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn max_pending_acks() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let config = SocketIoConfig {
+            max_pending_acks: Some(2),
+            ..Default::default()
+        };
+        let close_fn = Box::new(move |_, _| ());
+        let esocket = engineioxide::Socket::new_dummy(sid, close_fn).into();
+        let socket: Arc<Socket> = Socket::new(sid, ns, esocket, Arc::new(config)).into();
+
+        let ack1 = socket.emit_with_ack::<_, Value>("test", 1).unwrap();
+        let _ack2 = socket.emit_with_ack::<_, Value>("test", 2).unwrap();
+        let ack3 = socket.emit_with_ack::<_, Value>("test", 3).unwrap();
+        assert!(matches!(ack3.await, Err(AckError::TooManyPending)));
+        assert_eq!(socket.ack_message.lock().unwrap().len(), 2);
+
+        // A dropped ack frees a slot
+        drop(ack1);
+        let _ack4 = socket.emit_with_ack::<_, Value>("test", 4).unwrap();
+        let acks = socket.ack_message.lock().unwrap();
+        assert_eq!(acks.len(), 2);
+        assert!(acks.contains_key(&2) && acks.contains_key(&3));
+    }
+
+    #[tokio::test]
+    async fn emit_with_ack_from_outbound_middleware() {
+        let sid = Sid::new();
+        let ns = Namespace::<LocalAdapter>::new_dummy([sid]);
+        let socket: Arc<Socket> = Socket::new_dummy(sid, ns).into();
+        let weak = Arc::downgrade(&socket);
+        let pending = Arc::new(Mutex::new(Vec::new()));
+        let pending_clone = pending.clone();
+        socket.use_outbound(move |_| {
+            // The ack registry must not be locked while the middlewares run
+            if let Some(socket) = weak.upgrade() {
+                *pending_clone.lock().unwrap() = socket.pending_acks();
+            }
+        });
+
+        let _ack = socket.emit_with_ack::<_, Value>("test", 1).unwrap();
+        assert_eq!(*pending.lock().unwrap(), [1]);
+    }
 }
@@ -0,0 +1,115 @@
+//! Custom transports, to run the socket.io protocol over another channel than the engine.io
+//! HTTP long-polling and websocket transports (e.g. a message bus).
+//!
+//! A custom transport is a [`Stream`] of the [`Frame`]s received from the client and a [`Sink`]
+//! of the [`Frame`]s to send to it, attached to the server with [`SocketIo::attach_transport`].
+//! The frames are socket.io packets without any engine.io framing: a [`Frame::Text`] is an encoded
+//! [`Packet`] and each binary attachment of a packet is sent in its own [`Frame::Binary`], right after it.
+//! There is no heartbeat, the transport is responsible for detecting dead connections
+//! and for ending the stream.
+//!
+//! The client side can build and read the frames with the packet encoding of the [`packet`](crate::packet) module:
+//! * `String::from(packet)` encodes a [`Packet`], the binary payloads of a binary packet
+//!   must then be sent as [`Frame::Binary`] frames.
+//! * [`Packet::decode`] decodes a [`Frame::Text`], the payloads of a binary packet
+//!   are then added with [`BinaryPacket::add_payload`](crate::packet::BinaryPacket::add_payload).
+//!
+//! [`SocketIo::attach_transport`]: crate::SocketIo#method.attach_transport
+//! [`Packet`]: crate::packet::Packet
+//! [`Packet::decode`]: crate::packet::Packet::decode
+//!
+//! #### Example
+//! ```
+//! # use socketioxide::{SocketIo, extract::*, transport::Frame};
+//! # use futures::channel::mpsc;
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (_, io) = SocketIo::new_svc();
+//! io.ns("/", |socket: SocketRef| {});
+//!
+//! // Frames received from and sent to the message bus
+//! let (_in_tx, in_rx) = mpsc::unbounded::<Frame>();
+//! let (out_tx, _out_rx) = mpsc::unbounded::<Frame>();
+//! io.attach_transport(in_rx, out_tx);
+//! # }
+//! ```
+use std::sync::Arc;
+
+use engineioxide::{
+    handler::EngineIoHandler,
+    socket::{DetachedMessage, DisconnectReason as EIoDisconnectReason},
+    ProtocolVersion, Socket as EIoSocket,
+};
+use futures::{future::Either, Sink, SinkExt, Stream, StreamExt};
+use tokio::sync::mpsc;
+
+use crate::{adapter::Adapter, client::Client, socket::Sid};
+
+/// A frame sent or received over a custom transport, see the [module](self) doc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// An encoded socket.io packet
+    Text(String),
+    /// A binary attachment of the previous packet
+    Binary(Vec<u8>),
+}
+
+/// Drives a connection over a custom transport until the stream ends or the socket is closed.
+pub(crate) fn attach<A, St, Si>(client: Arc<Client<A>>, incoming: St, outgoing: Si) -> Sid
+where
+    A: Adapter,
+    St: Stream<Item = Frame> + Send + 'static,
+    Si: Sink<Frame> + Send + 'static,
+{
+    let (close_tx, mut close_rx) = mpsc::unbounded_channel();
+    let socket = Arc::new(EIoSocket::new_detached(
+        ProtocolVersion::V4,
+        client.config.engine_config.max_buffer_size,
+        http::Request::<()>::default().into_parts().0,
+        Box::new(move |_, reason| {
+            close_tx.send(reason).ok();
+        }),
+    ));
+    let sid = socket.id;
+
+    let writer = socket.clone();
+    tokio::spawn(async move {
+        futures::pin_mut!(outgoing);
+        while let Some(msg) = writer.recv_detached().await {
+            let frame = match msg {
+                DetachedMessage::Text(msg) => Frame::Text(msg),
+                DetachedMessage::Binary(bin) => Frame::Binary(bin),
+            };
+            if outgoing.send(frame).await.is_err() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(?sid, "custom transport sink closed");
+                writer.close(EIoDisconnectReason::TransportError);
+                break;
+            }
+        }
+    });
+
+    client.on_connect(socket.clone());
+    tokio::spawn(async move {
+        futures::pin_mut!(incoming);
+        let reason = loop {
+            let closed = Box::pin(close_rx.recv());
+            match futures::future::select(incoming.next(), closed).await {
+                Either::Left((Some(Frame::Text(msg)), _)) => client.on_message(msg, socket.clone()),
+                Either::Left((Some(Frame::Binary(bin)), _)) => {
+                    client.on_binary(bin, socket.clone())
+                }
+                Either::Left((None, _)) => {
+                    // Closes the writer, the reason sent back is ignored
+                    socket.close(EIoDisconnectReason::TransportClose);
+                    break EIoDisconnectReason::TransportClose;
+                }
+                Either::Right((reason, _)) => {
+                    break reason.unwrap_or(EIoDisconnectReason::TransportClose)
+                }
+            }
+        };
+        client.on_disconnect(socket, reason);
+    });
+    sid
+}
@@ -0,0 +1,50 @@
+//! Tests for the custom serialization of the acknowledgements
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use socketioxide::extract::{AckSender, SocketRef};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn ack_serializer() {
+    const PORT: u16 = 4300;
+    use Message::*;
+    let io = create_server_with(PORT, |b| {
+        b.ack_serializer(|data| Value::String(format!("enc:{data}")))
+    })
+    .await;
+    let (tx, mut rx) = mpsc::channel::<Value>(1);
+    io.ns("/", move |s: SocketRef| {
+        s.on("get", |ack: AckSender| ack.send("secret").unwrap());
+        s.emit("news", "public").unwrap();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let ack = s.emit_with_ack::<_, Value>("confirm", "secret").unwrap();
+            tx.try_send(ack.await.unwrap().data).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    // A regular emit does not go through the ack serializer
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["news","public"]"#.to_string()));
+
+    // The data of an event requesting an ack does
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["confirm","enc:\"secret\""]"#.to_string()));
+    // The client ack is received as is
+    assert_ok!(ws.send(Text(r#"431["ok"]"#.to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), Value::from(["ok"].to_vec()));
+
+    // And so does an ack response
+    assert_ok!(ws.send(Text(r#"421["get"]"#.to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"431["enc:\"secret\""]"#.to_string()));
+}
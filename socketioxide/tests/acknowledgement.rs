@@ -4,10 +4,12 @@
 mod fixture;
 mod utils;
 
-use fixture::{create_server, create_ws_connection};
+use fixture::{create_server, create_server_with, create_ws_connection};
 use futures::{SinkExt, StreamExt};
-use socketioxide::extract::SocketRef;
+use serde_json::{json, Value};
+use socketioxide::extract::{AckId, AckSender, Data, DeferredAck, SocketRef};
 use socketioxide::packet::{Packet, PacketData};
+use socketioxide::{socket::Sid, AckError, SendError, StagedShutdownConfig};
 use tokio::sync::mpsc;
 use tokio::time::Duration;
 use tokio_tungstenite::tungstenite::Message;
@@ -130,3 +132,574 @@ pub async fn broadcast_with_ack() {
         }
     }
 }
+
+#[tokio::test]
+pub async fn manual_ack() {
+    const PORT: u16 = 2102;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Option<i64>>(4);
+
+    io.ns("/", move |s: SocketRef| {
+        s.on(
+            "test",
+            move |s: SocketRef, AckId(id): AckId, Data::<String>(data)| {
+                assert_ok!(tx.try_send(id));
+                if let Some(id) = id {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_millis(10)).await;
+                        assert_ok!(s.send_manual_ack(id, data));
+                    });
+                }
+            },
+        );
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    assert_ok!(stx.send(Text("42[\"test\",\"foo\"]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), None);
+
+    assert_ok!(stx.send(Text("427[\"test\",\"bar\"]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), Some(7));
+
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("437[\"bar\"]".to_string()));
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn ack_id_with_ack_sender() {
+    const PORT: u16 = 2113;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Option<i64>>(4);
+
+    io.ns("/", move |s: SocketRef| {
+        s.on(
+            "test",
+            move |AckId(id): AckId, ack: AckSender, Data::<String>(data)| {
+                assert_ok!(tx.try_send(id));
+                assert_ok!(ack.send(data));
+            },
+        );
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    assert_ok!(stx.send(Text("423[\"test\",\"foo\"]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), Some(3));
+
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("433[\"foo\"]".to_string()));
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn deferred_ack() {
+    const PORT: u16 = 2114;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<DeferredAck>(4);
+
+    io.ns("/", move |s: SocketRef| {
+        s.on("test", move |ack: AckSender| {
+            assert_ok!(tx.try_send(ack.into_deferred()));
+        });
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    assert_ok!(stx.send(Text("425[\"test\"]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_ok!(ack.send("foo"));
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("435[\"foo\"]".to_string()));
+
+    // Once the socket is disconnected, the ack is not sent
+    assert_ok!(stx.send(Text("426[\"test\"]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    assert_ok!(stx.send(Text("41".to_string())).await);
+    while !assert_ok!(io.sockets()).is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(matches!(ack.send("foo"), Err(SendError::SocketGone(_))));
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn graceful_shutdown_drains_acks() {
+    const PORT: u16 = 2103;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<[String; 1]>(4);
+
+    io.ns("/", move |s: SocketRef| async move {
+        let res = assert_ok!(s.emit_with_ack::<_, [String; 1]>("test", "foo")).await;
+        let ack = assert_ok!(res);
+        assert_ok!(tx.try_send(ack.data));
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("421[\"test\",\"foo\"]".to_string()));
+
+    let config = StagedShutdownConfig {
+        ack_drain_timeout: Duration::from_secs(1),
+        ..Default::default()
+    };
+    let shutdown = tokio::spawn({
+        let io = io.clone();
+        async move { io.graceful_shutdown(config).await }
+    });
+
+    // The socket is not closed while the ack is pending
+    let res = tokio::time::timeout(Duration::from_millis(50), srx.next()).await;
+    assert!(res.is_err(), "the socket should not be closed yet");
+
+    assert_ok!(stx.send(Text("431[\"oof\"]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    assert_eq!(ack[0], "oof");
+
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("41".to_string()));
+    assert!(matches!(assert_ok!(srx.next().await.unwrap()), Close(_)));
+
+    let res = tokio::time::timeout(Duration::from_millis(500), shutdown).await;
+    assert_ok!(assert_ok!(res));
+}
+
+#[tokio::test]
+pub async fn emit_with_ack_tuple() {
+    const PORT: u16 = 2104;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Result<(String, i32), AckError<()>>>(4);
+
+    io.ns("/", move |s: SocketRef| async move {
+        let res = assert_ok!(s.emit_with_ack::<_, (String, i32)>("test", "foo")).await;
+        assert_ok!(tx.try_send(res.map(|ack| ack.data)));
+        let res = assert_ok!(s.emit_with_ack::<_, (String, i32)>("test", "foo")).await;
+        assert_ok!(tx.try_send(res.map(|ack| ack.data)));
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("421[\"test\",\"foo\"]".to_string()));
+    assert_ok!(stx.send(Text("431[\"oof\",2]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    assert_eq!(assert_ok!(ack), ("oof".to_string(), 2));
+
+    // A single argument ack cannot be deserialized into a 2-tuple
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("422[\"test\",\"foo\"]".to_string()));
+    assert_ok!(stx.send(Text("432[\"oof\"]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    assert!(matches!(ack, Err(AckError::Serde(_))));
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn emit_with_ack_max_pending() {
+    const PORT: u16 = 2105;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.max_pending_acks_per_socket(2)).await;
+    let (tx, mut rx) = mpsc::channel::<Result<Value, AckError<()>>>(4);
+
+    io.ns("/", move |s: SocketRef| async move {
+        let ack1 = assert_ok!(s.emit_with_ack::<_, Value>("test", 1));
+        let _ack2 = assert_ok!(s.emit_with_ack::<_, Value>("test", 2));
+        let ack3 = assert_ok!(s.emit_with_ack::<_, Value>("test", 3)).await;
+        assert_ok!(tx.try_send(ack3.map(|ack| ack.data)));
+
+        // Once an ack is received, a new one can be awaited
+        assert_ok!(ack1.await);
+        let ack4 = assert_ok!(s.emit_with_ack::<_, Value>("test", 4)).await;
+        assert_ok!(tx.try_send(ack4.map(|ack| ack.data)));
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    let ack = rx.recv().await.unwrap();
+    assert!(matches!(ack, Err(AckError::TooManyPending)));
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("421[\"test\",1]".to_string()));
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("422[\"test\",2]".to_string()));
+
+    // The third event was never sent
+    assert_ok!(stx.send(Text("431[\"ok\"]".to_string())).await);
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("423[\"test\",4]".to_string()));
+    assert_ok!(stx.send(Text("433[\"ok\"]".to_string())).await);
+    let ack = rx.recv().await.unwrap();
+    assert_eq!(assert_ok!(ack), json!(["ok"]));
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn broadcast_with_ack_each() {
+    const PORT: u16 = 2106;
+    use Message::*;
+
+    /// Fails to serialize for the given socket
+    struct Personalized(Sid, Sid);
+    impl serde::Serialize for Personalized {
+        fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            if self.0 == self.1 {
+                return Err(serde::ser::Error::custom("no data for this socket"));
+            }
+            s.serialize_str(&self.0.to_string())
+        }
+    }
+
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef| tx.try_send(s.id).unwrap());
+
+    // Spawn 3 clients echoing the data they received
+    for _ in 0..3 {
+        tokio::spawn(async move {
+            let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+            assert_ok!(srx.next().await.unwrap());
+            assert_ok!(srx.next().await.unwrap());
+            while let Some(msg) = srx.next().await {
+                let msg = match assert_ok!(msg) {
+                    Text(msg) => msg,
+                    _ => panic!("Unexpected message"),
+                };
+                let (data, ack) = match assert_ok!(Packet::try_from(msg[1..].to_string())).inner {
+                    PacketData::Event(_, data, Some(ack)) => (data, ack),
+                    _ => panic!("Unexpected packet"),
+                };
+                assert_ok!(stx.send(Text(format!("43{ack}{data}"))).await);
+            }
+        });
+    }
+    let mut sids = Vec::new();
+    for _ in 0..3 {
+        sids.push(rx.recv().await.unwrap());
+    }
+    let failing = sids[0];
+
+    let acks: Vec<_> = assert_ok!(io
+        .local()
+        .emit_with_ack_each::<_, [String; 1]>("test", |sid| Personalized(sid, failing)))
+    .collect()
+    .await;
+    assert_eq!(acks.len(), 3);
+    for (sid, ack) in acks {
+        if sid == failing {
+            assert!(matches!(ack, Err(AckError::Serde(_))));
+        } else {
+            assert_eq!(assert_ok!(ack).data, [sid.to_string()]);
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn broadcast_with_ack_timeout_item() {
+    const PORT: u16 = 2107;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(2);
+    io.ns("/", move |socket: SocketRef| {
+        tx.try_send(socket.id).unwrap()
+    });
+
+    // The fast client acks right away, the slow one never answers
+    let (mut fast_tx, mut fast_rx) = create_ws_connection(PORT).await.split();
+    assert_ok!(fast_rx.next().await.unwrap());
+    assert_ok!(fast_rx.next().await.unwrap());
+    let fast = rx.recv().await.unwrap();
+    let mut slow_ws = create_ws_connection(PORT).await;
+    assert_ok!(slow_ws.next().await.unwrap());
+    assert_ok!(slow_ws.next().await.unwrap());
+    let slow = rx.recv().await.unwrap();
+    tokio::spawn(async move {
+        let msg = match assert_ok!(fast_rx.next().await.unwrap()) {
+            Text(msg) => msg,
+            _ => panic!("Unexpected message"),
+        };
+        let ack = match assert_ok!(Packet::try_from(msg[1..].to_string())).inner {
+            PacketData::Event(_, _, Some(ack)) => ack,
+            _ => panic!("Unexpected packet"),
+        };
+        assert_ok!(fast_tx.send(Text(format!("43{ack}[\"oof\"]"))).await);
+        fast_rx.next().await;
+    });
+
+    let mut stream = assert_ok!(io
+        .timeout(Duration::from_millis(200))
+        .emit_with_ack::<[String; 1]>("test", "foo"));
+
+    // The fast ack is yielded before the slow socket times out, then the stream ends
+    let (sid, ack) = tokio::time::timeout(Duration::from_millis(100), stream.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(sid, fast);
+    assert_eq!(assert_ok!(ack).data, ["oof"]);
+    let (sid, ack) = stream.next().await.unwrap();
+    assert_eq!(sid, slow);
+    assert!(matches!(ack, Err(AckError::Timeout)));
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+pub async fn broadcast_with_ack_reduce() {
+    const PORT: u16 = 2108;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(3);
+    io.ns("/", move |socket: SocketRef| {
+        tx.try_send(socket.id).unwrap()
+    });
+
+    // Two clients vote with their number, the last one never answers
+    for vote in [2, 5] {
+        let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+        assert_ok!(srx.next().await.unwrap());
+        assert_ok!(srx.next().await.unwrap());
+        tokio::spawn(async move {
+            while let Some(msg) = srx.next().await {
+                let msg = match assert_ok!(msg) {
+                    Text(msg) => msg,
+                    _ => panic!("Unexpected message"),
+                };
+                let ack = match assert_ok!(Packet::try_from(msg[1..].to_string())).inner {
+                    PacketData::Event(_, _, Some(ack)) => ack,
+                    _ => panic!("Unexpected packet"),
+                };
+                assert_ok!(stx.send(Text(format!("43{ack}[{vote}]"))).await);
+            }
+        });
+        rx.recv().await.unwrap();
+    }
+    let mut silent = create_ws_connection(PORT).await;
+    assert_ok!(silent.next().await.unwrap());
+    assert_ok!(silent.next().await.unwrap());
+    let silent_sid = rx.recv().await.unwrap();
+
+    let (total, missing) = assert_ok!(io
+        .timeout(Duration::from_millis(200))
+        .emit_with_ack_reduce::<[u32; 1], u32>("vote", "question", 0, |total, (_, [vote])| {
+            total + vote
+        }))
+    .await;
+    assert_eq!(total, 7);
+    assert_eq!(missing, [silent_sid]);
+}
+
+#[tokio::test]
+pub async fn emit_with_ack_tagged_enum() {
+    const PORT: u16 = 2109;
+    use Message::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    #[serde(tag = "status", rename_all = "lowercase")]
+    enum Outcome {
+        Ok { id: u32 },
+        Error { reason: String },
+    }
+
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(Result<Outcome, AckError<()>>, Vec<Vec<u8>>)>(4);
+    io.ns("/", move |s: SocketRef| async move {
+        for _ in 0..4 {
+            let res = assert_ok!(s.emit_with_ack::<_, (Outcome,)>("test", "foo")).await;
+            let res = match res {
+                Ok(ack) => (Ok(ack.data.0), ack.binary),
+                Err(e) => (Err(e), vec![]),
+            };
+            assert_ok!(tx.try_send(res));
+        }
+    });
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(
+        stx.send(Text(r#"431[{"status":"ok","id":3}]"#.to_string()))
+            .await
+    );
+    let (ack, _) = rx.recv().await.unwrap();
+    assert_eq!(assert_ok!(ack), Outcome::Ok { id: 3 });
+
+    assert_ok!(srx.next().await.unwrap());
+    let msg = r#"432[{"status":"error","reason":"denied"}]"#;
+    assert_ok!(stx.send(Text(msg.to_string())).await);
+    let (ack, _) = rx.recv().await.unwrap();
+    let reason = "denied".to_string();
+    assert_eq!(assert_ok!(ack), Outcome::Error { reason });
+
+    // The binary attachment is not part of the deserialized data
+    assert_ok!(srx.next().await.unwrap());
+    let msg = r#"461-3[{"status":"ok","id":4},{"_placeholder":true,"num":0}]"#;
+    assert_ok!(stx.send(Text(msg.to_string())).await);
+    assert_ok!(stx.send(Binary(vec![1, 2, 3])).await);
+    let (ack, binary) = rx.recv().await.unwrap();
+    assert_eq!(assert_ok!(ack), Outcome::Ok { id: 4 });
+    assert_eq!(binary, vec![vec![1, 2, 3]]);
+
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(
+        stx.send(Text(r#"434[{"status":"maybe"}]"#.to_string()))
+            .await
+    );
+    let (ack, _) = rx.recv().await.unwrap();
+    match ack {
+        Err(AckError::Serde(e)) => {
+            let msg = e.to_string();
+            assert!(msg.contains("unknown variant `maybe`"), "{msg}");
+            assert!(msg.contains("`ok`") && msg.contains("`error`"), "{msg}");
+        }
+        ack => panic!("unexpected ack {ack:?}"),
+    }
+
+    assert_ok!(stx.close().await);
+}
+
+#[tokio::test]
+pub async fn emit_with_ack_timeout() {
+    const PORT: u16 = 2110;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Result<[String; 1], AckError<()>>>(4);
+    io.ns("/", move |s: SocketRef| async move {
+        let timeout = Duration::from_millis(50);
+        // The first ack is answered, the second one never is
+        for _ in 0..2 {
+            let ack = s.emit_with_ack_timeout::<_, [String; 1]>("test", "foo", timeout);
+            let res = assert_ok!(ack).await.map(|ack| ack.data);
+            assert_ok!(tx.try_send(res));
+        }
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["test","foo"]"#.into()));
+    assert_ok!(ws.send(Text(r#"431["oof"]"#.into())).await);
+    let res = tokio::time::timeout(Duration::from_millis(100), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(assert_ok!(res), ["oof"]);
+
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"422["test","foo"]"#.into()));
+    // It times out well before the 5 seconds of the config
+    let res = tokio::time::timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(res, Err(AckError::Timeout)));
+}
+
+#[tokio::test]
+pub async fn socket_broadcast_with_ack() {
+    const PORT: u16 = 2111;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.ack_timeout(Duration::from_millis(100))).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(3);
+    let (res_tx, mut res_rx) = mpsc::channel::<Vec<(Sid, Result<[String; 1], AckError<()>>)>>(1);
+    io.ns("/", move |s: SocketRef| {
+        let res_tx = res_tx.clone();
+        s.on("poll", move |s: SocketRef| async move {
+            let acks = assert_ok!(s.broadcast_with_ack::<[String; 1]>("vote", ()));
+            let res = acks
+                .map(|(sid, ack)| (sid, ack.map(|ack| ack.data)))
+                .collect()
+                .await;
+            assert_ok!(res_tx.try_send(res));
+        });
+        tx.try_send(s.id).unwrap();
+    });
+
+    // The voter acks, the silent client never answers
+    let mut voter = create_ws_connection(PORT).await;
+    assert_ok!(voter.next().await.unwrap());
+    assert_ok!(voter.next().await.unwrap());
+    let voter_id = rx.recv().await.unwrap();
+    let mut silent = create_ws_connection(PORT).await;
+    assert_ok!(silent.next().await.unwrap());
+    assert_ok!(silent.next().await.unwrap());
+    let silent_id = rx.recv().await.unwrap();
+    let mut emitter = create_ws_connection(PORT).await;
+    assert_ok!(emitter.next().await.unwrap());
+    assert_ok!(emitter.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    assert_ok!(emitter.send(Text(r#"42["poll"]"#.into())).await);
+    let msg = assert_ok!(voter.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["vote",null]"#.into()));
+    assert_ok!(voter.send(Text(r#"431["yes"]"#.into())).await);
+    let msg = assert_ok!(silent.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["vote",null]"#.into()));
+
+    // One item per targeted socket, the emitter is excluded
+    let res = tokio::time::timeout(Duration::from_millis(500), res_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(res.len(), 2);
+    assert_eq!(res[0].0, voter_id);
+    assert_eq!(assert_ok!(res[0].1.as_ref()), &["yes"]);
+    assert_eq!(res[1].0, silent_id);
+    assert!(matches!(res[1].1, Err(AckError::Timeout)));
+}
+
+#[tokio::test]
+pub async fn namespace_ack_timeout() {
+    const PORT: u16 = 2112;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(&'static str, bool)>(4);
+    for ns in ["/", "/chat"] {
+        let tx = tx.clone();
+        io.ns(ns, move |s: SocketRef| async move {
+            let res = assert_ok!(s.emit_with_ack::<_, Value>("test", "foo")).await;
+            let timed_out = matches!(res, Err(AckError::Timeout));
+            assert_ok!(tx.try_send((ns, timed_out)));
+        });
+    }
+    assert!(io.set_ack_timeout("/chat", Some(Duration::from_millis(10))));
+    assert!(!io.set_ack_timeout("/nope", Some(Duration::from_millis(10))));
+
+    let (mut stx, mut srx) = create_ws_connection(PORT).await.split();
+    assert_ok!(srx.next().await.unwrap());
+    assert_ok!(srx.next().await.unwrap());
+    let msg = assert_ok!(srx.next().await.unwrap());
+    assert_eq!(msg, Text("421[\"test\",\"foo\"]".to_string()));
+    assert_ok!(stx.send(Text("40/chat,".to_string())).await);
+    assert_ok!(srx.next().await.unwrap());
+
+    // The chat ack times out with the namespace timeout, the root one still waits for the global timeout
+    let res = tokio::time::timeout(Duration::from_millis(200), rx.recv()).await;
+    assert_eq!(res.unwrap().unwrap(), ("/chat", true));
+    assert_ok!(stx.send(Text("431[\"oof\"]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), ("/", false));
+}
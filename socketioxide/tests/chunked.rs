@@ -0,0 +1,53 @@
+//! Tests for the chunked emits of large arrays
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::StreamExt;
+use serde_json::Value;
+use socketioxide::extract::SocketRef;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn chunked_emit() {
+    const PORT: u16 = 6100;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.join("room").unwrap();
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    let items: Vec<u32> = (0..1000).collect();
+    assert_ok!(io.within("room").chunked(300).emit("items", &items));
+    assert!(io.within("room").chunked(300).emit("items", 1).is_err());
+
+    // 4 chunks followed by the completion event
+    let mut batch = None;
+    let mut received = Vec::new();
+    for index in 0..5 {
+        let msg = match assert_ok!(ws.next().await.unwrap()) {
+            Text(msg) => msg,
+            msg => panic!("unexpected message {msg:?}"),
+        };
+        let [event, data]: [Value; 2] = assert_ok!(serde_json::from_str(&msg[2..]));
+        assert_eq!(event, "items");
+        let batch = batch.get_or_insert_with(|| data["batch"].clone());
+        assert_eq!(&data["batch"], batch);
+        if index < 4 {
+            assert_eq!(data["index"], index);
+            let chunk: Vec<u32> = assert_ok!(serde_json::from_value(data["items"].clone()));
+            received.extend(chunk);
+        } else {
+            assert_eq!(data["complete"], true);
+            assert_eq!(data["chunks"], 4);
+        }
+    }
+    assert_eq!(received, items);
+}
@@ -0,0 +1,65 @@
+//! Tests for coalesced broadcasts
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::StreamExt;
+use socketioxide::{clock::ManualClock, extract::SocketRef};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn coalesce() {
+    const PORT: u16 = 5700;
+    const INTERVAL: Duration = Duration::from_millis(100);
+    use Message::*;
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| b.with_clock(clock.clone())).await;
+    io.ns("/", |s: SocketRef| {
+        s.join("room").unwrap();
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    for i in 0..10 {
+        let op = io.within("room").coalesce("counter", INTERVAL);
+        assert_ok!(op.emit("counter", i));
+    }
+    // Another key is not coalesced with the counter
+    assert_ok!(io
+        .within("room")
+        .coalesce("other", INTERVAL)
+        .emit("other", 1));
+    clock.advance(INTERVAL);
+    let mut msgs = vec![
+        assert_ok!(ws.next().await.unwrap()),
+        assert_ok!(ws.next().await.unwrap()),
+    ];
+    msgs.sort_by_key(|msg| msg.to_string());
+    assert_eq!(
+        msgs,
+        [
+            Text(r#"42["counter",9]"#.into()),
+            Text(r#"42["other",1]"#.into())
+        ]
+    );
+
+    // The next interval starts with the next emit
+    for i in 10..15 {
+        let op = io.within("room").coalesce("counter", INTERVAL);
+        assert_ok!(op.emit("counter", i));
+    }
+    clock.advance(INTERVAL / 2);
+    assert!(tokio::time::timeout(Duration::from_millis(20), ws.next())
+        .await
+        .is_err());
+    clock.advance(INTERVAL / 2);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["counter",14]"#.into()));
+}
@@ -0,0 +1,73 @@
+//! Tests for the selection of the sockets by connection time
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection_with_auth};
+use futures::StreamExt;
+use socketioxide::{
+    clock::ManualClock,
+    extract::{Data, SocketRef},
+    socket::Sid,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn connected_within() {
+    const PORT: u16 = 3300;
+    use Message::*;
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| b.with_clock(clock.clone())).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef, Data::<String>(room)| {
+        s.join(room).unwrap();
+        tx.try_send(s.id).unwrap();
+    });
+
+    // ws1 (beta) connects at t=0, ws2 at t=10min, ws3 (beta) at t=15min
+    let mut ws1 = create_ws_connection_with_auth(PORT, r#""beta""#).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = rx.recv().await.unwrap();
+    clock.advance(Duration::from_secs(10 * 60));
+    let mut ws2 = create_ws_connection_with_auth(PORT, r#""stable""#).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = rx.recv().await.unwrap();
+    clock.advance(Duration::from_secs(5 * 60));
+    let mut ws3 = create_ws_connection_with_auth(PORT, r#""beta""#).await;
+    assert_ok!(ws3.next().await.unwrap());
+    assert_ok!(ws3.next().await.unwrap());
+    let sid3 = rx.recv().await.unwrap();
+    clock.advance(Duration::from_secs(60));
+
+    let window = Duration::from_secs(7 * 60);
+    assert_ok!(io.connected_within(window).emit("cohort", 1));
+    assert_ok!(io.to("beta").connected_within(window).emit("cohort", 2));
+    let all = Duration::from_secs(60 * 60);
+    assert_ok!(io.to("beta").connected_within(all).emit("cohort", 3));
+
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["cohort",3]"#.to_string()));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["cohort",1]"#.to_string()));
+    let msg = assert_ok!(ws3.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["cohort",1]"#.to_string()));
+    let msg = assert_ok!(ws3.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["cohort",2]"#.to_string()));
+    let msg = assert_ok!(ws3.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["cohort",3]"#.to_string()));
+
+    let sockets = io
+        .connected_after(io.get_socket(sid2).unwrap().connected_at())
+        .sockets()
+        .unwrap();
+    let mut sids: Vec<_> = sockets.iter().map(|s| s.id).collect();
+    sids.sort();
+    let mut expected = vec![sid2, sid3];
+    expected.sort();
+    assert_eq!(sids, expected);
+    assert!(!sids.contains(&sid1));
+}
@@ -0,0 +1,42 @@
+//! Tests for the server-wide counts of connections and sockets
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::SocketRef;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn counts_across_namespaces() {
+    const PORT: u16 = 6000;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |_: SocketRef| {});
+    io.ns("/chat", |_: SocketRef| {});
+    io.ns("/admin", |_: SocketRef| {});
+    assert_eq!(io.connection_count().unwrap(), 0);
+    assert_eq!(io.socket_count().unwrap(), 0);
+
+    // The first client is in the three namespaces, the second one only in the root namespace
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    for ns in ["/chat", "/admin"] {
+        assert_ok!(ws1.send(Text(format!("40{ns},"))).await);
+        let msg = assert_ok!(ws1.next().await.unwrap());
+        assert!(msg.to_string().starts_with(&format!("40{ns},")));
+    }
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+
+    assert_eq!(io.connection_count().unwrap(), 2);
+    assert_eq!(io.socket_count().unwrap(), 4);
+
+    // Leaving a namespace doesn't close the connection
+    assert_ok!(ws1.send(Text("41/chat,".into())).await);
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert_eq!(io.connection_count().unwrap(), 2);
+    assert_eq!(io.socket_count().unwrap(), 3);
+}
@@ -0,0 +1,69 @@
+//! Tests for the socket.io connections over a custom transport
+mod utils;
+
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use serde_json::{json, Value};
+use socketioxide::{
+    extract::{Bin, Data, SocketRef},
+    packet::{Packet, PacketData},
+    socket::DisconnectReason,
+    transport::Frame,
+    ProtocolVersion, SocketIo,
+};
+use tokio::sync::mpsc as tmpsc;
+
+fn decode(frame: Frame) -> Packet<'static> {
+    match frame {
+        Frame::Text(msg) => assert_ok!(Packet::decode(msg, ProtocolVersion::V5)),
+        Frame::Binary(_) => panic!("unexpected binary frame"),
+    }
+}
+
+#[tokio::test]
+pub async fn round_trip() {
+    let (_svc, io) = SocketIo::new_svc();
+    let (dtx, mut drx) = tmpsc::channel::<DisconnectReason>(1);
+    io.ns("/", move |s: SocketRef| {
+        s.on("echo", |s: SocketRef, Data::<Value>(data), Bin(bin)| {
+            s.bin(bin).emit("echo", data).ok();
+        });
+        let dtx = dtx.clone();
+        s.on_disconnect(move |reason: DisconnectReason| dtx.try_send(reason).unwrap());
+    });
+
+    let (mut in_tx, in_rx) = mpsc::unbounded::<Frame>();
+    let (out_tx, mut out_rx) = mpsc::unbounded::<Frame>();
+    io.attach_transport(in_rx, out_tx);
+
+    let connect = Packet {
+        inner: PacketData::Connect(None),
+        ns: "/".into(),
+    };
+    assert_ok!(in_tx.send(Frame::Text(connect.into())).await);
+    let packet = decode(out_rx.next().await.unwrap());
+    assert!(matches!(packet.inner, PacketData::Connect(Some(_))));
+
+    let event = Packet::event("/", "echo", json!("hello"));
+    assert_ok!(in_tx.send(Frame::Text(event.into())).await);
+    let packet = decode(out_rx.next().await.unwrap());
+    assert_eq!(packet, Packet::event("/", "echo", json!(["hello"])));
+
+    // The binary attachments are sent as separate frames
+    let event = Packet::bin_event("/", "echo", json!("file"), vec![vec![1, 2, 3]]);
+    assert_ok!(in_tx.send(Frame::Text(event.into())).await);
+    assert_ok!(in_tx.send(Frame::Binary(vec![1, 2, 3])).await);
+    let packet = decode(out_rx.next().await.unwrap());
+    match packet.inner {
+        PacketData::BinaryEvent(e, bin, None) => {
+            assert_eq!(e, "echo");
+            assert_eq!(bin.data, json!(["file"]));
+        }
+        inner => panic!("unexpected packet {inner:?}"),
+    }
+    assert_eq!(out_rx.next().await.unwrap(), Frame::Binary(vec![1, 2, 3]));
+
+    // Ending the incoming stream closes the connection
+    drop(in_tx);
+    assert_eq!(drx.recv().await.unwrap(), DisconnectReason::TransportClose);
+    assert!(out_rx.next().await.is_none());
+}
@@ -0,0 +1,67 @@
+//! Tests for the events deferred until the client is ready
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{extract::SocketRef, SendError, SocketError};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn deferred_until_ready() {
+    const PORT: u16 = 5900;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        for i in 0..3 {
+            s.defer_until_ready("deferred", i).unwrap();
+        }
+        s.on("ready", |s: SocketRef| {
+            assert!(!s.is_ready());
+            s.mark_ready();
+            assert!(s.is_ready());
+            // Once ready, the events are sent right away
+            s.defer_until_ready("deferred", 3).unwrap();
+        });
+        s.emit("direct", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    // The deferred events are not sent before the client is ready
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["direct",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["ready"]"#.into())).await);
+    for i in 0..4 {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["deferred",{i}]"#)));
+    }
+}
+
+#[tokio::test]
+pub async fn deferred_buffer_full() {
+    const PORT: u16 = 5901;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.max_deferred_events(2)).await;
+    io.ns("/", |s: SocketRef| {
+        s.defer_until_ready("deferred", 0).unwrap();
+        s.defer_until_ready("deferred", 1).unwrap();
+        let err = s.defer_until_ready("deferred", 2).unwrap_err();
+        assert!(matches!(
+            err,
+            SendError::Socket(SocketError::InternalChannelFull(2))
+        ));
+        s.on("ready", |s: SocketRef| s.mark_ready());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.send(Text(r#"42["ready"]"#.into())).await);
+    for i in 0..2 {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["deferred",{i}]"#)));
+    }
+}
@@ -7,16 +7,29 @@
 //!
 //! * Client namespace disconnect
 //! * Server namespace disconnect
+//! * Server namespace disconnect with a payload
+//! * Server adapter close
+//!
+//! * Server disconnecting all the sockets
+//! * Server closing with the different [`ShutdownDisconnectPolicy`]
 
-use std::time::Duration;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use futures::{SinkExt, StreamExt};
-use socketioxide::{extract::SocketRef, socket::DisconnectReason, SocketIo};
+use socketioxide::{
+    extract::SocketRef, socket::DisconnectReason, ShutdownDisconnectPolicy, SocketIo,
+};
 use tokio::sync::mpsc;
 
 mod fixture;
 
-use fixture::{create_server, send_req};
+use fixture::{create_server, create_server_with, send_req};
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::fixture::{create_polling_connection, create_ws_connection};
@@ -228,6 +241,99 @@ pub async fn server_ns_disconnect() {
     assert_eq!(data, DisconnectReason::ServerNSDisconnect);
 }
 
+#[tokio::test]
+pub async fn server_ns_disconnect_from_ref() {
+    use tokio_tungstenite::tungstenite::Message::*;
+    let (tx, mut rx) = mpsc::channel::<DisconnectReason>(2);
+    let io = create_server(12354).await;
+    io.ns("/", move |socket: SocketRef| {
+        let tx = tx.clone();
+        socket.on_disconnect(move |reason: DisconnectReason| tx.try_send(reason).unwrap());
+        socket.on("leave", |socket: SocketRef| {
+            // The ref is borrowed, it is still usable afterwards
+            socket.disconnect().unwrap();
+            assert!(socket.rooms().unwrap().is_empty());
+        });
+    });
+
+    let mut stream = create_ws_connection(12354).await;
+    stream.next().await.unwrap().unwrap(); // engine.io open packet
+    stream.next().await.unwrap().unwrap(); // socket.io open packet
+    stream.send(Text(r#"42["leave"]"#.into())).await.unwrap();
+
+    let msg = stream.next().await.unwrap().unwrap();
+    assert_eq!(msg, Text("41".into()));
+    let data = tokio::time::timeout(Duration::from_millis(20), rx.recv())
+        .await
+        .expect("timeout waiting for DisconnectReason::ServerNSDisconnect")
+        .unwrap();
+    assert_eq!(data, DisconnectReason::ServerNSDisconnect);
+    // The disconnect handler is called only once
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn server_ns_disconnect_with_payload() {
+    use tokio_tungstenite::tungstenite::Message::*;
+    let (tx, mut rx) = mpsc::channel::<DisconnectReason>(2);
+    let io = create_server(12356).await;
+    io.ns("/", move |socket: SocketRef| {
+        let tx = tx.clone();
+        socket.on_disconnect(move |reason: DisconnectReason| tx.try_send(reason).unwrap());
+        socket.on("leave", |socket: SocketRef| {
+            socket
+                .disconnect_with(serde_json::json!({ "code": "session_expired" }))
+                .unwrap();
+        });
+    });
+
+    let mut stream = create_ws_connection(12356).await;
+    stream.next().await.unwrap().unwrap(); // engine.io open packet
+    stream.next().await.unwrap().unwrap(); // socket.io open packet
+    stream.send(Text(r#"42["leave"]"#.into())).await.unwrap();
+
+    let msg = stream.next().await.unwrap().unwrap();
+    assert_eq!(
+        msg,
+        Text(r#"42["_disconnect",{"code":"session_expired"}]"#.into())
+    );
+    let msg = stream.next().await.unwrap().unwrap();
+    assert_eq!(msg, Text("41".into()));
+    let data = tokio::time::timeout(Duration::from_millis(20), rx.recv())
+        .await
+        .expect("timeout waiting for DisconnectReason::ServerNSDisconnect")
+        .unwrap();
+    assert_eq!(data, DisconnectReason::ServerNSDisconnect);
+}
+
+#[tokio::test]
+pub async fn server_adapter_close() {
+    use tokio_tungstenite::tungstenite::Message::*;
+    let (tx, mut rx) = mpsc::channel::<DisconnectReason>(2);
+    let io = create_server(12355).await;
+    let io2 = io.clone();
+    io.ns("/", move |socket: SocketRef| {
+        let tx = tx.clone();
+        socket.on_disconnect(move |reason: DisconnectReason| tx.try_send(reason).unwrap());
+    });
+
+    let mut stream = create_ws_connection(12355).await;
+    stream.next().await.unwrap().unwrap(); // engine.io open packet
+    stream.next().await.unwrap().unwrap(); // socket.io open packet
+
+    let socket = io2.sockets().unwrap().into_iter().next().unwrap();
+    socket.adapter_disconnect().unwrap();
+
+    let msg = stream.next().await.unwrap().unwrap();
+    assert_eq!(msg, Text("41".into()));
+    let data = tokio::time::timeout(Duration::from_millis(20), rx.recv())
+        .await
+        .expect("timeout waiting for DisconnectReason::ServerAdapterClose")
+        .unwrap();
+    assert_eq!(data, DisconnectReason::ServerAdapterClose);
+}
+
 #[tokio::test]
 pub async fn server_ws_closing() {
     let io = create_server(12350).await;
@@ -289,3 +395,103 @@ pub async fn server_http_closing() {
         );
     }
 }
+
+#[tokio::test]
+pub async fn server_disconnect_all() {
+    let io = create_server(12357).await;
+    let mut rx = attach_handler(&io, 10);
+
+    let mut streams = futures::future::join_all((0..5).map(|_| create_ws_connection(12357))).await;
+    futures::future::join_all(streams.iter_mut().map(|s| async move {
+        s.next().await; // engine.io open packet
+        s.next().await; // socket.io open packet
+    }))
+    .await;
+
+    assert!(io.disconnect_all(Duration::from_millis(100)).await);
+    for _ in 0..5 {
+        let data = tokio::time::timeout(Duration::from_millis(20), rx.recv())
+            .await
+            .expect("timeout waiting for DisconnectReason::ClosingServer")
+            .unwrap();
+        assert_eq!(data, DisconnectReason::ClosingServer);
+    }
+    assert!(io.sockets().unwrap().is_empty());
+    assert!(io
+        .disconnect_all_ns("/unknown", Duration::ZERO)
+        .await
+        .is_none());
+
+    // The namespace is still open
+    let mut stream = create_ws_connection(12357).await;
+    stream.next().await.unwrap().unwrap(); // engine.io open packet
+    let msg = stream.next().await.unwrap().unwrap();
+    assert!(msg.to_string().starts_with("40"));
+}
+
+#[tokio::test]
+pub async fn server_closing_skip_disconnect_handlers() {
+    let io = create_server_with(12352, |b| {
+        b.disconnect_on_shutdown(ShutdownDisconnectPolicy::Skip)
+    })
+    .await;
+    let mut rx = attach_handler(&io, 10);
+
+    let mut streams = futures::future::join_all((0..10).map(|_| create_ws_connection(12352))).await;
+    futures::future::join_all(streams.iter_mut().map(|s| async move {
+        s.next().await; // engine.io open packet
+        s.next().await; // socket.io open packet
+    }))
+    .await;
+
+    tokio::time::timeout(Duration::from_millis(20), io.close())
+        .await
+        .expect("timeout waiting for server closing");
+    tokio::time::timeout(Duration::from_millis(50), rx.recv())
+        .await
+        .expect_err("no disconnect handler should be called");
+}
+
+#[tokio::test]
+pub async fn server_closing_bounded_disconnect_handlers() {
+    const MAX: usize = 3;
+    let io = create_server_with(12353, |b| {
+        b.disconnect_on_shutdown(ShutdownDisconnectPolicy::RunBounded(MAX))
+    })
+    .await;
+    let running = Arc::new(AtomicUsize::new(0));
+    let max_running = Arc::new(AtomicUsize::new(0));
+    let (tx, mut rx) = mpsc::channel::<DisconnectReason>(20);
+    {
+        let (running, max_running) = (running.clone(), max_running.clone());
+        io.ns("/", move |socket: SocketRef| {
+            let (running, max_running, tx) = (running.clone(), max_running.clone(), tx.clone());
+            socket.on_disconnect(move |reason: DisconnectReason| async move {
+                let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                tx.try_send(reason).unwrap();
+            });
+        });
+    }
+
+    let mut streams = futures::future::join_all((0..20).map(|_| create_ws_connection(12353))).await;
+    futures::future::join_all(streams.iter_mut().map(|s| async move {
+        s.next().await; // engine.io open packet
+        s.next().await; // socket.io open packet
+    }))
+    .await;
+
+    tokio::time::timeout(Duration::from_secs(1), io.close())
+        .await
+        .expect("timeout waiting for server closing");
+    for _ in 0..20 {
+        let reason = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+            .await
+            .expect("timeout waiting for DisconnectReason::ClosingServer")
+            .unwrap();
+        assert_eq!(reason, DisconnectReason::ClosingServer);
+    }
+    assert!(max_running.load(Ordering::SeqCst) <= MAX);
+}
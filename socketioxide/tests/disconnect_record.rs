@@ -0,0 +1,55 @@
+//! Tests for the disconnect records
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::SocketRef,
+    socket::{DisconnectReason, DisconnectRecord, Sid},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn disconnect_record() {
+    const PORT: u16 = 3800;
+    use Message::*;
+    let (tx, mut rx) = mpsc::channel::<DisconnectRecord>(4);
+    let io = create_server_with(PORT, move |b| {
+        b.on_disconnect_record(move |record| tx.try_send(record).unwrap())
+    })
+    .await;
+    let (sid_tx, mut sid_rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.join(["room1", "room2"]).unwrap();
+        s.emit("hello", "world").unwrap();
+        sid_tx.try_send(s.id).unwrap();
+        // The record is assembled before the disconnect handler runs
+        s.on_disconnect(|s: SocketRef| s.leave_all().unwrap());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    // The connect and hello packets, without their engine.io message prefix
+    let mut sent = 0;
+    for _ in 0..2 {
+        sent += assert_ok!(ws.next().await.unwrap()).len() - 1;
+    }
+    let sid = sid_rx.recv().await.unwrap();
+
+    assert_ok!(ws.send(Text(r#"42["ping",1]"#.to_string())).await);
+    assert_ok!(ws.send(Text("41".to_string())).await);
+    let record = rx.recv().await.unwrap();
+    assert_eq!(record.sid, sid);
+    assert_eq!(record.ns, "/");
+    assert_eq!(record.reason, DisconnectReason::ClientNSDisconnect);
+    assert_eq!(record.bytes_sent, sent as u64);
+    assert_eq!(
+        record.bytes_received,
+        (r#"2["ping",1]"#.len() + "1".len()) as u64
+    );
+    let mut rooms = record.rooms;
+    rooms.sort();
+    assert_eq!(rooms, ["room1", "room2"]);
+}
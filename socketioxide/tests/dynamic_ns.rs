@@ -0,0 +1,104 @@
+//! Tests for the namespaces created from a path pattern
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::{NsParam, SocketRef};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn dynamic_ns() {
+    const PORT: u16 = 6700;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(String, u32)>(4);
+    io.ns("/", || {});
+    io.dynamic_ns(
+        "/tenant/:id",
+        move |s: SocketRef, NsParam(id): NsParam<u32>| {
+            tx.try_send((s.ns().to_string(), id)).unwrap()
+        },
+    );
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text("40/tenant/12,".to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40/tenant/12,"));
+    assert_eq!(rx.recv().await.unwrap(), ("/tenant/12".to_string(), 12));
+    assert!(io.of("/tenant/12").is_some());
+
+    for ns in ["/tenant", "/tenant/12/room", "/other/12"] {
+        assert_ok!(ws.send(Text(format!("40{ns},"))).await);
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(
+            msg,
+            Text(format!(r#"44{ns},{{"message":"Invalid namespace"}}"#))
+        );
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn ephemeral_dynamic_ns() {
+    const PORT: u16 = 6701;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", || {});
+    io.dynamic_ns("/tenant/:id", |s: SocketRef| s.join("room").unwrap());
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    for _ in 0..2 {
+        assert_ok!(ws.send(Text("40/tenant/1,".to_string())).await);
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert!(msg.to_string().starts_with("40/tenant/1,"));
+        assert_eq!(io.of("/tenant/1").unwrap().sockets().unwrap().len(), 1);
+
+        assert_ok!(ws.send(Text("41/tenant/1,".to_string())).await);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while io.of("/tenant/1").is_some() {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("the empty namespace should be removed");
+    }
+    // The static namespaces are kept
+    assert!(io.of("/").is_some());
+}
+
+#[tokio::test]
+pub async fn persistent_dynamic_ns() {
+    const PORT: u16 = 6702;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.ephemeral_dynamic_ns(false)).await;
+    io.ns("/", || {});
+    io.dynamic_ns("/tenant/:id", || {});
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text("40/tenant/1,".to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40/tenant/1,"));
+    assert_ok!(ws.send(Text("41/tenant/1,".to_string())).await);
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !io.of("/tenant/1").unwrap().sockets().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("the socket should disconnect");
+    // The empty namespace is kept
+    assert!(io.of("/tenant/1").is_some());
+}
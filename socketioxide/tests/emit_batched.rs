@@ -0,0 +1,33 @@
+//! Tests for the emission of several events in a single batch
+mod fixture;
+mod utils;
+
+use fixture::{create_polling_connection, create_server, send_req};
+use socketioxide::extract::SocketRef;
+
+#[tokio::test]
+pub async fn emit_batched_polling() {
+    const PORT: u16 = 6800;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        let res = s.emit_batched((1..=3).map(|i| ("tick", i)));
+        assert!(res.iter().all(Result::is_ok));
+    });
+
+    let sid = create_polling_connection(PORT).await;
+    let body = send_req(
+        PORT,
+        format!("transport=polling&sid={sid}"),
+        http::Method::GET,
+        None,
+    )
+    .await;
+    // The connect packet and the batched events are sent in the same payload
+    let packets: Vec<_> = body.split('\x1e').collect();
+    assert_eq!(packets.len(), 4);
+    assert!(packets[0].starts_with("0{"));
+    assert_eq!(
+        packets[1..],
+        [r#"42["tick",1]"#, r#"42["tick",2]"#, r#"42["tick",3]"#]
+    );
+}
@@ -0,0 +1,40 @@
+//! Tests for the emits serialized on the blocking thread pool
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::StreamExt;
+use socketioxide::extract::SocketRef;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn emit_blocking_large_payload() {
+    const PORT: u16 = 3100;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+    io.ns("/", move |s: SocketRef| {
+        s.join("viewers").unwrap();
+        tx.try_send(()).unwrap();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    let payload = vec![42u32; 100_000];
+    assert_ok!(
+        io.to("viewers")
+            .emit_blocking("snapshot", [payload.clone()])
+            .await
+    );
+    let msg = match assert_ok!(ws.next().await.unwrap()) {
+        Text(msg) => msg,
+        msg => panic!("unexpected message: {msg:?}"),
+    };
+    let (event, data): (String, Vec<u32>) = assert_ok!(serde_json::from_str(&msg[2..]));
+    assert_eq!(event, "snapshot");
+    assert_eq!(data, payload);
+}
@@ -0,0 +1,62 @@
+//! Tests for the emission of several events in a single broadcast
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::StreamExt;
+use serde_json::json;
+use socketioxide::{extract::SocketRef, socket::Sid};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn emit_many_snapshot() {
+    const PORT: u16 = 5300;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef| tx.try_send(s.id).unwrap());
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = rx.recv().await.unwrap();
+    io.get_socket(sid1).unwrap().join("game").unwrap();
+
+    // The second socket joins the room while the events are emitted
+    let events = ["score", "rank", "dirty"]
+        .into_iter()
+        .enumerate()
+        .map(|(i, event)| {
+            if i == 1 {
+                io.get_socket(sid2).unwrap().join("game").unwrap();
+            }
+            (event, json!(i))
+        });
+    assert_ok!(io.to("game").emit_many(events));
+
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["score",0]"#.to_string()));
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["rank",1]"#.to_string()));
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["dirty",2]"#.to_string()));
+    let res = tokio::time::timeout(Duration::from_millis(50), ws2.next()).await;
+    assert!(res.is_err());
+
+    // The binary payloads are attached to every event
+    let events = [("a", json!(1)), ("b", json!(2))];
+    assert_ok!(io.to("game").bin(vec![vec![1, 2]]).emit_many(events));
+    for ws in [&mut ws1, &mut ws2] {
+        for event in ["a", "b"] {
+            let msg = assert_ok!(ws.next().await.unwrap());
+            assert!(msg.to_string().starts_with(&format!(r#"451-["{event}""#)));
+            let msg = assert_ok!(ws.next().await.unwrap());
+            assert_eq!(msg, Binary(vec![1, 2]));
+        }
+    }
+}
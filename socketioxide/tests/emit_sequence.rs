@@ -0,0 +1,92 @@
+//! Tests for the ack-gated sequences of events
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::SocketRef,
+    socket::{SequenceConfig, SequenceProgress},
+    AckError, SocketError,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn emit_sequence_timeout() {
+    const PORT: u16 = 3500;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<SequenceProgress>(1);
+    io.ns("/", move |s: SocketRef| {
+        let tx = tx.clone();
+        s.on("start", move |s: SocketRef| async move {
+            let steps = vec![("step", 1), ("step", 2), ("step", 3)];
+            let config = SequenceConfig::new()
+                .timeout(Duration::from_millis(50))
+                .retries(1);
+            tx.try_send(s.emit_sequence(steps, config).await).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.send(Text(r#"42["start"]"#.into())).await);
+
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["step",1]"#.into()));
+    assert_ok!(ws.send(Text(r#"431["ok"]"#.into())).await);
+
+    // Step 2 is never acknowledged: it is sent again once then the sequence is aborted
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"422["step",2]"#.into()));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"423["step",2]"#.into()));
+
+    let progress = rx.recv().await.unwrap();
+    assert_eq!(progress.acked, 1);
+    assert_eq!(progress.total, 3);
+    assert!(!progress.is_complete());
+    assert!(matches!(progress.error, Some(AckError::Timeout)));
+
+    // Step 3 is never sent
+    let res = tokio::time::timeout(Duration::from_millis(50), ws.next()).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+pub async fn emit_sequence_disconnect() {
+    const PORT: u16 = 3501;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<SequenceProgress>(1);
+    io.ns("/", move |s: SocketRef| {
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            let steps = vec![("step", 1), ("step", 2)];
+            let config = SequenceConfig::new().timeout(Duration::from_secs(10));
+            tx.try_send(s.emit_sequence(steps, config).await).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["step",1]"#.into()));
+
+    // The client leaves the namespace while the step is pending
+    assert_ok!(ws.send(Text("41".into())).await);
+    let progress = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(progress.acked, 0);
+    assert!(matches!(
+        progress.error,
+        Some(AckError::Socket(SocketError::Closed(())))
+    ));
+}
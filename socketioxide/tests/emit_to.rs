@@ -0,0 +1,92 @@
+//! Tests for the `Socket::emit_to` shorthand
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    BroadcastError,
+};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+struct Unserializable;
+impl serde::Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("nope"))
+    }
+}
+
+#[tokio::test]
+pub async fn emit_to_room() {
+    const PORT: u16 = 5000;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<bool>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.join("chat").unwrap();
+        tx.try_send(false).unwrap();
+        let tx = tx.clone();
+        s.on("message", move |s: SocketRef, Data::<String>(msg)| {
+            assert_ok!(s.emit_to("chat", "message", msg));
+            let err = s.emit_to("chat", "message", Unserializable).unwrap_err();
+            tx.try_send(matches!(err, BroadcastError::Serialize(_)))
+                .unwrap();
+        });
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    assert_ok!(ws1.send(Text(r#"42["message","hello"]"#.to_string())).await);
+    assert!(rx.recv().await.unwrap());
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["message","hello"]"#.to_string()));
+
+    // The sender is excluded, like with `socket.to(rooms).emit()`
+    let res = tokio::time::timeout(Duration::from_millis(50), ws1.next()).await;
+    assert!(res.is_err());
+}
+
+#[tokio::test]
+pub async fn emit_to_other_namespace() {
+    const PORT: u16 = 5001;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<bool>(4);
+    io.ns("/notifications", |s: SocketRef| s.join("all").unwrap());
+    let io2 = io.clone();
+    io.ns("/", move |s: SocketRef| {
+        let (io, tx) = (io2.clone(), tx.clone());
+        s.on("announce", move |Data::<String>(msg)| {
+            assert_ok!(assert_ok!(io.to_ns("/notifications", "all")).emit("announcement", msg));
+            let res = io.to_ns("/nope", "all");
+            tx.try_send(matches!(res, Err(BroadcastError::NamespaceNotFound(ns)) if ns == "/nope"))
+                .unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.send(Text("40/notifications,".to_string())).await);
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(
+        ws.send(Text(r#"42["announce","maintenance"]"#.to_string()))
+            .await
+    );
+    assert!(rx.recv().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(r#"42/notifications,["announcement","maintenance"]"#.to_string())
+    );
+}
@@ -0,0 +1,53 @@
+//! Tests for the emits to a socket id
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{extract::SocketRef, socket::Sid, SendError};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn emit_to_sid() {
+    const PORT: u16 = 6200;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(1);
+    io.ns("/", |_: SocketRef| {});
+    io.ns("/chat", move |s: SocketRef| tx.try_send(s.id).unwrap());
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.send(Text("40/chat,".into())).await);
+    assert_ok!(ws.next().await.unwrap());
+    let sid = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let chat = io.of("/chat").unwrap();
+    assert_ok!(chat.emit_to_sid(sid, "dm", "hello"));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42/chat,["dm","hello"]"#.into()));
+    assert_ok!(io.emit_to_sid(sid, "dm", "root"));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["dm","root"]"#.into()));
+
+    let unknown = Sid::new();
+    let err = io.emit_to_sid(unknown, "dm", "hello").unwrap_err();
+    assert!(matches!(err, SendError::SocketGone(id) if id == unknown));
+
+    // Once the socket left the namespace, it is not found anymore
+    assert_ok!(ws.send(Text("41/chat,".into())).await);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let err = io
+        .of("/chat")
+        .unwrap()
+        .emit_to_sid(sid, "dm", "hello")
+        .unwrap_err();
+    assert!(matches!(err, SendError::SocketGone(id) if id == sid));
+}
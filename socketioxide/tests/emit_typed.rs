@@ -0,0 +1,57 @@
+//! Tests for the typed emits
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::StreamExt;
+use serde::Serialize;
+use socketioxide::{extract::SocketRef, socket::Event};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, Serialize)]
+struct ChatMessage {
+    author: &'static str,
+    text: &'static str,
+}
+impl Event for ChatMessage {
+    const NAME: &'static str = "chat:message";
+}
+
+#[tokio::test]
+pub async fn emit_typed() {
+    const PORT: u16 = 4000;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+    io.ns("/", move |s: SocketRef| {
+        s.join("chat").unwrap();
+        let msg = ChatMessage {
+            author: "bot",
+            text: "welcome",
+        };
+        s.emit_typed(msg).unwrap();
+        tx.try_send(()).unwrap();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(r#"42["chat:message",{"author":"bot","text":"welcome"}]"#.to_string())
+    );
+
+    let msg = ChatMessage {
+        author: "alice",
+        text: "hi",
+    };
+    assert_ok!(io.to("chat").emit_typed(msg));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(r#"42["chat:message",{"author":"alice","text":"hi"}]"#.to_string())
+    );
+}
@@ -0,0 +1,52 @@
+//! Tests for the extensions shared by the namespace sockets of a connection
+#![cfg(feature = "extensions")]
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::{Data, SocketRef};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+struct Session(String);
+struct NsLocal;
+
+#[tokio::test]
+pub async fn shared_across_namespaces() {
+    const PORT: u16 = 4900;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(Option<String>, bool)>(4);
+    io.ns("/", |s: SocketRef, Data::<String>(user)| {
+        s.engine_extensions().insert(Session(user));
+        s.extensions.insert(NsLocal);
+        s.emit("ready", ()).unwrap();
+    });
+    io.ns("/admin", move |s: SocketRef| {
+        let session = s.engine_extensions().get::<Session>().map(|s| s.0.clone());
+        tx.try_send((session, s.extensions.get::<NsLocal>().is_some()))
+            .unwrap();
+    });
+
+    let mut ws1 = create_ws_connection_with_auth(PORT, r#""alice""#).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.to_string()));
+    assert_ok!(ws1.send(Text("40/admin,".to_string())).await);
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40/admin,"));
+    // The engine extensions are shared, the socket extensions are not
+    assert_eq!(rx.recv().await.unwrap(), (Some("alice".to_string()), false));
+
+    // Another connection has its own engine extensions
+    let mut ws2 = create_ws_connection_with_auth(PORT, r#""bob""#).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.to_string()));
+    assert_ok!(ws2.send(Text("40/admin,".to_string())).await);
+    assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(rx.recv().await.unwrap(), (Some("bob".to_string()), false));
+}
@@ -0,0 +1,56 @@
+//! Tests for the hooks called once per engine.io connection
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::SocketRef,
+    socket::{DisconnectReason, Sid},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn engine_hooks_once_per_connection() {
+    const PORT: u16 = 4600;
+    use Message::*;
+    let (ctx, mut crx) = mpsc::unbounded_channel::<(Sid, String)>();
+    let (dtx, mut drx) = mpsc::unbounded_channel::<(Sid, DisconnectReason)>();
+    let io = create_server_with(PORT, move |b| {
+        b.on_engine_connect(move |sid, parts| {
+            ctx.send((sid, parts.uri.path().to_string())).unwrap()
+        })
+        .on_engine_disconnect(move |sid, reason| dtx.send((sid, reason)).unwrap())
+    })
+    .await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<Sid>();
+    let tx1 = tx.clone();
+    io.ns("/", move |s: SocketRef| tx1.send(s.id).unwrap());
+    io.ns("/admin", move |s: SocketRef| tx.send(s.id).unwrap());
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let sid = rx.recv().await.unwrap();
+    assert_ok!(ws.send(Text("40/admin,".to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40/admin,"));
+    assert_eq!(rx.recv().await.unwrap(), sid);
+
+    assert_eq!(crx.recv().await.unwrap(), (sid, "/socket.io/".to_string()));
+    assert!(crx.try_recv().is_err());
+
+    ws.close(None).await.unwrap();
+    let (dsid, reason) = tokio::time::timeout(Duration::from_secs(1), drx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(dsid, sid);
+    assert_eq!(reason, DisconnectReason::TransportClose);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(drx.try_recv().is_err());
+    assert!(crx.try_recv().is_err());
+}
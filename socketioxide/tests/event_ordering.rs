@@ -0,0 +1,84 @@
+//! Tests for the order in which the events of a socket are processed
+mod fixture;
+mod utils;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::{DisconnectReason, EVENT_QUEUE_CAPACITY},
+    EventOrdering, SocketIo,
+};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Sends 5 events whose async handler completes faster for the later events,
+/// and returns the order in which the handlers completed.
+async fn completion_order(io: SocketIo, port: u16) -> Vec<u64> {
+    use Message::*;
+    let (tx, mut rx) = mpsc::channel::<u64>(5);
+    io.ns("/", move |s: SocketRef| {
+        s.on("work", move |Data::<u64>(i)| async move {
+            tokio::time::sleep(Duration::from_millis(50 - i * 10)).await;
+            tx.try_send(i).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(port).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    for i in 0..5 {
+        assert_ok!(ws.send(Text(format!(r#"42["work",{i}]"#))).await);
+    }
+    let mut order = Vec::new();
+    for _ in 0..5 {
+        order.push(rx.recv().await.unwrap());
+    }
+    order
+}
+
+#[tokio::test]
+pub async fn fifo_event_ordering() {
+    const PORT: u16 = 6600;
+    let io = create_server_with(PORT, |b| b.event_ordering(EventOrdering::Fifo)).await;
+    assert_eq!(completion_order(io, PORT).await, [0, 1, 2, 3, 4]);
+}
+
+#[tokio::test]
+pub async fn fifo_event_queue_full() {
+    const PORT: u16 = 6601;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.event_ordering(EventOrdering::Fifo)).await;
+    let gate = Arc::new(Semaphore::new(0));
+    let handled = Arc::new(AtomicUsize::new(0));
+    let (gate_, handled_) = (gate.clone(), handled.clone());
+    let (tx, mut rx) = mpsc::channel::<DisconnectReason>(1);
+    io.ns("/", move |s: SocketRef| {
+        s.on("work", move || async move {
+            gate_.acquire().await.unwrap().forget();
+            handled_.fetch_add(1, Ordering::SeqCst);
+        });
+        s.on_disconnect(move |reason: DisconnectReason| {
+            tx.try_send(reason).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let total = EVENT_QUEUE_CAPACITY + 10;
+    for _ in 0..total {
+        assert_ok!(ws.send(Text(r#"42["work"]"#.to_string())).await);
+    }
+    // The socket overflowing its queue is disconnected instead of dropping events
+    let reason = tokio::time::timeout(Duration::from_millis(100), rx.recv()).await;
+    assert_eq!(reason.unwrap().unwrap(), DisconnectReason::EventQueueFull);
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+    gate.add_permits(total);
+}
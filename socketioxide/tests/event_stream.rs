@@ -0,0 +1,138 @@
+//! Tests for the stream of the events received by a socket
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use socketioxide::{
+    extract::{AckId, AckSender, SocketRef},
+    socket::{InboundEvent, EVENT_STREAM_CAPACITY},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn events_in_order() {
+    const PORT: u16 = 5200;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<Option<InboundEvent>>();
+    let (htx, mut hrx) = mpsc::unbounded_channel::<()>();
+    io.ns("/", move |s: SocketRef| {
+        s.on("named", move || htx.send(()).unwrap());
+        let mut events = s.events();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                tx.send(Some(event)).unwrap();
+            }
+            tx.send(None).unwrap();
+        });
+        s.emit("ready", ()).unwrap();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text(r#"42["a",1]"#.to_string())).await);
+    assert_ok!(ws.send(Text(r#"427["named",{"x":2}]"#.to_string())).await);
+    let msg = r#"451-8["bin","c",{"_placeholder":true,"num":0}]"#;
+    assert_ok!(ws.send(Text(msg.to_string())).await);
+    assert_ok!(ws.send(Binary(vec![1, 2, 3])).await);
+
+    let event = rx.recv().await.unwrap().unwrap();
+    assert_eq!(event, ("a".into(), json!([1]), vec![], AckId(None)));
+    let event = rx.recv().await.unwrap().unwrap();
+    assert_eq!(
+        event,
+        ("named".into(), json!([{"x":2}]), vec![], AckId(Some(7)))
+    );
+    let event = rx.recv().await.unwrap().unwrap();
+    let bin = vec![vec![1, 2, 3]];
+    assert_eq!(event, ("bin".into(), json!(["c"]), bin, AckId(Some(8))));
+
+    // The named handler is still called
+    hrx.recv().await.unwrap();
+
+    // The stream ends with the socket
+    assert_ok!(ws.send(Text("41".to_string())).await);
+    assert!(rx.recv().await.unwrap().is_none());
+}
+
+#[tokio::test]
+pub async fn unhandled_event_stream() {
+    const PORT: u16 = 5201;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<(String, serde_json::Value)>();
+    let (htx, mut hrx) = mpsc::unbounded_channel::<&'static str>();
+    io.ns("/", move |s: SocketRef| {
+        let htx2 = htx.clone();
+        s.on("named", move || htx.send("named").unwrap());
+        s.on_fallback(move || htx2.send("fallback").unwrap());
+        let mut events = s.event_stream();
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            while let Some(ev) = events.next().await {
+                if let Some(ack) = ev.ack {
+                    ack.send(&ev.event).unwrap();
+                }
+                tx.send((ev.event, ev.data)).unwrap();
+            }
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text(r#"42["named",1]"#.to_string())).await);
+    assert_ok!(ws.send(Text(r#"423["other",2]"#.to_string())).await);
+
+    assert_eq!(hrx.recv().await.unwrap(), "named");
+    assert_eq!(rx.recv().await.unwrap(), ("other".into(), json!([2])));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"433["other"]"#.to_string()));
+    // The unhandled event was not sent to the fallback handler
+    assert!(hrx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn unhandled_event_stream_full() {
+    const PORT: u16 = 5202;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (htx, mut hrx) = mpsc::unbounded_channel::<Option<i64>>();
+    io.ns("/", move |s: SocketRef| {
+        let htx = htx.clone();
+        s.on_fallback(move |ack: AckSender, AckId(id): AckId| {
+            htx.send(id).unwrap();
+            ack.send("fallback").ok();
+        });
+        // The stream is kept alive but never consumed
+        let events = s.event_stream();
+        tokio::spawn(async move {
+            let _events = events;
+            futures::future::pending::<()>().await;
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    for _ in 0..=EVENT_STREAM_CAPACITY {
+        assert_ok!(ws.send(Text(r#"42["other"]"#.to_string())).await);
+    }
+    assert_ok!(ws.send(Text(r#"423["other"]"#.to_string())).await);
+
+    // The event expecting an ack is dispatched to the fallback handler once the stream is full,
+    // the other ones are dropped
+    assert_eq!(hrx.recv().await.unwrap(), Some(3));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"433["fallback"]"#.to_string()));
+    assert!(hrx.try_recv().is_err());
+}
@@ -0,0 +1,50 @@
+//! Tests for the exclusion of a socket from the operators built outside of a handler
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::StreamExt;
+use socketioxide::{extract::SocketRef, socket::Sid};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn exclude_from_io() {
+    const PORT: u16 = 3400;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.join("room1").unwrap();
+        tx.try_send(s.id).unwrap();
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = rx.recv().await.unwrap();
+
+    let sockets = io.exclude(sid1).sockets().unwrap();
+    assert_eq!(sockets.len(), 1);
+    assert_eq!(sockets[0].id, sid2);
+
+    assert_ok!(io.exclude(sid1).emit("update", 1));
+    assert_ok!(io.to("room1").exclude(sid2).emit("update", 2));
+    assert_ok!(io.exclude(Sid::new()).emit("update", 3));
+
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["update",2]"#.to_string()));
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["update",3]"#.to_string()));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["update",1]"#.to_string()));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["update",3]"#.to_string()));
+
+    let res = tokio::time::timeout(Duration::from_millis(50), ws1.next()).await;
+    assert!(res.is_err());
+}
@@ -2,10 +2,13 @@
 use std::time::Duration;
 
 use serde_json::json;
-use socketioxide::extract::{Data, SocketRef, State, TryData};
+use serde_json::Value;
+use socketioxide::extract::{Attachment, AuthData, Bin, Data, Reply, SocketRef, State, TryData};
 use tokio::sync::mpsc;
 
-use fixture::{create_server, create_server_with_state};
+use fixture::{
+    create_server, create_server_with_state, create_ws_connection, create_ws_connection_with_auth,
+};
 
 use crate::fixture::socketio_client;
 
@@ -112,3 +115,126 @@ pub async fn try_data_extractor() {
 
     assert_ok!(client.disconnect().await);
 }
+
+#[tokio::test]
+pub async fn reply_extractor() {
+    const PORT: u16 = 2003;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message::Text;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.on("query", |Data::<i32>(data), reply: Reply| {
+            assert_eq!(reply.event(), "query");
+            assert_ok!(reply.send("query-result", data + 1));
+        });
+        s.on("search", |Data::<String>(data), reply: Reply| async move {
+            assert_eq!(reply.event(), "search");
+            assert_ok!(reply.send_correlated("result", (data, 2)));
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text(r#"42["query",1]"#.to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["query-result",2]"#.to_string()));
+
+    assert_ok!(ws.send(Text(r#"42["search","foo"]"#.to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["result","foo",2,"search"]"#.to_string()));
+}
+
+#[tokio::test]
+pub async fn data_extractor_named_attachments() {
+    const PORT: u16 = 2004;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Media {
+        name: String,
+        thumbnail: Attachment,
+        video: Attachment,
+    }
+
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Media>(4);
+    let (raw_tx, mut raw_rx) = mpsc::channel::<(Value, Vec<Vec<u8>>)>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("media", move |Data::<Media>(media)| {
+            assert_ok!(tx.try_send(media));
+        });
+        // The placeholders are kept for the other data types
+        s.on("raw", move |Data::<Value>(data), Bin(bin)| {
+            assert_ok!(raw_tx.try_send((data, bin)));
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    let msg = r#"452-["media",{"name":"clip","thumbnail":{"_placeholder":true,"num":0},"video":{"_placeholder":true,"num":1}}]"#;
+    assert_ok!(ws.send(Text(msg.into())).await);
+    assert_ok!(ws.send(Binary(vec![1, 2])).await);
+    assert_ok!(ws.send(Binary(vec![3, 4, 5])).await);
+
+    let media = tokio::time::timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        media,
+        Media {
+            name: "clip".into(),
+            thumbnail: Attachment(vec![1, 2]),
+            video: Attachment(vec![3, 4, 5]),
+        }
+    );
+
+    let msg = r#"451-["raw",{"video":{"_placeholder":true,"num":0}}]"#;
+    assert_ok!(ws.send(Text(msg.into())).await);
+    assert_ok!(ws.send(Binary(vec![1, 2])).await);
+    let (data, bin) = tokio::time::timeout(Duration::from_millis(200), raw_rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data, json!({ "video": { "_placeholder": true, "num": 0 } }));
+    assert_eq!(bin, [vec![1, 2]]);
+}
+
+#[tokio::test]
+pub async fn auth_data_extractor() {
+    const PORT: u16 = 2005;
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Auth {
+        token: String,
+    }
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Option<Auth>>(4);
+    io.ns(
+        "/",
+        move |socket: SocketRef, AuthData(auth): AuthData<Option<Auth>>| {
+            assert_ok!(tx.try_send(auth));
+            let tx = tx.clone();
+            socket.on("test", move |AuthData(auth): AuthData<Option<Auth>>| {
+                assert_ok!(tx.try_send(auth));
+            });
+        },
+    );
+
+    let _ws = create_ws_connection_with_auth(PORT, "").await;
+    assert_eq!(rx.recv().await.unwrap(), None);
+
+    let client = assert_ok!(socketio_client(PORT, json!({ "token": "foo" })).await);
+    let auth = Some(Auth {
+        token: "foo".into(),
+    });
+    assert_eq!(rx.recv().await.unwrap(), auth);
+    assert_ok!(client.emit("test", json!("bar")).await);
+    assert_eq!(rx.recv().await.unwrap(), auth);
+
+    assert_ok!(client.disconnect().await);
+}
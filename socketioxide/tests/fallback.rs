@@ -0,0 +1,70 @@
+//! Tests for the fallback handler of the events without a registered handler
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use socketioxide::extract::{AckSender, Bin, Data, EventName, SocketRef};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn fallback_handler() {
+    const PORT: u16 = 5500;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(String, Value, Vec<Vec<u8>>)>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("known", |s: SocketRef| {
+            s.emit("known-ok", ()).ok();
+        });
+        // Only the last fallback is used
+        s.on_fallback(|| -> () { unreachable!("replaced fallback called") });
+        let tx = tx.clone();
+        s.on_fallback(
+            move |EventName(event), Data::<Value>(data), ack: AckSender, Bin(bin)| {
+                ack.send(&event).ok();
+                tx.try_send((event, data, bin)).unwrap();
+            },
+        );
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    // A registered handler takes precedence
+    assert_ok!(ws.send(Text(r#"42["known"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["known-ok",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"421["unknown",{"a":1}]"#.into())).await);
+    let (event, data, bin) = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event, "unknown");
+    assert_eq!(data, serde_json::json!({ "a": 1 }));
+    assert!(bin.is_empty());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"431["unknown"]"#.into()));
+
+    // Binary events are dispatched to the fallback as well
+    assert_ok!(
+        ws.send(Text(r#"451-["bin",{"_placeholder":true,"num":0}]"#.into()))
+            .await
+    );
+    assert_ok!(ws.send(Binary(vec![1, 2, 3])).await);
+    let (event, _, bin) = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(event, "bin");
+    assert_eq!(bin, vec![vec![1, 2, 3]]);
+}
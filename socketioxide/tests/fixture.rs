@@ -20,7 +20,7 @@ use rust_socketio::{
     Payload,
 };
 use serde::{Deserialize, Serialize};
-use socketioxide::{adapter::LocalAdapter, service::SocketIoService, SocketIo};
+use socketioxide::{adapter::LocalAdapter, service::SocketIoService, SocketIo, SocketIoBuilder};
 use tokio::net::{TcpListener, TcpStream};
 use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
 
@@ -91,6 +91,14 @@ pub async fn create_polling_connection(port: u16) -> String {
     open_packet.sid
 }
 pub async fn create_ws_connection(port: u16) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
+    create_ws_connection_with_auth(port, "{}").await
+}
+
+/// Creates a websocket connection to the root namespace with the given json auth payload
+pub async fn create_ws_connection_with_auth(
+    port: u16,
+    auth: &str,
+) -> WebSocketStream<MaybeTlsStream<TcpStream>> {
     let mut ws = tokio_tungstenite::connect_async(format!(
         "ws://127.0.0.1:{port}/socket.io/?EIO=4&transport=websocket"
     ))
@@ -98,7 +106,7 @@ pub async fn create_ws_connection(port: u16) -> WebSocketStream<MaybeTlsStream<T
     .unwrap()
     .0;
 
-    ws.send(Message::Text("40{}".to_string())).await.unwrap();
+    ws.send(Message::Text(format!("40{auth}"))).await.unwrap();
 
     ws
 }
@@ -125,6 +133,21 @@ pub async fn create_server(port: u16) -> SocketIo {
     io
 }
 
+/// Creates a server with the same defaults as [`create_server`]
+/// and lets the caller customize the builder.
+pub async fn create_server_with(
+    port: u16,
+    f: impl FnOnce(SocketIoBuilder) -> SocketIoBuilder,
+) -> SocketIo {
+    let builder = SocketIo::builder()
+        .ping_interval(Duration::from_millis(300))
+        .ping_timeout(Duration::from_millis(200));
+    let (svc, io) = f(builder).build_svc();
+
+    spawn_server(port, svc).await;
+    io
+}
+
 pub async fn socketio_client_with_handler<F>(
     port: u16,
     event: &str,
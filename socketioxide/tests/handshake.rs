@@ -0,0 +1,70 @@
+//! Tests for the handshake details of the sockets
+mod fixture;
+mod utils;
+
+use std::{net::SocketAddr, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use hyper::{body::Incoming, server::conn::http1, service::Service, Request};
+use hyper_util::rt::TokioIo;
+use serde_json::json;
+use socketioxide::{extract::SocketRef, socket::Handshake, SocketIo};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+#[tokio::test]
+pub async fn handshake() {
+    const PORT: u16 = 5800;
+    let (svc, io) = SocketIo::new_svc();
+    let (tx, mut rx) = mpsc::channel::<(Handshake, Handshake)>(1);
+    io.ns("/", move |s: SocketRef, handshake: Handshake| {
+        tx.try_send((handshake, s.handshake().clone())).unwrap();
+    });
+
+    // The server inserts the address of the client in the request extensions
+    let listener = TcpListener::bind(("127.0.0.1", PORT)).await.unwrap();
+    tokio::spawn(async move {
+        let (stream, addr) = listener.accept().await.unwrap();
+        let svc = hyper::service::service_fn(move |mut req: Request<Incoming>| {
+            req.extensions_mut().insert(addr);
+            svc.call(req)
+        });
+        http1::Builder::new()
+            .serve_connection(TokioIo::new(stream), svc)
+            .with_upgrades()
+            .await
+            .ok();
+    });
+
+    let mut req = format!(
+        "ws://127.0.0.1:{PORT}/socket.io/?EIO=4&transport=websocket&room=game%201&name=a+b&flag"
+    )
+    .into_client_request()
+    .unwrap();
+    req.headers_mut()
+        .insert("x-custom", "custom-value".parse().unwrap());
+    let (mut ws, _) = tokio_tungstenite::connect_async(req).await.unwrap();
+    assert_ok!(ws.next().await.unwrap());
+    let local_addr: SocketAddr = match ws.get_ref() {
+        tokio_tungstenite::MaybeTlsStream::Plain(s) => s.local_addr().unwrap(),
+        _ => unreachable!(),
+    };
+    assert_ok!(
+        ws.send(Message::Text(r#"40{"token":"secret"}"#.into()))
+            .await
+    );
+
+    let (extracted, handshake) = tokio::time::timeout(Duration::from_secs(1), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    for handshake in [extracted, handshake] {
+        assert_eq!(handshake.auth, json!({ "token": "secret" }));
+        assert_eq!(handshake.query["room"], "game 1");
+        assert_eq!(handshake.query["name"], "a b");
+        assert_eq!(handshake.query["flag"], "");
+        assert_eq!(handshake.query["transport"], "websocket");
+        assert_eq!(handshake.headers["x-custom"], "custom-value");
+        assert_eq!(handshake.address, Some(local_addr));
+    }
+}
@@ -0,0 +1,68 @@
+//! Tests for the idle sockets reaping
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    clock::ManualClock,
+    extract::SocketRef,
+    socket::{DisconnectReason, Sid},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn disconnect_idle() {
+    const PORT: u16 = 3000;
+    use Message::*;
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| b.with_clock(clock.clone())).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    let (dtx, mut drx) = mpsc::channel::<(Sid, DisconnectReason)>(4);
+    io.ns("/", move |s: SocketRef| {
+        tx.try_send(s.id).unwrap();
+        let tx = tx.clone();
+        s.on("activity", move |s: SocketRef| tx.try_send(s.id).unwrap());
+        let dtx = dtx.clone();
+        s.on_disconnect(move |s: SocketRef, reason: DisconnectReason| {
+            dtx.try_send((s.id, reason)).unwrap()
+        });
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = rx.recv().await.unwrap();
+
+    clock.advance(Duration::from_secs(30));
+    assert_ok!(ws1.send(Text(r#"42["activity"]"#.to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), sid1);
+    clock.advance(Duration::from_secs(30));
+
+    let s1 = io.get_socket(sid1).unwrap();
+    assert_eq!(s1.idle_for(), Duration::from_secs(30));
+    assert_eq!(
+        io.get_socket(sid2).unwrap().idle_for(),
+        Duration::from_secs(60)
+    );
+
+    let disconnected = io.disconnect_idle("/", Duration::from_secs(45)).unwrap();
+    assert_eq!(disconnected, Some(vec![sid2]));
+    assert_eq!(
+        drx.recv().await.unwrap(),
+        (sid2, DisconnectReason::IdleTimeout)
+    );
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text("41".to_string()));
+
+    assert!(io.get_socket(sid1).is_some());
+    assert!(io.get_socket(sid2).is_none());
+    assert_eq!(io.disconnect_idle("/nope", Duration::ZERO).unwrap(), None);
+}
@@ -0,0 +1,64 @@
+//! Tests for the application-level keepalive checks
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    clock::ManualClock,
+    extract::SocketRef,
+    socket::{DisconnectReason, Sid},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn disconnect_after_missed_pings() {
+    const PORT: u16 = 4800;
+    use Message::*;
+    const INTERVAL: Duration = Duration::from_secs(10);
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| {
+        b.with_clock(clock.clone()).app_keepalive(INTERVAL, 2)
+    })
+    .await;
+    let (dtx, mut drx) = mpsc::channel::<(Sid, DisconnectReason)>(4);
+    io.ns("/", move |s: SocketRef| {
+        let dtx = dtx.clone();
+        s.on_disconnect(move |s: SocketRef, reason: DisconnectReason| {
+            dtx.try_send((s.id, reason)).unwrap()
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    // The first ping is acknowledged
+    clock.advance(INTERVAL);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"421["_ping",null]"#.to_string()));
+    assert_ok!(ws.send(Text("431[]".to_string())).await);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // Then the client stops answering: the first miss is tolerated
+    clock.advance(INTERVAL);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"422["_ping",null]"#.to_string()));
+    // Lets the ack timeout be registered before advancing the clock
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    clock.advance(INTERVAL);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"423["_ping",null]"#.to_string()));
+    assert!(drx.try_recv().is_err());
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    clock.advance(INTERVAL);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text("41".to_string()));
+    let (sid, reason) = drx.recv().await.unwrap();
+    assert_eq!(reason, DisconnectReason::KeepaliveTimeout);
+    assert!(io.get_socket(sid).is_none());
+}
@@ -0,0 +1,49 @@
+//! Tests for the removal of a socket from a room with a notification
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::SocketRef;
+use tokio::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn kick_from_room() {
+    const PORT: u16 = 6500;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.join("game").unwrap();
+        s.on("cheat", |s: SocketRef| {
+            let kicked = s.kick_from_room("game", "kicked").unwrap();
+            s.within("game").emit("game-update", kicked).unwrap();
+        });
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+
+    assert_ok!(ws1.send(Text(r#"42["cheat"]"#.into())).await);
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["kicked","game"]"#.into()));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["game-update",true]"#.into()));
+    // The kicked socket doesn't receive the room broadcasts anymore
+    let res = tokio::time::timeout(Duration::from_millis(50), ws1.next()).await;
+    assert!(res.is_err());
+
+    // A socket that is not in the room is not notified
+    assert_ok!(ws1.send(Text(r#"42["cheat"]"#.into())).await);
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["game-update",false]"#.into()));
+    let res = tokio::time::timeout(Duration::from_millis(50), ws1.next()).await;
+    assert!(res.is_err());
+}
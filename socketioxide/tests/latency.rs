@@ -0,0 +1,43 @@
+//! Tests for the heartbeat round-trip time of the sockets
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{extract::SocketRef, socket::Sid};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn heartbeat_latency() {
+    const PORT: u16 = 7000;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(1);
+    io.ns("/", move |s: SocketRef| tx.try_send(s.id).unwrap());
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let socket = io.get_socket(rx.recv().await.unwrap()).unwrap();
+    assert_eq!(socket.latency(), None);
+
+    let ping = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(ping, Message::Text("2".to_string()));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_ok!(ws.send(Message::Text("3".to_string())).await);
+
+    let latency = tokio::time::timeout(Duration::from_secs(1), async {
+        loop {
+            if let Some(latency) = socket.latency() {
+                return latency;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(latency >= Duration::from_millis(50));
+    assert!(latency < Duration::from_millis(200));
+}
@@ -0,0 +1,100 @@
+//! Tests for the namespace middlewares
+mod fixture;
+mod utils;
+
+use std::sync::{Arc, Mutex};
+
+use fixture::{create_server, create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::{AckSender, SocketRef};
+use socketioxide::socket::{DisconnectReason, DisconnectRecord};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn runtime_middleware() {
+    const PORT: u16 = 3200;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(4);
+    io.ns("/", move |s: SocketRef| {
+        tx.try_send(()).unwrap();
+        s.on("ping", |ack: AckSender| ack.send("pong").unwrap());
+    });
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let order1 = order.clone();
+    let first = io
+        .push_middleware("/", move |_, _| {
+            order1.lock().unwrap().push(1);
+            Ok(())
+        })
+        .unwrap();
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40"));
+    rx.recv().await.unwrap();
+
+    // Enable a rejecting middleware at runtime
+    let order2 = order.clone();
+    let reject = io
+        .push_middleware("/", move |_, auth| {
+            order2.lock().unwrap().push(2);
+            match auth {
+                Some(auth) if auth.contains("token") => Ok(()),
+                _ => Err("missing token".into()),
+            }
+        })
+        .unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"44{"message":"missing token"}"#.to_string()));
+    assert!(rx.try_recv().is_err());
+    assert_eq!(*order.lock().unwrap(), [1, 1, 2]);
+
+    // The already connected socket is unaffected
+    assert_ok!(ws1.send(Text(r#"421["ping"]"#.to_string())).await);
+    let msg = assert_ok!(ws1.next().await.unwrap());
+    assert_eq!(msg, Text(r#"431["pong"]"#.to_string()));
+
+    assert!(io.remove_middleware("/", reject));
+    assert!(!io.remove_middleware("/", reject));
+    let mut ws3 = create_ws_connection(PORT).await;
+    assert_ok!(ws3.next().await.unwrap());
+    let msg = assert_ok!(ws3.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40"));
+    rx.recv().await.unwrap();
+
+    assert!(io.remove_middleware("/", first));
+    assert!(io.push_middleware("/unknown", |_, _| Ok(())).is_none());
+}
+
+#[tokio::test]
+pub async fn refused_socket_is_closed() {
+    const PORT: u16 = 3201;
+    use Message::*;
+    let (tx, mut rx) = mpsc::channel::<DisconnectRecord>(4);
+    let io = create_server_with(PORT, move |b| {
+        b.on_disconnect_record(move |record| tx.try_send(record).unwrap())
+    })
+    .await;
+    let (connect_tx, mut connect_rx) = mpsc::channel::<()>(1);
+    io.ns("/", move || connect_tx.try_send(()).unwrap());
+    io.push_middleware("/", |socket, _| {
+        socket.join("lobby").unwrap();
+        Err("refused".into())
+    })
+    .unwrap();
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"44{"message":"refused"}"#.to_string()));
+
+    let record = rx.recv().await.unwrap();
+    assert_eq!(record.reason, DisconnectReason::MiddlewareRefused);
+    assert_eq!(record.rooms, ["lobby"]);
+    assert!(connect_rx.try_recv().is_err());
+}
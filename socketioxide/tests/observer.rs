@@ -0,0 +1,58 @@
+//! Tests for the observer sockets
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::ObserverScope,
+};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn observe_namespace() {
+    const PORT: u16 = 3900;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(4);
+    io.ns("/", move |s: SocketRef, Data::<String>(role)| {
+        match role.as_str() {
+            "broadcasts" => s.make_observer(ObserverScope::Broadcasts),
+            "all" => s.make_observer(ObserverScope::All),
+            _ => s.join("room1").unwrap(),
+        }
+        s.on("ping", |s: SocketRef| s.emit("pong", "direct").unwrap());
+        tx.try_send(()).unwrap();
+    });
+
+    let mut user = create_ws_connection_with_auth(PORT, r#""user""#).await;
+    assert_ok!(user.next().await.unwrap());
+    assert_ok!(user.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let mut obs1 = create_ws_connection_with_auth(PORT, r#""broadcasts""#).await;
+    assert_ok!(obs1.next().await.unwrap());
+    assert_ok!(obs1.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let mut obs2 = create_ws_connection_with_auth(PORT, r#""all""#).await;
+    assert_ok!(obs2.next().await.unwrap());
+    assert_ok!(obs2.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    // The observers are not in room1 but receive the broadcast
+    assert_ok!(io.to("room1").emit("news", "hello"));
+    for ws in [&mut user, &mut obs1, &mut obs2] {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["news","hello"]"#.to_string()));
+    }
+
+    // Only the observer of all the traffic receives the direct emits
+    assert_ok!(user.send(Text(r#"42["ping"]"#.to_string())).await);
+    for ws in [&mut user, &mut obs2] {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["pong","direct"]"#.to_string()));
+    }
+    let res = tokio::time::timeout(Duration::from_millis(50), obs1.next()).await;
+    assert!(res.is_err());
+}
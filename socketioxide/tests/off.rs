@@ -0,0 +1,125 @@
+//! Tests for the removal of message handlers
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::{EventName, SocketRef};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn off_from_handler() {
+    const PORT: u16 = 5600;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.on("once", |s: SocketRef| {
+            let removed = s.off("once");
+            s.emit("once-ok", removed).ok();
+        });
+        s.on_fallback(|s: SocketRef, EventName(event)| {
+            s.emit("unhandled", event).ok();
+        });
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["once"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["once-ok",true]"#.into()));
+
+    // The removed handler is not called anymore
+    assert_ok!(ws.send(Text(r#"42["once"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["unhandled","once"]"#.into()));
+}
+
+#[tokio::test]
+pub async fn off_all() {
+    const PORT: u16 = 5601;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.on("a", |s: SocketRef| {
+            s.emit("a-ok", ()).ok();
+        });
+        s.on("reset", |s: SocketRef| {
+            s.off_all();
+            s.emit("reset-ok", s.off("a")).ok();
+        });
+        s.on_fallback(|s: SocketRef, EventName(event)| {
+            s.emit("unhandled", event).ok();
+        });
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["a"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["a-ok",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["reset"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["reset-ok",false]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["a"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["unhandled","a"]"#.into()));
+}
+
+#[tokio::test]
+pub async fn off_by_id() {
+    const PORT: u16 = 5602;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.on_with_id("move", |s: SocketRef| {
+            s.emit("game", ()).ok();
+        });
+        let audit = s.on_with_id("move", |s: SocketRef| {
+            s.emit("audit", ()).ok();
+        });
+        s.on("stop-audit", move |s: SocketRef| {
+            s.emit("stop-audit-ok", s.off_by_id(audit)).ok();
+        });
+        s.on_fallback(|s: SocketRef, EventName(event)| {
+            s.emit("unhandled", event).ok();
+        });
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    // Both handlers are called in registration order
+    assert_ok!(ws.send(Text(r#"42["move"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["game",null]"#.into()));
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["audit",null]"#.into()));
+
+    assert_ok!(ws.send(Text(r#"42["stop-audit"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["stop-audit-ok",true]"#.into()));
+
+    // Only the removed handler is not called anymore
+    assert_ok!(ws.send(Text(r#"42["move"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["game",null]"#.into()));
+    assert_ok!(ws.send(Text(r#"42["stop-audit"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["stop-audit-ok",false]"#.into()));
+}
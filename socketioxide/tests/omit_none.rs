@@ -0,0 +1,58 @@
+//! Tests for the removal of the `null` fields of the emitted data
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use socketioxide::extract::{AckSender, SocketRef};
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, Serialize)]
+struct Profile {
+    name: &'static str,
+    nickname: Option<&'static str>,
+}
+
+const PROFILE: Profile = Profile {
+    name: "alice",
+    nickname: None,
+};
+
+async fn profile_payloads(port: u16, omit_none_fields: bool) -> (Message, Message) {
+    let io = create_server_with(port, |b| b.omit_none_fields(omit_none_fields)).await;
+    io.ns("/", |s: SocketRef| {
+        s.emit("profile", PROFILE).unwrap();
+        s.on("get", |ack: AckSender| ack.send(PROFILE).unwrap());
+    });
+
+    let mut ws = create_ws_connection(port).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let event = assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.send(Message::Text(r#"421["get"]"#.to_string())).await);
+    let ack = assert_ok!(ws.next().await.unwrap());
+    (event, ack)
+}
+
+#[tokio::test]
+pub async fn keep_none_fields() {
+    use Message::*;
+    let (event, ack) = profile_payloads(3700, false).await;
+    assert_eq!(
+        event,
+        Text(r#"42["profile",{"name":"alice","nickname":null}]"#.to_string())
+    );
+    assert_eq!(
+        ack,
+        Text(r#"431[{"name":"alice","nickname":null}]"#.to_string())
+    );
+}
+
+#[tokio::test]
+pub async fn omit_none_fields() {
+    use Message::*;
+    let (event, ack) = profile_payloads(3701, true).await;
+    assert_eq!(event, Text(r#"42["profile",{"name":"alice"}]"#.to_string()));
+    assert_eq!(ack, Text(r#"431[{"name":"alice"}]"#.to_string()));
+}
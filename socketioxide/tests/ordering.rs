@@ -0,0 +1,52 @@
+//! Tests for the ordering of the packets sent to a socket through the different emit paths
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::Sid,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn direct_and_room_emits_order() {
+    const PORT: u16 = 4200;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(2);
+    let io2 = io.clone();
+    io.ns("/", move |s: SocketRef, Data::<String>(room)| {
+        s.join(room).unwrap();
+        // Interleave direct emits to the peer and broadcasts to the room containing it
+        let io = io2.clone();
+        s.on("go", move |s: SocketRef, Data::<Sid>(peer)| {
+            let peer = io.get_socket(peer).unwrap();
+            for i in 0..50 {
+                if i % 2 == 0 {
+                    peer.emit("msg", i).unwrap();
+                } else {
+                    s.to("room").emit("msg", i).unwrap();
+                }
+            }
+        });
+        tx.try_send(s.id).unwrap();
+    });
+
+    let mut ws1 = create_ws_connection_with_auth(PORT, r#""lobby""#).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection_with_auth(PORT, r#""room""#).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = rx.recv().await.unwrap();
+
+    assert_ok!(ws1.send(Text(format!(r#"42["go","{sid2}"]"#))).await);
+    for i in 0..50 {
+        let msg = assert_ok!(ws2.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["msg",{i}]"#)));
+    }
+}
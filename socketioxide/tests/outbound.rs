@@ -0,0 +1,90 @@
+//! Tests for the per-socket outbound middlewares
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    packet::{BinaryPacket, Packet, PacketData},
+};
+use tokio_tungstenite::tungstenite::Message;
+
+fn prefix_event(prefix: &'static str) -> impl Fn(&mut Packet<'_>) + Send + Sync {
+    move |packet| {
+        if let PacketData::Event(event, _, _) = &mut packet.inner {
+            *event = format!("{prefix}:{event}").into();
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn per_socket_middlewares() {
+    const PORT: u16 = 6300;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef, Data::<String>(tag)| {
+        if tag == "a" {
+            // The middlewares are applied in order
+            s.use_outbound(prefix_event("a"));
+            s.use_outbound(prefix_event("1"));
+        } else {
+            s.use_outbound(prefix_event("b"));
+            s.use_outbound_with_control(|packet| {
+                if let PacketData::Disconnect = packet.inner {
+                    packet.ns = "/b".into();
+                }
+            });
+        }
+        s.on("bye", |s: SocketRef| s.disconnect().unwrap());
+        s.emit("ready", ()).ok();
+    });
+
+    let mut a = create_ws_connection_with_auth(PORT, r#""a""#).await;
+    assert_ok!(a.next().await.unwrap());
+    assert_ok!(a.next().await.unwrap());
+    let msg = assert_ok!(a.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["1:a:ready",null]"#.into()));
+
+    let mut b = create_ws_connection_with_auth(PORT, r#""b""#).await;
+    assert_ok!(b.next().await.unwrap());
+    assert_ok!(b.next().await.unwrap());
+    let msg = assert_ok!(b.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["b:ready",null]"#.into()));
+
+    // The control packets are only transformed by the middlewares opting in
+    assert_ok!(a.send(Text(r#"42["bye"]"#.into())).await);
+    let msg = assert_ok!(a.next().await.unwrap());
+    assert_eq!(msg, Text("41".into()));
+    assert_ok!(b.send(Text(r#"42["bye"]"#.into())).await);
+    let msg = assert_ok!(b.next().await.unwrap());
+    assert_eq!(msg, Text("41/b,".into()));
+}
+
+#[tokio::test]
+pub async fn middleware_adding_binary_payload() {
+    const PORT: u16 = 6301;
+    use Message::*;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef| {
+        s.use_outbound(|packet| {
+            if let PacketData::Event(event, data, ack) = &packet.inner {
+                let bin = BinaryPacket::outgoing(data.clone(), vec![vec![1, 2, 3]]);
+                packet.inner = PacketData::BinaryEvent(event.clone(), bin, *ack);
+            }
+        });
+        s.emit("ready", "foo").ok();
+    });
+
+    // The permit of the added payload is reserved after the middleware
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(r#"451-["ready","foo",{"_placeholder":true,"num":0}]"#.into())
+    );
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Binary(vec![1, 2, 3]));
+}
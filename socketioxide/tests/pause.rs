@@ -0,0 +1,74 @@
+//! Tests for pausing inbound events
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Data, SocketRef},
+    PauseEventsPolicy,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn pause_events_buffer() {
+    const PORT: u16 = 2500;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<i32>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("test", move |Data::<i32>(data)| {
+            tx.try_send(data).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    io.pause_events(PauseEventsPolicy::Buffer(8));
+    for i in 1..=3 {
+        assert_ok!(ws.send(Text(format!("42[\"test\",{i}]"))).await);
+    }
+    let res = tokio::time::timeout(Duration::from_millis(50), rx.recv()).await;
+    assert!(res.is_err(), "events should be withheld while paused");
+
+    io.resume_events();
+    for i in 1..=3 {
+        assert_eq!(rx.recv().await.unwrap(), i);
+    }
+
+    // The connection is still alive and new events are dispatched directly
+    assert_ok!(ws.send(Text("42[\"test\",4]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), 4);
+}
+
+#[tokio::test]
+pub async fn pause_events_drop() {
+    const PORT: u16 = 2501;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<i32>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("test", move |Data::<i32>(data)| {
+            tx.try_send(data).unwrap();
+        });
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    io.pause_events(PauseEventsPolicy::Drop);
+    assert_ok!(ws.send(Text("42[\"test\",1]".to_string())).await);
+    let res = tokio::time::timeout(Duration::from_millis(50), rx.recv()).await;
+    assert!(res.is_err(), "events should be dropped while paused");
+
+    io.resume_events();
+    assert_ok!(ws.send(Text("42[\"test\",2]".to_string())).await);
+    assert_eq!(rx.recv().await.unwrap(), 2);
+    assert!(rx.try_recv().is_err());
+}
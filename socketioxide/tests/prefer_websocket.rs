@@ -0,0 +1,53 @@
+//! Tests for the `prefer_websocket` operator
+mod fixture;
+mod utils;
+
+use fixture::{create_polling_connection, create_server, create_ws_connection, send_req};
+use futures::StreamExt;
+use serde_json::json;
+use socketioxide::extract::SocketRef;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn prefer_websocket() {
+    const PORT: u16 = 4400;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(2);
+    io.ns("/", move |s: SocketRef| {
+        s.prefer_websocket(json!("fallback"))
+            .bin(vec![vec![1, 2, 3]])
+            .emit("data", "full")
+            .unwrap();
+        tx.try_send(()).unwrap();
+    });
+
+    // The websocket client receives the full payload with its attachment
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(r#"451-["data","full",{"_placeholder":true,"num":0}]"#.to_string())
+    );
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Binary(vec![1, 2, 3]));
+
+    // The polling client receives the fallback without any attachment
+    let sid = create_polling_connection(PORT).await;
+    rx.recv().await.unwrap();
+    let body = send_req(
+        PORT,
+        format!("transport=polling&sid={sid}"),
+        http::Method::GET,
+        None,
+    )
+    .await;
+    let packets: Vec<&str> = body.split('\x1e').collect();
+    assert_eq!(packets.len(), 2, "unexpected packets: {packets:?}");
+    assert!(packets[0].starts_with("0{"));
+    assert_eq!(packets[1], r#"42["data","fallback"]"#);
+}
@@ -0,0 +1,121 @@
+//! Tests for the protocol version negotiation
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    extract::{Bin, SocketRef},
+    EngineIoVersion, ProtocolVersion,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Error;
+
+#[tokio::test]
+pub async fn min_protocol_version() {
+    const PORT: u16 = 2700;
+    let (tx, mut rx) = mpsc::channel::<(ProtocolVersion, EngineIoVersion)>(4);
+    let io = create_server_with(PORT, move |b| {
+        b.min_protocol_version(ProtocolVersion::V5)
+            .on_protocol_negotiated(move |_, protocol, eio| tx.try_send((protocol, eio)).unwrap())
+    })
+    .await;
+    io.ns("/", |_: SocketRef| {});
+
+    // A legacy client is refused during the handshake
+    let res = tokio_tungstenite::connect_async(format!(
+        "ws://127.0.0.1:{PORT}/socket.io/?EIO=3&transport=websocket"
+    ))
+    .await;
+    match res {
+        Err(Error::Http(res)) => assert_eq!(res.status(), 400),
+        res => panic!("legacy client should be refused: {res:?}"),
+    }
+    assert!(rx.try_recv().is_err());
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        rx.recv().await.unwrap(),
+        (ProtocolVersion::V5, EngineIoVersion::V4)
+    );
+}
+
+#[cfg(feature = "v4")]
+#[tokio::test]
+pub async fn legacy_protocol_negotiated() {
+    const PORT: u16 = 2701;
+    let (tx, mut rx) = mpsc::channel::<(ProtocolVersion, EngineIoVersion)>(4);
+    let io = create_server_with(PORT, move |b| {
+        b.on_protocol_negotiated(move |_, protocol, eio| tx.try_send((protocol, eio)).unwrap())
+    })
+    .await;
+    io.ns("/", |_: SocketRef| {});
+
+    let (mut ws, _) = assert_ok!(
+        tokio_tungstenite::connect_async(format!(
+            "ws://127.0.0.1:{PORT}/socket.io/?EIO=3&transport=websocket"
+        ))
+        .await
+    );
+    assert_ok!(ws.next().await.unwrap());
+    assert_eq!(
+        rx.recv().await.unwrap(),
+        (ProtocolVersion::V4, EngineIoVersion::V3)
+    );
+}
+
+#[tokio::test]
+pub async fn binary_event_v5() {
+    const PORT: u16 = 2702;
+    use tokio_tungstenite::tungstenite::Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Vec<Vec<u8>>>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("bin", move |Bin(bin)| tx.try_send(bin).unwrap());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    let msg = r#"451-["bin",{"file":{"_placeholder":true,"num":0}}]"#;
+    assert_ok!(ws.send(Text(msg.into())).await);
+    assert_ok!(ws.send(Binary(vec![1, 2, 3])).await);
+    assert_eq!(rx.recv().await.unwrap(), vec![vec![1, 2, 3]]);
+
+    // Without attachments, the event is dispatched right away
+    assert_ok!(ws.send(Text(r#"450-["bin"]"#.into())).await);
+    assert_eq!(rx.recv().await.unwrap(), Vec::<Vec<u8>>::new());
+}
+
+#[cfg(feature = "v4")]
+#[tokio::test]
+pub async fn binary_event_v4() {
+    const PORT: u16 = 2703;
+    use tokio_tungstenite::tungstenite::Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Vec<Vec<u8>>>(4);
+    io.ns("/", move |s: SocketRef| {
+        s.on("bin", move |Bin(bin)| tx.try_send(bin).unwrap());
+    });
+
+    let (mut ws, _) = assert_ok!(
+        tokio_tungstenite::connect_async(format!(
+            "ws://127.0.0.1:{PORT}/socket.io/?EIO=3&transport=websocket"
+        ))
+        .await
+    );
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    // Engine.io v3 binary frames are prefixed with the message packet type
+    let msg = r#"451-["bin",{"_placeholder":true,"num":0}]"#;
+    assert_ok!(ws.send(Text(msg.into())).await);
+    assert_ok!(ws.send(Binary(vec![4, 1, 2, 3])).await);
+    assert_eq!(rx.recv().await.unwrap(), vec![vec![1, 2, 3]]);
+
+    // A connect packet with a V5 payload is refused
+    assert_ok!(ws.send(Text(r#"40/admin,{"token":"1"}"#.into())).await);
+    assert!(matches!(ws.next().await, Some(Ok(Close(_))) | None));
+}
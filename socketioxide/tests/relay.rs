@@ -0,0 +1,85 @@
+//! Tests for the peer to peer relay
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::Sid,
+    RelayError,
+};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn relay_to_peer() {
+    const PORT: u16 = 2900;
+    use Message::*;
+    let io = create_server_with(PORT, |b| {
+        b.relay_policy(|req| {
+            if let Some(obj) = req.data.as_object_mut() {
+                obj.insert("from".into(), req.from.to_string().into());
+            }
+            req.event == "signal" && req.shares_room()
+        })
+    })
+    .await;
+    let (sid_tx, mut sid_rx) = mpsc::channel::<Sid>(4);
+    let (tx, mut rx) = mpsc::channel::<Result<(), RelayError>>(4);
+    io.ns("/", move |s: SocketRef, Data::<String>(room)| {
+        s.join(room).unwrap();
+        sid_tx.try_send(s.id).unwrap();
+        let tx = tx.clone();
+        s.on(
+            "signal",
+            move |s: SocketRef, Data::<(Sid, Value)>((to, data))| {
+                tx.try_send(s.relay_to(to, "signal", data)).unwrap();
+            },
+        );
+    });
+
+    let mut ws1 = create_ws_connection_with_auth(PORT, r#""call-1""#).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = sid_rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection_with_auth(PORT, r#""call-1""#).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    let sid2 = sid_rx.recv().await.unwrap();
+    let mut ws3 = create_ws_connection_with_auth(PORT, r#""call-2""#).await;
+    assert_ok!(ws3.next().await.unwrap());
+    assert_ok!(ws3.next().await.unwrap());
+    let sid3 = sid_rx.recv().await.unwrap();
+
+    // Same room: relayed and transformed by the policy
+    let msg = format!(r#"42["signal","{sid2}",{{"sdp":"offer"}}]"#);
+    assert_ok!(ws1.send(Text(msg)).await);
+    assert_ok!(rx.recv().await.unwrap());
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(
+        msg,
+        Text(format!(r#"42["signal",{{"from":"{sid1}","sdp":"offer"}}]"#))
+    );
+
+    // Different rooms: rejected
+    let msg = format!(r#"42["signal","{sid3}",{{"sdp":"offer"}}]"#);
+    assert_ok!(ws1.send(Text(msg)).await);
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        Err(RelayError::Forbidden)
+    ));
+
+    // Unknown peer
+    let msg = format!(r#"42["signal","{}",{{"sdp":"offer"}}]"#, Sid::new());
+    assert_ok!(ws1.send(Text(msg)).await);
+    assert!(matches!(
+        rx.recv().await.unwrap(),
+        Err(RelayError::PeerNotFound)
+    ));
+
+    // The socket of the other room received nothing
+    let res = tokio::time::timeout(Duration::from_millis(50), ws3.next()).await;
+    assert!(res.is_err());
+}
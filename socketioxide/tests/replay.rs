@@ -0,0 +1,53 @@
+//! Tests for the replay of the events requested by the client
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::SocketRef;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn replay_missed_events() {
+    const PORT: u16 = 4500;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.enable_replay(3)).await;
+    let (tx, mut rx) = mpsc::channel::<()>(4);
+    io.ns("/", move |s: SocketRef| {
+        for i in 1..=5 {
+            s.emit("tick", i).unwrap();
+        }
+        let tx = tx.clone();
+        s.on("_replay", move || tx.try_send(()).unwrap());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    for i in 1..=5 {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["tick",{i},{{"_seq":{i}}}]"#)));
+    }
+
+    // The client noticed a gap after the 3rd event
+    assert_ok!(ws.send(Text(r#"42["_replay",3]"#.to_string())).await);
+    for i in 4..=5 {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["tick",{i},{{"_seq":{i}}}]"#)));
+    }
+
+    // Only the last 3 events are buffered
+    assert_ok!(ws.send(Text(r#"42["_replay",0]"#.to_string())).await);
+    for i in 3..=5 {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(format!(r#"42["tick",{i},{{"_seq":{i}}}]"#)));
+    }
+
+    // Nothing is replayed if the client is up to date, and the reserved event never reaches the handlers
+    assert_ok!(ws.send(Text(r#"42["_replay",5]"#.to_string())).await);
+    tokio::time::timeout(std::time::Duration::from_millis(50), ws.next())
+        .await
+        .unwrap_err();
+    assert!(rx.try_recv().is_err());
+}
@@ -0,0 +1,46 @@
+//! Tests for the namespaces requiring an auth payload
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::Data;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn ns_require_auth() {
+    const PORT: u16 = 4700;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<serde_json::Value>(4);
+    io.ns("/", || {});
+    io.ns_require_auth("/secure", move |Data(auth): Data<serde_json::Value>| {
+        tx.try_send(auth).unwrap()
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    for auth in ["", "null", "{}", r#""""#] {
+        assert_ok!(ws.send(Text(format!("40/secure,{auth}"))).await);
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(
+            msg,
+            Text(r#"44/secure,{"message":"missing auth payload"}"#.to_string())
+        );
+    }
+    assert!(rx.try_recv().is_err());
+
+    assert_ok!(
+        ws.send(Text(r#"40/secure,{"token":"abc"}"#.to_string()))
+            .await
+    );
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert!(msg.to_string().starts_with("40/secure,"));
+    assert_eq!(
+        rx.recv().await.unwrap(),
+        serde_json::json!({ "token": "abc" })
+    );
+}
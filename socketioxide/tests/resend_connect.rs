@@ -0,0 +1,39 @@
+//! Tests for the re-delivery of the namespace connect packet
+mod fixture;
+mod utils;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::SocketRef;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn resend_connect() {
+    const PORT: u16 = 6400;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let connections = Arc::new(AtomicUsize::new(0));
+    let connections2 = connections.clone();
+    io.ns("/", move |s: SocketRef| {
+        connections2.fetch_add(1, Ordering::SeqCst);
+        s.on("resync", |s: SocketRef| s.resend_connect().unwrap());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    let connect = assert_ok!(ws.next().await.unwrap());
+    assert!(matches!(&connect, Text(msg) if msg.starts_with("40{\"sid\":")));
+
+    assert_ok!(ws.send(Text(r#"42["resync"]"#.into())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, connect);
+
+    // The server state is left untouched
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+    assert_eq!(io.sockets().unwrap().len(), 1);
+}
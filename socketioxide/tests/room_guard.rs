@@ -0,0 +1,60 @@
+//! Tests for the guard of the rooms joined by the sockets
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{
+    adapter::Room,
+    extract::{Data, SocketRef},
+    JoinError,
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn veto_join() {
+    const PORT: u16 = 5100;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<(Option<Room>, Vec<Room>)>(4);
+    io.ns("/", move |s: SocketRef| {
+        let tx = tx.clone();
+        s.on("join", move |s: SocketRef, Data::<Vec<String>>(rooms)| {
+            let refused = match s.join(rooms) {
+                Ok(()) => None,
+                Err(JoinError::Refused(room)) => Some(room),
+                Err(JoinError::Full(room)) => unreachable!("room {room} has no capacity"),
+                Err(JoinError::Adapter(e)) => match e {},
+            };
+            let mut rooms = s.rooms().unwrap();
+            rooms.sort();
+            tx.try_send((refused, rooms)).unwrap();
+        });
+    });
+    assert!(io.on_room_join_request("/", |_, room| !room.starts_with("admin:")));
+    assert!(!io.on_room_join_request("/nope", |_, _| true));
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+
+    assert_ok!(ws.send(Text(r#"42["join",["lobby"]]"#.to_string())).await);
+    let (refused, rooms) = rx.recv().await.unwrap();
+    assert_eq!(refused, None);
+    assert_eq!(rooms, ["lobby"]);
+
+    // A single refused room cancels the whole join
+    let msg = r#"42["join",["chat","admin:logs"]]"#;
+    assert_ok!(ws.send(Text(msg.to_string())).await);
+    let (refused, rooms) = rx.recv().await.unwrap();
+    assert_eq!(refused.as_deref(), Some("admin:logs"));
+    assert_eq!(rooms, ["lobby"]);
+
+    // The server can still add the socket to any room
+    assert_ok!(io.join("admin:logs"));
+    assert_ok!(ws.send(Text(r#"42["join",["chat"]]"#.to_string())).await);
+    let (refused, rooms) = rx.recv().await.unwrap();
+    assert_eq!(refused, None);
+    assert_eq!(rooms, ["admin:logs", "chat", "lobby"]);
+}
@@ -0,0 +1,66 @@
+//! Tests for scheduled emits
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{clock::ManualClock, extract::SocketRef};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn emit_after() {
+    const PORT: u16 = 2600;
+    use Message::*;
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| b.with_clock(clock.clone())).await;
+    let (tx, mut rx) = mpsc::channel::<()>(4);
+    io.ns("/", move |s: SocketRef| {
+        let tx1 = tx.clone();
+        s.on("join", move |s: SocketRef| {
+            s.join("room").unwrap();
+            tx1.try_send(()).unwrap();
+        });
+        let tx2 = tx.clone();
+        s.on("leave", move |s: SocketRef| {
+            s.leave("room").unwrap();
+            tx2.try_send(()).unwrap();
+        });
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+
+    assert_ok!(ws1.send(Text("42[\"join\"]".to_string())).await);
+    rx.recv().await.unwrap();
+
+    let a = assert_ok!(io.to("room").emit_after(Duration::from_secs(30), "a", 1));
+    let b = assert_ok!(io.to("room").emit_after(Duration::from_secs(30), "b", 2));
+    let _c = assert_ok!(io.to("room").emit_after(Duration::from_secs(60), "c", 3));
+    assert!(b.cancel());
+
+    // The audience changes before the schedules fire
+    assert_ok!(ws2.send(Text("42[\"join\"]".to_string())).await);
+    rx.recv().await.unwrap();
+    assert_ok!(ws1.send(Text("42[\"leave\"]".to_string())).await);
+    rx.recv().await.unwrap();
+
+    clock.advance(Duration::from_secs(30));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text("42[\"a\",1]".to_string()));
+    assert!(!a.cancel());
+
+    clock.advance(Duration::from_secs(30));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text("42[\"c\",3]".to_string()));
+
+    // The socket that left the room never receives anything
+    let res = tokio::time::timeout(Duration::from_millis(50), ws1.next()).await;
+    assert!(res.is_err());
+}
@@ -0,0 +1,84 @@
+//! Tests for event sequence numbers
+mod fixture;
+mod utils;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::StreamExt;
+use socketioxide::extract::SocketRef;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn sequence_events() {
+    const PORT: u16 = 2300;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b.sequence_events(true)).await;
+    let (tx, mut rx) = mpsc::channel::<()>(2);
+    io.ns("/", move |s: SocketRef| {
+        assert_ok!(s.emit("hello", "foo"));
+        assert_ok!(s.emit("hello", "bar"));
+        tx.try_send(()).unwrap();
+    });
+
+    let mut ws1 = create_ws_connection(PORT).await;
+    assert_ok!(ws1.next().await.unwrap()); // engine.io open packet
+    assert_ok!(ws1.next().await.unwrap()); // socket.io connect packet
+    rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection(PORT).await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    // Broadcasts get a per-socket sequence number
+    assert_ok!(io.emit("broadcast", [1, 2]));
+
+    for ws in [&mut ws1, &mut ws2] {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["hello","foo",{"_seq":1}]"#.to_string()));
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["hello","bar",{"_seq":2}]"#.to_string()));
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["broadcast",1,2,{"_seq":3}]"#.to_string()));
+    }
+}
+
+#[tokio::test]
+pub async fn sequence_events_disabled() {
+    const PORT: u16 = 2301;
+    use Message::*;
+    let io = create_server_with(PORT, |b| b).await;
+    io.ns("/", move |s: SocketRef| {
+        assert_ok!(s.emit("hello", "foo"));
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["hello","foo"]"#.to_string()));
+}
+
+#[tokio::test]
+pub async fn sequence_events_concurrent_emits() {
+    const PORT: u16 = 2302;
+    let io = create_server_with(PORT, |b| b.sequence_events(true)).await;
+    io.ns("/", move |s: SocketRef| {
+        for _ in 0..4 {
+            let s = s.clone();
+            std::thread::spawn(move || {
+                for i in 0..20 {
+                    assert_ok!(s.emit("hello", i));
+                }
+            });
+        }
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    // The packets are received in the order of their sequence numbers
+    for seq in 1..=80 {
+        let msg = assert_ok!(ws.next().await.unwrap()).into_text().unwrap();
+        assert!(msg.ends_with(&format!(r#",{{"_seq":{seq}}}]"#)), "{msg}");
+    }
+}
@@ -0,0 +1,110 @@
+//! Tests for the session ids surviving the reconnections of a client
+#![cfg(feature = "extensions")]
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server, create_server_with, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::{Sid, MAX_SESSION_ID_LEN},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+struct Visits(usize);
+
+#[tokio::test]
+pub async fn session_restored_on_reconnect() {
+    const PORT: u16 = 6900;
+    let io = create_server_with(PORT, |b| b.session_retention(Duration::from_millis(200))).await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<(Sid, usize)>();
+    io.ns("/", move |s: SocketRef, Data(auth): Data<Value>| {
+        assert_eq!(s.session_id(), Some("session-1"));
+        s.set_user_id(auth["user"].as_str().unwrap());
+        let visits = match s.restore_session() {
+            true => s.extensions.get::<Visits>().unwrap().0 + 1,
+            false => 1,
+        };
+        s.extensions.insert(Visits(visits));
+        tx.send((s.id, visits)).unwrap();
+    });
+
+    let auth = r#"{"sessionId":"session-1","user":"alice"}"#;
+    for i in 1..=2 {
+        let mut ws = create_ws_connection_with_auth(PORT, auth).await;
+        assert_ok!(ws.next().await.unwrap());
+        assert_ok!(ws.next().await.unwrap());
+        let (sid, visits) = rx.recv().await.unwrap();
+        assert_eq!(visits, i);
+        assert_eq!(io.session_sid("/", "session-1"), Some(sid));
+
+        assert_ok!(ws.send(Message::Text("41".to_string())).await);
+        while io.get_socket(sid).is_some() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // The session is remembered after the disconnection
+        assert_eq!(io.session_sid("/", "session-1"), Some(sid));
+    }
+
+    // Another user sending the same session id doesn't get the extensions
+    let auth = r#"{"sessionId":"session-1","user":"mallory"}"#;
+    let mut ws = create_ws_connection_with_auth(PORT, auth).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let (_, visits) = rx.recv().await.unwrap();
+    assert_eq!(visits, 1);
+    drop(ws);
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(io.session_sid("/", "session-1"), None);
+    assert_eq!(io.session_sid("/", "unknown"), None);
+}
+
+#[tokio::test]
+pub async fn session_index_disabled_by_default() {
+    const PORT: u16 = 6901;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<Sid>();
+    io.ns("/", move |s: SocketRef| {
+        assert_eq!(s.session_id(), Some("session-1"));
+        assert!(!s.restore_session());
+        s.extensions.insert(Visits(1));
+        tx.send(s.id).unwrap();
+    });
+
+    let mut ws = create_ws_connection_with_auth(PORT, r#"{"sessionId":"session-1"}"#).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let sid = rx.recv().await.unwrap();
+    assert_eq!(io.session_sid("/", "session-1"), None);
+
+    let socket = io.get_socket(sid).unwrap();
+    assert_ok!(ws.send(Message::Text("41".to_string())).await);
+    while io.get_socket(sid).is_some() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    // The extensions are left untouched
+    assert_eq!(socket.extensions.get::<Visits>().unwrap().0, 1);
+}
+
+#[tokio::test]
+pub async fn long_session_id_ignored() {
+    const PORT: u16 = 6902;
+    let io = create_server_with(PORT, |b| b.session_retention(Duration::from_millis(200))).await;
+    let (tx, mut rx) = mpsc::unbounded_channel::<Option<String>>();
+    io.ns("/", move |s: SocketRef| {
+        tx.send(s.session_id().map(str::to_string)).unwrap();
+    });
+
+    let session_id = "a".repeat(MAX_SESSION_ID_LEN + 1);
+    let auth = format!(r#"{{"sessionId":"{session_id}"}}"#);
+    let mut ws = create_ws_connection_with_auth(PORT, &auth).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    assert_eq!(rx.recv().await.unwrap(), None);
+    assert_eq!(io.session_sid("/", &session_id), None);
+}
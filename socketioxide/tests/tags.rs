@@ -0,0 +1,56 @@
+//! Tests for the local socket tags
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::StreamExt;
+use socketioxide::{
+    extract::{Data, SocketRef},
+    socket::Sid,
+};
+use tokio::{sync::mpsc, time::Duration};
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn broadcast_with_tag() {
+    const PORT: u16 = 3600;
+    use Message::*;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<Sid>(4);
+    io.ns("/", move |s: SocketRef, Data::<bool>(beta)| {
+        if beta {
+            s.add_tag("beta");
+        }
+        tx.try_send(s.id).unwrap();
+    });
+
+    let mut ws1 = create_ws_connection_with_auth(PORT, "true").await;
+    assert_ok!(ws1.next().await.unwrap());
+    assert_ok!(ws1.next().await.unwrap());
+    let sid1 = rx.recv().await.unwrap();
+    let mut ws2 = create_ws_connection_with_auth(PORT, "true").await;
+    assert_ok!(ws2.next().await.unwrap());
+    assert_ok!(ws2.next().await.unwrap());
+    rx.recv().await.unwrap();
+    let mut ws3 = create_ws_connection_with_auth(PORT, "false").await;
+    assert_ok!(ws3.next().await.unwrap());
+    assert_ok!(ws3.next().await.unwrap());
+    rx.recv().await.unwrap();
+
+    assert_ok!(io.with_tag("beta").emit("feature", "new-ui"));
+    for ws in [&mut ws1, &mut ws2] {
+        let msg = assert_ok!(ws.next().await.unwrap());
+        assert_eq!(msg, Text(r#"42["feature","new-ui"]"#.to_string()));
+    }
+
+    // Once untagged, the socket is not targeted anymore
+    assert!(io.get_socket(sid1).unwrap().remove_tag("beta"));
+    assert_ok!(io.with_tag("beta").emit("feature", "v2"));
+    let msg = assert_ok!(ws2.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["feature","v2"]"#.to_string()));
+
+    for ws in [&mut ws1, &mut ws3] {
+        let res = tokio::time::timeout(Duration::from_millis(50), ws.next()).await;
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,53 @@
+//! Tests for the namespace throughput metrics
+#![cfg(feature = "metrics")]
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_server_with, create_ws_connection};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{clock::ManualClock, extract::SocketRef, metrics::THROUGHPUT_WINDOW};
+use tokio_tungstenite::tungstenite::Message;
+
+fn assert_rate(rate: f64, expected: f64) {
+    assert!(
+        (rate - expected).abs() <= expected * 0.01,
+        "rate {rate} is not within 1% of {expected}"
+    );
+}
+
+#[tokio::test]
+pub async fn namespace_throughput() {
+    const PORT: u16 = 4100;
+    use Message::*;
+    let clock = ManualClock::new();
+    let io = create_server_with(PORT, |b| b.with_clock(clock.clone())).await;
+    io.ns("/", |s: SocketRef| {
+        s.on("ping", |s: SocketRef| s.emit("pong", 1).unwrap());
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    // Leave the connection traffic out of the window
+    clock.advance(THROUGHPUT_WINDOW);
+
+    // 5 events per second in each direction: `2["ping"]` in and `2["pong",1]` out
+    for _ in 0..THROUGHPUT_WINDOW.as_secs() {
+        for _ in 0..5 {
+            assert_ok!(ws.send(Text(r#"42["ping"]"#.to_string())).await);
+            let msg = assert_ok!(ws.next().await.unwrap());
+            assert_eq!(msg, Text(r#"42["pong",1]"#.to_string()));
+        }
+        clock.advance(Duration::from_secs(1));
+    }
+
+    let throughput = io.throughput("/").unwrap();
+    assert_eq!(throughput.window, THROUGHPUT_WINDOW);
+    assert_rate(throughput.events_in, 5.0);
+    assert_rate(throughput.events_out, 5.0);
+    assert_rate(throughput.bytes_in, 45.0);
+    assert_rate(throughput.bytes_out, 55.0);
+    assert!(io.throughput("/unknown").is_none());
+}
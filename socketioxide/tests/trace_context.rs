@@ -0,0 +1,88 @@
+//! Tests for the client trace context propagation
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::{SinkExt, StreamExt};
+use socketioxide::extract::{SocketRef, TraceContext, TraceParent};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{client::IntoClientRequest, Message};
+
+const TRACE_PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+#[test]
+fn trace_parent_parse() {
+    let trace = TraceParent::parse(TRACE_PARENT).unwrap();
+    assert_eq!(trace.trace_id(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(trace.parent_id(), "00f067aa0ba902b7");
+    assert_eq!(trace.flags(), 1);
+    assert!(trace.sampled());
+
+    // Unknown versions may carry more fields
+    assert!(TraceParent::parse(&format!("cc{}-extra", &TRACE_PARENT[2..])).is_some());
+
+    assert!(TraceParent::parse("").is_none());
+    assert!(TraceParent::parse(&format!("{TRACE_PARENT}-extra")).is_none());
+    assert!(TraceParent::parse(&format!("ff{}", &TRACE_PARENT[2..])).is_none());
+    assert!(TraceParent::parse(&TRACE_PARENT.to_uppercase()).is_none());
+    assert!(
+        TraceParent::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01").is_none()
+    );
+    assert!(
+        TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01").is_none()
+    );
+    assert!(TraceParent::parse("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7").is_none());
+}
+
+#[tokio::test]
+pub async fn trace_context_from_header() {
+    const PORT: u16 = 2800;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<TraceContext>(4);
+    io.ns("/", move |s: SocketRef, TraceContext(trace)| {
+        assert_eq!(s.trace_parent(), trace.as_ref());
+        tx.try_send(TraceContext(trace)).unwrap();
+        let tx = tx.clone();
+        s.on("msg", move |trace: TraceContext| {
+            tx.try_send(trace).unwrap();
+        });
+    });
+
+    let mut req = format!("ws://127.0.0.1:{PORT}/socket.io/?EIO=4&transport=websocket")
+        .into_client_request()
+        .unwrap();
+    req.headers_mut()
+        .insert("traceparent", TRACE_PARENT.parse().unwrap());
+    let (mut ws, _) = tokio_tungstenite::connect_async(req).await.unwrap();
+    ws.send(Message::Text("40{}".to_string())).await.unwrap();
+    assert_ok!(ws.next().await.unwrap()); // engine.io open packet
+
+    let expected = TraceContext(TraceParent::parse(TRACE_PARENT));
+    assert_eq!(rx.recv().await.unwrap(), expected);
+
+    ws.send(Message::Text(r#"42["msg"]"#.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(rx.recv().await.unwrap(), expected);
+}
+
+#[tokio::test]
+pub async fn trace_context_from_auth() {
+    const PORT: u16 = 2801;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<TraceContext>(4);
+    io.ns("/", move |trace: TraceContext| {
+        tx.try_send(trace).unwrap();
+    });
+
+    let auth = format!(r#"{{"traceparent":"{TRACE_PARENT}"}}"#);
+    let _ws = create_ws_connection_with_auth(PORT, &auth).await;
+    assert_eq!(
+        rx.recv().await.unwrap(),
+        TraceContext(TraceParent::parse(TRACE_PARENT))
+    );
+
+    // An invalid value is ignored
+    let _ws = create_ws_connection_with_auth(PORT, r#"{"traceparent":"foo"}"#).await;
+    assert_eq!(rx.recv().await.unwrap(), TraceContext(None));
+}
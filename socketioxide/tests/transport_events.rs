@@ -0,0 +1,54 @@
+//! Tests for engine.io transport lifecycle events
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_polling_connection, create_server_with};
+use futures::{SinkExt, StreamExt};
+use socketioxide::{extract::SocketRef, socket::DisconnectReason, TransportEvent, TransportType};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn transport_events_upgrade() {
+    const PORT: u16 = 2400;
+    let (tx, mut rx) = mpsc::channel::<TransportEvent>(8);
+    let io = create_server_with(PORT, move |b| {
+        b.on_transport_event(move |_, ev| tx.try_send(ev).unwrap())
+    })
+    .await;
+    io.ns("/", |_: SocketRef| {});
+
+    let sid = create_polling_connection(PORT).await;
+    let mut ws = tokio_tungstenite::connect_async(format!(
+        "ws://127.0.0.1:{PORT}/socket.io/?EIO=4&transport=websocket&sid={sid}"
+    ))
+    .await
+    .unwrap()
+    .0;
+    assert_ok!(ws.send(Message::Text("2probe".to_string())).await);
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Message::Text("3probe".to_string()));
+    assert_ok!(ws.send(Message::Text("5".to_string())).await);
+
+    assert_eq!(
+        next_event(&mut rx).await,
+        TransportEvent::Open(TransportType::Polling)
+    );
+    assert_eq!(next_event(&mut rx).await, TransportEvent::UpgradeStarted);
+    assert_eq!(next_event(&mut rx).await, TransportEvent::UpgradeCompleted);
+
+    assert_ok!(ws.close(None).await);
+    assert_eq!(
+        next_event(&mut rx).await,
+        TransportEvent::Closed(DisconnectReason::TransportClose)
+    );
+}
+
+async fn next_event(rx: &mut mpsc::Receiver<TransportEvent>) -> TransportEvent {
+    tokio::time::timeout(Duration::from_millis(200), rx.recv())
+        .await
+        .expect("timeout waiting for a transport event")
+        .unwrap()
+}
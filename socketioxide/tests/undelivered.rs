@@ -0,0 +1,112 @@
+//! Tests for the undelivered events callback
+mod fixture;
+mod utils;
+
+use std::time::Duration;
+
+use fixture::{create_polling_connection, create_server_with, create_ws_connection, send_req};
+use futures::StreamExt;
+use socketioxide::{
+    extract::SocketRef,
+    socket::{Sid, UndeliveredPacket, UndeliveredReason},
+};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[tokio::test]
+pub async fn buffer_full() {
+    const PORT: u16 = 5400;
+    let (tx, mut rx) = mpsc::unbounded_channel::<UndeliveredPacket>();
+    let io = create_server_with(PORT, move |b| {
+        b.max_buffer_size(4)
+            .on_undelivered(move |packet| tx.send(packet).unwrap())
+    })
+    .await;
+    let (stx, mut srx) = mpsc::channel::<Sid>(1);
+    io.ns("/", move |s: SocketRef| {
+        // The connect packet and the first events fill the buffer before it is flushed
+        for i in 0..8 {
+            s.emit("flood", i).ok();
+        }
+        stx.try_send(s.id).unwrap();
+    });
+
+    let _ws = create_ws_connection(PORT).await;
+    let sid = tokio::time::timeout(Duration::from_secs(1), srx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let packet = rx.try_recv().unwrap();
+    assert_eq!(packet.sid, sid);
+    assert_eq!(packet.ns, "/");
+    assert_eq!(packet.event, "flood");
+    assert_eq!(packet.reason, UndeliveredReason::BufferFull);
+}
+
+#[tokio::test]
+pub async fn socket_closed() {
+    const PORT: u16 = 5401;
+    let (tx, mut rx) = mpsc::unbounded_channel::<UndeliveredPacket>();
+    let io = create_server_with(PORT, move |b| {
+        b.on_undelivered(move |packet| tx.send(packet).unwrap())
+    })
+    .await;
+    let (stx, mut srx) = mpsc::channel::<SocketRef>(1);
+    io.ns("/", move |s: SocketRef| {
+        let stx = stx.clone();
+        s.on_disconnect(move |s: SocketRef| stx.try_send(s).unwrap());
+    });
+
+    let sid = create_polling_connection(PORT).await;
+    send_req(
+        PORT,
+        format!("transport=polling&sid={sid}"),
+        http::Method::POST,
+        Some("1".into()),
+    )
+    .await;
+
+    // A socket kept after the client closed the connection
+    let socket = tokio::time::timeout(Duration::from_secs(1), srx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(socket.emit("late", ()).is_err());
+
+    let packet = rx.try_recv().unwrap();
+    assert_eq!(packet.sid, socket.id);
+    assert_eq!(packet.event, "late");
+    assert_eq!(packet.reason, UndeliveredReason::SocketClosed);
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+pub async fn broadcast_to_full_socket() {
+    const PORT: u16 = 5402;
+    use Message::*;
+    let (tx, mut rx) = mpsc::unbounded_channel::<UndeliveredPacket>();
+    let io = create_server_with(PORT, move |b| {
+        b.max_buffer_size(2)
+            .on_undelivered(move |packet| tx.send(packet).unwrap())
+    })
+    .await;
+    io.ns("/", |s: SocketRef| {
+        s.emit("ready", ()).ok();
+    });
+
+    let mut ws = create_ws_connection(PORT).await;
+    assert_ok!(ws.next().await.unwrap());
+    assert_ok!(ws.next().await.unwrap());
+    let msg = assert_ok!(ws.next().await.unwrap());
+    assert_eq!(msg, Text(r#"42["ready",null]"#.into()));
+
+    // The broadcast fails for the last events, the buffer being flushed only afterwards
+    assert!(io.emit("news", 1).is_ok());
+    assert!(io.emit("news", 2).is_ok());
+    assert!(io.emit("news", 3).is_err());
+
+    let packet = rx.try_recv().unwrap();
+    assert_eq!(packet.event, "news");
+    assert_eq!(packet.reason, UndeliveredReason::BufferFull);
+}
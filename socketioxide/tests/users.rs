@@ -0,0 +1,57 @@
+//! Tests for emitting to every socket of a user
+mod fixture;
+mod utils;
+
+use fixture::{create_server, create_ws_connection_with_auth};
+use futures::StreamExt;
+use socketioxide::extract::{Data, SocketRef};
+use tokio::sync::mpsc;
+use tokio::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, serde::Deserialize)]
+struct Auth {
+    user: String,
+}
+
+#[tokio::test]
+pub async fn emit_to_user() {
+    const PORT: u16 = 2200;
+    let io = create_server(PORT).await;
+    let (tx, mut rx) = mpsc::channel::<()>(3);
+    io.ns("/", move |s: SocketRef, Data(auth): Data<Auth>| {
+        s.set_user_id(auth.user);
+        tx.try_send(()).unwrap();
+    });
+
+    let mut alice1 = create_ws_connection_with_auth(PORT, r#"{"user":"alice"}"#).await;
+    let mut alice2 = create_ws_connection_with_auth(PORT, r#"{"user":"alice"}"#).await;
+    let mut bob = create_ws_connection_with_auth(PORT, r#"{"user":"bob"}"#).await;
+    for ws in [&mut alice1, &mut alice2, &mut bob] {
+        assert_ok!(ws.next().await.unwrap()); // engine.io open packet
+        assert_ok!(ws.next().await.unwrap()); // socket.io connect packet
+    }
+    for _ in 0..3 {
+        rx.recv().await.unwrap();
+    }
+
+    assert_ok!(io.emit_to_user("/", "alice", "test", "hello"));
+    let expected = Message::Text("42[\"test\",\"hello\"]".to_string());
+    assert_eq!(assert_ok!(alice1.next().await.unwrap()), expected);
+    assert_eq!(assert_ok!(alice2.next().await.unwrap()), expected);
+
+    let res = tokio::time::timeout(Duration::from_millis(50), bob.next()).await;
+    assert!(res.is_err(), "bob should not receive the message");
+}
+
+#[tokio::test]
+pub async fn emit_to_user_without_sockets() {
+    const PORT: u16 = 2201;
+    let io = create_server(PORT).await;
+    io.ns("/", |s: SocketRef, Data(auth): Data<Auth>| {
+        s.set_user_id(auth.user)
+    });
+
+    assert_ok!(io.emit_to_user("/", "alice", "test", "hello"));
+    assert_ok!(io.emit_to_user("/unknown", "alice", "test", "hello"));
+}